@@ -0,0 +1,33 @@
+//! Tauri commands for background jobs
+//!
+//! Long-running operations return a job id; these commands poll, cancel and
+//! clean up those jobs.
+
+use tauri::State;
+
+use crate::error::AppError;
+use crate::services::{JobInfo, JobService};
+
+/// List all background jobs
+#[tauri::command]
+pub async fn job_list(jobs: State<'_, JobService>) -> Result<Vec<JobInfo>, AppError> {
+    Ok(jobs.list().await)
+}
+
+/// Get a background job's status, progress and result
+#[tauri::command]
+pub async fn job_get(jobs: State<'_, JobService>, job_id: String) -> Result<JobInfo, AppError> {
+    jobs.get(&job_id).await
+}
+
+/// Request cancellation of a running job
+#[tauri::command]
+pub async fn job_cancel(jobs: State<'_, JobService>, job_id: String) -> Result<(), AppError> {
+    jobs.cancel(&job_id).await
+}
+
+/// Remove a finished job
+#[tauri::command]
+pub async fn job_remove(jobs: State<'_, JobService>, job_id: String) -> Result<(), AppError> {
+    jobs.remove(&job_id).await
+}
@@ -5,6 +5,7 @@
 pub mod cluster;
 pub mod connection;
 pub mod history;
+pub mod jobs;
 pub mod k8s;
 pub mod k8s_favorite;
 pub mod llm_config;
@@ -17,6 +18,7 @@ pub mod settings;
 pub use cluster::*;
 pub use connection::*;
 pub use history::*;
+pub use jobs::*;
 pub use k8s::*;
 pub use k8s_favorite::*;
 pub use llm_config::*;
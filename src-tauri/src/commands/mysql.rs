@@ -3,24 +3,51 @@
 //! These commands are exposed to the frontend via IPC.
 
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use serde_json::Value as JsonValue;
 use tauri::State;
 
 use crate::commands::PortForwardState;
 use crate::db::models::{
-    AlterDatabaseRequest, AlterTableRequest, AlterUserPasswordRequest, Connection,
-    CopyTableRequest, CreateDatabaseRequest, CreateForeignKeyRequest, CreateIndexRequest,
-    CreateTableRequest, CreateUserRequest, CreateViewRequest, DropUserRequest, ExportFormat,
-    ExportTableRequest, ExportTableResponse, ExplainResult, ForeignKeyInfo, GrantPrivilegesRequest,
-    ImportDataRequest, ImportResult, IndexInfo, MysqlDatabase, MysqlQueryResult, MysqlServerInfo,
-    MysqlTable, MysqlTableData, MysqlTableSchema, MysqlUserInfo, ProcedureDefinition, ProcedureInfo,
-    ProcessInfo, RenameTableRequest, RevokePrivilegesRequest, ServerVariable, TableMaintenanceResult,
-    TriggerDefinition, TriggerInfo, UserGrantsResponse, ViewDefinition, ViewInfo,
+    AccountDetails, AlterAccountRequest, AlterDatabaseRequest, AlterEventRequest,
+    AlterTableRequest, AlterUserPasswordRequest, ClonePrivilegesRequest, ClonePrivilegesResult,
+    CompareDigestSnapshotsRequest, CompareServerVariablesRequest, Connection, CopyTableRequest,
+    CreateDatabaseRequest, CreateEventRequest, CreateForeignKeyRequest, CreateIndexRequest,
+    CreateTableRequest, CreateUserRequest, CreateVariableBaselineRequest, CreateViewRequest,
+    DeadlockReport, DigestComparison, DigestSnapshotInfo, DropUserRequest, ErDiagramRequest,
+    ErDiagramResult, EventDefinition, EventInfo, EventSchedulerStatus, ExplainDigestRequest,
+    ExplainPlan, ExplainPlanRequest, ExplainResult, ExportQueryRequest, ExportTableFileRequest,
+    ExportTableRequest, ExportTableResponse, ForeignKeyInfo, GrantPrivilegesRequest,
+    ImportDataRequest, ImportFileRequest, ImportResult, IndexAdvisorReport, IndexAdvisorRequest,
+    IndexInfo, InferredTable, KeysetPage, KeysetPageRequest, LockGraph, MysqlCallRequest,
+    MysqlCallResult, MysqlDatabase, MysqlDumpRequest, MysqlParameterizedQueryRequest,
+    MysqlQueryPage, MysqlQueryResult, MysqlRestoreRequest, MysqlScriptRequest, MysqlScriptResult,
+    MysqlServerInfo, MysqlSessionInfo, MysqlTable, MysqlTableData, MysqlTableSchema, MysqlUserInfo,
+    PeriodicDigestSnapshotsRequest, PrivilegeMatrix, ProcedureDefinition, ProcedureInfo,
+    ProcessInfo, RenameTableRequest, ReplicationLagPollRequest, ReplicationLagSample,
+    ReplicationStatus, RevokePrivilegesRequest, RoleGrantRequest, RoleRequest,
+    RoutineParameterInfo, SaveStoredProgramRequest, SaveStoredProgramResult, SchemaCompareReport,
+    SchemaCompareRequest, ServerVariable, ServerVariableChange, ServerVariableInfo,
+    SetDefaultRolesRequest, SetServerVariableRequest, TableCopyRequest, TableMaintenanceResult,
+    TableRowsOptions, TakeDigestSnapshotRequest, TopQueriesReport, TopQueriesRequest,
+    TriggerDefinition, TriggerInfo, UserGrantsResponse, VariableBaselineInfo, VariableComparison,
+    ViewDefinition, ViewInfo,
 };
 use crate::db::SqlitePool;
 use crate::error::AppError;
-use crate::services::{ConnectionService, MysqlService};
+use crate::services::mysql_export::ExportSink;
+use crate::services::mysql_import::{infer_table, INFER_SAMPLE_ROWS};
+use crate::services::query_execution::record_cancelled;
+use crate::services::query_params::{extract_parameters, resolve_request};
+use crate::services::{
+    split_statements, ConnectionService, ErDiagramService, JobService, JobStarted,
+    MysqlAccountService, MysqlDumpService, MysqlExplainService, MysqlExportService,
+    MysqlImportService, MysqlIndexAdvisorService, MysqlLockService, MysqlReplicationService,
+    MysqlRoutineService, MysqlService, MysqlSessionService, MysqlTableCopyService,
+    MysqlVariableService, MysqlWorkloadService, QueryCursorService, QueryExecutionService,
+    QueryLimits, SchemaCompareService, SettingsService, SqlStatement,
+};
 
 /// Helper to get connection and create MySQL service
 /// For K8s connections, this will automatically start or use existing port forward
@@ -211,20 +238,166 @@ pub async fn mysql_get_table_primary_key(
 }
 
 /// Execute a SQL query
+///
+/// `execution_id` may be chosen by the caller so the query can be cancelled
+/// with `mysql_cancel_query` before it returns.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn mysql_execute_query(
     pool: State<'_, SqlitePool>,
     pf_state: State<'_, PortForwardState>,
+    cursors: State<'_, QueryCursorService>,
+    executions: State<'_, QueryExecutionService>,
     connection_id: i64,
     database: String,
     query: String,
+    execution_id: Option<String>,
 ) -> Result<MysqlQueryResult, AppError> {
     let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
-    mysql.execute_query(&database, &query).await
+    let settings = SettingsService::new(pool.inner().clone());
+    let limits = QueryLimits {
+        page_size: settings.get_mysql_query_limit().await?.max(1) as usize,
+        max_rows: settings.get_mysql_max_rows().await?,
+    };
+    let start = Instant::now();
+    let result = mysql
+        .execute_query(
+            &cursors,
+            &executions,
+            execution_id,
+            &database,
+            &query,
+            limits,
+        )
+        .await;
+    if let Err(AppError::Cancelled(_)) = &result {
+        let duration_ms = start.elapsed().as_millis() as u64;
+        record_cancelled(&pool, connection_id, &database, &query, duration_ms).await?;
+    }
+    result
+}
+
+/// Cancel a running query with KILL QUERY.
+/// Returns false if the execution already finished.
+#[tauri::command]
+pub async fn mysql_cancel_query(
+    executions: State<'_, QueryExecutionService>,
+    execution_id: String,
+) -> Result<bool, AppError> {
+    executions.cancel(&execution_id).await
+}
+
+/// List the ids of queries currently executing
+#[tauri::command]
+pub async fn mysql_list_running_queries(
+    executions: State<'_, QueryExecutionService>,
+) -> Result<Vec<String>, AppError> {
+    executions.running()
+}
+
+/// Run a multi-statement script, on a session if `session_id` is set
+#[tauri::command]
+pub async fn mysql_execute_script(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    executions: State<'_, QueryExecutionService>,
+    sessions: State<'_, MysqlSessionService>,
+    request: MysqlScriptRequest,
+) -> Result<MysqlScriptResult, AppError> {
+    let max_rows = SettingsService::new(pool.inner().clone())
+        .get_mysql_max_rows()
+        .await?;
+
+    if let Some(session_id) = &request.session_id {
+        return sessions
+            .execute_script(
+                &executions,
+                session_id,
+                request.execution_id,
+                &request.script,
+                request.mode,
+                max_rows,
+            )
+            .await;
+    }
+
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    mysql
+        .execute_script(
+            &executions,
+            request.execution_id,
+            &request.database,
+            &request.script,
+            request.mode,
+            max_rows,
+        )
+        .await
+}
+
+/// Execute a query with named parameters, given inline or from a saved query
+#[tauri::command]
+pub async fn mysql_execute_parameterized(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    executions: State<'_, QueryExecutionService>,
+    request: MysqlParameterizedQueryRequest,
+) -> Result<MysqlQueryResult, AppError> {
+    let resolved = resolve_request(pool.inner(), &request).await?;
+    let max_rows = SettingsService::new(pool.inner().clone())
+        .get_mysql_max_rows()
+        .await?;
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    mysql
+        .execute_parameterized(
+            &executions,
+            request.execution_id,
+            &resolved,
+            &request.values,
+            max_rows,
+        )
+        .await
+}
+
+/// List the named placeholders used in a query
+#[tauri::command]
+pub async fn mysql_extract_query_parameters(query: String) -> Result<Vec<String>, AppError> {
+    Ok(extract_parameters(&query))
 }
 
-/// Get table rows with pagination
+/// Split a script into classified statements without running it
 #[tauri::command]
+pub async fn mysql_split_script(script: String) -> Result<Vec<SqlStatement>, AppError> {
+    Ok(split_statements(&script))
+}
+
+/// Fetch the next page of a streamed query result
+#[tauri::command]
+pub async fn mysql_fetch_query_page(
+    cursors: State<'_, QueryCursorService>,
+    cursor_id: String,
+) -> Result<MysqlQueryPage, AppError> {
+    let page = cursors.fetch(&cursor_id).await?;
+    Ok(MysqlQueryPage {
+        cursor_id,
+        columns: page.columns,
+        rows: page.rows,
+        has_more: page.has_more,
+        truncated: page.truncated,
+    })
+}
+
+/// Close a query cursor, stopping the underlying query
+#[tauri::command]
+pub async fn mysql_close_query_cursor(
+    cursors: State<'_, QueryCursorService>,
+    cursor_id: String,
+) -> Result<bool, AppError> {
+    Ok(cursors.close(&cursor_id).await)
+}
+
+/// Get table rows with pagination, optionally filtered, sorted and projected
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn mysql_get_rows(
     pool: State<'_, SqlitePool>,
     pf_state: State<'_, PortForwardState>,
@@ -233,11 +406,49 @@ pub async fn mysql_get_rows(
     table: String,
     page: Option<i32>,
     page_size: Option<i32>,
+    options: Option<TableRowsOptions>,
 ) -> Result<MysqlTableData, AppError> {
     let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    let max_rows = SettingsService::new(pool.inner().clone())
+        .get_mysql_max_rows()
+        .await?;
     let page = page.unwrap_or(1);
-    let page_size = page_size.unwrap_or(100);
-    mysql.get_rows(&database, &table, page, page_size).await
+    let page_size = page_size
+        .unwrap_or(100)
+        .clamp(1, max_rows.min(i32::MAX as u64) as i32);
+    mysql
+        .get_rows(
+            &database,
+            &table,
+            page,
+            page_size,
+            &options.unwrap_or_default(),
+        )
+        .await
+}
+
+/// Get a page of table rows keyed on the primary key, with cursors to the
+/// neighbouring pages
+#[tauri::command]
+pub async fn mysql_get_rows_keyset(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    database: String,
+    table: String,
+    request: KeysetPageRequest,
+) -> Result<KeysetPage, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    let max_rows = SettingsService::new(pool.inner().clone())
+        .get_mysql_max_rows()
+        .await?;
+    let page_size = request
+        .page_size
+        .unwrap_or(100)
+        .clamp(1, max_rows.min(i32::MAX as u64) as i32);
+    mysql
+        .get_rows_keyset(&database, &table, page_size, &request)
+        .await
 }
 
 /// Insert a row into a table
@@ -371,6 +582,125 @@ pub async fn mysql_revoke_privileges(
     mysql.revoke_privileges(&data).await
 }
 
+// ==================== Roles and Account Management ====================
+
+/// Get the parsed grants of every account
+#[tauri::command]
+pub async fn mysql_get_privilege_matrix(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    include_system: Option<bool>,
+) -> Result<PrivilegeMatrix, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlAccountService::new(mysql)
+        .privilege_matrix(include_system.unwrap_or(false))
+        .await
+}
+
+/// Get an account's locking, password and resource limit settings
+#[tauri::command]
+pub async fn mysql_get_account_details(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    username: String,
+    host: String,
+) -> Result<AccountDetails, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlAccountService::new(mysql)
+        .account_details(&username, &host)
+        .await
+}
+
+/// Change account locking, password expiry or resource limits
+#[tauri::command]
+pub async fn mysql_alter_account(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    data: AlterAccountRequest,
+) -> Result<(), AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlAccountService::new(mysql).alter_account(&data).await
+}
+
+/// Create a role
+#[tauri::command]
+pub async fn mysql_create_role(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    data: RoleRequest,
+) -> Result<(), AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlAccountService::new(mysql).create_role(&data).await
+}
+
+/// Drop a role
+#[tauri::command]
+pub async fn mysql_drop_role(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    data: RoleRequest,
+) -> Result<(), AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlAccountService::new(mysql).drop_role(&data).await
+}
+
+/// Grant a role to an account
+#[tauri::command]
+pub async fn mysql_grant_role(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    data: RoleGrantRequest,
+) -> Result<(), AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlAccountService::new(mysql).grant_role(&data).await
+}
+
+/// Revoke a role from an account
+#[tauri::command]
+pub async fn mysql_revoke_role(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    data: RoleGrantRequest,
+) -> Result<(), AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlAccountService::new(mysql).revoke_role(&data).await
+}
+
+/// Set the roles activated when an account logs in
+#[tauri::command]
+pub async fn mysql_set_default_roles(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    data: SetDefaultRolesRequest,
+) -> Result<(), AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlAccountService::new(mysql)
+        .set_default_roles(&data)
+        .await
+}
+
+/// Give an account the privileges and roles of another account
+#[tauri::command]
+pub async fn mysql_clone_user_privileges(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    data: ClonePrivilegesRequest,
+) -> Result<ClonePrivilegesResult, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlAccountService::new(mysql)
+        .clone_privileges(&data)
+        .await
+}
+
 // ==================== Table Management Commands ====================
 
 /// Create a new table
@@ -486,6 +816,17 @@ pub async fn mysql_drop_index(
     mysql.drop_index(&database, &table, &index_name).await
 }
 
+/// Report unused, redundant and missing indexes
+#[tauri::command]
+pub async fn mysql_index_advice(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    request: IndexAdvisorRequest,
+) -> Result<IndexAdvisorReport, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    MysqlIndexAdvisorService::new(mysql).analyze(&request).await
+}
+
 // ==================== Foreign Key Management ====================
 
 /// List all foreign keys on a table
@@ -529,6 +870,35 @@ pub async fn mysql_drop_foreign_key(
     mysql.drop_foreign_key(&database, &table, &fk_name).await
 }
 
+// ==================== Schema Comparison ====================
+
+/// Compare two databases and generate a script migrating the target to the source
+#[tauri::command]
+pub async fn mysql_compare_schemas(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    request: SchemaCompareRequest,
+) -> Result<SchemaCompareReport, AppError> {
+    let source = get_mysql_service(&pool, &pf_state, request.source_connection_id).await?;
+    let target = get_mysql_service(&pool, &pf_state, request.target_connection_id).await?;
+    SchemaCompareService::new(source, target)
+        .compare(&request)
+        .await
+}
+
+// ==================== ER Diagrams ====================
+
+/// Build the relationship graph of a database, rendered in the requested format
+#[tauri::command]
+pub async fn mysql_er_diagram(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    request: ErDiagramRequest,
+) -> Result<ErDiagramResult, AppError> {
+    let mysql = get_mysql_service(&pool, &pf_state, request.connection_id).await?;
+    ErDiagramService::new(mysql).build(&request).await
+}
+
 // ==================== Data Export ====================
 
 /// Export table data to specified format (CSV, JSON, SQL)
@@ -542,28 +912,108 @@ pub async fn mysql_export_table(
     data: ExportTableRequest,
 ) -> Result<ExportTableResponse, AppError> {
     let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    mysql.export_table(&database, &table, &data).await
+}
 
-    let columns = data.columns.as_ref().map(|v| v.as_slice());
-    let where_clause = data.where_clause.as_deref();
+/// Start a job streaming a table to a file
+#[tauri::command]
+pub async fn mysql_export_table_to_file(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    jobs: State<'_, JobService>,
+    request: ExportTableFileRequest,
+) -> Result<JobStarted, AppError> {
+    let mysql = get_mysql_service(&pool, &pf_state, request.connection_id).await?;
+    let service = MysqlExportService::new(mysql);
+    let sink = ExportSink::file(&request.file_path)?;
+    Ok(jobs
+        .spawn("mysql_export", move |ctx| async move {
+            service
+                .export_table(
+                    &request.database,
+                    &request.table,
+                    &request.options,
+                    sink,
+                    Some(&ctx),
+                )
+                .await
+        })
+        .await)
+}
 
-    match data.format {
-        ExportFormat::Csv => {
-            mysql.export_table_csv(
-                &database,
-                &table,
-                columns,
-                where_clause,
-                data.limit,
-                data.include_headers,
-            ).await
-        }
-        ExportFormat::Json => {
-            mysql.export_table_json(&database, &table, columns, where_clause, data.limit).await
-        }
-        ExportFormat::Sql => {
-            mysql.export_table_sql(&database, &table, columns, where_clause, data.limit).await
-        }
-    }
+/// Start a job streaming the result of a query to a file
+#[tauri::command]
+pub async fn mysql_export_query_to_file(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    jobs: State<'_, JobService>,
+    request: ExportQueryRequest,
+    file_path: String,
+) -> Result<JobStarted, AppError> {
+    let mysql = get_mysql_service(&pool, &pf_state, request.connection_id).await?;
+    let service = MysqlExportService::new(mysql);
+    let sink = ExportSink::file(&file_path)?;
+    Ok(jobs
+        .spawn("mysql_export", move |ctx| async move {
+            service.export_query(&request, sink, Some(&ctx)).await
+        })
+        .await)
+}
+
+// ==================== Dump and Restore ====================
+
+/// Start a job dumping a database, or some of its tables, to a file
+#[tauri::command]
+pub async fn mysql_dump_database(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    jobs: State<'_, JobService>,
+    request: MysqlDumpRequest,
+) -> Result<JobStarted, AppError> {
+    let mysql = get_mysql_service(&pool, &pf_state, request.connection_id).await?;
+    let service = MysqlDumpService::new(mysql);
+    Ok(jobs
+        .spawn("mysql_dump", move |ctx| async move {
+            service.dump(&request, &ctx).await
+        })
+        .await)
+}
+
+/// Start a job restoring a dump file into a database
+#[tauri::command]
+pub async fn mysql_restore_database(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    jobs: State<'_, JobService>,
+    request: MysqlRestoreRequest,
+) -> Result<JobStarted, AppError> {
+    let mysql = get_mysql_service(&pool, &pf_state, request.connection_id).await?;
+    let service = MysqlDumpService::new(mysql);
+    Ok(jobs
+        .spawn("mysql_restore", move |ctx| async move {
+            service.restore(&request, &ctx).await
+        })
+        .await)
+}
+
+// ==================== Table Copy ====================
+
+/// Start a job copying a table, or a filtered subset, to another connection
+#[tauri::command]
+pub async fn mysql_copy_table_between_connections(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    jobs: State<'_, JobService>,
+    request: TableCopyRequest,
+) -> Result<JobStarted, AppError> {
+    let source = get_mysql_service(&pool, &pf_state, request.source_connection_id).await?;
+    let target = get_mysql_service(&pool, &pf_state, request.target_connection_id).await?;
+    let service = MysqlTableCopyService::new(source, target);
+    Ok(jobs
+        .spawn("mysql_table_copy", move |ctx| async move {
+            service.copy(&request, &ctx).await
+        })
+        .await)
 }
 
 // ==================== Data Import ====================
@@ -591,6 +1041,31 @@ pub async fn mysql_import_data(
     }
 }
 
+/// Start a job importing a CSV, JSON, NDJSON, XLSX or Parquet file into a table
+#[tauri::command]
+pub async fn mysql_import_file(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    jobs: State<'_, JobService>,
+    request: ImportFileRequest,
+) -> Result<JobStarted, AppError> {
+    let mysql = get_mysql_service(&pool, &pf_state, request.connection_id).await?;
+    let service = MysqlImportService::new(mysql);
+    Ok(jobs
+        .spawn("mysql_import", move |ctx| async move {
+            service.import_file(&request, &ctx).await
+        })
+        .await)
+}
+
+/// Infer a table definition from the first rows of an import file
+#[tauri::command]
+pub async fn mysql_infer_import_schema(
+    request: ImportFileRequest,
+) -> Result<InferredTable, AppError> {
+    infer_table(&request, Some(INFER_SAMPLE_ROWS)).await
+}
+
 // ==================== View Management ====================
 
 /// List all views in a database
@@ -738,6 +1213,163 @@ pub async fn mysql_drop_trigger(
     mysql.drop_trigger(&database, &name).await
 }
 
+// ==================== Stored Program Editing ====================
+
+/// Create a procedure, function or trigger from its CREATE statement
+#[tauri::command]
+pub async fn mysql_create_stored_program(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    database: String,
+    data: SaveStoredProgramRequest,
+) -> Result<SaveStoredProgramResult, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlRoutineService::new(mysql)
+        .create(&database, &data)
+        .await
+}
+
+/// Replace the definition of an existing procedure, function or trigger
+#[tauri::command]
+pub async fn mysql_alter_stored_program(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    database: String,
+    name: String,
+    data: SaveStoredProgramRequest,
+) -> Result<SaveStoredProgramResult, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlRoutineService::new(mysql)
+        .alter(&database, &name, &data)
+        .await
+}
+
+/// Get the declared parameters of a stored procedure
+#[tauri::command]
+pub async fn mysql_get_procedure_parameters(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    database: String,
+    name: String,
+) -> Result<Vec<RoutineParameterInfo>, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlRoutineService::new(mysql)
+        .get_procedure_parameters(&database, &name)
+        .await
+}
+
+/// CALL a stored procedure with IN/OUT/INOUT arguments
+#[tauri::command]
+pub async fn mysql_call_procedure(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    executions: State<'_, QueryExecutionService>,
+    request: MysqlCallRequest,
+) -> Result<MysqlCallResult, AppError> {
+    let max_rows = SettingsService::new(pool.inner().clone())
+        .get_mysql_max_rows()
+        .await?;
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    MysqlRoutineService::new(mysql)
+        .call(&executions, &request, max_rows)
+        .await
+}
+
+// ==================== Event Management ====================
+
+/// List all scheduled events in a database
+#[tauri::command]
+pub async fn mysql_list_events(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    database: String,
+) -> Result<Vec<EventInfo>, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    mysql.list_events(&database).await
+}
+
+/// Get event definition
+#[tauri::command]
+pub async fn mysql_get_event_definition(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    database: String,
+    name: String,
+) -> Result<EventDefinition, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    mysql.get_event_definition(&database, &name).await
+}
+
+/// Create a scheduled event
+#[tauri::command]
+pub async fn mysql_create_event(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    database: String,
+    data: CreateEventRequest,
+) -> Result<(), AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    mysql.create_event(&database, &data).await
+}
+
+/// Alter a scheduled event
+#[tauri::command]
+pub async fn mysql_alter_event(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    database: String,
+    name: String,
+    data: AlterEventRequest,
+) -> Result<(), AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    mysql.alter_event(&database, &name, &data).await
+}
+
+/// Enable or disable a scheduled event
+#[tauri::command]
+pub async fn mysql_set_event_enabled(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    database: String,
+    name: String,
+    enabled: bool,
+) -> Result<(), AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    mysql.set_event_enabled(&database, &name, enabled).await
+}
+
+/// Drop a scheduled event
+#[tauri::command]
+pub async fn mysql_drop_event(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    database: String,
+    name: String,
+) -> Result<(), AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    mysql.drop_event(&database, &name).await
+}
+
+/// Get the state of the event scheduler
+#[tauri::command]
+pub async fn mysql_get_event_scheduler_status(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+) -> Result<EventSchedulerStatus, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    mysql.get_event_scheduler_status().await
+}
+
 // ==================== Server Monitoring ====================
 
 /// Get server variables
@@ -775,6 +1407,205 @@ pub async fn mysql_kill_process(
     mysql.kill_process(process_id).await
 }
 
+// ==================== Server Configuration ====================
+
+/// Get global variables with scope, type and allowed range
+#[tauri::command]
+pub async fn mysql_get_server_variable_info(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    filter: Option<String>,
+) -> Result<Vec<ServerVariableInfo>, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlVariableService::new(mysql)
+        .list(filter.as_deref())
+        .await
+}
+
+/// Change a server variable with SET GLOBAL, SESSION, PERSIST or PERSIST_ONLY
+#[tauri::command]
+pub async fn mysql_set_server_variable(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    sessions: State<'_, MysqlSessionService>,
+    executions: State<'_, QueryExecutionService>,
+    request: SetServerVariableRequest,
+) -> Result<ServerVariableChange, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    MysqlVariableService::new(mysql)
+        .set_variable(pool.inner(), &sessions, &executions, &request)
+        .await
+}
+
+/// Get audited variable changes, newest first
+#[tauri::command]
+pub async fn mysql_get_server_variable_changes(
+    pool: State<'_, SqlitePool>,
+    connection_id: i64,
+    limit: Option<i64>,
+) -> Result<Vec<ServerVariableChange>, AppError> {
+    pool.get_server_variable_changes(connection_id, limit.unwrap_or(200))
+        .await
+}
+
+/// Store the current global variables as a baseline
+#[tauri::command]
+pub async fn mysql_create_variable_baseline(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    request: CreateVariableBaselineRequest,
+) -> Result<VariableBaselineInfo, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    MysqlVariableService::new(mysql)
+        .create_baseline(
+            pool.inner(),
+            request.connection_id,
+            request.label.as_deref(),
+        )
+        .await
+}
+
+/// List stored variable baselines, newest first
+#[tauri::command]
+pub async fn mysql_list_variable_baselines(
+    pool: State<'_, SqlitePool>,
+    connection_id: i64,
+) -> Result<Vec<VariableBaselineInfo>, AppError> {
+    pool.get_variable_baselines(connection_id).await
+}
+
+/// Delete a stored variable baseline
+#[tauri::command]
+pub async fn mysql_delete_variable_baseline(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<(), AppError> {
+    pool.delete_variable_baseline(id).await
+}
+
+/// Compare global variables with a baseline or another server
+#[tauri::command]
+pub async fn mysql_compare_server_variables(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    request: CompareServerVariablesRequest,
+) -> Result<VariableComparison, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    let other = match request.other_connection_id {
+        Some(other_id) => {
+            let name = ConnectionService::new(pool.inner().clone())
+                .get_by_id(other_id)
+                .await?
+                .name;
+            let other = get_mysql_service(pool.inner(), &pf_state, other_id).await?;
+            Some((name, other))
+        }
+        None => None,
+    };
+    MysqlVariableService::new(mysql)
+        .compare(
+            pool.inner(),
+            &request,
+            other.as_ref().map(|(name, other)| (name.as_str(), other)),
+        )
+        .await
+}
+
+// ==================== Lock Inspection ====================
+
+/// Get the graph of InnoDB lock waits
+#[tauri::command]
+pub async fn mysql_get_lock_waits(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+) -> Result<LockGraph, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlLockService::new(mysql).lock_graph().await
+}
+
+/// Get the latest InnoDB deadlock, if any
+#[tauri::command]
+pub async fn mysql_get_latest_deadlock(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+) -> Result<Option<DeadlockReport>, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlLockService::new(mysql).latest_deadlock().await
+}
+
+/// Kill a process that holds locks other transactions wait for
+#[tauri::command]
+pub async fn mysql_kill_lock_blocker(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    process_id: u64,
+) -> Result<(), AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlLockService::new(mysql).kill_blocker(process_id).await
+}
+
+// ==================== Replication Monitoring ====================
+
+/// Get replica channels, binary log position, group members and health
+#[tauri::command]
+pub async fn mysql_get_replication_status(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+) -> Result<ReplicationStatus, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    MysqlReplicationService::new(mysql).status().await
+}
+
+/// Start a job recording replica lag at a fixed interval
+#[tauri::command]
+pub async fn mysql_start_replication_lag_polling(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    jobs: State<'_, JobService>,
+    request: ReplicationLagPollRequest,
+) -> Result<JobStarted, AppError> {
+    let mysql = get_mysql_service(&pool, &pf_state, request.connection_id).await?;
+    let mut service = MysqlReplicationService::new(mysql);
+    let sqlite = pool.inner().clone();
+    let pf_state = pf_state.inner().clone();
+    Ok(jobs
+        .spawn("mysql_replication_lag", move |ctx| async move {
+            let reconnect = || get_mysql_service(&sqlite, &pf_state, request.connection_id);
+            service.poll_lag(&sqlite, &request, &ctx, reconnect).await
+        })
+        .await)
+}
+
+/// Get recorded replica lag samples, oldest first
+#[tauri::command]
+pub async fn mysql_get_replication_lag_history(
+    pool: State<'_, SqlitePool>,
+    connection_id: i64,
+    channel_name: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<ReplicationLagSample>, AppError> {
+    pool.get_replication_lag_samples(
+        connection_id,
+        channel_name.as_deref(),
+        limit.unwrap_or(1000),
+    )
+    .await
+}
+
+/// Delete recorded replica lag samples of a connection
+#[tauri::command]
+pub async fn mysql_clear_replication_lag_history(
+    pool: State<'_, SqlitePool>,
+    connection_id: i64,
+) -> Result<(), AppError> {
+    pool.clear_replication_lag_samples(connection_id).await
+}
+
 // ==================== Query Analysis ====================
 
 /// Explain a query
@@ -790,6 +1621,113 @@ pub async fn mysql_explain_query(
     mysql.explain_query(&database, &query).await
 }
 
+/// Explain a query as a typed plan tree (FORMAT=JSON or ANALYZE)
+#[tauri::command]
+pub async fn mysql_explain_plan(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    request: ExplainPlanRequest,
+) -> Result<ExplainPlan, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    MysqlExplainService::new(mysql)
+        .explain_plan(&request)
+        .await
+}
+
+// ==================== Workload Analysis ====================
+
+/// Rank statement digests from performance_schema
+#[tauri::command]
+pub async fn mysql_get_top_queries(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    request: TopQueriesRequest,
+) -> Result<TopQueriesReport, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    MysqlWorkloadService::new(mysql).top_queries(&request).await
+}
+
+/// Store the current statement digest counters
+#[tauri::command]
+pub async fn mysql_take_digest_snapshot(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    request: TakeDigestSnapshotRequest,
+) -> Result<DigestSnapshotInfo, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    MysqlWorkloadService::new(mysql)
+        .take_snapshot(
+            pool.inner(),
+            request.connection_id,
+            request.label.as_deref(),
+        )
+        .await
+}
+
+/// Start a job taking digest snapshots at a fixed interval
+#[tauri::command]
+pub async fn mysql_start_digest_snapshots(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    jobs: State<'_, JobService>,
+    request: PeriodicDigestSnapshotsRequest,
+) -> Result<JobStarted, AppError> {
+    let mysql = get_mysql_service(&pool, &pf_state, request.connection_id).await?;
+    let service = MysqlWorkloadService::new(mysql);
+    let sqlite = pool.inner().clone();
+    Ok(jobs
+        .spawn("mysql_digest_snapshots", move |ctx| async move {
+            service
+                .take_periodic_snapshots(&sqlite, &request, &ctx)
+                .await
+        })
+        .await)
+}
+
+/// List stored digest snapshots, newest first
+#[tauri::command]
+pub async fn mysql_list_digest_snapshots(
+    pool: State<'_, SqlitePool>,
+    connection_id: i64,
+) -> Result<Vec<DigestSnapshotInfo>, AppError> {
+    pool.get_digest_snapshots(connection_id).await
+}
+
+/// Delete a stored digest snapshot
+#[tauri::command]
+pub async fn mysql_delete_digest_snapshot(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<(), AppError> {
+    pool.delete_digest_snapshot(id).await
+}
+
+/// Compare a digest snapshot with a later snapshot or the live counters
+#[tauri::command]
+pub async fn mysql_compare_digest_snapshots(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    request: CompareDigestSnapshotsRequest,
+) -> Result<DigestComparison, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    MysqlWorkloadService::new(mysql)
+        .compare(pool.inner(), &request)
+        .await
+}
+
+/// Explain the sample statement of a digest
+#[tauri::command]
+pub async fn mysql_explain_digest(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    request: ExplainDigestRequest,
+) -> Result<ExplainResult, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, request.connection_id).await?;
+    MysqlWorkloadService::new(mysql)
+        .explain_digest(&request)
+        .await
+}
+
 // ==================== Table Maintenance ====================
 
 /// Optimize a table
@@ -830,3 +1768,105 @@ pub async fn mysql_check_table(
     let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
     mysql.check_table(&database, &table).await
 }
+
+// ==================== Session Management ====================
+
+/// Open a session pinned to a dedicated connection, e.g. for one editor tab
+#[tauri::command]
+pub async fn mysql_session_open(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    sessions: State<'_, MysqlSessionService>,
+    connection_id: i64,
+    database: Option<String>,
+    idle_timeout_secs: Option<u64>,
+) -> Result<MysqlSessionInfo, AppError> {
+    let mysql = get_mysql_service(pool.inner(), &pf_state, connection_id).await?;
+    let idle_timeout_secs = match idle_timeout_secs {
+        Some(secs) => secs.max(1),
+        None => {
+            SettingsService::new(pool.inner().clone())
+                .get_mysql_session_idle_timeout()
+                .await?
+        }
+    };
+    sessions
+        .open(
+            &mysql,
+            connection_id,
+            database.as_deref(),
+            Duration::from_secs(idle_timeout_secs),
+        )
+        .await
+}
+
+/// List open sessions
+#[tauri::command]
+pub async fn mysql_session_list(
+    sessions: State<'_, MysqlSessionService>,
+) -> Result<Vec<MysqlSessionInfo>, AppError> {
+    Ok(sessions.list().await)
+}
+
+/// Get a session's state, including its open transaction
+#[tauri::command]
+pub async fn mysql_session_get(
+    sessions: State<'_, MysqlSessionService>,
+    session_id: String,
+) -> Result<MysqlSessionInfo, AppError> {
+    sessions.get(&session_id).await
+}
+
+/// Execute a query in a session
+#[tauri::command]
+pub async fn mysql_session_execute(
+    pool: State<'_, SqlitePool>,
+    sessions: State<'_, MysqlSessionService>,
+    executions: State<'_, QueryExecutionService>,
+    session_id: String,
+    query: String,
+    execution_id: Option<String>,
+) -> Result<MysqlQueryResult, AppError> {
+    let max_rows = SettingsService::new(pool.inner().clone())
+        .get_mysql_max_rows()
+        .await?;
+    sessions
+        .execute(&executions, &session_id, execution_id, &query, max_rows)
+        .await
+}
+
+/// Start a transaction in a session
+#[tauri::command]
+pub async fn mysql_session_begin(
+    sessions: State<'_, MysqlSessionService>,
+    session_id: String,
+) -> Result<MysqlSessionInfo, AppError> {
+    sessions.begin(&session_id).await
+}
+
+/// Commit a session's transaction
+#[tauri::command]
+pub async fn mysql_session_commit(
+    sessions: State<'_, MysqlSessionService>,
+    session_id: String,
+) -> Result<MysqlSessionInfo, AppError> {
+    sessions.commit(&session_id).await
+}
+
+/// Roll back a session's transaction
+#[tauri::command]
+pub async fn mysql_session_rollback(
+    sessions: State<'_, MysqlSessionService>,
+    session_id: String,
+) -> Result<MysqlSessionInfo, AppError> {
+    sessions.rollback(&session_id).await
+}
+
+/// Close a session, rolling back any open transaction
+#[tauri::command]
+pub async fn mysql_session_close(
+    sessions: State<'_, MysqlSessionService>,
+    session_id: String,
+) -> Result<bool, AppError> {
+    Ok(sessions.close(&session_id).await)
+}
@@ -14,6 +14,7 @@ use crate::services::PortForwardService;
 
 /// Shared state for port forward service
 /// This ensures the service state persists across all commands
+#[derive(Clone)]
 pub struct PortForwardState {
     service: Arc<RwLock<Option<PortForwardService>>>,
     pool: Arc<RwLock<Option<SqlitePool>>>,
@@ -6,12 +6,15 @@ use tauri::State;
 
 use crate::commands::PortForwardState;
 use crate::db::models::{
-    Connection, RedisExportData, RedisKeyListResponse, RedisKeyValue, RedisServerInfo,
-    SetKeyRequest,
+    Connection, RedisAclDiff, RedisAclLogEntry, RedisAclUser, RedisAclUserSpec,
+    RedisApplySyncPlanRequest, RedisCompareRequest, RedisExportData, RedisKeyListResponse,
+    RedisKeyValue, RedisServerInfo, SetKeyRequest,
 };
 use crate::db::SqlitePool;
 use crate::error::AppError;
-use crate::services::{ConnectionService, RedisService};
+use crate::services::{
+    ConnectionService, JobService, JobStarted, RedisCompareService, RedisService,
+};
 
 /// Helper to get connection and create Redis service
 /// For K8s connections, this will automatically start or use existing port forward
@@ -20,6 +23,16 @@ async fn get_redis_service(
     pf_state: &PortForwardState,
     connection_id: i64,
 ) -> Result<RedisService, AppError> {
+    let conn = get_redis_connection(pool, pf_state, connection_id).await?;
+    RedisService::connect(&conn).await
+}
+
+/// Helper to get a Redis connection with an active port forward for K8s connections
+async fn get_redis_connection(
+    pool: &SqlitePool,
+    pf_state: &PortForwardState,
+    connection_id: i64,
+) -> Result<Connection, AppError> {
     let service = ConnectionService::new(pool.clone());
     let mut conn = service.get_by_id(connection_id).await?;
 
@@ -34,7 +47,7 @@ async fn get_redis_service(
         conn = ensure_port_forward(pool, pf_state, conn).await?;
     }
 
-    RedisService::connect(&conn).await
+    Ok(conn)
 }
 
 /// Ensure port forward is active for K8s connection
@@ -198,3 +211,157 @@ pub async fn redis_import_keys(
     let mut redis = get_redis_service(pool.inner(), &pf_state, connection_id).await?;
     redis.import_keys(&data).await
 }
+
+// ==================== ACL Commands ====================
+
+/// List ACL users
+#[tauri::command]
+pub async fn redis_acl_list(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+) -> Result<Vec<RedisAclUser>, AppError> {
+    let mut redis = get_redis_service(pool.inner(), &pf_state, connection_id).await?;
+    redis.acl_list().await
+}
+
+/// Get a single ACL user
+#[tauri::command]
+pub async fn redis_acl_get_user(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    username: String,
+) -> Result<RedisAclUser, AppError> {
+    let mut redis = get_redis_service(pool.inner(), &pf_state, connection_id).await?;
+    redis.acl_get_user(&username).await
+}
+
+/// Create or update an ACL user, returning the diff against the current rules
+#[tauri::command]
+pub async fn redis_acl_set_user(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    data: RedisAclUserSpec,
+) -> Result<RedisAclDiff, AppError> {
+    let mut redis = get_redis_service(pool.inner(), &pf_state, connection_id).await?;
+    redis.acl_set_user(&data).await
+}
+
+/// Delete ACL users
+#[tauri::command]
+pub async fn redis_acl_del_user(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    usernames: Vec<String>,
+) -> Result<i64, AppError> {
+    let mut redis = get_redis_service(pool.inner(), &pf_state, connection_id).await?;
+    redis.acl_del_user(&usernames).await
+}
+
+/// Get the current ACL username
+#[tauri::command]
+pub async fn redis_acl_whoami(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+) -> Result<String, AppError> {
+    let mut redis = get_redis_service(pool.inner(), &pf_state, connection_id).await?;
+    redis.acl_whoami().await
+}
+
+/// Get ACL log entries
+#[tauri::command]
+pub async fn redis_acl_log(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+    count: Option<u64>,
+) -> Result<Vec<RedisAclLogEntry>, AppError> {
+    let mut redis = get_redis_service(pool.inner(), &pf_state, connection_id).await?;
+    redis.acl_log(count).await
+}
+
+/// Reset the ACL log
+#[tauri::command]
+pub async fn redis_acl_log_reset(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    connection_id: i64,
+) -> Result<(), AppError> {
+    let mut redis = get_redis_service(pool.inner(), &pf_state, connection_id).await?;
+    redis.acl_log_reset().await
+}
+
+// ==================== Compare Commands ====================
+
+/// Connect to both sides of a comparison, applying DB index overrides
+async fn get_compare_service(
+    pool: &SqlitePool,
+    pf_state: &PortForwardState,
+    source_connection_id: i64,
+    source_db: Option<i64>,
+    target_connection_id: i64,
+    target_db: Option<i64>,
+) -> Result<RedisCompareService, AppError> {
+    let source = get_redis_connection(pool, pf_state, source_connection_id).await?;
+    let target = get_redis_connection(pool, pf_state, target_connection_id).await?;
+    let source =
+        RedisService::connect(&RedisCompareService::with_database(&source, source_db)).await?;
+    let target =
+        RedisService::connect(&RedisCompareService::with_database(&target, target_db)).await?;
+    Ok(RedisCompareService::new(source, target))
+}
+
+/// Start a key-level comparison job between two Redis instances or DBs
+#[tauri::command]
+pub async fn redis_compare_start(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    jobs: State<'_, JobService>,
+    data: RedisCompareRequest,
+) -> Result<JobStarted, AppError> {
+    let mut service = get_compare_service(
+        pool.inner(),
+        &pf_state,
+        data.source_connection_id,
+        data.source_db,
+        data.target_connection_id,
+        data.target_db,
+    )
+    .await?;
+
+    Ok(jobs
+        .spawn("redis_compare", move |ctx| async move {
+            service.compare(&data, &ctx).await
+        })
+        .await)
+}
+
+/// Start a job applying a sync plan produced by a comparison
+#[tauri::command]
+pub async fn redis_apply_sync_plan(
+    pool: State<'_, SqlitePool>,
+    pf_state: State<'_, PortForwardState>,
+    jobs: State<'_, JobService>,
+    data: RedisApplySyncPlanRequest,
+) -> Result<JobStarted, AppError> {
+    let plan = &data.plan;
+    let mut service = get_compare_service(
+        pool.inner(),
+        &pf_state,
+        plan.source_connection_id,
+        plan.source_db,
+        plan.target_connection_id,
+        plan.target_db,
+    )
+    .await?;
+
+    Ok(jobs
+        .spawn("redis_sync", move |ctx| async move {
+            service.apply_sync_plan(&data, &ctx).await
+        })
+        .await)
+}
@@ -7,6 +7,7 @@ use tauri::State;
 use crate::db::models::{SavedQuery, CreateSavedQueryRequest, UpdateSavedQueryRequest};
 use crate::db::SqlitePool;
 use crate::error::AppError;
+use crate::services::query_params::validate_parameters;
 
 /// Get saved queries with optional category filter
 #[tauri::command]
@@ -23,6 +24,7 @@ pub async fn create_saved_query(
     pool: State<'_, SqlitePool>,
     data: CreateSavedQueryRequest,
 ) -> Result<SavedQuery, AppError> {
+    validate_parameters(&data.parameters)?;
     pool.create_saved_query(&data).await
 }
 
@@ -33,6 +35,9 @@ pub async fn update_saved_query(
     id: i64,
     data: UpdateSavedQueryRequest,
 ) -> Result<SavedQuery, AppError> {
+    if let Some(parameters) = &data.parameters {
+        validate_parameters(parameters)?;
+    }
     pool.update_saved_query(id, &data).await
 }
 
@@ -4,7 +4,9 @@
 
 use tauri::State;
 
-use crate::db::models::{UserSetting, UpsertSettingRequest, BatchGetSettingsRequest, BatchSettingsResponse};
+use crate::db::models::{
+    BatchGetSettingsRequest, BatchSettingsResponse, FilterPreset, UpsertSettingRequest, UserSetting,
+};
 use crate::db::SqlitePool;
 use crate::error::AppError;
 use crate::services::SettingsService;
@@ -57,3 +59,45 @@ pub async fn delete_setting(
     let service = SettingsService::new(pool.inner().clone());
     service.delete(&key).await
 }
+
+/// Get the filter presets saved for a MySQL table
+#[tauri::command]
+pub async fn get_mysql_filter_presets(
+    pool: State<'_, SqlitePool>,
+    connection_id: i64,
+    database: String,
+    table: String,
+) -> Result<Vec<FilterPreset>, AppError> {
+    let service = SettingsService::new(pool.inner().clone());
+    service.get_filter_presets(connection_id, &database, &table).await
+}
+
+/// Save a filter preset for a MySQL table
+#[tauri::command]
+pub async fn save_mysql_filter_preset(
+    pool: State<'_, SqlitePool>,
+    connection_id: i64,
+    database: String,
+    table: String,
+    preset: FilterPreset,
+) -> Result<Vec<FilterPreset>, AppError> {
+    let service = SettingsService::new(pool.inner().clone());
+    service
+        .save_filter_preset(connection_id, &database, &table, preset)
+        .await
+}
+
+/// Delete a filter preset of a MySQL table
+#[tauri::command]
+pub async fn delete_mysql_filter_preset(
+    pool: State<'_, SqlitePool>,
+    connection_id: i64,
+    database: String,
+    table: String,
+    name: String,
+) -> Result<Vec<FilterPreset>, AppError> {
+    let service = SettingsService::new(pool.inner().clone());
+    service
+        .delete_filter_preset(connection_id, &database, &table, &name)
+        .await
+}
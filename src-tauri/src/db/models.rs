@@ -304,6 +304,85 @@ pub struct MysqlQueryResult {
     pub execution_time_ms: u64,
     /// Query type (select, insert, update, delete, etc.)
     pub query_type: String,
+    /// Cursor for fetching further pages, set when `has_more` is true
+    #[serde(default)]
+    pub cursor_id: Option<String>,
+    /// More rows are available through the cursor
+    #[serde(default)]
+    pub has_more: bool,
+    /// The configured row cap was reached before the result set ended
+    #[serde(default)]
+    pub truncated: bool,
+    /// Execution id, usable with the cancel endpoint while the query runs
+    #[serde(default)]
+    pub execution_id: Option<String>,
+}
+
+/// A further page of rows fetched from a query cursor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlQueryPage {
+    pub cursor_id: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<std::collections::HashMap<String, serde_json::Value>>,
+    pub has_more: bool,
+    pub truncated: bool,
+}
+
+/// How a script run handles a failing statement
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptErrorMode {
+    /// Skip the remaining statements
+    #[default]
+    Stop,
+    /// Run the remaining statements anyway
+    Continue,
+}
+
+/// Execute a multi-statement script
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlScriptRequest {
+    pub connection_id: i64,
+    pub database: String,
+    pub script: String,
+    #[serde(default)]
+    pub mode: ScriptErrorMode,
+    pub execution_id: Option<String>,
+    /// Run in this session instead of on a pooled connection
+    pub session_id: Option<String>,
+}
+
+/// Result of one statement of a script
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlStatementResult {
+    /// Position of the statement in the script
+    pub index: usize,
+    /// 1-based line on which the statement starts
+    pub line: usize,
+    pub sql: String,
+    pub query_type: String,
+    /// Status: success, error, skipped, cancelled
+    pub status: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<std::collections::HashMap<String, serde_json::Value>>,
+    pub affected_rows: u64,
+    pub execution_time_ms: u64,
+    /// The row cap was reached before the result set ended
+    pub truncated: bool,
+    pub error: Option<String>,
+}
+
+/// Ordered results of a script run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlScriptResult {
+    pub statements: Vec<MysqlStatementResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// The run was cancelled before all statements finished
+    pub cancelled: bool,
+    pub execution_time_ms: u64,
+    pub execution_id: Option<String>,
 }
 
 /// MySQL table data with pagination
@@ -311,13 +390,161 @@ pub struct MysqlQueryResult {
 pub struct MysqlTableData {
     pub columns: Vec<String>,
     pub rows: Vec<std::collections::HashMap<String, serde_json::Value>>,
+    /// Matching rows, or -1 when counting was skipped
     pub total: i64,
+    /// The total is an estimate (see `RowCountMode::Approximate`)
+    #[serde(default)]
+    pub total_is_estimate: bool,
     pub page: i32,
     pub page_size: i32,
 }
 
-/// MySQL user info
+// ==================== Table Row Filters ====================
+
+/// Comparison made by a filter condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOperator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// LIKE with a pattern written by the user
+    Like,
+    NotLike,
+    /// Substring match; `%` and `_` in the value match literally
+    Contains,
+    StartsWith,
+    EndsWith,
+    /// Value is an array
+    In,
+    NotIn,
+    /// Value is an array of the lower and upper bound
+    Between,
+    IsNull,
+    IsNotNull,
+}
+
+/// How the members of a filter group combine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterLogic {
+    #[default]
+    And,
+    Or,
+}
+
+/// A condition or a nested group of a row filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FilterNode {
+    Condition {
+        column: String,
+        operator: FilterOperator,
+        #[serde(default)]
+        value: serde_json::Value,
+    },
+    Group(FilterGroup),
+}
+
+/// Filter conditions joined with AND or OR
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterGroup {
+    #[serde(default)]
+    pub logic: FilterLogic,
+    #[serde(default)]
+    pub conditions: Vec<FilterNode>,
+}
+
+/// Sort direction of an ORDER BY column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Column of an ORDER BY clause
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortColumn {
+    pub column: String,
+    #[serde(default)]
+    pub direction: SortDirection,
+}
+
+/// Filter, sort order and columns used when browsing table rows
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableRowsOptions {
+    #[serde(default)]
+    pub filter: Option<FilterGroup>,
+    #[serde(default)]
+    pub order_by: Vec<SortColumn>,
+    /// Columns to return (default: all)
+    #[serde(default)]
+    pub columns: Option<Vec<String>>,
+    #[serde(default)]
+    pub count: RowCountMode,
+}
+
+/// How the total number of matching rows is obtained
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowCountMode {
+    /// SELECT COUNT(*), which scans the table
+    #[default]
+    Exact,
+    /// InnoDB statistics from information_schema.TABLES, or the optimizer's
+    /// EXPLAIN estimate when a filter is applied
+    Approximate,
+    /// Don't count
+    Skip,
+}
+
+/// Request for a page of table rows ordered and split by the primary key
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeysetPageRequest {
+    #[serde(flatten)]
+    pub options: TableRowsOptions,
+    pub page_size: Option<i32>,
+    /// Cursor returned with a previous page; the first page when absent
+    pub cursor: Option<String>,
+    /// Fetch the rows before the cursor rather than after it
+    #[serde(default)]
+    pub backward: bool,
+    /// Order by the primary key descending
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// Page of table rows with cursors to the pages around it
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeysetPage {
+    pub columns: Vec<String>,
+    pub rows: Vec<std::collections::HashMap<String, serde_json::Value>>,
+    /// Primary key columns the pages are ordered by
+    pub key_columns: Vec<String>,
+    pub total: Option<i64>,
+    pub total_is_estimate: bool,
+    pub page_size: i32,
+    /// Cursor of the following page, absent on the last page
+    pub next_cursor: Option<String>,
+    /// Cursor of the preceding page, absent on the first page
+    pub prev_cursor: Option<String>,
+}
+
+/// Filter saved under a name for a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterPreset {
+    pub name: String,
+    #[serde(flatten)]
+    pub options: TableRowsOptions,
+}
+
+/// MySQL user info
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MysqlUserInfo {
     pub user: String,
     pub host: String,
@@ -395,6 +622,157 @@ pub struct UserGrantsResponse {
     pub grants: Vec<UserGrantInfo>,
 }
 
+/// Level a privilege is granted at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivilegeLevel {
+    Global,
+    Database,
+    Table,
+    Routine,
+    Proxy,
+}
+
+/// One privilege parsed from a GRANT statement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrivilegeGrant {
+    /// Privilege name (SELECT, ALL PRIVILEGES, BACKUP_ADMIN, ...)
+    pub privilege: String,
+    pub level: PrivilegeLevel,
+    /// Object as written in the grant, e.g. *.*, `db`.*, `db`.`t`,
+    /// PROCEDURE `db`.`p` or the proxied account
+    pub object: String,
+    pub database: Option<String>,
+    /// Table or routine name
+    pub name: Option<String>,
+    /// Columns of a column-level privilege
+    pub columns: Vec<String>,
+    pub grantable: bool,
+}
+
+/// Role granted to an account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoleGrant {
+    pub role: MysqlUserInfo,
+    pub with_admin_option: bool,
+}
+
+/// Parsed grants of an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountPrivileges {
+    pub user: String,
+    pub host: String,
+    pub is_role: bool,
+    pub privileges: Vec<PrivilegeGrant>,
+    pub roles: Vec<RoleGrant>,
+    /// SHOW GRANTS output, including partial revokes
+    pub grants: Vec<String>,
+}
+
+/// Accounts × objects × privileges. Each account lists its privileges;
+/// `objects` and `privileges` are the distinct values over all accounts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivilegeMatrix {
+    pub accounts: Vec<AccountPrivileges>,
+    pub objects: Vec<String>,
+    pub privileges: Vec<String>,
+}
+
+/// Account with its locking, password and resource limit settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountDetails {
+    pub user: String,
+    pub host: String,
+    pub is_role: bool,
+    pub account_locked: bool,
+    pub password_expired: bool,
+    /// Days before the password expires; None follows default_password_lifetime
+    pub password_lifetime_days: Option<u32>,
+    pub password_last_changed: Option<String>,
+    pub max_queries_per_hour: u64,
+    pub max_updates_per_hour: u64,
+    pub max_connections_per_hour: u64,
+    pub max_user_connections: u64,
+    /// Roles activated at login, MySQL 8.0+
+    pub default_roles: Vec<MysqlUserInfo>,
+    pub privileges: AccountPrivileges,
+}
+
+/// Request to create or drop a role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleRequest {
+    pub role: String,
+    /// Defaults to %
+    pub host: Option<String>,
+}
+
+/// Request to grant a role to, or revoke it from, an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleGrantRequest {
+    pub role: MysqlUserInfo,
+    pub username: String,
+    pub host: String,
+    /// Lets the account grant the role to others (grant only)
+    #[serde(default)]
+    pub with_admin_option: bool,
+}
+
+/// Request to set the roles activated when an account logs in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetDefaultRolesRequest {
+    pub username: String,
+    pub host: String,
+    /// Roles to activate; empty activates none
+    #[serde(default)]
+    pub roles: Vec<MysqlUserInfo>,
+    /// Activate all granted roles instead
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// When an account's password expires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordExpiry {
+    Now,
+    Default,
+    Never,
+    Days(u32),
+}
+
+/// Request to change account locking, password expiry or resource limits.
+/// Unset fields are left unchanged; a limit of 0 removes it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlterAccountRequest {
+    pub username: String,
+    pub host: String,
+    pub account_locked: Option<bool>,
+    pub password_expiry: Option<PasswordExpiry>,
+    pub max_queries_per_hour: Option<u64>,
+    pub max_updates_per_hour: Option<u64>,
+    pub max_connections_per_hour: Option<u64>,
+    pub max_user_connections: Option<u64>,
+}
+
+/// Request to give an account the same privileges and roles as another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClonePrivilegesRequest {
+    pub source_user: String,
+    pub source_host: String,
+    pub target_user: String,
+    pub target_host: String,
+    /// Only return the statements
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Statements run (or to run) to clone privileges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClonePrivilegesResult {
+    pub statements: Vec<String>,
+    pub executed: bool,
+}
+
 // ==================== MySQL Table Management Models ====================
 
 /// Column definition for creating/altering tables
@@ -498,8 +876,14 @@ pub struct CopyTableRequest {
 pub struct IndexInfo {
     /// Index name
     pub name: String,
-    /// Column names in the index
+    /// Column names in the index, empty for functional key parts
     pub columns: Vec<String>,
+    /// Prefix length of each column, for indexes on a column prefix
+    #[serde(default)]
+    pub sub_parts: Vec<Option<u32>>,
+    /// Expression of each functional key part
+    #[serde(default)]
+    pub expressions: Vec<Option<String>>,
     /// Whether this is a unique index
     pub unique: bool,
     /// Index type (BTREE, HASH, FULLTEXT, SPATIAL)
@@ -568,6 +952,226 @@ fn default_fk_action() -> String {
     "RESTRICT".to_string()
 }
 
+// ==================== Schema Compare Models ====================
+
+/// Attributes left out of a schema comparison
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchemaCompareOptions {
+    /// Ignore table AUTO_INCREMENT counters
+    #[serde(default)]
+    pub ignore_auto_increment: bool,
+    /// Ignore table and column character sets and collations
+    #[serde(default)]
+    pub ignore_collation: bool,
+    /// Ignore table, column and index comments
+    #[serde(default)]
+    pub ignore_comments: bool,
+}
+
+/// Request to compare two databases. The source is the desired schema; the
+/// generated script migrates the target to match it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaCompareRequest {
+    pub source_connection_id: i64,
+    pub source_database: String,
+    pub target_connection_id: i64,
+    pub target_database: String,
+    #[serde(default)]
+    pub options: SchemaCompareOptions,
+}
+
+/// A column, index or foreign key present on both sides but different
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaElementChange {
+    pub name: String,
+    /// What differs, e.g. "type: int -> bigint"
+    pub differences: Vec<String>,
+}
+
+/// Differences within a table present on both sides
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableDiff {
+    pub name: String,
+    /// Columns only in the source
+    pub added_columns: Vec<String>,
+    /// Columns only in the target
+    pub removed_columns: Vec<String>,
+    pub modified_columns: Vec<SchemaElementChange>,
+    pub added_indexes: Vec<String>,
+    pub removed_indexes: Vec<String>,
+    pub modified_indexes: Vec<SchemaElementChange>,
+    pub added_foreign_keys: Vec<String>,
+    pub removed_foreign_keys: Vec<String>,
+    pub modified_foreign_keys: Vec<SchemaElementChange>,
+    /// Table option differences (engine, collation, comment, auto_increment)
+    pub option_changes: Vec<String>,
+}
+
+/// A view, routine or trigger that differs between the two sides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaObjectChange {
+    pub name: String,
+    /// view, procedure, function or trigger
+    pub object_type: String,
+    /// added (only in source), removed (only in target) or modified
+    pub change: String,
+}
+
+/// Result of a schema comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaCompareReport {
+    pub source_database: String,
+    pub target_database: String,
+    /// Tables only in the source
+    pub added_tables: Vec<String>,
+    /// Tables only in the target
+    pub removed_tables: Vec<String>,
+    pub modified_tables: Vec<TableDiff>,
+    pub objects: Vec<SchemaObjectChange>,
+    /// Migration statements for the target, in execution order
+    pub statements: Vec<String>,
+    /// The statements as a script, using DELIMITER for routine and trigger bodies
+    pub script: String,
+    pub identical: bool,
+}
+
+// ==================== ER Diagram Models ====================
+
+/// Output format of an ER diagram
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErDiagramFormat {
+    #[default]
+    Json,
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid erDiagram
+    Mermaid,
+    #[serde(rename = "plantuml")]
+    PlantUml,
+}
+
+/// Request to build the relationship graph of a database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErDiagramRequest {
+    pub connection_id: i64,
+    pub database: String,
+    /// Tables to include (default: all)
+    #[serde(default)]
+    pub tables: Option<Vec<String>>,
+    /// Add relations guessed from column names, such as `customer_id`
+    /// referring to the primary key of `customers`
+    #[serde(default)]
+    pub infer_relations: bool,
+    #[serde(default)]
+    pub format: ErDiagramFormat,
+}
+
+/// Column of an ER diagram table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErColumn {
+    pub name: String,
+    pub column_type: String,
+    pub nullable: bool,
+    pub primary_key: bool,
+    pub foreign_key: bool,
+    pub unique: bool,
+}
+
+/// Table of an ER diagram
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErNode {
+    pub table: String,
+    pub columns: Vec<ErColumn>,
+    pub primary_key: Vec<String>,
+    /// Columns of each unique key other than the primary key
+    pub unique_keys: Vec<Vec<String>>,
+    pub comment: Option<String>,
+}
+
+/// How many rows one end of a relation may have per row of the other end
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cardinality {
+    ZeroOrOne,
+    ExactlyOne,
+    ZeroOrMany,
+}
+
+/// Relation from the referencing table to the referenced one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErEdge {
+    /// Foreign key name, or a generated name for inferred relations
+    pub name: String,
+    pub from_table: String,
+    pub from_columns: Vec<String>,
+    pub to_table: String,
+    pub to_columns: Vec<String>,
+    /// Referencing rows per referenced row
+    pub from_cardinality: Cardinality,
+    /// Referenced rows per referencing row
+    pub to_cardinality: Cardinality,
+    /// Guessed from column names rather than declared
+    pub inferred: bool,
+    pub on_delete: Option<String>,
+    pub on_update: Option<String>,
+}
+
+/// Relationship graph of a database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErDiagram {
+    pub database: String,
+    pub nodes: Vec<ErNode>,
+    pub edges: Vec<ErEdge>,
+}
+
+/// ER diagram with its rendering in the requested format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErDiagramResult {
+    pub diagram: ErDiagram,
+    pub format: ErDiagramFormat,
+    /// DOT, Mermaid or PlantUML source; absent for JSON
+    pub rendered: Option<String>,
+}
+
+// ==================== MySQL Session Models ====================
+
+/// State of a MySQL session pinned to a dedicated connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlSessionInfo {
+    pub session_id: String,
+    pub connection_id: i64,
+    /// Current default database of the session
+    pub database: Option<String>,
+    /// Server thread id (CONNECTION_ID()) of the pinned connection
+    pub thread_id: u64,
+    /// A transaction is open on the session
+    pub in_transaction: bool,
+    pub transaction_started_at: Option<String>,
+    /// Rows modified by the open transaction, when reported by InnoDB
+    pub transaction_rows_modified: Option<u64>,
+    pub created_at: String,
+    pub last_used_at: String,
+    /// Idle time after which the session is rolled back and closed
+    pub idle_timeout_secs: u64,
+}
+
+/// Open MySQL session request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenMysqlSessionRequest {
+    pub connection_id: i64,
+    pub database: Option<String>,
+    /// Overrides the idle timeout setting
+    pub idle_timeout_secs: Option<u64>,
+}
+
+/// Execute a query in a MySQL session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlSessionQueryRequest {
+    pub query: String,
+    pub execution_id: Option<String>,
+}
+
 // ==================== Data Export/Import Models ====================
 
 /// Export format options
@@ -576,7 +1180,15 @@ fn default_fk_action() -> String {
 pub enum ExportFormat {
     Csv,
     Json,
+    /// One JSON object per line
+    Ndjson,
+    /// Markdown table
+    Markdown,
     Sql,
+    /// Excel workbook with typed cells (file exports and downloads only)
+    Xlsx,
+    /// Parquet file (file exports and downloads only)
+    Parquet,
 }
 
 impl Default for ExportFormat {
@@ -585,10 +1197,23 @@ impl Default for ExportFormat {
     }
 }
 
+/// Statement written by SQL exports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SqlInsertMode {
+    /// Plain `INSERT`
+    #[default]
+    Insert,
+    /// `INSERT IGNORE`, skipping rows whose key already exists
+    InsertIgnore,
+    /// `INSERT ... ON DUPLICATE KEY UPDATE` of every exported column
+    Upsert,
+}
+
 /// Request to export table data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportTableRequest {
-    /// Export format (csv, json, sql)
+    /// Export format (csv, json, ndjson, markdown, sql, xlsx, parquet)
     #[serde(default)]
     pub format: ExportFormat,
     /// Columns to export (None = all columns)
@@ -597,9 +1222,14 @@ pub struct ExportTableRequest {
     pub where_clause: Option<String>,
     /// Limit number of rows (optional)
     pub limit: Option<u32>,
-    /// Include column headers (for CSV, default: true)
+    /// Include column headers (for CSV and XLSX, default: true)
     #[serde(default = "default_true")]
     pub include_headers: bool,
+    /// Statement written by SQL exports
+    #[serde(default)]
+    pub insert_mode: SqlInsertMode,
+    /// Rows per INSERT statement of streamed SQL exports (default: 100)
+    pub rows_per_insert: Option<usize>,
 }
 
 fn default_true() -> bool {
@@ -617,6 +1247,50 @@ pub struct ExportTableResponse {
     pub row_count: usize,
 }
 
+/// Request to export a table to a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportTableFileRequest {
+    pub connection_id: i64,
+    pub database: String,
+    pub table: String,
+    /// Destination file
+    pub file_path: String,
+    #[serde(flatten)]
+    pub options: ExportTableRequest,
+}
+
+/// Request to export the result of a query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportQueryRequest {
+    pub connection_id: i64,
+    pub database: String,
+    /// A single statement returning rows
+    pub query: String,
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Include column headers (for CSV and XLSX, default: true)
+    #[serde(default = "default_true")]
+    pub include_headers: bool,
+    /// Table name used in SQL INSERT statements (default: query_result)
+    pub table_name: Option<String>,
+    /// Statement written by SQL exports
+    #[serde(default)]
+    pub insert_mode: SqlInsertMode,
+    /// Rows per INSERT statement of SQL exports (default: 100)
+    pub rows_per_insert: Option<usize>,
+}
+
+/// Summary of a streamed export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    /// Destination file, for file exports
+    pub file_path: Option<String>,
+    pub format: String,
+    pub row_count: u64,
+    /// Bytes written
+    pub bytes: u64,
+}
+
 /// Request to import data into a table
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportDataRequest {
@@ -651,6 +1325,259 @@ pub struct ImportResult {
     pub errors: Vec<String>,
 }
 
+/// File formats accepted by file imports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFileFormat {
+    Csv,
+    /// A JSON array of objects
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    Xlsx,
+    Parquet,
+}
+
+/// Request to import a file into a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportFileRequest {
+    pub connection_id: i64,
+    pub database: String,
+    pub table: String,
+    pub file_path: String,
+    /// File format (default: from the file extension)
+    pub format: Option<ImportFileFormat>,
+    /// CSV field delimiter (default: ,)
+    pub delimiter: Option<char>,
+    /// Whether the first CSV or XLSX row holds column names (default: true)
+    #[serde(default = "default_true")]
+    pub has_header: bool,
+    /// Records skipped after the header
+    #[serde(default)]
+    pub skip_rows: usize,
+    /// XLSX worksheet (default: the first one)
+    pub sheet: Option<String>,
+    /// CSV and XLSX text read as NULL (default: empty cells)
+    pub null_value: Option<String>,
+    /// File column -> table column; other file columns are matched by name
+    pub column_mapping: Option<std::collections::HashMap<String, String>>,
+    /// Statement used to insert rows
+    #[serde(default)]
+    pub insert_mode: SqlInsertMode,
+    /// Rows per INSERT statement (default: 500)
+    pub batch_size: Option<usize>,
+    /// Check every row against the target columns without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Create the table from the schema inferred from the file if it does
+    /// not exist
+    #[serde(default)]
+    pub create_table: bool,
+    /// Stop rolls the whole import back on the first bad row; continue
+    /// skips bad rows. Dry runs always continue.
+    #[serde(default)]
+    pub mode: ScriptErrorMode,
+}
+
+/// A row rejected by an import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRowError {
+    /// Line of a CSV or NDJSON file, row of a JSON, XLSX or Parquet file
+    pub line: u64,
+    pub column: Option<String>,
+    pub error: String,
+}
+
+/// Column inferred from an import file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferredColumn {
+    pub name: String,
+    pub column_type: String,
+    pub nullable: bool,
+}
+
+/// Table schema inferred from an import file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferredTable {
+    pub columns: Vec<InferredColumn>,
+    /// Rows the inference looked at
+    pub rows_sampled: u64,
+    pub create_sql: String,
+}
+
+/// Summary of a file import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportFileResult {
+    pub rows_read: u64,
+    /// Rows sent to the server, or rows that passed a dry run
+    pub imported: u64,
+    pub failed: u64,
+    pub dry_run: bool,
+    pub created_table: bool,
+    /// File columns matching no table column
+    pub ignored_columns: Vec<String>,
+    /// The first failures, see `failed` for the total
+    pub errors: Vec<ImportRowError>,
+    pub execution_time_ms: u64,
+}
+
+/// Request to dump a database, or some of its tables, to a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlDumpRequest {
+    pub connection_id: i64,
+    pub database: String,
+    /// Tables to dump (empty = whole database, including views, routines
+    /// and events)
+    #[serde(default)]
+    pub tables: Vec<String>,
+    /// Destination file
+    pub file_path: String,
+    /// Gzip the output
+    #[serde(default)]
+    pub gzip: bool,
+    /// Dump table rows as well as DDL (default: true)
+    #[serde(default = "default_true")]
+    pub include_data: bool,
+    /// Dump procedures, functions and events (default: true)
+    #[serde(default = "default_true")]
+    pub include_routines: bool,
+    /// Dump triggers of the dumped tables (default: true)
+    #[serde(default = "default_true")]
+    pub include_triggers: bool,
+    /// Emit DROP ... IF EXISTS before each CREATE (default: true)
+    #[serde(default = "default_true")]
+    pub add_drop_statements: bool,
+    /// Rows per extended INSERT (default: 1000)
+    pub batch_size: Option<usize>,
+}
+
+/// Summary of a finished dump
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlDumpResult {
+    pub file_path: String,
+    pub tables: usize,
+    pub views: usize,
+    pub routines: usize,
+    pub triggers: usize,
+    pub events: usize,
+    pub rows: u64,
+    /// Size of the written file
+    pub bytes: u64,
+}
+
+/// Request to run a dump file (plain or gzipped) against a database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlRestoreRequest {
+    pub connection_id: i64,
+    pub database: String,
+    pub file_path: String,
+    /// Whether to stop at the first failing statement
+    #[serde(default)]
+    pub mode: ScriptErrorMode,
+}
+
+/// A statement that failed during a restore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlRestoreError {
+    /// 1-based line of the statement in the file
+    pub line: usize,
+    /// Start of the statement text
+    pub statement: String,
+    pub error: String,
+}
+
+/// Summary of a finished restore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlRestoreResult {
+    pub statements: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    /// The first failures, see `failed` for the total
+    pub errors: Vec<MysqlRestoreError>,
+    pub execution_time_ms: u64,
+}
+
+/// How a table copy treats rows already in the target table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableCopyMode {
+    /// Empty the target table before copying
+    #[default]
+    Truncate,
+    /// Insert next to the existing rows; duplicate keys fail the copy
+    Append,
+    /// Insert, replacing rows with the same primary or unique key
+    Upsert,
+}
+
+/// Replacement for a column's values, evaluated on the source server so
+/// the original values never leave it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ColumnMask {
+    Null,
+    /// The same value for every row
+    Fixed {
+        value: String,
+    },
+    /// SHA-256 hex digest; equal values stay equal, so joins still match
+    Hash,
+    /// Asterisks, keeping the last `keep_last` characters
+    Redact {
+        #[serde(default)]
+        keep_last: u32,
+    },
+    /// Any SQL expression over the source row, e.g.
+    /// `CONCAT('user', id, '@example.com')`
+    Expression {
+        sql: String,
+    },
+}
+
+/// Mask applied to one column of a table copy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMaskRule {
+    pub column: String,
+    pub mask: ColumnMask,
+}
+
+/// Request to copy a table, or a filtered subset of it, to another
+/// connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableCopyRequest {
+    pub source_connection_id: i64,
+    pub source_database: String,
+    pub source_table: String,
+    pub target_connection_id: i64,
+    pub target_database: String,
+    /// Target table name (default: the source table name)
+    pub target_table: Option<String>,
+    /// Condition selecting the source rows to copy
+    pub where_clause: Option<String>,
+    #[serde(default)]
+    pub mode: TableCopyMode,
+    /// Drop the target table and recreate it from the source DDL; a missing
+    /// target table is always created
+    #[serde(default)]
+    pub recreate_table: bool,
+    #[serde(default)]
+    pub masks: Vec<ColumnMaskRule>,
+    /// Rows per INSERT (default: 1000)
+    pub batch_size: Option<usize>,
+}
+
+/// Summary of a finished table copy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableCopyResult {
+    pub target_database: String,
+    pub target_table: String,
+    /// Whether the target table was created from the source DDL
+    pub table_created: bool,
+    pub rows_copied: u64,
+    pub batches: u64,
+    pub execution_time_ms: u64,
+}
+
 // ==================== Cluster Models ====================
 
 /// Kubernetes cluster configuration
@@ -886,6 +1813,217 @@ pub struct RedisExportData {
     pub keys: Vec<RedisKeyValue>,
 }
 
+// ==================== Redis Compare Models ====================
+
+/// Request to compare keys between two Redis instances, or two DBs on one instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisCompareRequest {
+    pub source_connection_id: i64,
+    pub target_connection_id: i64,
+    /// DB index override for the source (defaults to the connection's DB)
+    pub source_db: Option<i64>,
+    /// DB index override for the target (defaults to the connection's DB)
+    pub target_db: Option<i64>,
+    /// SCAN MATCH pattern (defaults to *)
+    #[serde(default)]
+    pub pattern: String,
+    /// SCAN COUNT hint (defaults to 500)
+    pub scan_count: Option<u64>,
+    /// Maximum sample keys reported per category (defaults to 20)
+    pub sample_limit: Option<usize>,
+    /// Compare only types and TTL buckets, skip value hashing
+    #[serde(default)]
+    pub skip_values: bool,
+    /// Produce a sync plan with every key that needs copying or deleting
+    #[serde(default)]
+    pub include_sync_plan: bool,
+}
+
+/// A key that exists on both sides but differs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisKeyDifference {
+    pub key: String,
+    /// What differs: type, ttl, value
+    pub differences: Vec<String>,
+    pub source_type: String,
+    pub target_type: String,
+    pub source_ttl: i64,
+    pub target_ttl: i64,
+}
+
+/// Keys to copy from source to target (and optionally delete from target)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisSyncPlan {
+    pub source_connection_id: i64,
+    pub target_connection_id: i64,
+    pub source_db: Option<i64>,
+    pub target_db: Option<i64>,
+    /// Keys missing on, or differing from, the target
+    pub copy_keys: Vec<String>,
+    /// Keys only present on the target
+    pub delete_keys: Vec<String>,
+}
+
+/// Result of a Redis comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisCompareReport {
+    pub source_key_count: u64,
+    pub target_key_count: u64,
+    pub matching_count: u64,
+    /// Keys on the source but not on the target
+    pub missing_count: u64,
+    /// Keys on the target but not on the source
+    pub extra_count: u64,
+    pub differing_count: u64,
+    pub missing_samples: Vec<String>,
+    pub extra_samples: Vec<String>,
+    pub differing_samples: Vec<RedisKeyDifference>,
+    pub sync_plan: Option<RedisSyncPlan>,
+}
+
+/// Request to apply a sync plan produced by a comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisApplySyncPlanRequest {
+    pub plan: RedisSyncPlan,
+    /// Also delete keys that only exist on the target
+    #[serde(default)]
+    pub delete_extra: bool,
+}
+
+/// A key that could not be synced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisSyncFailure {
+    pub key: String,
+    pub error: String,
+}
+
+/// Result of applying a sync plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisSyncResult {
+    pub copied: u64,
+    pub deleted: u64,
+    /// Keys that vanished from the source before they could be copied
+    pub skipped: u64,
+    pub failures: Vec<RedisSyncFailure>,
+}
+
+// ==================== Redis ACL Models ====================
+
+/// Access level granted by an ACL key pattern (Redis 7 adds %R~ and %W~)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AclKeyPermission {
+    Read,
+    Write,
+    #[default]
+    All,
+}
+
+/// Key pattern an ACL user may access (e.g., ~cache:* or %R~report:*)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AclKeyPattern {
+    pub pattern: String,
+    #[serde(default)]
+    pub permission: AclKeyPermission,
+}
+
+/// Pub/Sub channel pattern an ACL user may access (e.g., &notifications:*)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AclChannelPattern {
+    pub pattern: String,
+}
+
+/// Command permission rule; order is significant when applied
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AclCommandRule {
+    /// Command category such as @read, @write or @dangerous
+    Category { category: String, allowed: bool },
+    /// Single command or command|subcommand such as config|get
+    Command { command: String, allowed: bool },
+}
+
+/// Redis ACL user with parsed rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisAclUser {
+    pub username: String,
+    pub enabled: bool,
+    pub nopass: bool,
+    /// SHA-256 hashes of the user's passwords
+    pub password_hashes: Vec<String>,
+    pub commands: Vec<AclCommandRule>,
+    pub keys: Vec<AclKeyPattern>,
+    pub channels: Vec<AclChannelPattern>,
+    /// Other flags (e.g., sanitize-payload) and selectors kept verbatim
+    pub flags: Vec<String>,
+    /// Raw rule string as reported by the server
+    pub rules: String,
+}
+
+/// Desired state of an ACL user for ACL SETUSER
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisAclUserSpec {
+    pub username: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub nopass: bool,
+    /// Plaintext passwords to add
+    #[serde(default)]
+    pub add_passwords: Vec<String>,
+    /// SHA-256 hashes of passwords to remove
+    #[serde(default)]
+    pub remove_password_hashes: Vec<String>,
+    #[serde(default)]
+    pub commands: Vec<AclCommandRule>,
+    #[serde(default)]
+    pub keys: Vec<AclKeyPattern>,
+    #[serde(default)]
+    pub channels: Vec<AclChannelPattern>,
+    /// Only compute the diff without applying it
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// A single difference between the current and requested ACL rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisAclChange {
+    /// Field that changed: enabled, nopass, passwords, commands, keys, channels
+    pub field: String,
+    /// Change action: added, removed, changed
+    pub action: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Result of diffing (and optionally applying) an ACL user spec
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisAclDiff {
+    pub username: String,
+    pub user_exists: bool,
+    pub changes: Vec<RedisAclChange>,
+    /// ACL SETUSER rules needed to reach the requested state (passwords masked)
+    pub rules: Vec<String>,
+    pub applied: bool,
+}
+
+/// Entry from ACL LOG
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedisAclLogEntry {
+    pub count: i64,
+    /// Denial reason: command, key, channel, auth
+    pub reason: String,
+    /// Context: toplevel, multi, lua, module
+    pub context: String,
+    pub object: String,
+    pub username: String,
+    pub age_seconds: f64,
+    pub client_info: String,
+    pub entry_id: Option<i64>,
+    pub timestamp_created: Option<i64>,
+    pub timestamp_last_updated: Option<i64>,
+}
+
 // ==================== Query History Models ====================
 
 /// Query history entry
@@ -915,7 +2053,7 @@ pub struct QueryHistory {
     /// Number of rows affected/returned
     pub row_count: i64,
 
-    /// Status: success, error
+    /// Status: success, error, cancelled
     pub status: String,
 
     /// Error message if status is error
@@ -929,6 +2067,9 @@ pub struct QueryHistoryListResponse {
     pub total: i64,
 }
 
+/// Statuses a query history entry can have
+pub const QUERY_HISTORY_STATUSES: [&str; 3] = ["success", "error", "cancelled"];
+
 /// Add query history request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddQueryHistoryRequest {
@@ -944,6 +2085,35 @@ pub struct AddQueryHistoryRequest {
 
 // ==================== Saved Query Models ====================
 
+/// Declared type of a query parameter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryParameterType {
+    #[default]
+    String,
+    Integer,
+    Decimal,
+    Boolean,
+    Date,
+    Datetime,
+    Json,
+    /// Table or column name, quoted with backticks
+    Identifier,
+    /// Array of values expanded into a comma separated list, e.g. for IN (...)
+    List,
+}
+
+/// Named placeholder (`:name` or `${name}`) declared for a query
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryParameter {
+    pub name: String,
+    #[serde(default, rename = "type")]
+    pub param_type: QueryParameterType,
+    /// Used when no value is supplied
+    pub default_value: Option<serde_json::Value>,
+    pub description: Option<String>,
+}
+
 /// Saved query entry
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, Default)]
 pub struct SavedQuery {
@@ -968,6 +2138,11 @@ pub struct SavedQuery {
     /// Category for grouping
     pub category: Option<String>,
 
+    /// Declared placeholders with their types and defaults
+    #[sqlx(json)]
+    #[serde(default)]
+    pub parameters: Vec<QueryParameter>,
+
     /// Creation timestamp
     pub created_at: Option<String>,
 
@@ -984,6 +2159,8 @@ pub struct CreateSavedQueryRequest {
     pub query_text: String,
     pub description: Option<String>,
     pub category: Option<String>,
+    #[serde(default)]
+    pub parameters: Vec<QueryParameter>,
 }
 
 /// Update saved query request
@@ -993,6 +2170,24 @@ pub struct UpdateSavedQueryRequest {
     pub query_text: Option<String>,
     pub description: Option<String>,
     pub category: Option<String>,
+    pub parameters: Option<Vec<QueryParameter>>,
+}
+
+/// Execute a query with named parameters, given inline or from a saved query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlParameterizedQueryRequest {
+    pub connection_id: i64,
+    /// Defaults to the saved query's database
+    pub database: Option<String>,
+    /// Query text; required unless `saved_query_id` is set
+    pub query: Option<String>,
+    /// Parameter declarations; taken from the saved query when omitted
+    pub parameters: Option<Vec<QueryParameter>>,
+    pub saved_query_id: Option<i64>,
+    /// Values by parameter name
+    #[serde(default)]
+    pub values: std::collections::HashMap<String, serde_json::Value>,
+    pub execution_id: Option<String>,
 }
 
 // ==================== K8s Resource Models ====================
@@ -1609,6 +2804,95 @@ pub struct TriggerDefinition {
     pub definition: String,
 }
 
+/// Kind of stored program, as named in its CREATE statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoredProgramKind {
+    Procedure,
+    Function,
+    Trigger,
+}
+
+/// Request to create or replace a procedure, function or trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveStoredProgramRequest {
+    /// Full CREATE statement; `DELIMITER` lines around it are accepted
+    pub definition: String,
+    /// Replace an existing object of the same kind and name
+    pub replace: Option<bool>,
+}
+
+/// Outcome of saving a stored program
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveStoredProgramResult {
+    pub kind: StoredProgramKind,
+    pub name: String,
+    /// An existing definition was dropped and recreated
+    pub replaced: bool,
+}
+
+/// Direction of a stored procedure parameter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ParameterMode {
+    #[default]
+    In,
+    Out,
+    InOut,
+}
+
+/// Declared parameter of a stored procedure or function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutineParameterInfo {
+    pub position: u32,
+    pub name: String,
+    pub mode: ParameterMode,
+    pub data_type: String, // Full type, e.g. varchar(32)
+}
+
+/// Argument for a stored procedure call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcedureCallArgument {
+    pub name: String,
+    #[serde(default)]
+    pub mode: ParameterMode,
+    #[serde(default, rename = "type")]
+    pub param_type: QueryParameterType,
+    /// Input value for IN and INOUT parameters
+    pub value: Option<serde_json::Value>,
+}
+
+/// Call a stored procedure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlCallRequest {
+    pub connection_id: i64,
+    pub database: String,
+    pub procedure: String,
+    #[serde(default)]
+    pub arguments: Vec<ProcedureCallArgument>,
+    pub execution_id: Option<String>,
+}
+
+/// One result set produced by a procedure call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlCallResultSet {
+    pub columns: Vec<String>,
+    pub rows: Vec<std::collections::HashMap<String, serde_json::Value>>,
+    /// Rows beyond the row limit were dropped
+    pub truncated: bool,
+}
+
+/// Result of a stored procedure call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MysqlCallResult {
+    pub result_sets: Vec<MysqlCallResultSet>,
+    /// Values of the OUT and INOUT parameters after the call, by name
+    pub out_values: std::collections::HashMap<String, serde_json::Value>,
+    pub affected_rows: u64,
+    pub execution_time_ms: u64,
+    pub execution_id: Option<String>,
+}
+
 /// Event information (for scheduled events)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventInfo {
@@ -1632,6 +2916,59 @@ pub struct EventDefinition {
     pub definition: String,
 }
 
+/// When an event fires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventSchedule {
+    /// AT timestamp
+    OneTime { execute_at: String },
+    /// EVERY interval [STARTS timestamp] [ENDS timestamp]
+    Recurring {
+        interval_value: String,
+        interval_field: String, // SECOND, MINUTE, HOUR, DAY, ..., DAY_HOUR, ...
+        starts: Option<String>,
+        ends: Option<String>,
+    },
+}
+
+/// Request to create a scheduled event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateEventRequest {
+    pub name: String,
+    pub schedule: EventSchedule,
+    pub body: String, // The statement(s) executed by the event
+    pub if_not_exists: Option<bool>,
+    pub on_completion_preserve: Option<bool>,
+    pub enabled: Option<bool>,
+    pub comment: Option<String>,
+}
+
+/// Request to alter a scheduled event; omitted fields are left unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlterEventRequest {
+    pub schedule: Option<EventSchedule>,
+    pub body: Option<String>,
+    pub on_completion_preserve: Option<bool>,
+    pub enabled: Option<bool>,
+    pub comment: Option<String>,
+    pub rename_to: Option<String>,
+}
+
+/// Request to enable or disable a scheduled event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetEventEnabledRequest {
+    pub enabled: bool,
+}
+
+/// State of the server's event scheduler thread
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSchedulerStatus {
+    pub value: String, // ON, OFF, DISABLED
+    pub running: bool,
+    /// False when the scheduler was disabled at startup and cannot be turned on at runtime
+    pub can_enable: bool,
+}
+
 // ==================== Server Monitoring Types ====================
 
 /// Server variable information
@@ -1654,6 +2991,89 @@ pub struct ProcessInfo {
     pub info: Option<String>,
 }
 
+/// Transaction involved in a lock wait
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LockTransaction {
+    pub trx_id: String,
+    /// Connection id, as taken by kill_process
+    pub process_id: Option<u64>,
+    pub user: Option<String>,
+    pub host: Option<String>,
+    pub db: Option<String>,
+    /// RUNNING, LOCK WAIT, ROLLING BACK or COMMITTING
+    pub state: Option<String>,
+    /// Running statement, or the last one when the transaction is idle
+    pub query: Option<String>,
+    pub started: Option<String>,
+    pub age_secs: Option<i64>,
+    pub wait_secs: Option<i64>,
+    pub rows_locked: Option<u64>,
+    pub rows_modified: Option<u64>,
+}
+
+/// One transaction waiting for a lock another one holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockWait {
+    pub waiting_trx_id: String,
+    pub blocking_trx_id: String,
+    pub object_schema: Option<String>,
+    pub object_name: Option<String>,
+    pub index_name: Option<String>,
+    /// RECORD or TABLE
+    pub lock_type: Option<String>,
+    pub waiting_lock_mode: Option<String>,
+    pub blocking_lock_mode: Option<String>,
+    /// Locked key values, performance_schema only
+    pub lock_data: Option<String>,
+}
+
+/// Who blocks whom
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockGraph {
+    /// performance_schema or sys
+    pub source: String,
+    pub transactions: Vec<LockTransaction>,
+    pub waits: Vec<LockWait>,
+    /// Blocking transactions that are not waiting themselves
+    pub root_blockers: Vec<String>,
+    pub collected_at: String,
+}
+
+/// Lock named in a deadlock report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadlockLock {
+    /// RECORD or TABLE
+    pub lock_type: String,
+    pub table: Option<String>,
+    pub index: Option<String>,
+    pub lock_mode: Option<String>,
+    pub raw: String,
+}
+
+/// Transaction in a deadlock report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeadlockTransaction {
+    /// (1), (2)... as numbered by InnoDB
+    pub number: u32,
+    pub trx_id: Option<String>,
+    pub active_secs: Option<u64>,
+    pub thread_id: Option<u64>,
+    pub host: Option<String>,
+    pub user: Option<String>,
+    pub query: Option<String>,
+    pub holds: Vec<DeadlockLock>,
+    pub waits_for: Vec<DeadlockLock>,
+    pub rolled_back: bool,
+}
+
+/// LATEST DETECTED DEADLOCK section of SHOW ENGINE INNODB STATUS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadlockReport {
+    pub detected_at: Option<String>,
+    pub transactions: Vec<DeadlockTransaction>,
+    pub raw: String,
+}
+
 /// Query explain result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExplainResult {
@@ -1661,6 +3081,72 @@ pub struct ExplainResult {
     pub rows: Vec<serde_json::Value>,
 }
 
+/// How a structured plan was produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExplainFormat {
+    /// EXPLAIN FORMAT=JSON, optimizer estimates only
+    Json,
+    /// EXPLAIN ANALYZE (MySQL 8.0.18+), runs the query and measures it
+    Analyze,
+}
+
+/// Request for a structured query plan
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExplainPlanRequest {
+    pub connection_id: i64,
+    pub database: String,
+    pub query: String,
+    /// Use EXPLAIN ANALYZE; only SELECT statements are accepted
+    #[serde(default)]
+    pub analyze: bool,
+}
+
+/// Costly operations spotted in a plan node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanFlag {
+    FullTableScan,
+    Filesort,
+    TemporaryTable,
+}
+
+/// One operation in a query plan tree
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlanNode {
+    pub operation: String,
+    pub table: Option<String>,
+    /// EXPLAIN access type: ALL, index, range, ref, eq_ref, const...
+    pub access_type: Option<String>,
+    pub key: Option<String>,
+    pub condition: Option<String>,
+    pub cost: Option<f64>,
+    pub estimated_rows: Option<f64>,
+    /// Rows per loop, EXPLAIN ANALYZE only
+    pub actual_rows: Option<f64>,
+    pub loops: Option<u64>,
+    /// Milliseconds to the first and to the last row, per loop
+    pub first_row_ms: Option<f64>,
+    pub last_row_ms: Option<f64>,
+    pub flags: Vec<PlanFlag>,
+    pub warnings: Vec<String>,
+    pub children: Vec<PlanNode>,
+}
+
+/// Structured query plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainPlan {
+    pub query: String,
+    pub format: ExplainFormat,
+    pub root: PlanNode,
+    /// Flags found anywhere in the tree
+    pub flags: Vec<PlanFlag>,
+    /// Notes from SHOW WARNINGS after the EXPLAIN
+    pub warnings: Vec<String>,
+    /// Unparsed EXPLAIN output
+    pub raw: String,
+}
+
 /// Table check/repair result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableMaintenanceResult {
@@ -1669,3 +3155,454 @@ pub struct TableMaintenanceResult {
     pub msg_type: String,
     pub msg_text: String,
 }
+
+// ==================== Workload Analysis Types ====================
+
+/// Ordering of statement digests in a top-queries report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestRanking {
+    #[default]
+    TotalLatency,
+    /// Rows examined per row sent
+    RowsExaminedRatio,
+    /// On-disk then in-memory temporary tables
+    TmpTables,
+    /// Executions that used no index
+    FullScans,
+    ExecCount,
+}
+
+/// Statistics of one normalized statement from
+/// performance_schema.events_statements_summary_by_digest
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatementDigest {
+    pub schema_name: Option<String>,
+    pub digest: String,
+    pub digest_text: String,
+    pub exec_count: u64,
+    pub total_latency_ms: f64,
+    pub avg_latency_ms: f64,
+    pub max_latency_ms: f64,
+    pub lock_latency_ms: f64,
+    pub rows_examined: u64,
+    pub rows_sent: u64,
+    pub rows_affected: u64,
+    pub rows_examined_per_sent: f64,
+    pub tmp_tables: u64,
+    pub tmp_disk_tables: u64,
+    pub full_scans: u64,
+    pub errors: u64,
+    pub first_seen: Option<String>,
+    pub last_seen: Option<String>,
+    /// Runnable example of the statement (MySQL 8.0+), used for EXPLAIN
+    pub sample_query: Option<String>,
+    /// Average latency is in the slowest 5%; needs the sys schema
+    pub in_95th_percentile: Option<bool>,
+}
+
+/// Request for the most expensive statements
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopQueriesRequest {
+    pub connection_id: i64,
+    /// Only statements run with this default schema
+    pub schema: Option<String>,
+    #[serde(default)]
+    pub rank_by: DigestRanking,
+    pub limit: Option<u32>,
+}
+
+/// Most expensive statements by the requested ranking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopQueriesReport {
+    pub rank_by: DigestRanking,
+    pub digests: Vec<StatementDigest>,
+    /// The sys schema was available for percentile data
+    pub sys_schema: bool,
+    pub collected_at: String,
+}
+
+/// Stored copy of the digest counters at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DigestSnapshotInfo {
+    pub id: i64,
+    pub connection_id: i64,
+    pub label: Option<String>,
+    pub taken_at: String,
+    pub digest_count: i64,
+}
+
+/// Request to store a digest snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TakeDigestSnapshotRequest {
+    pub connection_id: i64,
+    pub label: Option<String>,
+}
+
+/// Request to take digest snapshots at a fixed interval as a background job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodicDigestSnapshotsRequest {
+    pub connection_id: i64,
+    pub label: Option<String>,
+    pub interval_secs: u64,
+    pub count: u32,
+}
+
+/// Compare the workload between two snapshots, or a snapshot and now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareDigestSnapshotsRequest {
+    pub connection_id: i64,
+    pub before_id: i64,
+    /// Compare against the live counters when not set
+    pub after_id: Option<i64>,
+    #[serde(default)]
+    pub rank_by: DigestRanking,
+    pub limit: Option<u32>,
+}
+
+/// Statement activity between two points in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestComparison {
+    pub before: DigestSnapshotInfo,
+    /// None when compared against the live counters
+    pub after: Option<DigestSnapshotInfo>,
+    pub rank_by: DigestRanking,
+    /// Counter differences; max_latency_ms and first_seen are not deltas
+    pub digests: Vec<StatementDigest>,
+    /// Digests that first appeared after the earlier point
+    pub new_digests: Vec<String>,
+    /// Counters went backwards, e.g. after a restart or TRUNCATE
+    pub counters_reset: bool,
+}
+
+/// EXPLAIN the sample statement of a digest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainDigestRequest {
+    pub connection_id: i64,
+    pub digest: String,
+    /// Schema the statement ran in; also selects among digests shared by schemas
+    pub schema: Option<String>,
+}
+
+// ==================== Index Advisor Types ====================
+
+/// What an index recommendation proposes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexAdviceKind {
+    /// Not read since the server started; drop candidate
+    Unused,
+    /// Duplicates or is a leftmost prefix of another index; drop candidate
+    Redundant,
+    /// Would serve statements that currently scan the table
+    Missing,
+}
+
+/// Request for index advice on a database, or one of its tables
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexAdvisorRequest {
+    pub connection_id: i64,
+    pub database: String,
+    pub table: Option<String>,
+}
+
+/// One index to drop or create
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexAdvice {
+    pub kind: IndexAdviceKind,
+    pub table: String,
+    pub index_name: String,
+    pub columns: Vec<String>,
+    pub reason: String,
+    /// DROP INDEX or CREATE INDEX statement
+    pub statement: String,
+    /// Estimated from information_schema row counts and column widths
+    pub estimated_size_bytes: Option<i64>,
+    /// Index that makes a redundant index unnecessary
+    pub covered_by: Option<String>,
+    /// Statement digest a missing index would serve
+    pub digest: Option<String>,
+    pub digest_text: Option<String>,
+    pub full_scans: Option<u64>,
+}
+
+/// Index recommendations for a database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexAdvisorReport {
+    pub database: String,
+    pub advice: Vec<IndexAdvice>,
+    /// Usage statistics only cover the time since the server started
+    pub uptime_secs: Option<u64>,
+    /// Source of the unused index list: sys or performance_schema
+    pub usage_source: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+// ==================== Replication Types ====================
+
+/// Consecutive transaction numbers of a GTID set, both ends included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtidRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Transactions of one source server (and tag, MySQL 8.3+) in a GTID set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GtidSetMember {
+    pub source_uuid: String,
+    pub tag: Option<String>,
+    pub ranges: Vec<GtidRange>,
+}
+
+/// Parsed GTID set such as `3e11fa47-...:1-5:11-18`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GtidSet {
+    pub raw: String,
+    pub members: Vec<GtidSetMember>,
+    pub transaction_count: u64,
+}
+
+/// One replication channel from SHOW REPLICA STATUS / SHOW SLAVE STATUS
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplicaStatus {
+    pub channel_name: String,
+    pub source_host: Option<String>,
+    pub source_port: Option<u16>,
+    pub source_user: Option<String>,
+    pub source_uuid: Option<String>,
+    /// Yes, No or Connecting
+    pub io_running: String,
+    /// Yes or No
+    pub sql_running: String,
+    pub io_state: Option<String>,
+    pub sql_state: Option<String>,
+    /// None while a thread is stopped or the lag is unknown
+    pub seconds_behind_source: Option<i64>,
+    pub sql_delay_secs: Option<i64>,
+    pub source_log_file: Option<String>,
+    pub read_source_log_pos: Option<u64>,
+    pub relay_source_log_file: Option<String>,
+    pub exec_source_log_pos: Option<u64>,
+    pub last_io_errno: Option<i64>,
+    pub last_io_error: Option<String>,
+    pub last_io_error_at: Option<String>,
+    pub last_sql_errno: Option<i64>,
+    pub last_sql_error: Option<String>,
+    pub last_sql_error_at: Option<String>,
+    pub auto_position: bool,
+    pub retrieved_gtid_set: GtidSet,
+    pub executed_gtid_set: GtidSet,
+}
+
+/// SHOW MASTER STATUS / SHOW BINARY LOG STATUS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryLogStatus {
+    pub file: String,
+    pub position: u64,
+    pub do_db: Option<String>,
+    pub ignore_db: Option<String>,
+    pub executed_gtid_set: GtidSet,
+}
+
+/// Member of a Group Replication group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupReplicationMember {
+    pub channel_name: String,
+    pub member_id: String,
+    pub host: String,
+    pub port: Option<u16>,
+    /// ONLINE, RECOVERING, OFFLINE, ERROR or UNREACHABLE
+    pub state: String,
+    /// PRIMARY or SECONDARY, MySQL 8.0+
+    pub role: Option<String>,
+    pub version: Option<String>,
+    /// Transactions waiting for conflict detection
+    pub transactions_in_queue: Option<u64>,
+    /// Transactions waiting to be applied, MySQL 8.0+
+    pub applier_queue: Option<u64>,
+}
+
+/// Replication health at a glance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationHealth {
+    pub healthy: bool,
+    pub is_replica: bool,
+    pub is_group_member: bool,
+    pub binary_logging: bool,
+    /// Highest lag over all channels
+    pub max_lag_secs: Option<i64>,
+    pub issues: Vec<String>,
+}
+
+/// Replication state of a server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationStatus {
+    pub gtid_mode: Option<String>,
+    pub binary_log: Option<BinaryLogStatus>,
+    pub replicas: Vec<ReplicaStatus>,
+    pub group_members: Vec<GroupReplicationMember>,
+    pub health: ReplicationHealth,
+    pub collected_at: String,
+}
+
+/// Stored replica lag sample
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ReplicationLagSample {
+    pub id: i64,
+    pub connection_id: i64,
+    pub channel_name: String,
+    pub lag_secs: Option<i64>,
+    pub io_running: bool,
+    pub sql_running: bool,
+    pub recorded_at: String,
+}
+
+/// Request to record replica lag at a fixed interval as a background job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationLagPollRequest {
+    pub connection_id: i64,
+    pub interval_secs: u64,
+    /// Poll until cancelled when not set
+    pub count: Option<u32>,
+}
+
+// ==================== Server Configuration Types ====================
+
+/// Scope at which a server variable exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableScope {
+    Global,
+    Session,
+    Both,
+}
+
+/// Value type of a server variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableType {
+    Boolean,
+    Integer,
+    Float,
+    Enumeration,
+    Set,
+    String,
+}
+
+/// Global server variable with its metadata. Scope and whether it can be
+/// changed at runtime are only known for well-known variables.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerVariableInfo {
+    pub name: String,
+    pub value: String,
+    pub scope: Option<VariableScope>,
+    pub dynamic: Option<bool>,
+    pub var_type: VariableType,
+    pub min_value: Option<String>,
+    pub max_value: Option<String>,
+    /// Accepted values of enumeration and set variables
+    pub allowed_values: Vec<String>,
+    /// Where the value came from (COMPILED, PERSISTED, DYNAMIC, ...), MySQL 8.0+
+    pub source: Option<String>,
+    pub set_by: Option<String>,
+    pub set_at: Option<String>,
+}
+
+/// Statement used to change a variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableSetMode {
+    /// SET GLOBAL
+    Global,
+    /// SET SESSION, on a pinned session
+    Session,
+    /// SET PERSIST, applied now and after restarts
+    Persist,
+    /// SET PERSIST_ONLY, applied after the next restart
+    PersistOnly,
+}
+
+impl VariableSetMode {
+    /// Keyword following SET
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            VariableSetMode::Global => "GLOBAL",
+            VariableSetMode::Session => "SESSION",
+            VariableSetMode::Persist => "PERSIST",
+            VariableSetMode::PersistOnly => "PERSIST_ONLY",
+        }
+    }
+}
+
+/// Request to change a server variable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetServerVariableRequest {
+    pub connection_id: i64,
+    pub name: String,
+    /// New value, or DEFAULT
+    pub value: String,
+    pub mode: VariableSetMode,
+    /// Pinned session to change, required for session changes
+    pub session_id: Option<String>,
+}
+
+/// Audited server variable change
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ServerVariableChange {
+    pub id: i64,
+    pub connection_id: i64,
+    pub variable_name: String,
+    /// GLOBAL, SESSION, PERSIST or PERSIST_ONLY
+    pub mode: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: String,
+}
+
+/// Stored snapshot of a server's global variables
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct VariableBaselineInfo {
+    pub id: i64,
+    pub connection_id: i64,
+    pub label: Option<String>,
+    pub variable_count: i64,
+    pub created_at: String,
+}
+
+/// Request to store the current global variables as a baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateVariableBaselineRequest {
+    pub connection_id: i64,
+    pub label: Option<String>,
+}
+
+/// Request to compare global variables with a baseline or another server.
+/// Exactly one of `baseline_id` and `other_connection_id` must be set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareServerVariablesRequest {
+    pub connection_id: i64,
+    pub baseline_id: Option<i64>,
+    pub other_connection_id: Option<i64>,
+    /// Also compare variables that always differ between servers
+    /// (server_uuid, hostname, file paths, ...)
+    #[serde(default)]
+    pub include_server_specific: bool,
+}
+
+/// Variable whose value differs; None when it only exists on one side
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariableDifference {
+    pub name: String,
+    pub current: Option<String>,
+    pub other: Option<String>,
+}
+
+/// Differences between the current configuration and a baseline or server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableComparison {
+    /// Baseline label or connection name compared against
+    pub compared_with: String,
+    pub compared_count: usize,
+    pub differences: Vec<VariableDifference>,
+}
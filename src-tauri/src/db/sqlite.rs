@@ -10,10 +10,11 @@ use std::path::Path;
 
 use crate::db::models::{
     Cluster, Connection, PortForward,
-    QueryHistory, AddQueryHistoryRequest,
+    QueryHistory, AddQueryHistoryRequest, QUERY_HISTORY_STATUSES,
     SavedQuery, CreateSavedQueryRequest, UpdateSavedQueryRequest,
     UserSetting, LLMConfig,
     K8sFavorite, K8sFavoriteWithCluster, CreateK8sFavoriteRequest, UpdateK8sFavoriteRequest,
+    DigestSnapshotInfo, ReplicationLagSample, ServerVariableChange, VariableBaselineInfo,
 };
 use crate::error::{AppError, AppResult};
 
@@ -208,6 +209,15 @@ impl SqlitePool {
         .execute(&self.pool)
         .await?;
 
+        // Add parameters column if not exists (migration for existing databases)
+        let _ = sqlx::query(
+            r#"
+            ALTER TABLE saved_queries ADD COLUMN parameters TEXT NOT NULL DEFAULT '[]'
+            "#,
+        )
+        .execute(&self.pool)
+        .await;
+
         // Create indexes for saved_queries
         sqlx::query(
             r#"
@@ -308,6 +318,92 @@ impl SqlitePool {
         .execute(&self.pool)
         .await?;
 
+        // Create digest_snapshots table (performance_schema statement digests as JSON)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS digest_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                connection_id INTEGER NOT NULL,
+                label TEXT,
+                taken_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                digest_count INTEGER NOT NULL,
+                digests TEXT NOT NULL,
+                FOREIGN KEY (connection_id) REFERENCES connections(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_digest_snapshots_connection ON digest_snapshots(connection_id, id)
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create replication_lag_samples table (polled replica lag per channel)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS replication_lag_samples (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                connection_id INTEGER NOT NULL,
+                channel_name TEXT NOT NULL,
+                lag_secs INTEGER,
+                io_running INTEGER NOT NULL,
+                sql_running INTEGER NOT NULL,
+                recorded_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (connection_id) REFERENCES connections(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_replication_lag_connection ON replication_lag_samples(connection_id, id)
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create server_variable_changes table (audit of SET GLOBAL/SESSION/PERSIST)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS server_variable_changes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                connection_id INTEGER NOT NULL,
+                variable_name TEXT NOT NULL,
+                mode TEXT NOT NULL,
+                old_value TEXT,
+                new_value TEXT,
+                changed_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (connection_id) REFERENCES connections(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create variable_baselines table (global variables as JSON)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS variable_baselines (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                connection_id INTEGER NOT NULL,
+                label TEXT,
+                variable_count INTEGER NOT NULL,
+                variables TEXT NOT NULL,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (connection_id) REFERENCES connections(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         Ok(())
     }
 
@@ -775,6 +871,13 @@ impl SqlitePool {
 
     /// Add a query history entry
     pub async fn add_query_history(&self, history: &AddQueryHistoryRequest) -> AppResult<QueryHistory> {
+        if !QUERY_HISTORY_STATUSES.contains(&history.status.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Invalid query history status: {}",
+                history.status
+            )));
+        }
+
         let result = sqlx::query(
             r#"
             INSERT INTO query_history (connection_id, database, query_type, query_text, duration_ms, row_count, status, error_message)
@@ -840,7 +943,7 @@ impl SqlitePool {
         let queries = if let Some(cat) = category {
             sqlx::query_as::<_, SavedQuery>(
                 r#"
-                SELECT id, connection_id, database, name, query_text, description, category, created_at, updated_at
+                SELECT id, connection_id, database, name, query_text, description, category, parameters, created_at, updated_at
                 FROM saved_queries
                 WHERE category = ?
                 ORDER BY name
@@ -852,7 +955,7 @@ impl SqlitePool {
         } else {
             sqlx::query_as::<_, SavedQuery>(
                 r#"
-                SELECT id, connection_id, database, name, query_text, description, category, created_at, updated_at
+                SELECT id, connection_id, database, name, query_text, description, category, parameters, created_at, updated_at
                 FROM saved_queries
                 ORDER BY category, name
                 "#,
@@ -868,7 +971,7 @@ impl SqlitePool {
     pub async fn get_saved_query(&self, id: i64) -> AppResult<SavedQuery> {
         let query = sqlx::query_as::<_, SavedQuery>(
             r#"
-            SELECT id, connection_id, database, name, query_text, description, category, created_at, updated_at
+            SELECT id, connection_id, database, name, query_text, description, category, parameters, created_at, updated_at
             FROM saved_queries WHERE id = ?
             "#,
         )
@@ -883,8 +986,8 @@ impl SqlitePool {
     pub async fn create_saved_query(&self, query: &CreateSavedQueryRequest) -> AppResult<SavedQuery> {
         let result = sqlx::query(
             r#"
-            INSERT INTO saved_queries (connection_id, database, name, query_text, description, category)
-            VALUES (?, ?, ?, ?, ?, ?)
+            INSERT INTO saved_queries (connection_id, database, name, query_text, description, category, parameters)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(query.connection_id)
@@ -893,6 +996,7 @@ impl SqlitePool {
         .bind(&query.query_text)
         .bind(&query.description)
         .bind(&query.category)
+        .bind(serde_json::to_string(&query.parameters)?)
         .execute(&self.pool)
         .await?;
 
@@ -922,6 +1026,10 @@ impl SqlitePool {
             updates.push("category = ?");
             params.push(category.clone());
         }
+        if let Some(parameters) = &update.parameters {
+            updates.push("parameters = ?");
+            params.push(serde_json::to_string(parameters)?);
+        }
 
         if updates.is_empty() {
             return self.get_saved_query(id).await;
@@ -1341,6 +1449,338 @@ impl SqlitePool {
 
         Ok(())
     }
+
+    // ==================== Digest Snapshot Operations ====================
+
+    /// Get digest snapshots of a connection, newest first
+    pub async fn get_digest_snapshots(
+        &self,
+        connection_id: i64,
+    ) -> AppResult<Vec<DigestSnapshotInfo>> {
+        let snapshots = sqlx::query_as::<_, DigestSnapshotInfo>(
+            r#"
+            SELECT id, connection_id, label, taken_at, digest_count
+            FROM digest_snapshots
+            WHERE connection_id = ?
+            ORDER BY id DESC
+            "#,
+        )
+        .bind(connection_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(snapshots)
+    }
+
+    /// Get a digest snapshot with its digests as stored JSON
+    pub async fn get_digest_snapshot(&self, id: i64) -> AppResult<(DigestSnapshotInfo, String)> {
+        let snapshot = sqlx::query_as::<_, DigestSnapshotInfo>(
+            r#"
+            SELECT id, connection_id, label, taken_at, digest_count
+            FROM digest_snapshots WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Digest snapshot {} not found", id)))?;
+
+        let digests: String =
+            sqlx::query_scalar("SELECT digests FROM digest_snapshots WHERE id = ?")
+                .bind(id)
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok((snapshot, digests))
+    }
+
+    /// Store a digest snapshot, keeping only the newest `keep` per connection
+    pub async fn create_digest_snapshot(
+        &self,
+        connection_id: i64,
+        label: Option<&str>,
+        digest_count: usize,
+        digests: &str,
+        keep: i64,
+    ) -> AppResult<DigestSnapshotInfo> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO digest_snapshots (connection_id, label, digest_count, digests)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(connection_id)
+        .bind(label)
+        .bind(digest_count as i64)
+        .bind(digests)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM digest_snapshots
+            WHERE connection_id = ? AND id NOT IN (
+                SELECT id FROM digest_snapshots WHERE connection_id = ? ORDER BY id DESC LIMIT ?
+            )
+            "#,
+        )
+        .bind(connection_id)
+        .bind(connection_id)
+        .bind(keep)
+        .execute(&self.pool)
+        .await?;
+
+        let (snapshot, _) = self.get_digest_snapshot(result.last_insert_rowid()).await?;
+        Ok(snapshot)
+    }
+
+    /// Delete a digest snapshot
+    pub async fn delete_digest_snapshot(&self, id: i64) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM digest_snapshots WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!(
+                "Digest snapshot {} not found",
+                id
+            )));
+        }
+
+        Ok(())
+    }
+
+    // ==================== Replication Lag Operations ====================
+
+    /// Get the newest lag samples of a connection, oldest first
+    pub async fn get_replication_lag_samples(
+        &self,
+        connection_id: i64,
+        channel_name: Option<&str>,
+        limit: i64,
+    ) -> AppResult<Vec<ReplicationLagSample>> {
+        let samples = sqlx::query_as::<_, ReplicationLagSample>(
+            r#"
+            SELECT * FROM (
+                SELECT id, connection_id, channel_name, lag_secs, io_running, sql_running, recorded_at
+                FROM replication_lag_samples
+                WHERE connection_id = ? AND (? IS NULL OR channel_name = ?)
+                ORDER BY id DESC
+                LIMIT ?
+            ) ORDER BY id
+            "#,
+        )
+        .bind(connection_id)
+        .bind(channel_name)
+        .bind(channel_name)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(samples)
+    }
+
+    /// Record a lag sample, keeping only the newest `keep` per connection
+    pub async fn add_replication_lag_sample(
+        &self,
+        connection_id: i64,
+        channel_name: &str,
+        lag_secs: Option<i64>,
+        io_running: bool,
+        sql_running: bool,
+        keep: i64,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO replication_lag_samples (connection_id, channel_name, lag_secs, io_running, sql_running)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(connection_id)
+        .bind(channel_name)
+        .bind(lag_secs)
+        .bind(io_running)
+        .bind(sql_running)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM replication_lag_samples
+            WHERE connection_id = ? AND id NOT IN (
+                SELECT id FROM replication_lag_samples WHERE connection_id = ? ORDER BY id DESC LIMIT ?
+            )
+            "#,
+        )
+        .bind(connection_id)
+        .bind(connection_id)
+        .bind(keep)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delete all lag samples of a connection
+    pub async fn clear_replication_lag_samples(&self, connection_id: i64) -> AppResult<()> {
+        sqlx::query("DELETE FROM replication_lag_samples WHERE connection_id = ?")
+            .bind(connection_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // ==================== Server Variable Operations ====================
+
+    /// Get audited variable changes of a connection, newest first
+    pub async fn get_server_variable_changes(
+        &self,
+        connection_id: i64,
+        limit: i64,
+    ) -> AppResult<Vec<ServerVariableChange>> {
+        let changes = sqlx::query_as::<_, ServerVariableChange>(
+            r#"
+            SELECT id, connection_id, variable_name, mode, old_value, new_value, changed_at
+            FROM server_variable_changes
+            WHERE connection_id = ?
+            ORDER BY id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(connection_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(changes)
+    }
+
+    /// Record a variable change
+    pub async fn add_server_variable_change(
+        &self,
+        connection_id: i64,
+        variable_name: &str,
+        mode: &str,
+        old_value: Option<&str>,
+        new_value: Option<&str>,
+    ) -> AppResult<ServerVariableChange> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO server_variable_changes (connection_id, variable_name, mode, old_value, new_value)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(connection_id)
+        .bind(variable_name)
+        .bind(mode)
+        .bind(old_value)
+        .bind(new_value)
+        .execute(&self.pool)
+        .await?;
+
+        let change = sqlx::query_as::<_, ServerVariableChange>(
+            r#"
+            SELECT id, connection_id, variable_name, mode, old_value, new_value, changed_at
+            FROM server_variable_changes WHERE id = ?
+            "#,
+        )
+        .bind(result.last_insert_rowid())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(change)
+    }
+
+    /// Get variable baselines of a connection, newest first
+    pub async fn get_variable_baselines(
+        &self,
+        connection_id: i64,
+    ) -> AppResult<Vec<VariableBaselineInfo>> {
+        let baselines = sqlx::query_as::<_, VariableBaselineInfo>(
+            r#"
+            SELECT id, connection_id, label, variable_count, created_at
+            FROM variable_baselines
+            WHERE connection_id = ?
+            ORDER BY id DESC
+            "#,
+        )
+        .bind(connection_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(baselines)
+    }
+
+    /// Get a variable baseline with its variables as stored JSON
+    pub async fn get_variable_baseline(
+        &self,
+        id: i64,
+    ) -> AppResult<(VariableBaselineInfo, String)> {
+        let baseline = sqlx::query_as::<_, VariableBaselineInfo>(
+            r#"
+            SELECT id, connection_id, label, variable_count, created_at
+            FROM variable_baselines WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Variable baseline {} not found", id)))?;
+
+        let variables: String =
+            sqlx::query_scalar("SELECT variables FROM variable_baselines WHERE id = ?")
+                .bind(id)
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok((baseline, variables))
+    }
+
+    /// Store a variable baseline
+    pub async fn create_variable_baseline(
+        &self,
+        connection_id: i64,
+        label: Option<&str>,
+        variable_count: usize,
+        variables: &str,
+    ) -> AppResult<VariableBaselineInfo> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO variable_baselines (connection_id, label, variable_count, variables)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(connection_id)
+        .bind(label)
+        .bind(variable_count as i64)
+        .bind(variables)
+        .execute(&self.pool)
+        .await?;
+
+        let (baseline, _) = self
+            .get_variable_baseline(result.last_insert_rowid())
+            .await?;
+        Ok(baseline)
+    }
+
+    /// Delete a variable baseline
+    pub async fn delete_variable_baseline(&self, id: i64) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM variable_baselines WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!(
+                "Variable baseline {} not found",
+                id
+            )));
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1462,4 +1902,37 @@ mod tests {
         let result = pool.get_connection(id).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_digest_snapshots_keep_newest() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let pool = SqlitePool::new(&db_path).await.unwrap();
+
+        let conn = Connection {
+            name: "Workload".to_string(),
+            conn_type: "mysql".to_string(),
+            host: "localhost".to_string(),
+            port: 3306,
+            ..Default::default()
+        };
+        let connection_id = pool.create_connection(&conn).await.unwrap().id.unwrap();
+
+        for label in ["first", "second", "third"] {
+            pool.create_digest_snapshot(connection_id, Some(label), 0, "[]", 2)
+                .await
+                .unwrap();
+        }
+
+        let snapshots = pool.get_digest_snapshots(connection_id).await.unwrap();
+        let labels: Vec<_> = snapshots
+            .iter()
+            .map(|s| s.label.as_deref().unwrap())
+            .collect();
+        assert_eq!(labels, vec!["third", "second"]);
+
+        let (snapshot, digests) = pool.get_digest_snapshot(snapshots[0].id).await.unwrap();
+        assert_eq!(snapshot.label.as_deref(), Some("third"));
+        assert_eq!(digests, "[]");
+    }
 }
@@ -34,6 +34,9 @@ pub enum AppError {
     #[error("Port forward error: {0}")]
     PortForward(String),
 
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -5,48 +5,71 @@
 
 use std::net::TcpListener;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
+    http::{header, HeaderMap, HeaderName, StatusCode},
     response::{
         sse::{Event, KeepAlive, Sse},
-        IntoResponse,
+        IntoResponse, Response,
     },
     routing::{delete, get, post, put},
     Json, Router,
 };
 use futures::stream::Stream;
-use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::{BroadcastStream, ReceiverStream};
 use tokio_stream::StreamExt;
 use k8s_openapi::api::core::v1::Pod;
 use kube::config::{KubeConfigOptions, Kubeconfig};
 use kube::{Api, Client, Config};
 use serde::Deserialize;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 use tower_http::cors::{Any, CorsLayer};
 
 use crate::db::models::{
-    AddQueryHistoryRequest, AlterTableRequest, AlterUserPasswordRequest, Cluster, Connection,
-    CopyTableRequest, CreateDatabaseRequest, CreateForeignKeyRequest, CreateIndexRequest,
-    CreateSavedQueryRequest, CreateTableRequest, CreateUserRequest, CreateViewRequest,
-    DiscoveredService, DropUserRequest, ExplainResult, ExportTableRequest, ExportTableResponse,
+    AccountDetails, AddQueryHistoryRequest, AlterAccountRequest, AlterEventRequest, AlterTableRequest, AlterUserPasswordRequest, ClonePrivilegesRequest, ClonePrivilegesResult, Cluster,
+    CompareDigestSnapshotsRequest, CompareServerVariablesRequest, Connection, CopyTableRequest, CreateDatabaseRequest, CreateEventRequest, CreateForeignKeyRequest, CreateIndexRequest,
+    CreateSavedQueryRequest, CreateTableRequest, CreateUserRequest, CreateVariableBaselineRequest, CreateViewRequest,
+    DeadlockReport, DigestComparison, DigestSnapshotInfo, DiscoveredService, DropUserRequest, ErDiagramRequest, ErDiagramResult, EventDefinition, EventInfo,
+    EventSchedulerStatus, ExplainDigestRequest, ExplainPlan, ExplainPlanRequest, ExplainResult, ExportFormat, ExportQueryRequest,
+    ExportTableRequest, ExportTableResponse, FilterPreset,
     ForeignKeyInfo, GrantPrivilegesRequest, ImportConnectionResult, ImportConnectionsRequest,
-    ImportConnectionsResponse, ImportDataRequest, ImportResult, IndexInfo, ListClustersResponse,
-    MysqlDatabase, MysqlQueryResult, MysqlServerInfo, MysqlTable, MysqlTableData, MysqlTableSchema,
-    MysqlUserInfo, PortForward, ProcedureDefinition, ProcedureInfo, ProcessInfo, QueryHistory,
-    QueryHistoryListResponse, RedisKeyListResponse, RedisKeyValue, RedisServerInfo, RenameTableRequest,
-    RevokePrivilegesRequest, SavedQuery, ServerVariable, SetKeyRequest, TableMaintenanceResult,
-    TestConnectionRequest, TestConnectionResult, TestK8sConnectionRequest, TriggerDefinition,
+    ImportConnectionsResponse, ImportDataRequest, ImportFileRequest, ImportResult, IndexAdvisorReport, IndexAdvisorRequest, IndexInfo,
+    InferredTable, KeysetPage, KeysetPageRequest, ListClustersResponse, LockGraph,
+    MysqlCallRequest, MysqlCallResult, MysqlDatabase, MysqlDumpRequest, MysqlParameterizedQueryRequest, MysqlQueryPage,
+    MysqlQueryResult, MysqlRestoreRequest,
+    MysqlScriptRequest, MysqlScriptResult,
+    MysqlServerInfo, MysqlSessionInfo,
+    MysqlSessionQueryRequest, MysqlTable, MysqlTableData, MysqlTableSchema, MysqlUserInfo,
+    OpenMysqlSessionRequest, PeriodicDigestSnapshotsRequest, PortForward, PrivilegeMatrix, ProcedureDefinition, ProcedureInfo, ProcessInfo,
+    QueryHistory,
+    QueryHistoryListResponse, RedisAclDiff, RedisAclLogEntry, RedisAclUser, RedisAclUserSpec, RedisApplySyncPlanRequest,
+    RedisCompareRequest, RedisKeyListResponse,
+    RedisKeyValue, RedisServerInfo, RenameTableRequest, ReplicationLagPollRequest, ReplicationLagSample, ReplicationStatus,
+    RevokePrivilegesRequest, RoleGrantRequest, RoleRequest, RoutineParameterInfo, SaveStoredProgramRequest,
+    SaveStoredProgramResult, SavedQuery, SchemaCompareReport, SchemaCompareRequest,
+    ServerVariable, ServerVariableChange, ServerVariableInfo, SetDefaultRolesRequest, SetEventEnabledRequest, SetKeyRequest, SetServerVariableRequest, TableCopyRequest, TableMaintenanceResult, TableRowsOptions,
+    TakeDigestSnapshotRequest, TestConnectionRequest, TestConnectionResult, TestK8sConnectionRequest,
+    TopQueriesReport, TopQueriesRequest, TriggerDefinition,
     TriggerInfo, UpdateConnectionRequest, UpdateSavedQueryRequest, UserGrantsResponse,
-    ViewDefinition, ViewInfo,
+    VariableBaselineInfo, VariableComparison, ViewDefinition, ViewInfo,
 };
 use crate::db::SqlitePool;
 use crate::error::AppError;
+use crate::services::mysql_export::{content_type, file_extension, ExportSink};
+use crate::services::mysql_import::{infer_table, INFER_SAMPLE_ROWS};
+use crate::services::query_execution::record_cancelled;
+use crate::services::query_params::{extract_parameters, resolve_request, validate_parameters};
 use crate::services::{
-    AddLogRequest, ClusterService, ConnectionService, K8sService, LogEntry, LogService, MysqlService,
-    PortForwardService, RedisService,
+    split_statements, AddLogRequest, ClusterService, ConnectionService, ErDiagramService, JobInfo,
+    JobService, JobStarted, K8sService, LogEntry, LogService, MysqlAccountService,
+    MysqlDumpService, MysqlExplainService, MysqlExportService, MysqlImportService,
+    MysqlIndexAdvisorService, MysqlLockService, MysqlReplicationService, MysqlRoutineService,
+    MysqlService, MysqlSessionService, MysqlTableCopyService, MysqlVariableService,
+    MysqlWorkloadService, PortForwardService, QueryCursorService, QueryExecutionService,
+    QueryLimits, RedisCompareService, RedisService, SchemaCompareService, SqlStatement,
 };
 
 /// Application state shared across all routes
@@ -55,6 +78,10 @@ pub struct AppState {
     pub pool: SqlitePool,
     pub port_forward_service: Arc<RwLock<PortForwardService>>,
     pub log_service: LogService,
+    pub job_service: JobService,
+    pub query_cursors: QueryCursorService,
+    pub query_executions: QueryExecutionService,
+    pub mysql_sessions: MysqlSessionService,
 }
 
 /// Create the HTTP router with all API routes
@@ -65,6 +92,10 @@ pub fn create_router(pool: SqlitePool, pf_service: PortForwardService) -> Router
         pool,
         port_forward_service: Arc::new(RwLock::new(pf_service)),
         log_service,
+        job_service: JobService::new(),
+        query_cursors: QueryCursorService::new(),
+        query_executions: QueryExecutionService::new(),
+        mysql_sessions: MysqlSessionService::new(),
     });
 
     let cors = CorsLayer::new()
@@ -140,7 +171,35 @@ pub fn create_router(pool: SqlitePool, pf_service: PortForwardService) -> Router
         .route("/api/mysql/databases/:db/tables/:table/rows", post(mysql_insert_row))
         .route("/api/mysql/databases/:db/tables/:table/rows", put(mysql_update_record))
         .route("/api/mysql/databases/:db/tables/:table/rows", delete(mysql_delete_row))
+        .route(
+            "/api/mysql/databases/:db/tables/:table/rows/query",
+            post(mysql_query_rows),
+        )
+        .route(
+            "/api/mysql/databases/:db/tables/:table/rows/keyset",
+            post(mysql_get_rows_keyset),
+        )
+        .route(
+            "/api/mysql/databases/:db/tables/:table/filter-presets",
+            get(mysql_get_filter_presets),
+        )
+        .route(
+            "/api/mysql/databases/:db/tables/:table/filter-presets",
+            post(mysql_save_filter_preset),
+        )
+        .route(
+            "/api/mysql/databases/:db/tables/:table/filter-presets/:name",
+            delete(mysql_delete_filter_preset),
+        )
         .route("/api/mysql/query", post(mysql_execute_query))
+        .route("/api/mysql/query/cursors/:cursor_id", get(mysql_fetch_query_page))
+        .route("/api/mysql/query/cursors/:cursor_id", delete(mysql_close_query_cursor))
+        .route("/api/mysql/query/executions", get(mysql_list_running_queries))
+        .route("/api/mysql/query/executions/:execution_id/cancel", post(mysql_cancel_query))
+        .route("/api/mysql/script", post(mysql_execute_script))
+        .route("/api/mysql/script/split", post(mysql_split_script))
+        .route("/api/mysql/query/parameterized", post(mysql_execute_parameterized))
+        .route("/api/mysql/query/parameters", post(mysql_extract_query_parameters))
         // MySQL table management routes
         .route("/api/mysql/databases/:db/tables", post(mysql_create_table))
         .route("/api/mysql/databases/:db/tables/:table", put(mysql_alter_table))
@@ -151,13 +210,32 @@ pub fn create_router(pool: SqlitePool, pf_service: PortForwardService) -> Router
         .route("/api/mysql/databases/:db/tables/:table/indexes", get(mysql_list_indexes))
         .route("/api/mysql/databases/:db/tables/:table/indexes", post(mysql_create_index))
         .route("/api/mysql/databases/:db/tables/:table/indexes/:index", delete(mysql_drop_index))
+        .route("/api/mysql/index-advisor", post(mysql_index_advice))
         // MySQL foreign key management routes
         .route("/api/mysql/databases/:db/tables/:table/foreign-keys", get(mysql_list_foreign_keys))
         .route("/api/mysql/databases/:db/tables/:table/foreign-keys", post(mysql_create_foreign_key))
         .route("/api/mysql/databases/:db/tables/:table/foreign-keys/:fk", delete(mysql_drop_foreign_key))
+        // MySQL schema comparison routes
+        .route("/api/mysql/schema/compare", post(mysql_compare_schemas))
+        .route("/api/mysql/schema/er-diagram", post(mysql_er_diagram))
         // MySQL data export/import routes
         .route("/api/mysql/databases/:db/tables/:table/export", post(mysql_export_table))
+        .route(
+            "/api/mysql/databases/:db/tables/:table/export/download",
+            post(mysql_download_table_export),
+        )
+        .route("/api/mysql/query/export", post(mysql_download_query_export))
         .route("/api/mysql/databases/:db/tables/:table/import", post(mysql_import_data))
+        .route("/api/mysql/import/file", post(mysql_import_file))
+        .route("/api/mysql/import/infer", post(mysql_infer_import_schema))
+        // MySQL dump and restore routes
+        .route("/api/mysql/dump", post(mysql_dump_database))
+        .route("/api/mysql/restore", post(mysql_restore_database))
+        // MySQL cross-connection table copy routes
+        .route(
+            "/api/mysql/table-copy",
+            post(mysql_copy_table_between_connections),
+        )
         // MySQL user management routes
         .route("/api/mysql/users", get(mysql_list_users))
         .route("/api/mysql/users", post(mysql_create_user))
@@ -166,6 +244,24 @@ pub fn create_router(pool: SqlitePool, pf_service: PortForwardService) -> Router
         .route("/api/mysql/users/grants", get(mysql_show_grants))
         .route("/api/mysql/users/grant", post(mysql_grant_privileges))
         .route("/api/mysql/users/revoke", post(mysql_revoke_privileges))
+        .route(
+            "/api/mysql/users/privileges",
+            get(mysql_get_privilege_matrix),
+        )
+        .route("/api/mysql/users/details", get(mysql_get_account_details))
+        .route("/api/mysql/users/account", put(mysql_alter_account))
+        .route(
+            "/api/mysql/users/default-roles",
+            put(mysql_set_default_roles),
+        )
+        .route(
+            "/api/mysql/users/clone-privileges",
+            post(mysql_clone_user_privileges),
+        )
+        .route("/api/mysql/roles", post(mysql_create_role))
+        .route("/api/mysql/roles/drop", post(mysql_drop_role))
+        .route("/api/mysql/roles/grant", post(mysql_grant_role))
+        .route("/api/mysql/roles/revoke", post(mysql_revoke_role))
         // MySQL view management routes
         .route("/api/mysql/databases/:db/views", get(mysql_list_views))
         .route("/api/mysql/databases/:db/views", post(mysql_create_view))
@@ -180,16 +276,66 @@ pub fn create_router(pool: SqlitePool, pf_service: PortForwardService) -> Router
         .route("/api/mysql/databases/:db/triggers", get(mysql_list_triggers))
         .route("/api/mysql/databases/:db/triggers/:name", get(mysql_get_trigger_definition))
         .route("/api/mysql/databases/:db/triggers/:name", delete(mysql_drop_trigger))
+        // MySQL stored program editing routes
+        .route("/api/mysql/databases/:db/stored-programs", post(mysql_create_stored_program))
+        .route("/api/mysql/databases/:db/stored-programs/:name", put(mysql_alter_stored_program))
+        .route(
+            "/api/mysql/databases/:db/procedures/:name/parameters",
+            get(mysql_get_procedure_parameters),
+        )
+        .route("/api/mysql/call", post(mysql_call_procedure))
+        // MySQL event management routes
+        .route("/api/mysql/databases/:db/events", get(mysql_list_events))
+        .route("/api/mysql/databases/:db/events", post(mysql_create_event))
+        .route("/api/mysql/databases/:db/events/:name", get(mysql_get_event_definition))
+        .route("/api/mysql/databases/:db/events/:name", put(mysql_alter_event))
+        .route("/api/mysql/databases/:db/events/:name", delete(mysql_drop_event))
+        .route("/api/mysql/databases/:db/events/:name/enabled", put(mysql_set_event_enabled))
+        .route("/api/mysql/server/event-scheduler", get(mysql_get_event_scheduler_status))
         // MySQL server monitoring routes
         .route("/api/mysql/server/variables", get(mysql_get_server_variables))
         .route("/api/mysql/server/processes", get(mysql_get_process_list))
         .route("/api/mysql/server/processes/:id", delete(mysql_kill_process))
+        // MySQL server configuration routes
+        .route("/api/mysql/server/variables", put(mysql_set_server_variable))
+        .route("/api/mysql/server/variables/info", get(mysql_get_server_variable_info))
+        .route("/api/mysql/server/variables/changes", get(mysql_get_server_variable_changes))
+        .route("/api/mysql/server/variables/baselines", get(mysql_list_variable_baselines))
+        .route("/api/mysql/server/variables/baselines", post(mysql_create_variable_baseline))
+        .route("/api/mysql/server/variables/baselines/:id", delete(mysql_delete_variable_baseline))
+        .route("/api/mysql/server/variables/compare", post(mysql_compare_server_variables))
+        // MySQL lock inspection routes
+        .route("/api/mysql/server/lock-waits", get(mysql_get_lock_waits))
+        .route("/api/mysql/server/lock-waits/blockers/:id", delete(mysql_kill_lock_blocker))
+        .route("/api/mysql/server/deadlock", get(mysql_get_latest_deadlock))
+        // MySQL replication monitoring routes
+        .route("/api/mysql/server/replication", get(mysql_get_replication_status))
+        .route("/api/mysql/server/replication/lag/poll", post(mysql_start_replication_lag_polling))
+        .route("/api/mysql/server/replication/lag", get(mysql_get_replication_lag_history).delete(mysql_clear_replication_lag_history))
         // MySQL query analysis routes
         .route("/api/mysql/explain", post(mysql_explain_query))
+        .route("/api/mysql/explain/plan", post(mysql_explain_plan))
+        // MySQL workload analysis routes
+        .route("/api/mysql/workload/top-queries", post(mysql_get_top_queries))
+        .route("/api/mysql/workload/snapshots", get(mysql_list_digest_snapshots))
+        .route("/api/mysql/workload/snapshots", post(mysql_take_digest_snapshot))
+        .route("/api/mysql/workload/snapshots/periodic", post(mysql_start_digest_snapshots))
+        .route("/api/mysql/workload/snapshots/:id", delete(mysql_delete_digest_snapshot))
+        .route("/api/mysql/workload/compare", post(mysql_compare_digest_snapshots))
+        .route("/api/mysql/workload/explain", post(mysql_explain_digest))
         // MySQL table maintenance routes
         .route("/api/mysql/databases/:db/tables/:table/optimize", post(mysql_optimize_table))
         .route("/api/mysql/databases/:db/tables/:table/analyze", post(mysql_analyze_table))
         .route("/api/mysql/databases/:db/tables/:table/check", post(mysql_check_table))
+        // MySQL session routes
+        .route("/api/mysql/sessions", get(mysql_session_list))
+        .route("/api/mysql/sessions", post(mysql_session_open))
+        .route("/api/mysql/sessions/:session_id", get(mysql_session_get))
+        .route("/api/mysql/sessions/:session_id", delete(mysql_session_close))
+        .route("/api/mysql/sessions/:session_id/query", post(mysql_session_execute))
+        .route("/api/mysql/sessions/:session_id/begin", post(mysql_session_begin))
+        .route("/api/mysql/sessions/:session_id/commit", post(mysql_session_commit))
+        .route("/api/mysql/sessions/:session_id/rollback", post(mysql_session_rollback))
         // Redis routes
         .route("/api/redis/info", get(redis_get_info))
         .route("/api/redis/keys", get(redis_list_keys))
@@ -197,6 +343,15 @@ pub fn create_router(pool: SqlitePool, pf_service: PortForwardService) -> Router
         .route("/api/redis/keys", post(redis_set_key))
         .route("/api/redis/keys/:key", delete(redis_delete_key))
         .route("/api/redis/keys/:key/ttl", put(redis_set_ttl))
+        .route("/api/redis/acl/users", get(redis_acl_list))
+        .route("/api/redis/acl/users", post(redis_acl_set_user))
+        .route("/api/redis/acl/users/:username", get(redis_acl_get_user))
+        .route("/api/redis/acl/users/:username", delete(redis_acl_del_user))
+        .route("/api/redis/acl/whoami", get(redis_acl_whoami))
+        .route("/api/redis/acl/log", get(redis_acl_log))
+        .route("/api/redis/acl/log", delete(redis_acl_log_reset))
+        .route("/api/redis/compare", post(redis_compare_start))
+        .route("/api/redis/sync", post(redis_apply_sync_plan))
         // History routes
         .route("/api/history", get(get_history))
         .route("/api/history", post(add_history))
@@ -227,6 +382,10 @@ pub fn create_router(pool: SqlitePool, pf_service: PortForwardService) -> Router
         .route("/api/k8s/favorites", get(get_k8s_favorites_http).post(create_k8s_favorite_http))
         .route("/api/k8s/favorites/:id", get(get_k8s_favorite_http).put(update_k8s_favorite_http).delete(delete_k8s_favorite_http))
         .route("/api/k8s/favorites/check", post(k8s_favorite_exists_http))
+        // Background job routes
+        .route("/api/jobs", get(job_list))
+        .route("/api/jobs/:id", get(job_get).delete(job_remove))
+        .route("/api/jobs/:id/cancel", post(job_cancel))
         // Health check route
         .route("/api/health", get(health_check))
         // Log streaming routes
@@ -243,6 +402,7 @@ impl IntoResponse for AppError {
         let (status, message) = match &self {
             AppError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
             AppError::Validation(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AppError::Cancelled(_) => (StatusCode::CONFLICT, self.to_string()),
             _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
@@ -254,6 +414,36 @@ impl IntoResponse for AppError {
     }
 }
 
+// ==================== Background Jobs ====================
+
+async fn job_list(State(state): State<Arc<AppState>>) -> Json<Vec<JobInfo>> {
+    Json(state.job_service.list().await)
+}
+
+async fn job_get(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<JobInfo>, AppError> {
+    let job = state.job_service.get(&id).await?;
+    Ok(Json(job))
+}
+
+async fn job_cancel(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    state.job_service.cancel(&id).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn job_remove(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    state.job_service.remove(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // ==================== Health Check ====================
 
 /// Health check endpoint for verifying server readiness
@@ -1077,6 +1267,7 @@ struct MysqlQueryRequest {
     connection_id: i64,
     database: String,
     query: String,
+    execution_id: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -1086,6 +1277,14 @@ struct MysqlRowsQuery {
     page_size: Option<i32>,
 }
 
+#[derive(Deserialize)]
+struct MysqlRowsQueryBody {
+    page: Option<i32>,
+    page_size: Option<i32>,
+    #[serde(flatten)]
+    options: TableRowsOptions,
+}
+
 #[derive(Deserialize)]
 struct MysqlInsertRequest {
     connection_id: i64,
@@ -1240,12 +1439,114 @@ async fn mysql_get_rows(
     let connection = conn_service.get_by_id(connection_id).await?;
     let connection = ensure_port_forward_for_http(&state, connection).await?;
     let mysql_service = MysqlService::connect(&connection).await?;
+    let max_rows = SettingsService::new(state.pool.clone())
+        .get_mysql_max_rows()
+        .await?;
     let page = params.page.unwrap_or(1);
-    let page_size = params.page_size.unwrap_or(100);
-    let data = mysql_service.get_rows(&db, &table, page, page_size).await?;
+    let page_size = params
+        .page_size
+        .unwrap_or(100)
+        .clamp(1, max_rows.min(i32::MAX as u64) as i32);
+    let data = mysql_service
+        .get_rows(&db, &table, page, page_size, &TableRowsOptions::default())
+        .await?;
+    Ok(Json(data))
+}
+
+async fn mysql_query_rows(
+    State(state): State<Arc<AppState>>,
+    Path((db, table)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(body): Json<MysqlRowsQueryBody>,
+) -> Result<Json<MysqlTableData>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let max_rows = SettingsService::new(state.pool.clone())
+        .get_mysql_max_rows()
+        .await?;
+    let page = body.page.unwrap_or(1);
+    let page_size = body
+        .page_size
+        .unwrap_or(100)
+        .clamp(1, max_rows.min(i32::MAX as u64) as i32);
+    let data = mysql_service
+        .get_rows(&db, &table, page, page_size, &body.options)
+        .await?;
     Ok(Json(data))
 }
 
+async fn mysql_get_rows_keyset(
+    State(state): State<Arc<AppState>>,
+    Path((db, table)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<KeysetPageRequest>,
+) -> Result<Json<KeysetPage>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let max_rows = SettingsService::new(state.pool.clone())
+        .get_mysql_max_rows()
+        .await?;
+    let page_size = req
+        .page_size
+        .unwrap_or(100)
+        .clamp(1, max_rows.min(i32::MAX as u64) as i32);
+    let page = mysql_service
+        .get_rows_keyset(&db, &table, page_size, &req)
+        .await?;
+    Ok(Json(page))
+}
+
+async fn mysql_get_filter_presets(
+    State(state): State<Arc<AppState>>,
+    Path((db, table)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<FilterPreset>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let service = SettingsService::new(state.pool.clone());
+    let presets = service
+        .get_filter_presets(connection_id, &db, &table)
+        .await?;
+    Ok(Json(presets))
+}
+
+async fn mysql_save_filter_preset(
+    State(state): State<Arc<AppState>>,
+    Path((db, table)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(preset): Json<FilterPreset>,
+) -> Result<Json<Vec<FilterPreset>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let service = SettingsService::new(state.pool.clone());
+    let presets = service
+        .save_filter_preset(connection_id, &db, &table, preset)
+        .await?;
+    Ok(Json(presets))
+}
+
+async fn mysql_delete_filter_preset(
+    State(state): State<Arc<AppState>>,
+    Path((db, table, name)): Path<(String, String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<FilterPreset>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let service = SettingsService::new(state.pool.clone());
+    let presets = service
+        .delete_filter_preset(connection_id, &db, &table, &name)
+        .await?;
+    Ok(Json(presets))
+}
+
 async fn mysql_insert_row(
     State(state): State<Arc<AppState>>,
     Path((db, table)): Path<(String, String)>,
@@ -1295,10 +1596,161 @@ async fn mysql_execute_query(
     let connection = conn_service.get_by_id(req.connection_id).await?;
     let connection = ensure_port_forward_for_http(&state, connection).await?;
     let mysql_service = MysqlService::connect(&connection).await?;
-    let result = mysql_service.execute_query(&req.database, &req.query).await?;
+    let settings = SettingsService::new(state.pool.clone());
+    let limits = QueryLimits {
+        page_size: settings.get_mysql_query_limit().await?.max(1) as usize,
+        max_rows: settings.get_mysql_max_rows().await?,
+    };
+    let start = Instant::now();
+    let result = mysql_service
+        .execute_query(
+            &state.query_cursors,
+            &state.query_executions,
+            req.execution_id,
+            &req.database,
+            &req.query,
+            limits,
+        )
+        .await;
+    if let Err(AppError::Cancelled(_)) = &result {
+        let duration_ms = start.elapsed().as_millis() as u64;
+        record_cancelled(
+            &state.pool,
+            req.connection_id,
+            &req.database,
+            &req.query,
+            duration_ms,
+        )
+        .await?;
+    }
+    Ok(Json(result?))
+}
+
+async fn mysql_cancel_query(
+    State(state): State<Arc<AppState>>,
+    Path(execution_id): Path<String>,
+) -> Result<Json<bool>, AppError> {
+    let cancelled = state.query_executions.cancel(&execution_id).await?;
+    Ok(Json(cancelled))
+}
+
+async fn mysql_list_running_queries(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<String>>, AppError> {
+    Ok(Json(state.query_executions.running()?))
+}
+
+async fn mysql_execute_script(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MysqlScriptRequest>,
+) -> Result<Json<MysqlScriptResult>, AppError> {
+    let max_rows = SettingsService::new(state.pool.clone())
+        .get_mysql_max_rows()
+        .await?;
+
+    if let Some(session_id) = &req.session_id {
+        let result = state
+            .mysql_sessions
+            .execute_script(
+                &state.query_executions,
+                session_id,
+                req.execution_id,
+                &req.script,
+                req.mode,
+                max_rows,
+            )
+            .await?;
+        return Ok(Json(result));
+    }
+
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let result = mysql_service
+        .execute_script(
+            &state.query_executions,
+            req.execution_id,
+            &req.database,
+            &req.script,
+            req.mode,
+            max_rows,
+        )
+        .await?;
+    Ok(Json(result))
+}
+
+#[derive(Deserialize)]
+struct SplitScriptRequest {
+    script: String,
+}
+
+async fn mysql_split_script(Json(req): Json<SplitScriptRequest>) -> Json<Vec<SqlStatement>> {
+    Json(split_statements(&req.script))
+}
+
+async fn mysql_execute_parameterized(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MysqlParameterizedQueryRequest>,
+) -> Result<Json<MysqlQueryResult>, AppError> {
+    let resolved = resolve_request(&state.pool, &req).await?;
+    let max_rows = SettingsService::new(state.pool.clone())
+        .get_mysql_max_rows()
+        .await?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let result = mysql_service
+        .execute_parameterized(
+            &state.query_executions,
+            req.execution_id,
+            &resolved,
+            &req.values,
+            max_rows,
+        )
+        .await?;
     Ok(Json(result))
 }
 
+#[derive(Deserialize)]
+struct ExtractParametersRequest {
+    query: String,
+}
+
+async fn mysql_extract_query_parameters(
+    Json(req): Json<ExtractParametersRequest>,
+) -> Json<Vec<String>> {
+    Json(extract_parameters(&req.query))
+}
+
+async fn mysql_fetch_query_page(
+    State(state): State<Arc<AppState>>,
+    Path(cursor_id): Path<String>,
+) -> Result<Json<MysqlQueryPage>, AppError> {
+    let page = state.query_cursors.fetch(&cursor_id).await?;
+    Ok(Json(MysqlQueryPage {
+        cursor_id,
+        columns: page.columns,
+        rows: page.rows,
+        has_more: page.has_more,
+        truncated: page.truncated,
+    }))
+}
+
+async fn mysql_close_query_cursor(
+    State(state): State<Arc<AppState>>,
+    Path(cursor_id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    if !state.query_cursors.close(&cursor_id).await {
+        return Err(AppError::NotFound(format!(
+            "Cursor not found: {}",
+            cursor_id
+        )));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // ==================== MySQL Table Management Handlers ====================
 
 async fn mysql_create_table(
@@ -1428,6 +1880,18 @@ async fn mysql_drop_index(
     Ok(Json(()))
 }
 
+async fn mysql_index_advice(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<IndexAdvisorRequest>,
+) -> Result<Json<IndexAdvisorReport>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlIndexAdvisorService::new(MysqlService::connect(&connection).await?);
+    let report = service.analyze(&req).await?;
+    Ok(Json(report))
+}
+
 // ==================== MySQL Foreign Key handlers ====================
 
 async fn mysql_list_foreign_keys(
@@ -1476,6 +1940,93 @@ async fn mysql_drop_foreign_key(
     Ok(Json(()))
 }
 
+// ==================== MySQL Schema Comparison handlers ====================
+
+async fn mysql_compare_schemas(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SchemaCompareRequest>,
+) -> Result<Json<SchemaCompareReport>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let source = conn_service.get_by_id(req.source_connection_id).await?;
+    let source = ensure_port_forward_for_http(&state, source).await?;
+    let target = conn_service.get_by_id(req.target_connection_id).await?;
+    let target = ensure_port_forward_for_http(&state, target).await?;
+    let service = SchemaCompareService::new(
+        MysqlService::connect(&source).await?,
+        MysqlService::connect(&target).await?,
+    );
+    Ok(Json(service.compare(&req).await?))
+}
+
+async fn mysql_er_diagram(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ErDiagramRequest>,
+) -> Result<Json<ErDiagramResult>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = ErDiagramService::new(MysqlService::connect(&connection).await?);
+    Ok(Json(service.build(&req).await?))
+}
+
+// ==================== MySQL Dump/Restore handlers ====================
+
+async fn mysql_dump_database(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MysqlDumpRequest>,
+) -> Result<Json<JobStarted>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlDumpService::new(MysqlService::connect(&connection).await?);
+    let started = state
+        .job_service
+        .spawn("mysql_dump", move |ctx| async move {
+            service.dump(&req, &ctx).await
+        })
+        .await;
+    Ok(Json(started))
+}
+
+async fn mysql_restore_database(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MysqlRestoreRequest>,
+) -> Result<Json<JobStarted>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlDumpService::new(MysqlService::connect(&connection).await?);
+    let started = state
+        .job_service
+        .spawn("mysql_restore", move |ctx| async move {
+            service.restore(&req, &ctx).await
+        })
+        .await;
+    Ok(Json(started))
+}
+
+async fn mysql_copy_table_between_connections(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TableCopyRequest>,
+) -> Result<Json<JobStarted>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let source = conn_service.get_by_id(req.source_connection_id).await?;
+    let source = ensure_port_forward_for_http(&state, source).await?;
+    let target = conn_service.get_by_id(req.target_connection_id).await?;
+    let target = ensure_port_forward_for_http(&state, target).await?;
+    let service = MysqlTableCopyService::new(
+        MysqlService::connect(&source).await?,
+        MysqlService::connect(&target).await?,
+    );
+    let started = state
+        .job_service
+        .spawn("mysql_table_copy", move |ctx| async move {
+            service.copy(&req, &ctx).await
+        })
+        .await;
+    Ok(Json(started))
+}
+
 // ==================== MySQL Data Export/Import handlers ====================
 
 async fn mysql_export_table(
@@ -1494,6 +2045,89 @@ async fn mysql_export_table(
     Ok(Json(response))
 }
 
+async fn mysql_download_table_export(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Path((db, table)): Path<(String, String)>,
+    Json(req): Json<ExportTableRequest>,
+) -> Result<Response, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlExportService::new(MysqlService::connect(&connection).await?);
+    let format = req.format.clone();
+    let filename = table.clone();
+    let (job, rx) = ExportSink::download(
+        &state.job_service,
+        "mysql_export",
+        move |sink, ctx| async move {
+            service
+                .export_table(&db, &table, &req, sink, Some(&ctx))
+                .await
+        },
+    )
+    .await;
+    Ok(download_response(job, rx, &filename, &format))
+}
+
+async fn mysql_download_query_export(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ExportQueryRequest>,
+) -> Result<Response, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlExportService::new(MysqlService::connect(&connection).await?);
+    let name = req
+        .table_name
+        .clone()
+        .unwrap_or_else(|| "query_result".to_string());
+    let format = req.format.clone();
+    let (job, rx) = ExportSink::download(
+        &state.job_service,
+        "mysql_export",
+        move |sink, ctx| async move { service.export_query(&req, sink, Some(&ctx)).await },
+    )
+    .await;
+    Ok(download_response(job, rx, &name, &format))
+}
+
+/// Chunked attachment response streaming an export. The X-Job-ID header
+/// names the export job, for progress and cancellation.
+fn download_response(
+    job: JobStarted,
+    rx: mpsc::Receiver<std::io::Result<Vec<u8>>>,
+    name: &str,
+    format: &ExportFormat,
+) -> Response {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let disposition = format!(
+        "attachment; filename=\"{}.{}\"",
+        name,
+        file_extension(format)
+    );
+    (
+        [
+            (header::CONTENT_TYPE, content_type(format).to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+            (HeaderName::from_static("x-job-id"), job.job_id),
+        ],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
+
 async fn mysql_import_data(
     State(state): State<Arc<AppState>>,
     Query(params): Query<ConnectionIdQuery>,
@@ -1510,6 +2144,29 @@ async fn mysql_import_data(
     Ok(Json(result))
 }
 
+async fn mysql_import_file(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ImportFileRequest>,
+) -> Result<Json<JobStarted>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlImportService::new(MysqlService::connect(&connection).await?);
+    let started = state
+        .job_service
+        .spawn("mysql_import", move |ctx| async move {
+            service.import_file(&req, &ctx).await
+        })
+        .await;
+    Ok(Json(started))
+}
+
+async fn mysql_infer_import_schema(
+    Json(req): Json<ImportFileRequest>,
+) -> Result<Json<InferredTable>, AppError> {
+    Ok(Json(infer_table(&req, Some(INFER_SAMPLE_ROWS)).await?))
+}
+
 // ==================== MySQL User Management handlers ====================
 
 async fn mysql_list_users(
@@ -1629,51 +2286,196 @@ async fn mysql_revoke_privileges(
     Ok(StatusCode::OK)
 }
 
-// ==================== MySQL View handlers ====================
+// ==================== MySQL Role and Account handlers ====================
 
-async fn mysql_list_views(
+#[derive(Deserialize)]
+struct PrivilegeMatrixQuery {
+    connection_id: Option<i64>,
+    include_system: Option<bool>,
+}
+
+async fn mysql_get_privilege_matrix(
     State(state): State<Arc<AppState>>,
-    Path(db): Path<String>,
-    Query(params): Query<ConnectionIdQuery>,
+    Query(params): Query<PrivilegeMatrixQuery>,
     headers: HeaderMap,
-) -> Result<Json<Vec<ViewInfo>>, AppError> {
+) -> Result<Json<PrivilegeMatrix>, AppError> {
     let connection_id = extract_connection_id(params.connection_id, &headers)?;
     let conn_service = ConnectionService::new(state.pool.clone());
     let connection = conn_service.get_by_id(connection_id).await?;
     let connection = ensure_port_forward_for_http(&state, connection).await?;
-    let mysql_service = MysqlService::connect(&connection).await?;
-    let views = mysql_service.list_views(&db).await?;
-    Ok(Json(views))
+    let service = MysqlAccountService::new(MysqlService::connect(&connection).await?);
+    let matrix = service
+        .privilege_matrix(params.include_system.unwrap_or(false))
+        .await?;
+    Ok(Json(matrix))
 }
 
-async fn mysql_get_view_definition(
+async fn mysql_get_account_details(
     State(state): State<Arc<AppState>>,
-    Path((db, view)): Path<(String, String)>,
-    Query(params): Query<ConnectionIdQuery>,
+    Query(params): Query<ShowGrantsQuery>,
     headers: HeaderMap,
-) -> Result<Json<ViewDefinition>, AppError> {
+) -> Result<Json<AccountDetails>, AppError> {
     let connection_id = extract_connection_id(params.connection_id, &headers)?;
     let conn_service = ConnectionService::new(state.pool.clone());
     let connection = conn_service.get_by_id(connection_id).await?;
     let connection = ensure_port_forward_for_http(&state, connection).await?;
-    let mysql_service = MysqlService::connect(&connection).await?;
-    let definition = mysql_service.get_view_definition(&db, &view).await?;
-    Ok(Json(definition))
+    let service = MysqlAccountService::new(MysqlService::connect(&connection).await?);
+    let details = service
+        .account_details(&params.username, &params.host)
+        .await?;
+    Ok(Json(details))
 }
 
-async fn mysql_create_view(
+async fn mysql_alter_account(
     State(state): State<Arc<AppState>>,
-    Path(db): Path<String>,
     Query(params): Query<ConnectionIdQuery>,
     headers: HeaderMap,
-    Json(req): Json<CreateViewRequest>,
+    Json(req): Json<AlterAccountRequest>,
 ) -> Result<StatusCode, AppError> {
     let connection_id = extract_connection_id(params.connection_id, &headers)?;
     let conn_service = ConnectionService::new(state.pool.clone());
     let connection = conn_service.get_by_id(connection_id).await?;
     let connection = ensure_port_forward_for_http(&state, connection).await?;
-    let mysql_service = MysqlService::connect(&connection).await?;
-    mysql_service.create_view(&db, &req).await?;
+    let service = MysqlAccountService::new(MysqlService::connect(&connection).await?);
+    service.alter_account(&req).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn mysql_set_default_roles(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<SetDefaultRolesRequest>,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlAccountService::new(MysqlService::connect(&connection).await?);
+    service.set_default_roles(&req).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn mysql_clone_user_privileges(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<ClonePrivilegesRequest>,
+) -> Result<Json<ClonePrivilegesResult>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlAccountService::new(MysqlService::connect(&connection).await?);
+    let result = service.clone_privileges(&req).await?;
+    Ok(Json(result))
+}
+
+async fn mysql_create_role(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<RoleRequest>,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlAccountService::new(MysqlService::connect(&connection).await?);
+    service.create_role(&req).await?;
+    Ok(StatusCode::CREATED)
+}
+
+async fn mysql_drop_role(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<RoleRequest>,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlAccountService::new(MysqlService::connect(&connection).await?);
+    service.drop_role(&req).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn mysql_grant_role(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<RoleGrantRequest>,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlAccountService::new(MysqlService::connect(&connection).await?);
+    service.grant_role(&req).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn mysql_revoke_role(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<RoleGrantRequest>,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlAccountService::new(MysqlService::connect(&connection).await?);
+    service.revoke_role(&req).await?;
+    Ok(StatusCode::OK)
+}
+
+// ==================== MySQL View handlers ====================
+
+async fn mysql_list_views(
+    State(state): State<Arc<AppState>>,
+    Path(db): Path<String>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ViewInfo>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let views = mysql_service.list_views(&db).await?;
+    Ok(Json(views))
+}
+
+async fn mysql_get_view_definition(
+    State(state): State<Arc<AppState>>,
+    Path((db, view)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<ViewDefinition>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let definition = mysql_service.get_view_definition(&db, &view).await?;
+    Ok(Json(definition))
+}
+
+async fn mysql_create_view(
+    State(state): State<Arc<AppState>>,
+    Path(db): Path<String>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<CreateViewRequest>,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    mysql_service.create_view(&db, &req).await?;
     Ok(StatusCode::CREATED)
 }
 
@@ -1765,97 +2567,516 @@ async fn mysql_drop_function(
 
 async fn mysql_list_triggers(
     State(state): State<Arc<AppState>>,
-    Path(db): Path<String>,
+    Path(db): Path<String>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<TriggerInfo>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let triggers = mysql_service.list_triggers(&db).await?;
+    Ok(Json(triggers))
+}
+
+async fn mysql_get_trigger_definition(
+    State(state): State<Arc<AppState>>,
+    Path((db, name)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<TriggerDefinition>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let definition = mysql_service.get_trigger_definition(&db, &name).await?;
+    Ok(Json(definition))
+}
+
+async fn mysql_drop_trigger(
+    State(state): State<Arc<AppState>>,
+    Path((db, name)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    mysql_service.drop_trigger(&db, &name).await?;
+    Ok(StatusCode::OK)
+}
+
+// ==================== MySQL Stored Program handlers ====================
+
+async fn mysql_create_stored_program(
+    State(state): State<Arc<AppState>>,
+    Path(db): Path<String>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<SaveStoredProgramRequest>,
+) -> Result<Json<SaveStoredProgramResult>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let result = MysqlRoutineService::new(mysql_service)
+        .create(&db, &req)
+        .await?;
+    Ok(Json(result))
+}
+
+async fn mysql_alter_stored_program(
+    State(state): State<Arc<AppState>>,
+    Path((db, name)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<SaveStoredProgramRequest>,
+) -> Result<Json<SaveStoredProgramResult>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let result = MysqlRoutineService::new(mysql_service)
+        .alter(&db, &name, &req)
+        .await?;
+    Ok(Json(result))
+}
+
+async fn mysql_get_procedure_parameters(
+    State(state): State<Arc<AppState>>,
+    Path((db, name)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<RoutineParameterInfo>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let parameters = MysqlRoutineService::new(mysql_service)
+        .get_procedure_parameters(&db, &name)
+        .await?;
+    Ok(Json(parameters))
+}
+
+async fn mysql_call_procedure(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MysqlCallRequest>,
+) -> Result<Json<MysqlCallResult>, AppError> {
+    let max_rows = SettingsService::new(state.pool.clone())
+        .get_mysql_max_rows()
+        .await?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let result = MysqlRoutineService::new(mysql_service)
+        .call(&state.query_executions, &req, max_rows)
+        .await?;
+    Ok(Json(result))
+}
+
+// ==================== MySQL Event handlers ====================
+
+async fn mysql_list_events(
+    State(state): State<Arc<AppState>>,
+    Path(db): Path<String>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<EventInfo>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let events = mysql_service.list_events(&db).await?;
+    Ok(Json(events))
+}
+
+async fn mysql_get_event_definition(
+    State(state): State<Arc<AppState>>,
+    Path((db, name)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<EventDefinition>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let definition = mysql_service.get_event_definition(&db, &name).await?;
+    Ok(Json(definition))
+}
+
+async fn mysql_create_event(
+    State(state): State<Arc<AppState>>,
+    Path(db): Path<String>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<CreateEventRequest>,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    mysql_service.create_event(&db, &req).await?;
+    Ok(StatusCode::CREATED)
+}
+
+async fn mysql_alter_event(
+    State(state): State<Arc<AppState>>,
+    Path((db, name)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<AlterEventRequest>,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    mysql_service.alter_event(&db, &name, &req).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn mysql_set_event_enabled(
+    State(state): State<Arc<AppState>>,
+    Path((db, name)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+    Json(req): Json<SetEventEnabledRequest>,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    mysql_service
+        .set_event_enabled(&db, &name, req.enabled)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+async fn mysql_drop_event(
+    State(state): State<Arc<AppState>>,
+    Path((db, name)): Path<(String, String)>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    mysql_service.drop_event(&db, &name).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn mysql_get_event_scheduler_status(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<EventSchedulerStatus>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let status = mysql_service.get_event_scheduler_status().await?;
+    Ok(Json(status))
+}
+
+// ==================== MySQL Server Monitoring handlers ====================
+
+#[derive(Deserialize)]
+struct ServerVariablesQuery {
+    connection_id: Option<i64>,
+    filter: Option<String>,
+}
+
+async fn mysql_get_server_variables(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ServerVariablesQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ServerVariable>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let variables = mysql_service.get_server_variables(params.filter.as_deref()).await?;
+    Ok(Json(variables))
+}
+
+async fn mysql_get_process_list(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ProcessInfo>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let processes = mysql_service.get_process_list().await?;
+    Ok(Json(processes))
+}
+
+async fn mysql_kill_process(
+    State(state): State<Arc<AppState>>,
+    Path(process_id): Path<u64>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    mysql_service.kill_process(process_id).await?;
+    Ok(StatusCode::OK)
+}
+
+// ==================== MySQL Server Configuration handlers ====================
+
+#[derive(Deserialize)]
+struct VariableChangesQuery {
+    connection_id: Option<i64>,
+    limit: Option<i64>,
+}
+
+async fn mysql_get_server_variable_info(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ServerVariablesQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ServerVariableInfo>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlVariableService::new(MysqlService::connect(&connection).await?);
+    let variables = service.list(params.filter.as_deref()).await?;
+    Ok(Json(variables))
+}
+
+async fn mysql_set_server_variable(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SetServerVariableRequest>,
+) -> Result<Json<ServerVariableChange>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlVariableService::new(MysqlService::connect(&connection).await?);
+    let change = service
+        .set_variable(
+            &state.pool,
+            &state.mysql_sessions,
+            &state.query_executions,
+            &req,
+        )
+        .await?;
+    Ok(Json(change))
+}
+
+async fn mysql_get_server_variable_changes(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<VariableChangesQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ServerVariableChange>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let changes = state
+        .pool
+        .get_server_variable_changes(connection_id, params.limit.unwrap_or(200))
+        .await?;
+    Ok(Json(changes))
+}
+
+async fn mysql_list_variable_baselines(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<VariableBaselineInfo>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let baselines = state.pool.get_variable_baselines(connection_id).await?;
+    Ok(Json(baselines))
+}
+
+async fn mysql_create_variable_baseline(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateVariableBaselineRequest>,
+) -> Result<Json<VariableBaselineInfo>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlVariableService::new(MysqlService::connect(&connection).await?);
+    let baseline = service
+        .create_baseline(&state.pool, req.connection_id, req.label.as_deref())
+        .await?;
+    Ok(Json(baseline))
+}
+
+async fn mysql_delete_variable_baseline(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    state.pool.delete_variable_baseline(id).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn mysql_compare_server_variables(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CompareServerVariablesRequest>,
+) -> Result<Json<VariableComparison>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlVariableService::new(MysqlService::connect(&connection).await?);
+    let other = match req.other_connection_id {
+        Some(other_id) => {
+            let other = conn_service.get_by_id(other_id).await?;
+            let other = ensure_port_forward_for_http(&state, other).await?;
+            Some((other.name.clone(), MysqlService::connect(&other).await?))
+        }
+        None => None,
+    };
+    let comparison = service
+        .compare(
+            &state.pool,
+            &req,
+            other.as_ref().map(|(name, other)| (name.as_str(), other)),
+        )
+        .await?;
+    Ok(Json(comparison))
+}
+
+// ==================== MySQL Lock Inspection handlers ====================
+
+async fn mysql_get_lock_waits(
+    State(state): State<Arc<AppState>>,
     Query(params): Query<ConnectionIdQuery>,
     headers: HeaderMap,
-) -> Result<Json<Vec<TriggerInfo>>, AppError> {
+) -> Result<Json<LockGraph>, AppError> {
     let connection_id = extract_connection_id(params.connection_id, &headers)?;
     let conn_service = ConnectionService::new(state.pool.clone());
     let connection = conn_service.get_by_id(connection_id).await?;
     let connection = ensure_port_forward_for_http(&state, connection).await?;
-    let mysql_service = MysqlService::connect(&connection).await?;
-    let triggers = mysql_service.list_triggers(&db).await?;
-    Ok(Json(triggers))
+    let service = MysqlLockService::new(MysqlService::connect(&connection).await?);
+    let graph = service.lock_graph().await?;
+    Ok(Json(graph))
 }
 
-async fn mysql_get_trigger_definition(
+async fn mysql_kill_lock_blocker(
     State(state): State<Arc<AppState>>,
-    Path((db, name)): Path<(String, String)>,
+    Path(process_id): Path<u64>,
     Query(params): Query<ConnectionIdQuery>,
     headers: HeaderMap,
-) -> Result<Json<TriggerDefinition>, AppError> {
+) -> Result<StatusCode, AppError> {
     let connection_id = extract_connection_id(params.connection_id, &headers)?;
     let conn_service = ConnectionService::new(state.pool.clone());
     let connection = conn_service.get_by_id(connection_id).await?;
     let connection = ensure_port_forward_for_http(&state, connection).await?;
-    let mysql_service = MysqlService::connect(&connection).await?;
-    let definition = mysql_service.get_trigger_definition(&db, &name).await?;
-    Ok(Json(definition))
+    let service = MysqlLockService::new(MysqlService::connect(&connection).await?);
+    service.kill_blocker(process_id).await?;
+    Ok(StatusCode::OK)
 }
 
-async fn mysql_drop_trigger(
+async fn mysql_get_latest_deadlock(
     State(state): State<Arc<AppState>>,
-    Path((db, name)): Path<(String, String)>,
     Query(params): Query<ConnectionIdQuery>,
     headers: HeaderMap,
-) -> Result<StatusCode, AppError> {
+) -> Result<Json<Option<DeadlockReport>>, AppError> {
     let connection_id = extract_connection_id(params.connection_id, &headers)?;
     let conn_service = ConnectionService::new(state.pool.clone());
     let connection = conn_service.get_by_id(connection_id).await?;
     let connection = ensure_port_forward_for_http(&state, connection).await?;
-    let mysql_service = MysqlService::connect(&connection).await?;
-    mysql_service.drop_trigger(&db, &name).await?;
-    Ok(StatusCode::OK)
+    let service = MysqlLockService::new(MysqlService::connect(&connection).await?);
+    let deadlock = service.latest_deadlock().await?;
+    Ok(Json(deadlock))
 }
 
-// ==================== MySQL Server Monitoring handlers ====================
+// ==================== MySQL Replication Monitoring handlers ====================
 
 #[derive(Deserialize)]
-struct ServerVariablesQuery {
+struct ReplicationLagQuery {
     connection_id: Option<i64>,
-    filter: Option<String>,
+    channel_name: Option<String>,
+    limit: Option<i64>,
 }
 
-async fn mysql_get_server_variables(
+async fn mysql_get_replication_status(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<ServerVariablesQuery>,
+    Query(params): Query<ConnectionIdQuery>,
     headers: HeaderMap,
-) -> Result<Json<Vec<ServerVariable>>, AppError> {
+) -> Result<Json<ReplicationStatus>, AppError> {
     let connection_id = extract_connection_id(params.connection_id, &headers)?;
     let conn_service = ConnectionService::new(state.pool.clone());
     let connection = conn_service.get_by_id(connection_id).await?;
     let connection = ensure_port_forward_for_http(&state, connection).await?;
-    let mysql_service = MysqlService::connect(&connection).await?;
-    let variables = mysql_service.get_server_variables(params.filter.as_deref()).await?;
-    Ok(Json(variables))
+    let service = MysqlReplicationService::new(MysqlService::connect(&connection).await?);
+    let status = service.status().await?;
+    Ok(Json(status))
 }
 
-async fn mysql_get_process_list(
+async fn mysql_start_replication_lag_polling(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<ConnectionIdQuery>,
-    headers: HeaderMap,
-) -> Result<Json<Vec<ProcessInfo>>, AppError> {
-    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    Json(req): Json<ReplicationLagPollRequest>,
+) -> Result<Json<JobStarted>, AppError> {
     let conn_service = ConnectionService::new(state.pool.clone());
-    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = conn_service.get_by_id(req.connection_id).await?;
     let connection = ensure_port_forward_for_http(&state, connection).await?;
-    let mysql_service = MysqlService::connect(&connection).await?;
-    let processes = mysql_service.get_process_list().await?;
-    Ok(Json(processes))
+    let mut service = MysqlReplicationService::new(MysqlService::connect(&connection).await?);
+    let sqlite = state.pool.clone();
+    let app = state.clone();
+    let started = state
+        .job_service
+        .spawn("mysql_replication_lag", move |ctx| async move {
+            let reconnect = || async {
+                let connection = ConnectionService::new(app.pool.clone())
+                    .get_by_id(req.connection_id)
+                    .await?;
+                let connection = ensure_port_forward_for_http(&app, connection).await?;
+                MysqlService::connect(&connection).await
+            };
+            service.poll_lag(&sqlite, &req, &ctx, reconnect).await
+        })
+        .await;
+    Ok(Json(started))
 }
 
-async fn mysql_kill_process(
+async fn mysql_get_replication_lag_history(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ReplicationLagQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ReplicationLagSample>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let samples = state
+        .pool
+        .get_replication_lag_samples(
+            connection_id,
+            params.channel_name.as_deref(),
+            params.limit.unwrap_or(1000),
+        )
+        .await?;
+    Ok(Json(samples))
+}
+
+async fn mysql_clear_replication_lag_history(
     State(state): State<Arc<AppState>>,
-    Path(process_id): Path<u64>,
     Query(params): Query<ConnectionIdQuery>,
     headers: HeaderMap,
 ) -> Result<StatusCode, AppError> {
     let connection_id = extract_connection_id(params.connection_id, &headers)?;
-    let conn_service = ConnectionService::new(state.pool.clone());
-    let connection = conn_service.get_by_id(connection_id).await?;
-    let connection = ensure_port_forward_for_http(&state, connection).await?;
-    let mysql_service = MysqlService::connect(&connection).await?;
-    mysql_service.kill_process(process_id).await?;
+    state
+        .pool
+        .clear_replication_lag_samples(connection_id)
+        .await?;
     Ok(StatusCode::OK)
 }
 
@@ -1880,6 +3101,106 @@ async fn mysql_explain_query(
     Ok(Json(result))
 }
 
+async fn mysql_explain_plan(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ExplainPlanRequest>,
+) -> Result<Json<ExplainPlan>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlExplainService::new(MysqlService::connect(&connection).await?);
+    let plan = service.explain_plan(&req).await?;
+    Ok(Json(plan))
+}
+
+// ==================== MySQL Workload Analysis handlers ====================
+
+async fn mysql_get_top_queries(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TopQueriesRequest>,
+) -> Result<Json<TopQueriesReport>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlWorkloadService::new(MysqlService::connect(&connection).await?);
+    let report = service.top_queries(&req).await?;
+    Ok(Json(report))
+}
+
+async fn mysql_list_digest_snapshots(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<DigestSnapshotInfo>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let snapshots = state.pool.get_digest_snapshots(connection_id).await?;
+    Ok(Json(snapshots))
+}
+
+async fn mysql_take_digest_snapshot(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TakeDigestSnapshotRequest>,
+) -> Result<Json<DigestSnapshotInfo>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlWorkloadService::new(MysqlService::connect(&connection).await?);
+    let snapshot = service
+        .take_snapshot(&state.pool, req.connection_id, req.label.as_deref())
+        .await?;
+    Ok(Json(snapshot))
+}
+
+async fn mysql_start_digest_snapshots(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<PeriodicDigestSnapshotsRequest>,
+) -> Result<Json<JobStarted>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlWorkloadService::new(MysqlService::connect(&connection).await?);
+    let sqlite = state.pool.clone();
+    let started = state
+        .job_service
+        .spawn("mysql_digest_snapshots", move |ctx| async move {
+            service.take_periodic_snapshots(&sqlite, &req, &ctx).await
+        })
+        .await;
+    Ok(Json(started))
+}
+
+async fn mysql_delete_digest_snapshot(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    state.pool.delete_digest_snapshot(id).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn mysql_compare_digest_snapshots(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CompareDigestSnapshotsRequest>,
+) -> Result<Json<DigestComparison>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlWorkloadService::new(MysqlService::connect(&connection).await?);
+    let comparison = service.compare(&state.pool, &req).await?;
+    Ok(Json(comparison))
+}
+
+async fn mysql_explain_digest(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ExplainDigestRequest>,
+) -> Result<Json<ExplainResult>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let service = MysqlWorkloadService::new(MysqlService::connect(&connection).await?);
+    let result = service.explain_digest(&req).await?;
+    Ok(Json(result))
+}
+
 // ==================== MySQL Table Maintenance handlers ====================
 
 async fn mysql_optimize_table(
@@ -1927,6 +3248,102 @@ async fn mysql_check_table(
     Ok(Json(result))
 }
 
+// ==================== MySQL Session handlers ====================
+
+async fn mysql_session_open(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<OpenMysqlSessionRequest>,
+) -> Result<Json<MysqlSessionInfo>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mysql_service = MysqlService::connect(&connection).await?;
+    let idle_timeout_secs = match req.idle_timeout_secs {
+        Some(secs) => secs.max(1),
+        None => {
+            SettingsService::new(state.pool.clone())
+                .get_mysql_session_idle_timeout()
+                .await?
+        }
+    };
+    let info = state
+        .mysql_sessions
+        .open(
+            &mysql_service,
+            req.connection_id,
+            req.database.as_deref(),
+            Duration::from_secs(idle_timeout_secs),
+        )
+        .await?;
+    Ok(Json(info))
+}
+
+async fn mysql_session_list(State(state): State<Arc<AppState>>) -> Json<Vec<MysqlSessionInfo>> {
+    Json(state.mysql_sessions.list().await)
+}
+
+async fn mysql_session_get(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<MysqlSessionInfo>, AppError> {
+    Ok(Json(state.mysql_sessions.get(&session_id).await?))
+}
+
+async fn mysql_session_execute(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(req): Json<MysqlSessionQueryRequest>,
+) -> Result<Json<MysqlQueryResult>, AppError> {
+    let max_rows = SettingsService::new(state.pool.clone())
+        .get_mysql_max_rows()
+        .await?;
+    let result = state
+        .mysql_sessions
+        .execute(
+            &state.query_executions,
+            &session_id,
+            req.execution_id,
+            &req.query,
+            max_rows,
+        )
+        .await?;
+    Ok(Json(result))
+}
+
+async fn mysql_session_begin(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<MysqlSessionInfo>, AppError> {
+    Ok(Json(state.mysql_sessions.begin(&session_id).await?))
+}
+
+async fn mysql_session_commit(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<MysqlSessionInfo>, AppError> {
+    Ok(Json(state.mysql_sessions.commit(&session_id).await?))
+}
+
+async fn mysql_session_rollback(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<MysqlSessionInfo>, AppError> {
+    Ok(Json(state.mysql_sessions.rollback(&session_id).await?))
+}
+
+async fn mysql_session_close(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    if !state.mysql_sessions.close(&session_id).await {
+        return Err(AppError::NotFound(format!(
+            "Session not found: {}",
+            session_id
+        )));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // ==================== Redis handlers ====================
 
 #[derive(Deserialize)]
@@ -2050,6 +3467,191 @@ async fn redis_set_ttl(
     Ok(StatusCode::OK)
 }
 
+// ==================== Redis ACL handlers ====================
+
+#[derive(Deserialize)]
+struct RedisAclSetUserRequest {
+    connection_id: i64,
+    #[serde(flatten)]
+    spec: RedisAclUserSpec,
+}
+
+#[derive(Deserialize)]
+struct RedisAclLogQuery {
+    connection_id: Option<i64>,
+    count: Option<u64>,
+}
+
+async fn redis_acl_list(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<RedisAclUser>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mut redis_service = RedisService::connect(&connection).await?;
+    let users = redis_service.acl_list().await?;
+    Ok(Json(users))
+}
+
+async fn redis_acl_get_user(
+    State(state): State<Arc<AppState>>,
+    Path(username): Path<String>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<RedisAclUser>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mut redis_service = RedisService::connect(&connection).await?;
+    let user = redis_service.acl_get_user(&username).await?;
+    Ok(Json(user))
+}
+
+async fn redis_acl_set_user(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RedisAclSetUserRequest>,
+) -> Result<Json<RedisAclDiff>, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(req.connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mut redis_service = RedisService::connect(&connection).await?;
+    let diff = redis_service.acl_set_user(&req.spec).await?;
+    Ok(Json(diff))
+}
+
+async fn redis_acl_del_user(
+    State(state): State<Arc<AppState>>,
+    Path(username): Path<String>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mut redis_service = RedisService::connect(&connection).await?;
+    let deleted = redis_service
+        .acl_del_user(std::slice::from_ref(&username))
+        .await?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!(
+            "ACL user not found: {}",
+            username
+        )));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn redis_acl_whoami(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<Json<String>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mut redis_service = RedisService::connect(&connection).await?;
+    let username = redis_service.acl_whoami().await?;
+    Ok(Json(username))
+}
+
+async fn redis_acl_log(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RedisAclLogQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<RedisAclLogEntry>>, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mut redis_service = RedisService::connect(&connection).await?;
+    let entries = redis_service.acl_log(params.count).await?;
+    Ok(Json(entries))
+}
+
+async fn redis_acl_log_reset(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConnectionIdQuery>,
+    headers: HeaderMap,
+) -> Result<StatusCode, AppError> {
+    let connection_id = extract_connection_id(params.connection_id, &headers)?;
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let connection = conn_service.get_by_id(connection_id).await?;
+    let connection = ensure_port_forward_for_http(&state, connection).await?;
+    let mut redis_service = RedisService::connect(&connection).await?;
+    redis_service.acl_log_reset().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ==================== Redis compare handlers ====================
+
+/// Connect to both sides of a comparison, applying DB index overrides
+async fn connect_redis_compare(
+    state: &Arc<AppState>,
+    source_connection_id: i64,
+    source_db: Option<i64>,
+    target_connection_id: i64,
+    target_db: Option<i64>,
+) -> Result<RedisCompareService, AppError> {
+    let conn_service = ConnectionService::new(state.pool.clone());
+    let source = conn_service.get_by_id(source_connection_id).await?;
+    let source = ensure_port_forward_for_http(state, source).await?;
+    let target = conn_service.get_by_id(target_connection_id).await?;
+    let target = ensure_port_forward_for_http(state, target).await?;
+    let source =
+        RedisService::connect(&RedisCompareService::with_database(&source, source_db)).await?;
+    let target =
+        RedisService::connect(&RedisCompareService::with_database(&target, target_db)).await?;
+    Ok(RedisCompareService::new(source, target))
+}
+
+async fn redis_compare_start(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RedisCompareRequest>,
+) -> Result<Json<JobStarted>, AppError> {
+    let mut service = connect_redis_compare(
+        &state,
+        req.source_connection_id,
+        req.source_db,
+        req.target_connection_id,
+        req.target_db,
+    )
+    .await?;
+    let started = state
+        .job_service
+        .spawn("redis_compare", move |ctx| async move {
+            service.compare(&req, &ctx).await
+        })
+        .await;
+    Ok(Json(started))
+}
+
+async fn redis_apply_sync_plan(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RedisApplySyncPlanRequest>,
+) -> Result<Json<JobStarted>, AppError> {
+    let mut service = connect_redis_compare(
+        &state,
+        req.plan.source_connection_id,
+        req.plan.source_db,
+        req.plan.target_connection_id,
+        req.plan.target_db,
+    )
+    .await?;
+    let started = state
+        .job_service
+        .spawn("redis_sync", move |ctx| async move {
+            service.apply_sync_plan(&req, &ctx).await
+        })
+        .await;
+    Ok(Json(started))
+}
+
 // ==================== History handlers ====================
 
 #[derive(Deserialize)]
@@ -2135,6 +3737,7 @@ async fn create_saved_query(
     State(state): State<Arc<AppState>>,
     Json(data): Json<CreateSavedQueryRequest>,
 ) -> Result<Json<SavedQuery>, AppError> {
+    validate_parameters(&data.parameters)?;
     let query = state.pool.create_saved_query(&data).await?;
     Ok(Json(query))
 }
@@ -2144,6 +3747,9 @@ async fn update_saved_query(
     Path(id): Path<i64>,
     Json(data): Json<UpdateSavedQueryRequest>,
 ) -> Result<Json<SavedQuery>, AppError> {
+    if let Some(parameters) = &data.parameters {
+        validate_parameters(parameters)?;
+    }
     let query = state.pool.update_saved_query(id, &data).await?;
     Ok(Json(query))
 }
@@ -15,7 +15,9 @@ use tauri::Manager;
 
 use commands::PortForwardState;
 use db::SqlitePool;
-use services::PortForwardService;
+use services::{
+    JobService, MysqlSessionService, PortForwardService, QueryCursorService, QueryExecutionService,
+};
 
 /// Get the application data directory for database storage
 fn get_app_data_dir(app: &tauri::App) -> PathBuf {
@@ -91,6 +93,10 @@ pub fn run() {
 
                     app.manage(pool);
                     app.manage(pf_state);
+                    app.manage(JobService::new());
+                    app.manage(QueryCursorService::new());
+                    app.manage(QueryExecutionService::new());
+                    app.manage(MysqlSessionService::new());
                 }
                 Err(e) => {
                     log::error!("Failed to initialize SQLite database: {}", e);
@@ -163,7 +169,16 @@ pub fn run() {
             commands::mysql_get_table_schema,
             commands::mysql_get_table_primary_key,
             commands::mysql_execute_query,
+            commands::mysql_fetch_query_page,
+            commands::mysql_close_query_cursor,
+            commands::mysql_cancel_query,
+            commands::mysql_list_running_queries,
+            commands::mysql_execute_script,
+            commands::mysql_split_script,
+            commands::mysql_execute_parameterized,
+            commands::mysql_extract_query_parameters,
             commands::mysql_get_rows,
+            commands::mysql_get_rows_keyset,
             commands::mysql_insert_row,
             commands::mysql_update_record,
             commands::mysql_delete_row,
@@ -175,6 +190,16 @@ pub fn run() {
             commands::mysql_drop_user,
             commands::mysql_show_grants,
             commands::mysql_revoke_privileges,
+            // MySQL roles and accounts
+            commands::mysql_get_privilege_matrix,
+            commands::mysql_get_account_details,
+            commands::mysql_alter_account,
+            commands::mysql_create_role,
+            commands::mysql_drop_role,
+            commands::mysql_grant_role,
+            commands::mysql_revoke_role,
+            commands::mysql_set_default_roles,
+            commands::mysql_clone_user_privileges,
             // MySQL table management
             commands::mysql_create_table,
             commands::mysql_alter_table,
@@ -185,13 +210,26 @@ pub fn run() {
             commands::mysql_list_indexes,
             commands::mysql_create_index,
             commands::mysql_drop_index,
+            commands::mysql_index_advice,
             // MySQL foreign key management
             commands::mysql_list_foreign_keys,
             commands::mysql_create_foreign_key,
             commands::mysql_drop_foreign_key,
+            // MySQL schema comparison
+            commands::mysql_compare_schemas,
+            commands::mysql_er_diagram,
             // MySQL data export/import
             commands::mysql_export_table,
+            commands::mysql_export_table_to_file,
+            commands::mysql_export_query_to_file,
             commands::mysql_import_data,
+            commands::mysql_import_file,
+            commands::mysql_infer_import_schema,
+            // MySQL dump and restore
+            commands::mysql_dump_database,
+            commands::mysql_restore_database,
+            // MySQL cross-connection table copy
+            commands::mysql_copy_table_between_connections,
             // MySQL view management
             commands::mysql_list_views,
             commands::mysql_get_view_definition,
@@ -206,16 +244,64 @@ pub fn run() {
             commands::mysql_list_triggers,
             commands::mysql_get_trigger_definition,
             commands::mysql_drop_trigger,
+            // MySQL stored program editing
+            commands::mysql_create_stored_program,
+            commands::mysql_alter_stored_program,
+            commands::mysql_get_procedure_parameters,
+            commands::mysql_call_procedure,
+            // MySQL event management
+            commands::mysql_list_events,
+            commands::mysql_get_event_definition,
+            commands::mysql_create_event,
+            commands::mysql_alter_event,
+            commands::mysql_set_event_enabled,
+            commands::mysql_drop_event,
+            commands::mysql_get_event_scheduler_status,
             // MySQL server monitoring
             commands::mysql_get_server_variables,
             commands::mysql_get_process_list,
             commands::mysql_kill_process,
+            // MySQL server configuration
+            commands::mysql_get_server_variable_info,
+            commands::mysql_set_server_variable,
+            commands::mysql_get_server_variable_changes,
+            commands::mysql_create_variable_baseline,
+            commands::mysql_list_variable_baselines,
+            commands::mysql_delete_variable_baseline,
+            commands::mysql_compare_server_variables,
+            // MySQL lock inspection
+            commands::mysql_get_lock_waits,
+            commands::mysql_get_latest_deadlock,
+            commands::mysql_kill_lock_blocker,
+            // MySQL replication monitoring
+            commands::mysql_get_replication_status,
+            commands::mysql_start_replication_lag_polling,
+            commands::mysql_get_replication_lag_history,
+            commands::mysql_clear_replication_lag_history,
             // MySQL query analysis
             commands::mysql_explain_query,
+            commands::mysql_explain_plan,
+            // MySQL workload analysis
+            commands::mysql_get_top_queries,
+            commands::mysql_take_digest_snapshot,
+            commands::mysql_start_digest_snapshots,
+            commands::mysql_list_digest_snapshots,
+            commands::mysql_delete_digest_snapshot,
+            commands::mysql_compare_digest_snapshots,
+            commands::mysql_explain_digest,
             // MySQL table maintenance
             commands::mysql_optimize_table,
             commands::mysql_analyze_table,
             commands::mysql_check_table,
+            // MySQL sessions
+            commands::mysql_session_open,
+            commands::mysql_session_list,
+            commands::mysql_session_get,
+            commands::mysql_session_execute,
+            commands::mysql_session_begin,
+            commands::mysql_session_commit,
+            commands::mysql_session_rollback,
+            commands::mysql_session_close,
             // Redis operations
             commands::redis_get_info,
             commands::redis_list_keys,
@@ -226,6 +312,17 @@ pub fn run() {
             commands::redis_set_ttl,
             commands::redis_export_keys,
             commands::redis_import_keys,
+            // Redis ACL operations
+            commands::redis_acl_list,
+            commands::redis_acl_get_user,
+            commands::redis_acl_set_user,
+            commands::redis_acl_del_user,
+            commands::redis_acl_whoami,
+            commands::redis_acl_log,
+            commands::redis_acl_log_reset,
+            // Redis compare operations
+            commands::redis_compare_start,
+            commands::redis_apply_sync_plan,
             // Port forward operations
             commands::start_port_forward,
             commands::stop_port_forward,
@@ -250,6 +347,9 @@ pub fn run() {
             commands::get_settings_batch,
             commands::set_setting,
             commands::delete_setting,
+            commands::get_mysql_filter_presets,
+            commands::save_mysql_filter_preset,
+            commands::delete_mysql_filter_preset,
             // LLM config operations
             commands::get_all_llm_configs,
             commands::get_llm_config,
@@ -266,6 +366,11 @@ pub fn run() {
             commands::create_k8s_favorite,
             commands::update_k8s_favorite,
             commands::delete_k8s_favorite,
+            // Background job operations
+            commands::job_list,
+            commands::job_get,
+            commands::job_cancel,
+            commands::job_remove,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
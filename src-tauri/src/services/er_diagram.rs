@@ -0,0 +1,573 @@
+//! Entity-relationship diagrams
+//!
+//! Builds the relationship graph of a database from its tables and foreign
+//! keys, optionally adding relations guessed from column names for legacy
+//! schemas without declared keys, and renders it as Graphviz DOT, Mermaid or
+//! PlantUML.
+
+use std::fmt::Write;
+
+use crate::db::models::{
+    Cardinality, ErColumn, ErDiagram, ErDiagramFormat, ErDiagramRequest, ErDiagramResult, ErEdge,
+    ErNode, ForeignKeyInfo, MysqlTable, MysqlTableSchema,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::mysql::MysqlService;
+
+/// Builds ER diagrams of MySQL databases
+pub struct ErDiagramService {
+    mysql: MysqlService,
+}
+
+impl ErDiagramService {
+    /// Create a diagram service on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// Read the tables and foreign keys of a database and render the diagram
+    pub async fn build(&self, req: &ErDiagramRequest) -> AppResult<ErDiagramResult> {
+        let mut tables = self.mysql.list_tables(&req.database).await?;
+        if let Some(wanted) = &req.tables {
+            if let Some(missing) = wanted
+                .iter()
+                .find(|w| !tables.iter().any(|t| &t.name == *w))
+            {
+                return Err(AppError::NotFound(format!("Table not found: {}", missing)));
+            }
+            tables.retain(|t| wanted.contains(&t.name));
+        }
+
+        let mut nodes = Vec::with_capacity(tables.len());
+        let mut foreign_keys = Vec::new();
+        for table in &tables {
+            let schema = self
+                .mysql
+                .get_table_schema(&req.database, &table.name)
+                .await?;
+            let keys = self
+                .mysql
+                .list_foreign_keys(&req.database, &table.name)
+                .await?;
+            nodes.push(table_node(table, &schema, &keys));
+            foreign_keys.extend(keys.into_iter().map(|key| (table.name.clone(), key)));
+        }
+
+        let diagram = build_diagram(&req.database, nodes, &foreign_keys, req.infer_relations);
+        let rendered = match req.format {
+            ErDiagramFormat::Json => None,
+            ErDiagramFormat::Dot => Some(render_dot(&diagram)),
+            ErDiagramFormat::Mermaid => Some(render_mermaid(&diagram)),
+            ErDiagramFormat::PlantUml => Some(render_plantuml(&diagram)),
+        };
+        Ok(ErDiagramResult {
+            diagram,
+            format: req.format,
+            rendered,
+        })
+    }
+}
+
+fn table_node(table: &MysqlTable, schema: &MysqlTableSchema, keys: &[ForeignKeyInfo]) -> ErNode {
+    let primary_key: Vec<String> = schema
+        .indexes
+        .iter()
+        .find(|index| index.name == "PRIMARY")
+        .map(|index| index.columns.clone())
+        .unwrap_or_default();
+    let unique_keys: Vec<Vec<String>> = schema
+        .indexes
+        .iter()
+        .filter(|index| index.unique && index.name != "PRIMARY")
+        .map(|index| index.columns.clone())
+        .collect();
+    let columns = schema
+        .columns
+        .iter()
+        .map(|column| ErColumn {
+            name: column.name.clone(),
+            column_type: column.column_type.clone(),
+            nullable: column.nullable,
+            primary_key: primary_key.contains(&column.name),
+            foreign_key: keys.iter().any(|key| key.columns.contains(&column.name)),
+            unique: unique_keys
+                .iter()
+                .any(|key| key == std::slice::from_ref(&column.name)),
+        })
+        .collect();
+    ErNode {
+        table: table.name.clone(),
+        columns,
+        primary_key,
+        unique_keys,
+        comment: table.comment.clone().filter(|c| !c.is_empty()),
+    }
+}
+
+/// Join tables by their foreign keys, given as (table, key) pairs. Keys to
+/// tables outside the diagram are left out.
+fn build_diagram(
+    database: &str,
+    nodes: Vec<ErNode>,
+    foreign_keys: &[(String, ForeignKeyInfo)],
+    infer_relations: bool,
+) -> ErDiagram {
+    let mut edges = Vec::new();
+    for (table, key) in foreign_keys {
+        let (Some(from), true) = (
+            nodes.iter().find(|n| &n.table == table),
+            nodes.iter().any(|n| n.table == key.ref_table),
+        ) else {
+            continue;
+        };
+        let (from_cardinality, to_cardinality) = cardinality(from, &key.columns);
+        edges.push(ErEdge {
+            name: key.name.clone(),
+            from_table: table.clone(),
+            from_columns: key.columns.clone(),
+            to_table: key.ref_table.clone(),
+            to_columns: key.ref_columns.clone(),
+            from_cardinality,
+            to_cardinality,
+            inferred: false,
+            on_delete: Some(key.on_delete.clone()),
+            on_update: Some(key.on_update.clone()),
+        });
+    }
+    if infer_relations {
+        edges.extend(inferred_edges(&nodes));
+    }
+    ErDiagram {
+        database: database.to_string(),
+        nodes,
+        edges,
+    }
+}
+
+/// Referencing rows per referenced row, and referenced rows per referencing
+/// row, of a relation from `columns` of `from`
+fn cardinality(from: &ErNode, columns: &[String]) -> (Cardinality, Cardinality) {
+    let covers = |key: &Vec<String>| !key.is_empty() && key.iter().all(|k| columns.contains(k));
+    let unique = covers(&from.primary_key) || from.unique_keys.iter().any(covers);
+    let optional = from
+        .columns
+        .iter()
+        .any(|c| c.nullable && columns.contains(&c.name));
+    (
+        if unique {
+            Cardinality::ZeroOrOne
+        } else {
+            Cardinality::ZeroOrMany
+        },
+        if optional {
+            Cardinality::ZeroOrOne
+        } else {
+            Cardinality::ExactlyOne
+        },
+    )
+}
+
+/// Relations guessed from column names: `customer_id` or `customerId` refers
+/// to the single-column primary key of `customer` or `customers`, and a
+/// column named like another table's primary key (other than `id`) refers
+/// to that key. Columns of declared foreign keys are skipped.
+fn inferred_edges(nodes: &[ErNode]) -> Vec<ErEdge> {
+    let mut edges = Vec::new();
+    for node in nodes {
+        for column in node.columns.iter().filter(|c| !c.foreign_key) {
+            let name = column.name.to_ascii_lowercase();
+            let stem = name.strip_suffix("_id").or_else(|| {
+                column
+                    .name
+                    .strip_suffix("Id")
+                    .filter(|s| !s.is_empty())
+                    .map(|_| &name[..name.len() - 2])
+            });
+            let candidates = nodes.iter().filter(|target| {
+                target.primary_key.len() == 1
+                    && !(target.table == node.table && column.primary_key)
+                    && target
+                        .columns
+                        .iter()
+                        .find(|c| c.name == target.primary_key[0])
+                        .is_some_and(|key| {
+                            type_family(&key.column_type) == type_family(&column.column_type)
+                        })
+            });
+            let by_table = |target: &&ErNode| {
+                let table = target.table.to_ascii_lowercase();
+                stem.is_some_and(|stem| {
+                    table == stem
+                        || table == format!("{}s", stem)
+                        || table == format!("{}es", stem)
+                        || stem
+                            .strip_suffix('y')
+                            .is_some_and(|base| table == format!("{}ies", base))
+                })
+            };
+            let by_key = |target: &&ErNode| {
+                name != "id" && target.primary_key[0].eq_ignore_ascii_case(&column.name)
+            };
+            let target = candidates.clone().find(by_table).or_else(|| {
+                candidates
+                    .clone()
+                    .find(|t| t.table != node.table && by_key(t))
+            });
+            let Some(target) = target else {
+                continue;
+            };
+            let columns = vec![column.name.clone()];
+            let (from_cardinality, to_cardinality) = cardinality(node, &columns);
+            edges.push(ErEdge {
+                name: format!("inferred_{}_{}", node.table, column.name),
+                from_table: node.table.clone(),
+                from_columns: columns,
+                to_table: target.table.clone(),
+                to_columns: target.primary_key.clone(),
+                from_cardinality,
+                to_cardinality,
+                inferred: true,
+                on_delete: None,
+                on_update: None,
+            });
+        }
+    }
+    edges
+}
+
+/// Types that can hold each other's key values
+fn type_family(column_type: &str) -> String {
+    let base = column_type
+        .to_ascii_lowercase()
+        .split(['(', ' '])
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    match base.as_str() {
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" => "int".to_string(),
+        "char" | "varchar" => "char".to_string(),
+        "binary" | "varbinary" => "binary".to_string(),
+        _ => base,
+    }
+}
+
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn dot_arrow(cardinality: Cardinality) -> &'static str {
+    match cardinality {
+        Cardinality::ExactlyOne => "teetee",
+        Cardinality::ZeroOrOne => "teeodot",
+        Cardinality::ZeroOrMany => "crowodot",
+    }
+}
+
+/// Graphviz DOT with one HTML-like table per node; columns are ports that
+/// edges attach to
+pub fn render_dot(diagram: &ErDiagram) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "digraph {} {{", dot_id(&diagram.database));
+    out.push_str("  rankdir=LR;\n");
+    out.push_str("  node [shape=plaintext, fontname=\"Helvetica\"];\n");
+    out.push_str("  edge [dir=both, fontname=\"Helvetica\", fontsize=10];\n");
+    for node in &diagram.nodes {
+        let _ = writeln!(out, "\n  {} [label=<", dot_id(&node.table));
+        out.push_str(
+            "    <TABLE BORDER=\"0\" CELLBORDER=\"1\" CELLSPACING=\"0\" CELLPADDING=\"4\">\n",
+        );
+        let _ = writeln!(
+            out,
+            "      <TR><TD BGCOLOR=\"lightgrey\" COLSPAN=\"2\"><B>{}</B></TD></TR>",
+            html_escape(&node.table)
+        );
+        for (i, column) in node.columns.iter().enumerate() {
+            let mut name = html_escape(&column.name);
+            if column.primary_key {
+                name = format!("<B>{}</B>", name);
+            }
+            if column.foreign_key {
+                name = format!("<I>{}</I>", name);
+            }
+            let _ = writeln!(
+                out,
+                "      <TR><TD PORT=\"c{}\" ALIGN=\"LEFT\">{}</TD><TD ALIGN=\"LEFT\">{}</TD></TR>",
+                i,
+                name,
+                html_escape(&column.column_type)
+            );
+        }
+        out.push_str("    </TABLE>>];\n");
+    }
+    if !diagram.edges.is_empty() {
+        out.push('\n');
+    }
+    for edge in &diagram.edges {
+        let port = |table: &str, columns: &[String]| {
+            diagram
+                .nodes
+                .iter()
+                .find(|n| n.table == table)
+                .and_then(|n| {
+                    n.columns
+                        .iter()
+                        .position(|c| columns.first() == Some(&c.name))
+                })
+                .map(|i| format!(":c{}", i))
+                .unwrap_or_default()
+        };
+        let _ = writeln!(
+            out,
+            "  {}{} -> {}{} [arrowtail={}, arrowhead={}, label={}{}];",
+            dot_id(&edge.from_table),
+            port(&edge.from_table, &edge.from_columns),
+            dot_id(&edge.to_table),
+            port(&edge.to_table, &edge.to_columns),
+            dot_arrow(edge.from_cardinality),
+            dot_arrow(edge.to_cardinality),
+            dot_id(&edge.name),
+            if edge.inferred { ", style=dashed" } else { "" }
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Mermaid and PlantUML names: letters, digits, `_` and `-`
+fn plain_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Crow's foot markers for the left and right end of a relation
+fn crow_foot(cardinality: Cardinality) -> (&'static str, &'static str) {
+    match cardinality {
+        Cardinality::ZeroOrOne => ("|o", "o|"),
+        Cardinality::ExactlyOne => ("||", "||"),
+        Cardinality::ZeroOrMany => ("}o", "o{"),
+    }
+}
+
+/// Mermaid `erDiagram`. Attribute types are reduced to the base type, as
+/// Mermaid doesn't accept lengths with commas.
+pub fn render_mermaid(diagram: &ErDiagram) -> String {
+    let mut out = String::from("erDiagram\n");
+    for node in &diagram.nodes {
+        let _ = writeln!(out, "    {} {{", plain_name(&node.table));
+        for column in &node.columns {
+            let base = column
+                .column_type
+                .split(['(', ' '])
+                .next()
+                .unwrap_or_default();
+            let mut keys = Vec::new();
+            if column.primary_key {
+                keys.push("PK");
+            }
+            if column.foreign_key {
+                keys.push("FK");
+            }
+            if column.unique {
+                keys.push("UK");
+            }
+            let _ = write!(
+                out,
+                "        {} {}",
+                plain_name(base),
+                plain_name(&column.name)
+            );
+            if !keys.is_empty() {
+                let _ = write!(out, " {}", keys.join(", "));
+            }
+            if column.column_type != base {
+                let _ = write!(out, " \"{}\"", column.column_type.replace('"', "'"));
+            }
+            out.push('\n');
+        }
+        out.push_str("    }\n");
+    }
+    for edge in &diagram.edges {
+        let _ = writeln!(
+            out,
+            "    {} {}{}{} {} : \"{}\"",
+            plain_name(&edge.from_table),
+            crow_foot(edge.from_cardinality).0,
+            if edge.inferred { ".." } else { "--" },
+            crow_foot(edge.to_cardinality).1,
+            plain_name(&edge.to_table),
+            edge.name.replace('"', "'")
+        );
+    }
+    out
+}
+
+/// PlantUML entity diagram in Information Engineering notation. Mandatory
+/// columns are starred and primary key columns sit above the separator.
+pub fn render_plantuml(diagram: &ErDiagram) -> String {
+    let mut out = String::from("@startuml\nhide circle\nskinparam linetype ortho\n");
+    for node in &diagram.nodes {
+        let _ = writeln!(
+            out,
+            "\nentity \"{}\" as {} {{",
+            node.table.replace('"', "'"),
+            plain_name(&node.table)
+        );
+        let (key, rest): (Vec<&ErColumn>, Vec<&ErColumn>) =
+            node.columns.iter().partition(|c| c.primary_key);
+        let line = |column: &ErColumn| {
+            let mut stereotypes = String::new();
+            if column.primary_key {
+                stereotypes.push_str(" <<PK>>");
+            }
+            if column.foreign_key {
+                stereotypes.push_str(" <<FK>>");
+            }
+            if column.unique {
+                stereotypes.push_str(" <<UK>>");
+            }
+            format!(
+                "  {}{} : {}{}\n",
+                if column.nullable { "" } else { "* " },
+                column.name,
+                column.column_type,
+                stereotypes
+            )
+        };
+        for column in &key {
+            out.push_str(&line(column));
+        }
+        if !key.is_empty() {
+            out.push_str("  --\n");
+        }
+        for column in &rest {
+            out.push_str(&line(column));
+        }
+        out.push_str("}\n");
+    }
+    if !diagram.edges.is_empty() {
+        out.push('\n');
+    }
+    for edge in &diagram.edges {
+        let _ = writeln!(
+            out,
+            "{} {}{}{} {} : {}",
+            plain_name(&edge.from_table),
+            crow_foot(edge.from_cardinality).0,
+            if edge.inferred { ".." } else { "--" },
+            crow_foot(edge.to_cardinality).1,
+            plain_name(&edge.to_table),
+            edge.name
+        );
+    }
+    out.push_str("@enduml\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, column_type: &str, nullable: bool, primary_key: bool) -> ErColumn {
+        ErColumn {
+            name: name.to_string(),
+            column_type: column_type.to_string(),
+            nullable,
+            primary_key,
+            foreign_key: false,
+            unique: false,
+        }
+    }
+
+    fn node(table: &str, columns: Vec<ErColumn>) -> ErNode {
+        ErNode {
+            table: table.to_string(),
+            primary_key: columns
+                .iter()
+                .filter(|c| c.primary_key)
+                .map(|c| c.name.clone())
+                .collect(),
+            columns,
+            unique_keys: Vec::new(),
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_er_diagram() {
+        let mut customer_id = column("customer_id", "int(11)", false, false);
+        customer_id.foreign_key = true;
+        let nodes = vec![
+            node(
+                "categories",
+                vec![column("id", "bigint unsigned", false, true)],
+            ),
+            node(
+                "customers",
+                vec![
+                    column("id", "int(11)", false, true),
+                    column("name", "varchar(255)", true, false),
+                ],
+            ),
+            node(
+                "orders",
+                vec![
+                    column("id", "int(11)", false, true),
+                    customer_id,
+                    column("category_id", "int", true, false),
+                    column("note", "text", true, false),
+                ],
+            ),
+        ];
+        let keys = vec![(
+            "orders".to_string(),
+            ForeignKeyInfo {
+                name: "fk_orders_customer".to_string(),
+                columns: vec!["customer_id".to_string()],
+                ref_table: "customers".to_string(),
+                ref_columns: vec!["id".to_string()],
+                on_delete: "CASCADE".to_string(),
+                on_update: "NO ACTION".to_string(),
+            },
+        )];
+
+        let diagram = build_diagram("shop", nodes, &keys, true);
+        assert_eq!(diagram.edges.len(), 2);
+        let declared = &diagram.edges[0];
+        assert_eq!(declared.from_cardinality, Cardinality::ZeroOrMany);
+        assert_eq!(declared.to_cardinality, Cardinality::ExactlyOne);
+        let inferred = &diagram.edges[1];
+        assert!(inferred.inferred);
+        assert_eq!(inferred.to_table, "categories");
+        assert_eq!(inferred.to_cardinality, Cardinality::ZeroOrOne);
+
+        let mermaid = render_mermaid(&diagram);
+        assert!(mermaid.contains("        int customer_id FK \"int(11)\"\n"));
+        assert!(mermaid.contains("    orders }o--|| customers : \"fk_orders_customer\"\n"));
+        assert!(
+            mermaid.contains("    orders }o..o| categories : \"inferred_orders_category_id\"\n")
+        );
+
+        let plantuml = render_plantuml(&diagram);
+        assert!(plantuml.contains("  * id : int(11) <<PK>>\n  --\n"));
+        assert!(plantuml.contains("orders }o--|| customers : fk_orders_customer\n"));
+
+        let dot = render_dot(&diagram);
+        assert!(dot.contains(
+            "\"orders\":c1 -> \"customers\":c0 [arrowtail=crowodot, arrowhead=teetee, label=\"fk_orders_customer\"];"
+        ));
+    }
+}
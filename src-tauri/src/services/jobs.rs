@@ -0,0 +1,353 @@
+//! Background job registry
+//!
+//! Long-running operations (comparisons, dumps, exports, copies) run as
+//! background tasks and report their progress here, so the frontend can poll
+//! them and request cancellation.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tokio::sync::RwLock;
+
+use crate::error::{AppError, AppResult};
+
+/// Maximum number of finished jobs to keep in memory
+const MAX_FINISHED_JOBS: usize = 100;
+
+/// How often a sleeping job checks for cancellation
+const SLEEP_STEP: Duration = Duration::from_millis(250);
+
+/// Job lifecycle status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Snapshot of a background job
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: String,
+    /// Job kind, e.g. redis_compare
+    pub kind: String,
+    pub status: JobStatus,
+    /// Units of work done so far (keys, rows, statements...)
+    pub processed: u64,
+    /// Total units of work, if known
+    pub total: Option<u64>,
+    /// Human readable description of the current step
+    pub message: Option<String>,
+    /// Job output once completed
+    pub result: Option<JsonValue>,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+/// Response returned when a job is started
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStarted {
+    pub job_id: String,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    cancelled: Arc<AtomicBool>,
+}
+
+type JobMap = Arc<RwLock<HashMap<String, JobEntry>>>;
+
+/// Handle passed to a running job for progress reporting and cancellation
+#[derive(Clone)]
+pub struct JobContext {
+    id: String,
+    jobs: JobMap,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl JobContext {
+    /// Job identifier
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Return an error if cancellation has been requested.
+    /// Jobs should call this between units of work.
+    pub fn check_cancelled(&self) -> AppResult<()> {
+        if self.is_cancelled() {
+            return Err(AppError::Cancelled("Job cancelled".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Wait `secs` seconds between units of work, returning early with an
+    /// error if cancellation is requested meanwhile
+    pub async fn sleep(&self, secs: u64) -> AppResult<()> {
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(secs);
+        loop {
+            self.check_cancelled()?;
+            let left = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if left.is_zero() {
+                return Ok(());
+            }
+            tokio::time::sleep(left.min(SLEEP_STEP)).await;
+        }
+    }
+
+    /// Update the progress counters
+    pub async fn set_progress(&self, processed: u64, total: Option<u64>) {
+        if let Some(entry) = self.jobs.write().await.get_mut(&self.id) {
+            entry.info.processed = processed;
+            if total.is_some() {
+                entry.info.total = total;
+            }
+        }
+    }
+
+    /// Update the current step description
+    pub async fn set_message(&self, message: impl Into<String>) {
+        if let Some(entry) = self.jobs.write().await.get_mut(&self.id) {
+            entry.info.message = Some(message.into());
+        }
+    }
+}
+
+/// Registry of background jobs
+#[derive(Clone)]
+pub struct JobService {
+    jobs: JobMap,
+}
+
+impl JobService {
+    /// Create an empty job registry
+    pub fn new() -> Self {
+        Self {
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Start a job in the background and return its id.
+    ///
+    /// The task's output is serialized into the job result. A task that fails
+    /// after cancellation was requested is reported as cancelled.
+    pub async fn spawn<F, Fut, T>(&self, kind: &str, task: F) -> JobStarted
+    where
+        F: FnOnce(JobContext) -> Fut + Send + 'static,
+        Fut: Future<Output = AppResult<T>> + Send + 'static,
+        T: Serialize + Send + 'static,
+    {
+        let id = uuid::Uuid::new_v4().to_string();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        self.jobs.write().await.insert(
+            id.clone(),
+            JobEntry {
+                info: JobInfo {
+                    id: id.clone(),
+                    kind: kind.to_string(),
+                    status: JobStatus::Running,
+                    processed: 0,
+                    total: None,
+                    message: None,
+                    result: None,
+                    error: None,
+                    started_at: chrono::Utc::now().to_rfc3339(),
+                    finished_at: None,
+                },
+                cancelled: cancelled.clone(),
+            },
+        );
+
+        let ctx = JobContext {
+            id: id.clone(),
+            jobs: self.jobs.clone(),
+            cancelled,
+        };
+        let jobs = self.jobs.clone();
+        let kind = kind.to_string();
+
+        tokio::spawn(async move {
+            let job_id = ctx.id.clone();
+            let cancelled = ctx.cancelled.clone();
+            let outcome = task(ctx).await;
+
+            let mut jobs = jobs.write().await;
+            if let Some(entry) = jobs.get_mut(&job_id) {
+                let info = &mut entry.info;
+                match outcome {
+                    Ok(result) => match serde_json::to_value(result) {
+                        Ok(value) => {
+                            info.status = JobStatus::Completed;
+                            info.result = Some(value);
+                        }
+                        Err(e) => {
+                            info.status = JobStatus::Failed;
+                            info.error = Some(e.to_string());
+                        }
+                    },
+                    Err(_) if cancelled.load(Ordering::Relaxed) => {
+                        info.status = JobStatus::Cancelled;
+                    }
+                    Err(e) => {
+                        log::warn!("Job {} ({}) failed: {}", job_id, kind, e);
+                        info.status = JobStatus::Failed;
+                        info.error = Some(e.to_string());
+                    }
+                }
+                info.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            }
+            prune_finished(&mut jobs);
+        });
+
+        JobStarted { job_id: id }
+    }
+
+    /// Get a job snapshot
+    pub async fn get(&self, id: &str) -> AppResult<JobInfo> {
+        self.jobs
+            .read()
+            .await
+            .get(id)
+            .map(|entry| entry.info.clone())
+            .ok_or_else(|| AppError::NotFound(format!("Job not found: {}", id)))
+    }
+
+    /// List all jobs, newest first
+    pub async fn list(&self) -> Vec<JobInfo> {
+        let mut jobs: Vec<JobInfo> = self
+            .jobs
+            .read()
+            .await
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect();
+        jobs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        jobs
+    }
+
+    /// Request cancellation of a running job
+    pub async fn cancel(&self, id: &str) -> AppResult<()> {
+        let jobs = self.jobs.read().await;
+        let entry = jobs
+            .get(id)
+            .ok_or_else(|| AppError::NotFound(format!("Job not found: {}", id)))?;
+        entry.cancelled.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Remove a finished job from the registry
+    pub async fn remove(&self, id: &str) -> AppResult<()> {
+        let mut jobs = self.jobs.write().await;
+        match jobs.get(id) {
+            None => Err(AppError::NotFound(format!("Job not found: {}", id))),
+            Some(entry) if entry.info.status == JobStatus::Running => Err(AppError::Validation(
+                "Cannot remove a running job, cancel it first".to_string(),
+            )),
+            Some(_) => {
+                jobs.remove(id);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Default for JobService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Drop the oldest finished jobs beyond MAX_FINISHED_JOBS
+fn prune_finished(jobs: &mut HashMap<String, JobEntry>) {
+    let mut finished: Vec<(String, String)> = jobs
+        .values()
+        .filter(|entry| entry.info.status != JobStatus::Running)
+        .map(|entry| {
+            (
+                entry.info.finished_at.clone().unwrap_or_default(),
+                entry.info.id.clone(),
+            )
+        })
+        .collect();
+
+    if finished.len() <= MAX_FINISHED_JOBS {
+        return;
+    }
+
+    finished.sort();
+    let excess = finished.len() - MAX_FINISHED_JOBS;
+    for (_, id) in finished.into_iter().take(excess) {
+        jobs.remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn wait_until_finished(service: &JobService, id: &str) -> JobInfo {
+        for _ in 0..100 {
+            let info = service.get(id).await.unwrap();
+            if info.status != JobStatus::Running {
+                return info;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job did not finish");
+    }
+
+    #[tokio::test]
+    async fn test_job_completes_with_result() {
+        let service = JobService::new();
+
+        let started = service
+            .spawn("test", |ctx| async move {
+                ctx.set_progress(3, Some(3)).await;
+                Ok(42)
+            })
+            .await;
+
+        let info = wait_until_finished(&service, &started.job_id).await;
+        assert_eq!(info.status, JobStatus::Completed);
+        assert_eq!(info.processed, 3);
+        assert_eq!(info.result, Some(serde_json::json!(42)));
+
+        service.remove(&started.job_id).await.unwrap();
+        assert!(service.list().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_job_cancellation() {
+        let service = JobService::new();
+
+        let started = service
+            .spawn("test", |ctx| async move { ctx.sleep(3600).await })
+            .await;
+
+        assert!(service.remove(&started.job_id).await.is_err());
+        service.cancel(&started.job_id).await.unwrap();
+
+        let info = wait_until_finished(&service, &started.job_id).await;
+        assert_eq!(info.status, JobStatus::Cancelled);
+
+        let ctx = JobContext {
+            id: "x".to_string(),
+            jobs: service.jobs.clone(),
+            cancelled: Arc::new(AtomicBool::new(true)),
+        };
+        assert!(matches!(ctx.check_cancelled(), Err(AppError::Cancelled(_))));
+    }
+}
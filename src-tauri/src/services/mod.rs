@@ -5,31 +5,97 @@
 //! - Cluster management
 //! - Crypto (password encryption)
 //! - MySQL operations
+//! - MySQL accounts, roles and privilege matrix
+//! - MySQL logical dump and restore
+//! - MySQL streaming file exports
+//! - MySQL bulk file imports
+//! - MySQL index advisor (unused, redundant and missing indexes)
+//! - MySQL structured query plans (EXPLAIN JSON / ANALYZE)
+//! - MySQL stored procedures, functions and triggers (create, replace, CALL)
+//! - MySQL table row filters
+//! - MySQL cross-connection table copy (masking, upsert)
+//! - MySQL InnoDB lock waits and deadlocks
+//! - MySQL replication status and lag history
+//! - MySQL server variables (editing, audit, baselines)
+//! - MySQL workload analysis (statement digests, snapshots)
+//! - MySQL query cursors (streamed result sets)
+//! - MySQL query execution tracking (cancellation)
+//! - Named query parameters
+//! - MySQL sticky sessions (pinned connections, transactions)
+//! - MySQL schema comparison and migration scripts
+//! - MySQL ER diagrams (DOT, Mermaid, PlantUML)
+//! - SQL identifier quoting
+//! - SQL script splitting and statement classification
 //! - Redis operations
+//! - Redis instance comparison
 //! - Kubernetes operations
 //! - Port forwarding
 //! - User settings
 //! - LLM configuration
 //! - Log aggregation (for web debug mode)
+//! - Background jobs
 
 pub mod cluster;
 pub mod connection;
 pub mod crypto;
+pub mod er_diagram;
+pub mod jobs;
 pub mod k8s;
 pub mod llm_config;
 pub mod log_service;
 pub mod mysql;
+pub mod mysql_accounts;
+pub mod mysql_dump;
+pub mod mysql_explain;
+pub mod mysql_export;
+pub mod mysql_filter;
+pub mod mysql_import;
+pub mod mysql_index_advisor;
+pub mod mysql_locks;
+pub mod mysql_replication;
+pub mod mysql_routines;
+pub mod mysql_session;
+pub mod mysql_table_copy;
+pub mod mysql_variables;
+pub mod mysql_workload;
 pub mod port_forward;
+pub mod query_cursor;
+pub mod query_execution;
+pub mod query_params;
 pub mod redis;
+pub mod redis_compare;
+pub mod schema_diff;
 pub mod settings;
+pub mod sql_identifiers;
+pub mod sql_splitter;
 
 pub use cluster::ClusterService;
 pub use connection::ConnectionService;
 pub use crypto::CryptoService;
+pub use er_diagram::ErDiagramService;
+pub use jobs::{JobContext, JobInfo, JobService, JobStarted, JobStatus};
 pub use k8s::K8sService;
 pub use llm_config::LLMConfigService;
 pub use log_service::{AddLogRequest, LogEntry, LogLevel, LogService, LogSource};
 pub use mysql::MysqlService;
+pub use mysql_accounts::MysqlAccountService;
+pub use mysql_dump::MysqlDumpService;
+pub use mysql_explain::MysqlExplainService;
+pub use mysql_export::MysqlExportService;
+pub use mysql_import::MysqlImportService;
+pub use mysql_index_advisor::MysqlIndexAdvisorService;
+pub use mysql_locks::MysqlLockService;
+pub use mysql_replication::MysqlReplicationService;
+pub use mysql_routines::MysqlRoutineService;
+pub use mysql_session::MysqlSessionService;
+pub use mysql_table_copy::MysqlTableCopyService;
+pub use mysql_variables::MysqlVariableService;
+pub use mysql_workload::MysqlWorkloadService;
 pub use port_forward::PortForwardService;
+pub use query_cursor::{QueryCursorService, QueryLimits};
+pub use query_execution::QueryExecutionService;
 pub use redis::RedisService;
+pub use redis_compare::RedisCompareService;
+pub use schema_diff::SchemaCompareService;
 pub use settings::SettingsService;
+pub use sql_splitter::{split_statements, SqlStatement};
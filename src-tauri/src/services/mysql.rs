@@ -9,22 +9,39 @@
 use std::collections::HashMap;
 use std::time::Instant;
 
+use futures::stream::BoxStream;
+use futures::TryStreamExt;
 use serde_json::Value as JsonValue;
-use sqlx::mysql::{MySqlPool, MySqlPoolOptions, MySqlRow};
-use sqlx::{Column, Row, TypeInfo};
+use sqlx::mysql::{
+    MySql, MySqlConnection, MySqlDatabaseError, MySqlPool, MySqlPoolOptions, MySqlRow,
+};
+use sqlx::{Column, Either, Execute, Executor, Row, TypeInfo};
 
 use crate::db::models::{
-    AlterDatabaseRequest, AlterTableRequest, AlterUserPasswordRequest, Connection,
-    CreateDatabaseRequest, CreateForeignKeyRequest, CreateIndexRequest, CreateTableRequest,
-    CreateUserRequest, CreateViewRequest, DropUserRequest, ExplainResult, ExportFormat,
+    AlterDatabaseRequest, AlterEventRequest, AlterTableRequest, AlterUserPasswordRequest,
+    Connection, CreateDatabaseRequest, CreateEventRequest, CreateForeignKeyRequest,
+    CreateIndexRequest, CreateTableRequest, CreateUserRequest, CreateViewRequest, DropUserRequest,
+    EventDefinition, EventInfo, EventSchedule, EventSchedulerStatus, ExplainResult, ExportFormat,
     ExportTableRequest, ExportTableResponse, ForeignKeyInfo, GrantPrivilegesRequest,
-    ImportDataRequest, ImportResult, IndexInfo, MysqlColumn, MysqlDatabase, MysqlIndex,
-    MysqlQueryResult, MysqlServerInfo, MysqlTable, MysqlTableData, MysqlTableSchema,
-    MysqlUserInfo, ProcedureDefinition, ProcedureInfo, ProcessInfo, RevokePrivilegesRequest,
-    ServerVariable, TableMaintenanceResult, TriggerDefinition, TriggerInfo, UserGrantInfo,
-    UserGrantsResponse, ViewDefinition, ViewInfo,
+    ImportDataRequest, ImportResult, IndexInfo, KeysetPage, KeysetPageRequest, MysqlColumn,
+    MysqlDatabase, MysqlIndex, MysqlQueryResult, MysqlScriptResult, MysqlServerInfo,
+    MysqlStatementResult, MysqlTable, MysqlTableData, MysqlTableSchema, MysqlUserInfo,
+    ProcedureDefinition, ProcedureInfo, ProcessInfo, RevokePrivilegesRequest, RowCountMode,
+    ScriptErrorMode, ServerVariable, TableMaintenanceResult, TableRowsOptions, TriggerDefinition,
+    TriggerInfo, UserGrantInfo, UserGrantsResponse, ViewDefinition, ViewInfo,
 };
 use crate::error::{AppError, AppResult};
+use crate::services::mysql_filter::{encode_cursor, has_options, Keyset, RowClauses};
+use crate::services::query_cursor::{QueryCursorService, QueryLimits};
+use crate::services::query_execution::{ExecutionHandle, QueryExecutionService};
+use crate::services::query_params::{
+    quote_plain_string, quote_string, render_query, BindValue, ResolvedQuery,
+};
+use crate::services::sql_identifiers::quote_name;
+use crate::services::sql_splitter::{classify_statement, returns_rows, split_statements};
+
+/// Number of rows converted to JSON at a time when streaming results
+const ROW_CHUNK_SIZE: usize = 500;
 
 /// MySQL service for database operations
 pub struct MysqlService {
@@ -80,6 +97,11 @@ impl MysqlService {
         })
     }
 
+    /// Underlying connection pool
+    pub(crate) fn pool(&self) -> &MySqlPool {
+        &self.pool
+    }
+
     /// Get MySQL server info
     pub async fn get_info(&self) -> AppResult<MysqlServerInfo> {
         let version: (String,) = sqlx::query_as("SELECT VERSION()")
@@ -212,7 +234,7 @@ impl MysqlService {
     }
 
     /// Helper function to extract string from row that might be VARCHAR or VARBINARY
-    fn get_string_from_row(row: &MySqlRow, column: &str) -> String {
+    pub(crate) fn get_string_from_row(row: &MySqlRow, column: &str) -> String {
         row.try_get::<String, _>(column)
             .or_else(|_| {
                 row.try_get::<Vec<u8>, _>(column)
@@ -222,7 +244,7 @@ impl MysqlService {
     }
 
     /// Helper function to extract optional string from row
-    fn get_optional_string_from_row(row: &MySqlRow, column: &str) -> Option<String> {
+    pub(crate) fn get_optional_string_from_row(row: &MySqlRow, column: &str) -> Option<String> {
         row.try_get::<Option<String>, _>(column)
             .ok()
             .flatten()
@@ -382,63 +404,90 @@ impl MysqlService {
 
     /// Get table primary key column
     pub async fn get_table_primary_key(&self, database: &str, table: &str) -> AppResult<String> {
+        self.get_primary_key_columns(database, table)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::NotFound("No primary key found".to_string()))
+    }
+
+    /// Get all primary key columns of a table, in key order
+    pub async fn get_primary_key_columns(
+        &self,
+        database: &str,
+        table: &str,
+    ) -> AppResult<Vec<String>> {
         let query = format!(
             r#"SELECT COLUMN_NAME
             FROM information_schema.KEY_COLUMN_USAGE
             WHERE TABLE_SCHEMA = '{}'
             AND TABLE_NAME = '{}'
             AND CONSTRAINT_NAME = 'PRIMARY'
-            ORDER BY ORDINAL_POSITION
-            LIMIT 1"#,
+            ORDER BY ORDINAL_POSITION"#,
             database, table
         );
 
-        let row: Option<(String,)> = sqlx::query_as(&query)
-            .fetch_optional(&self.pool)
+        let rows: Vec<(String,)> = sqlx::query_as(&query)
+            .fetch_all(&self.pool)
             .await
             .map_err(|e| AppError::Database(e.to_string()))?;
 
-        row.map(|(name,)| name)
-            .ok_or_else(|| AppError::NotFound("No primary key found".to_string()))
+        Ok(rows.into_iter().map(|(name,)| name).collect())
     }
 
     /// Execute a SQL query
     /// Uses raw_sql to avoid prepared statements, which some MySQL proxies don't support
-    pub async fn execute_query(&self, database: &str, query: &str) -> AppResult<MysqlQueryResult> {
+    ///
+    /// Row-returning queries are streamed: the first `page_size` rows are returned
+    /// directly and further pages are served through a cursor, up to `max_rows` in total.
+    pub async fn execute_query(
+        &self,
+        cursors: &QueryCursorService,
+        executions: &QueryExecutionService,
+        execution_id: Option<String>,
+        database: &str,
+        query: &str,
+        limits: QueryLimits,
+    ) -> AppResult<MysqlQueryResult> {
         let start = Instant::now();
-        let query_type = detect_query_type(query);
-
-        // Combine USE and query into a single raw_sql call to ensure same connection
-        // For SELECT queries, we need special handling to get rows back
-        if query_type == "select" || query_type == "show" || query_type == "describe" {
-            // For queries that return rows, use USE statement first, then the query
-            // Build the full query with USE prepended
-            let full_query = format!("USE `{}`; {}", database, query);
-
-            // SELECT query - return rows using raw_sql to avoid prepared statements
-            let rows = sqlx::raw_sql(&full_query)
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| AppError::Database(e.to_string()))?;
+        let query_type = classify_statement(query);
 
-            let execution_time_ms = start.elapsed().as_millis() as u64;
+        // Pin the query to one connection so it can be killed by thread id
+        let conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        let mut execution = executions
+            .register_connection(execution_id, self.pool.clone(), conn)
+            .await?;
 
-            let (columns, json_rows) = mysql_rows_to_json(&rows);
+        if returns_rows(&query_type) {
+            let execution_id = execution.id().to_string();
+            let (cursor_id, page) = cursors.open(execution, database, query, limits).await?;
+
+            let execution_time_ms = start.elapsed().as_millis() as u64;
 
             Ok(MysqlQueryResult {
-                columns,
-                rows: json_rows,
-                affected_rows: rows.len() as u64,
+                columns: page.columns,
+                affected_rows: page.rows.len() as u64,
+                rows: page.rows,
                 execution_time_ms,
                 query_type,
+                cursor_id,
+                has_more: page.has_more,
+                truncated: page.truncated,
+                execution_id: Some(execution_id),
             })
         } else {
             // Non-SELECT query - return affected rows count
-            let full_query = format!("USE `{}`; {}", database, query);
-            let result = sqlx::raw_sql(&full_query)
-                .execute(&self.pool)
+            // Combine USE and query into a single raw_sql call to ensure same connection
+            let full_query = format!("USE {}; {}", quote_name(database), query);
+            let result = execution
+                .conn()?
+                .execute(sqlx::raw_sql(&full_query))
                 .await
-                .map_err(|e| AppError::Database(e.to_string()))?;
+                .map_err(|e| execution.map_error(AppError::Database(e.to_string())))?;
 
             let execution_time_ms = start.elapsed().as_millis() as u64;
 
@@ -448,10 +497,123 @@ impl MysqlService {
                 affected_rows: result.rows_affected(),
                 execution_time_ms,
                 query_type,
+                cursor_id: None,
+                has_more: false,
+                truncated: false,
+                execution_id: Some(execution.id().to_string()),
             })
         }
     }
 
+    /// Run a multi-statement script on one connection, returning a result
+    /// per statement
+    pub async fn execute_script(
+        &self,
+        executions: &QueryExecutionService,
+        execution_id: Option<String>,
+        database: &str,
+        script: &str,
+        mode: ScriptErrorMode,
+        max_rows: u64,
+    ) -> AppResult<MysqlScriptResult> {
+        let conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        let mut execution = executions
+            .register_connection(execution_id, self.pool.clone(), conn)
+            .await?;
+
+        let handle = execution.handle();
+        let conn = execution.conn()?;
+        conn.execute(sqlx::raw_sql(&format!("USE {}", quote_name(database))))
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(run_script(conn, script, mode, max_rows, &handle).await)
+    }
+
+    /// Run a query with named parameters.
+    ///
+    /// A single SELECT/INSERT/UPDATE/DELETE is executed as a prepared
+    /// statement with bound values; anything else gets quoted literals.
+    /// Results are not paged; at most `max_rows` rows are returned.
+    pub async fn execute_parameterized(
+        &self,
+        executions: &QueryExecutionService,
+        execution_id: Option<String>,
+        resolved: &ResolvedQuery,
+        values: &HashMap<String, JsonValue>,
+        max_rows: u64,
+    ) -> AppResult<MysqlQueryResult> {
+        let query = resolved.query.as_str();
+        let query_type = classify_statement(query);
+        let statements = split_statements(query);
+        let preparable = statements.len() == 1
+            && matches!(
+                statements[0].query_type.as_str(),
+                "select" | "insert" | "update" | "delete"
+            );
+        let rendered = render_query(query, &resolved.parameters, values, !preparable)?;
+
+        let conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        let mut execution = executions
+            .register_connection(execution_id, self.pool.clone(), conn)
+            .await?;
+
+        let conn = execution.conn()?;
+        conn.execute(sqlx::raw_sql(&format!(
+            "USE {}",
+            quote_name(&resolved.database)
+        )))
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let start = Instant::now();
+        let outcome = if preparable {
+            let mut prepared = sqlx::query(&rendered.sql);
+            for value in rendered.binds {
+                prepared = match value {
+                    BindValue::Null => prepared.bind(None::<String>),
+                    BindValue::Integer(i) => prepared.bind(i),
+                    BindValue::Float(f) => prepared.bind(f),
+                    BindValue::Boolean(b) => prepared.bind(b),
+                    BindValue::Text(s) => prepared.bind(s),
+                };
+            }
+            fetch_limited(conn, prepared, max_rows.max(1)).await
+        } else {
+            fetch_limited(conn, sqlx::raw_sql(&rendered.sql), max_rows.max(1)).await
+        };
+        let (rows, rows_affected, truncated) =
+            outcome.map_err(|e| execution.map_error(AppError::Database(e.to_string())))?;
+        let execution_time_ms = start.elapsed().as_millis() as u64;
+
+        let (columns, rows) = mysql_rows_to_json(&rows);
+        let affected_rows = if columns.is_empty() {
+            rows_affected
+        } else {
+            rows.len() as u64
+        };
+
+        Ok(MysqlQueryResult {
+            columns,
+            rows,
+            affected_rows,
+            execution_time_ms,
+            query_type,
+            cursor_id: None,
+            has_more: false,
+            truncated,
+            execution_id: Some(execution.id().to_string()),
+        })
+    }
+
     /// Get table data with pagination
     pub async fn get_rows(
         &self,
@@ -459,37 +621,203 @@ impl MysqlService {
         table: &str,
         page: i32,
         page_size: i32,
+        options: &TableRowsOptions,
     ) -> AppResult<MysqlTableData> {
         let offset = (page - 1) * page_size;
 
+        // Filters, sorting and projection refer to columns of the table
+        let table_columns = if has_options(options) {
+            self.get_table_columns(database, table).await?
+        } else {
+            Vec::new()
+        };
+        if has_options(options) && table_columns.is_empty() {
+            return Err(AppError::NotFound(format!("Table not found: {}", table)));
+        }
+        let clauses = RowClauses::new(options, &table_columns)?;
+
         // Get total count
-        let count_query = format!("SELECT COUNT(*) FROM `{}`.`{}`", database, table);
-        let total: (i64,) = sqlx::query_as(&count_query)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        let (total, total_is_estimate) = self
+            .count_rows(database, table, &clauses, options.count)
+            .await?;
 
         // Get rows
         let query = format!(
-            "SELECT * FROM `{}`.`{}` LIMIT {} OFFSET {}",
-            database, table, page_size, offset
+            "SELECT {} FROM {}.{}{}{} LIMIT {} OFFSET {}",
+            clauses.select,
+            quote_name(database),
+            quote_name(table),
+            clauses.filter,
+            clauses.order_by,
+            page_size,
+            offset
         );
-        let rows = sqlx::query(&query)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
-
-        let (columns, json_rows) = mysql_rows_to_json(&rows);
+        let (columns, json_rows) = collect_json_rows(sqlx::query(&query).fetch(&self.pool)).await?;
 
         Ok(MysqlTableData {
             columns,
             rows: json_rows,
-            total: total.0,
+            total: total.unwrap_or(-1),
+            total_is_estimate,
             page,
             page_size,
         })
     }
 
+    /// Get a page of table rows ordered and split by the primary key.
+    /// Unlike OFFSET paging, the cost of a page doesn't grow with its depth.
+    pub async fn get_rows_keyset(
+        &self,
+        database: &str,
+        table: &str,
+        page_size: i32,
+        request: &KeysetPageRequest,
+    ) -> AppResult<KeysetPage> {
+        if !request.options.order_by.is_empty() {
+            return Err(AppError::Validation(
+                "Keyset pages are ordered by the primary key".to_string(),
+            ));
+        }
+        let table_columns = self.get_table_columns(database, table).await?;
+        if table_columns.is_empty() {
+            return Err(AppError::NotFound(format!("Table not found: {}", table)));
+        }
+        let key_columns = self.get_primary_key_columns(database, table).await?;
+        let key: Vec<&MysqlColumn> = key_columns
+            .iter()
+            .filter_map(|name| table_columns.iter().find(|c| &c.name == name))
+            .collect();
+        if key.is_empty() {
+            return Err(AppError::Validation(format!(
+                "Table {} has no primary key to page by",
+                table
+            )));
+        }
+        let keyset = Keyset::new(&key, request.descending);
+        let clauses = RowClauses::new(&request.options, &table_columns)?;
+
+        let backward = request.backward && request.cursor.is_some();
+        let filter = match &request.cursor {
+            Some(cursor) => clauses.filter_and(&keyset.condition(cursor, backward)?),
+            None => clauses.filter.clone(),
+        };
+        // One row more than the page tells whether another page follows
+        let query = format!(
+            "SELECT {}{} FROM {}.{}{}{} LIMIT {}",
+            clauses.select,
+            keyset.select(),
+            quote_name(database),
+            quote_name(table),
+            filter,
+            keyset.order_by(backward),
+            page_size as i64 + 1
+        );
+        let (mut columns, mut rows) =
+            collect_json_rows(sqlx::raw_sql(&query).fetch(&self.pool)).await?;
+        let aliases = keyset.aliases();
+        columns.retain(|c| !aliases.contains(c));
+
+        let more = rows.len() > page_size as usize;
+        rows.truncate(page_size as usize);
+        if backward {
+            rows.reverse();
+        }
+        let keys: Vec<Vec<String>> = rows.iter_mut().map(|row| keyset.take_key(row)).collect();
+        let first = keys.first().map(|key| encode_cursor(key));
+        let last = keys.last().map(|key| encode_cursor(key));
+        // Paging backward from a cursor leaves the cursor's row after the page
+        let (next_cursor, prev_cursor) = if backward {
+            (last, first.filter(|_| more))
+        } else {
+            (
+                last.filter(|_| more),
+                first.filter(|_| request.cursor.is_some()),
+            )
+        };
+
+        let (total, total_is_estimate) = self
+            .count_rows(database, table, &clauses, request.options.count)
+            .await?;
+
+        Ok(KeysetPage {
+            columns,
+            rows,
+            key_columns,
+            total,
+            total_is_estimate,
+            page_size,
+            next_cursor,
+            prev_cursor,
+        })
+    }
+
+    /// Count the rows matching a filter, exactly or from estimates
+    async fn count_rows(
+        &self,
+        database: &str,
+        table: &str,
+        clauses: &RowClauses,
+        mode: RowCountMode,
+    ) -> AppResult<(Option<i64>, bool)> {
+        match mode {
+            RowCountMode::Skip => Ok((None, false)),
+            RowCountMode::Exact => {
+                let count_query = format!(
+                    "SELECT COUNT(*) FROM {}.{}{}",
+                    quote_name(database),
+                    quote_name(table),
+                    clauses.filter
+                );
+                let total: (i64,) = sqlx::query_as(&count_query)
+                    .fetch_one(&self.pool)
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                Ok((Some(total.0), false))
+            }
+            RowCountMode::Approximate => {
+                if clauses.filter.is_empty() {
+                    // Statistics kept by the storage engine; NULL for views
+                    let rows: Option<(Option<u64>,)> = sqlx::query_as(
+                        "SELECT TABLE_ROWS FROM information_schema.TABLES \
+                         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?",
+                    )
+                    .bind(database)
+                    .bind(table)
+                    .fetch_optional(&self.pool)
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                    if let Some((Some(rows),)) = rows {
+                        return Ok((Some(rows as i64), true));
+                    }
+                }
+
+                // The optimizer's estimate of rows examined, scaled by the
+                // share it expects the filter to keep
+                let explain = format!(
+                    "EXPLAIN SELECT * FROM {}.{}{}",
+                    quote_name(database),
+                    quote_name(table),
+                    clauses.filter
+                );
+                let row = self
+                    .pool
+                    .fetch_optional(sqlx::raw_sql(&explain))
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                let number = |name: &str| -> Option<f64> {
+                    let bytes = row
+                        .as_ref()?
+                        .try_get_unchecked::<Option<Vec<u8>>, _>(name)
+                        .ok()??;
+                    String::from_utf8(bytes).ok()?.parse().ok()
+                };
+                let estimate =
+                    number("rows").unwrap_or(0.0) * number("filtered").unwrap_or(100.0) / 100.0;
+                Ok((Some(estimate.round() as i64), true))
+            }
+        }
+    }
+
     /// Insert a row into a table
     pub async fn insert_row(
         &self,
@@ -1053,43 +1381,55 @@ impl MysqlService {
 
     /// List all indexes on a table
     pub async fn list_indexes(&self, database: &str, table: &str) -> AppResult<Vec<IndexInfo>> {
-        let query = format!(
-            r#"
-            SELECT
-                INDEX_NAME as name,
-                GROUP_CONCAT(COLUMN_NAME ORDER BY SEQ_IN_INDEX) as columns,
-                NOT NON_UNIQUE as is_unique,
-                CAST(INDEX_TYPE AS CHAR) as index_type,
-                INDEX_NAME = 'PRIMARY' as is_primary,
-                INDEX_COMMENT as comment
-            FROM INFORMATION_SCHEMA.STATISTICS
-            WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?
-            GROUP BY INDEX_NAME, NON_UNIQUE, INDEX_TYPE, INDEX_COMMENT
-            ORDER BY INDEX_NAME = 'PRIMARY' DESC, INDEX_NAME
-            "#
-        );
-
-        let rows = sqlx::query(&query)
-            .bind(database)
-            .bind(table)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        // SELECT * because the EXPRESSION column of functional key parts
+        // only exists from MySQL 8.0.13
+        let rows = sqlx::query(
+            "SELECT * FROM INFORMATION_SCHEMA.STATISTICS \
+             WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? \
+             ORDER BY INDEX_NAME = 'PRIMARY' DESC, INDEX_NAME, SEQ_IN_INDEX",
+        )
+        .bind(database)
+        .bind(table)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
 
-        let indexes: Vec<IndexInfo> = rows
-            .iter()
-            .map(|row| {
-                let columns_str: String = row.get("columns");
-                IndexInfo {
-                    name: row.get("name"),
-                    columns: columns_str.split(',').map(|s| s.to_string()).collect(),
-                    unique: row.get::<i32, _>("is_unique") != 0,
-                    index_type: row.get("index_type"),
-                    is_primary: row.get::<i32, _>("is_primary") != 0,
-                    comment: row.try_get("comment").ok(),
-                }
-            })
-            .collect();
+        let int = |row: &MySqlRow, column: &str| {
+            row.try_get::<Option<i64>, _>(column)
+                .ok()
+                .flatten()
+                .or_else(|| {
+                    row.try_get::<Option<u64>, _>(column)
+                        .ok()
+                        .flatten()
+                        .map(|v| v as i64)
+                })
+        };
+        let mut indexes: Vec<IndexInfo> = Vec::new();
+        for row in &rows {
+            let name = Self::get_string_from_row(row, "INDEX_NAME");
+            if indexes.last().map(|i| &i.name) != Some(&name) {
+                indexes.push(IndexInfo {
+                    unique: int(row, "NON_UNIQUE") == Some(0),
+                    index_type: Self::get_string_from_row(row, "INDEX_TYPE"),
+                    is_primary: name == "PRIMARY",
+                    comment: Self::get_optional_string_from_row(row, "INDEX_COMMENT"),
+                    name,
+                    columns: Vec::new(),
+                    sub_parts: Vec::new(),
+                    expressions: Vec::new(),
+                });
+            }
+            if let Some(index) = indexes.last_mut() {
+                index.columns.push(
+                    Self::get_optional_string_from_row(row, "COLUMN_NAME").unwrap_or_default(),
+                );
+                index.sub_parts.push(int(row, "SUB_PART").map(|n| n as u32));
+                index
+                    .expressions
+                    .push(Self::get_optional_string_from_row(row, "EXPRESSION"));
+            }
+        }
 
         Ok(indexes)
     }
@@ -1376,10 +1716,10 @@ impl MysqlService {
             let values: Vec<String> = column_names.iter().map(|col| {
                 match row.get(*col) {
                     Some(JsonValue::Null) => "NULL".to_string(),
-                    Some(JsonValue::String(s)) => format!("'{}'", s.replace('\'', "\\'")),
+                    Some(JsonValue::String(s)) => quote_string(s),
                     Some(JsonValue::Number(n)) => n.to_string(),
                     Some(JsonValue::Bool(b)) => if *b { "1".to_string() } else { "0".to_string() },
-                    Some(v) => format!("'{}'", v.to_string().replace('\'', "\\'")),
+                    Some(v) => quote_string(&v.to_string()),
                     None => "NULL".to_string(),
                 }
             }).collect();
@@ -1418,6 +1758,10 @@ impl MysqlService {
             ExportFormat::Sql => {
                 self.export_table_sql(database, table, columns, where_clause, limit).await
             }
+            ref format => Err(AppError::Validation(format!(
+                "{:?} exports are only available as files or downloads",
+                format
+            ))),
         }
     }
 
@@ -1917,6 +2261,228 @@ impl MysqlService {
         Ok(())
     }
 
+    // ==================== Event Management ====================
+
+    /// List all scheduled events in a database
+    pub async fn list_events(&self, database: &str) -> AppResult<Vec<EventInfo>> {
+        let query = format!(
+            "SELECT EVENT_NAME, DEFINER, STATUS, EVENT_TYPE, CAST(EXECUTE_AT AS CHAR) AS EXECUTE_AT, \
+             INTERVAL_VALUE, INTERVAL_FIELD, CAST(STARTS AS CHAR) AS STARTS, CAST(ENDS AS CHAR) AS ENDS, \
+             CAST(CREATED AS CHAR) AS CREATED, CAST(LAST_ALTERED AS CHAR) AS LAST_ALTERED \
+             FROM information_schema.EVENTS WHERE EVENT_SCHEMA = {} ORDER BY EVENT_NAME",
+            quote_string(database)
+        );
+
+        let rows = sqlx::query(&query)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let events = rows
+            .iter()
+            .map(|row| EventInfo {
+                name: row.try_get::<String, _>("EVENT_NAME").unwrap_or_default(),
+                definer: row.try_get::<Option<String>, _>("DEFINER").ok().flatten(),
+                status: row.try_get::<String, _>("STATUS").unwrap_or_default(),
+                event_type: row.try_get::<String, _>("EVENT_TYPE").unwrap_or_default(),
+                execute_at: row
+                    .try_get::<Option<String>, _>("EXECUTE_AT")
+                    .ok()
+                    .flatten(),
+                interval_value: row
+                    .try_get::<Option<String>, _>("INTERVAL_VALUE")
+                    .ok()
+                    .flatten(),
+                interval_field: row
+                    .try_get::<Option<String>, _>("INTERVAL_FIELD")
+                    .ok()
+                    .flatten(),
+                starts: row.try_get::<Option<String>, _>("STARTS").ok().flatten(),
+                ends: row.try_get::<Option<String>, _>("ENDS").ok().flatten(),
+                created: row.try_get::<Option<String>, _>("CREATED").ok().flatten(),
+                modified: row
+                    .try_get::<Option<String>, _>("LAST_ALTERED")
+                    .ok()
+                    .flatten(),
+            })
+            .collect();
+
+        Ok(events)
+    }
+
+    /// Get event definition (CREATE EVENT statement)
+    pub async fn get_event_definition(
+        &self,
+        database: &str,
+        name: &str,
+    ) -> AppResult<EventDefinition> {
+        let query = format!("SHOW CREATE EVENT {}", event_identifier(database, name));
+
+        let row = sqlx::query(&query)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| match mysql_error_number(&e) {
+                Some(ER_EVENT_DOES_NOT_EXIST) => {
+                    AppError::NotFound(format!("Event {}.{} not found", database, name))
+                }
+                _ => AppError::Database(e.to_string()),
+            })?;
+
+        // SHOW CREATE EVENT returns: Event, sql_mode, time_zone, Create Event, ...
+        let definition = row
+            .try_get::<String, _>(3)
+            .or_else(|_| row.try_get::<String, _>("Create Event"))
+            .unwrap_or_default();
+
+        Ok(EventDefinition {
+            name: name.to_string(),
+            definition,
+        })
+    }
+
+    /// Create a scheduled event
+    pub async fn create_event(&self, database: &str, req: &CreateEventRequest) -> AppResult<()> {
+        if req.name.trim().is_empty() {
+            return Err(AppError::Validation("Event name is required".to_string()));
+        }
+        if req.body.trim().is_empty() {
+            return Err(AppError::Validation("Event body is required".to_string()));
+        }
+
+        let if_not_exists = if req.if_not_exists.unwrap_or(false) {
+            "IF NOT EXISTS "
+        } else {
+            ""
+        };
+        let mut query = format!(
+            "CREATE EVENT {}{} ON SCHEDULE {}",
+            if_not_exists,
+            event_identifier(database, &req.name),
+            render_event_schedule(&req.schedule)?
+        );
+        if let Some(preserve) = req.on_completion_preserve {
+            query.push_str(if preserve {
+                " ON COMPLETION PRESERVE"
+            } else {
+                " ON COMPLETION NOT PRESERVE"
+            });
+        }
+        if let Some(enabled) = req.enabled {
+            query.push_str(if enabled { " ENABLE" } else { " DISABLE" });
+        }
+        if let Some(comment) = &req.comment {
+            query.push_str(&format!(" COMMENT {}", quote_plain_string(comment)));
+        }
+        query.push_str(&format!(" DO {}", req.body.trim().trim_end_matches(';')));
+
+        // CREATE/ALTER/DROP EVENT are not supported by the prepared statement protocol
+        sqlx::raw_sql(&query)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Alter a scheduled event
+    pub async fn alter_event(
+        &self,
+        database: &str,
+        name: &str,
+        req: &AlterEventRequest,
+    ) -> AppResult<()> {
+        let mut clauses = Vec::new();
+        if let Some(schedule) = &req.schedule {
+            clauses.push(format!("ON SCHEDULE {}", render_event_schedule(schedule)?));
+        }
+        if let Some(preserve) = req.on_completion_preserve {
+            clauses.push(
+                if preserve {
+                    "ON COMPLETION PRESERVE"
+                } else {
+                    "ON COMPLETION NOT PRESERVE"
+                }
+                .to_string(),
+            );
+        }
+        if let Some(new_name) = req.rename_to.as_deref().filter(|n| !n.trim().is_empty()) {
+            clauses.push(format!(
+                "RENAME TO {}",
+                event_identifier(database, new_name)
+            ));
+        }
+        if let Some(enabled) = req.enabled {
+            clauses.push(if enabled { "ENABLE" } else { "DISABLE" }.to_string());
+        }
+        if let Some(comment) = &req.comment {
+            clauses.push(format!("COMMENT {}", quote_plain_string(comment)));
+        }
+        if let Some(body) = req.body.as_deref().filter(|b| !b.trim().is_empty()) {
+            clauses.push(format!("DO {}", body.trim().trim_end_matches(';')));
+        }
+        if clauses.is_empty() {
+            return Err(AppError::Validation("Nothing to alter".to_string()));
+        }
+
+        let query = format!(
+            "ALTER EVENT {} {}",
+            event_identifier(database, name),
+            clauses.join(" ")
+        );
+
+        sqlx::raw_sql(&query)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Enable or disable a scheduled event
+    pub async fn set_event_enabled(
+        &self,
+        database: &str,
+        name: &str,
+        enabled: bool,
+    ) -> AppResult<()> {
+        let req = AlterEventRequest {
+            enabled: Some(enabled),
+            ..Default::default()
+        };
+        self.alter_event(database, name, &req).await
+    }
+
+    /// Drop a scheduled event
+    pub async fn drop_event(&self, database: &str, name: &str) -> AppResult<()> {
+        let query = format!("DROP EVENT IF EXISTS {}", event_identifier(database, name));
+
+        sqlx::raw_sql(&query)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Get the state of the event scheduler
+    pub async fn get_event_scheduler_status(&self) -> AppResult<EventSchedulerStatus> {
+        let row = sqlx::query("SELECT CAST(@@GLOBAL.event_scheduler AS CHAR) AS value")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let value = row
+            .try_get::<String, _>("value")
+            .unwrap_or_default()
+            .to_uppercase();
+
+        Ok(EventSchedulerStatus {
+            running: value == "ON",
+            can_enable: value != "DISABLED",
+            value,
+        })
+    }
+
     // ==================== Server Monitoring ====================
 
     /// Get server variables
@@ -2074,7 +2640,7 @@ impl MysqlService {
 }
 
 /// Escape a field for CSV output
-fn escape_csv_field(field: &str) -> String {
+pub(crate) fn escape_csv_field(field: &str) -> String {
     if field.contains(',') || field.contains('"') || field.contains('\n') {
         format!("\"{}\"", field.replace('"', "\"\""))
     } else {
@@ -2082,6 +2648,94 @@ fn escape_csv_field(field: &str) -> String {
     }
 }
 
+/// Quote a `database`.`event` pair
+fn event_identifier(database: &str, name: &str) -> String {
+    format!("{}.{}", quote_name(database), quote_name(name))
+}
+
+/// Server error for an unknown event
+const ER_EVENT_DOES_NOT_EXIST: u16 = 1539;
+
+/// MySQL error number of a failed query, if the server reported one
+pub(crate) fn mysql_error_number(err: &sqlx::Error) -> Option<u16> {
+    err.as_database_error()
+        .and_then(|e| e.try_downcast_ref::<MySqlDatabaseError>())
+        .map(MySqlDatabaseError::number)
+}
+
+/// Interval units accepted by EVERY in an event schedule
+const EVENT_INTERVAL_FIELDS: &[&str] = &[
+    "YEAR",
+    "QUARTER",
+    "MONTH",
+    "DAY",
+    "HOUR",
+    "MINUTE",
+    "WEEK",
+    "SECOND",
+    "YEAR_MONTH",
+    "DAY_HOUR",
+    "DAY_MINUTE",
+    "DAY_SECOND",
+    "HOUR_MINUTE",
+    "HOUR_SECOND",
+    "MINUTE_SECOND",
+];
+
+/// Render the ON SCHEDULE clause body for an event
+fn render_event_schedule(schedule: &EventSchedule) -> AppResult<String> {
+    match schedule {
+        EventSchedule::OneTime { execute_at } => {
+            if execute_at.trim().is_empty() {
+                return Err(AppError::Validation(
+                    "Execution time is required".to_string(),
+                ));
+            }
+            Ok(format!("AT {}", quote_string(execute_at.trim())))
+        }
+        EventSchedule::Recurring {
+            interval_value,
+            interval_field,
+            starts,
+            ends,
+        } => {
+            let field = interval_field.trim().to_uppercase();
+            if !EVENT_INTERVAL_FIELDS.contains(&field.as_str()) {
+                return Err(AppError::Validation(format!(
+                    "Invalid interval unit: {}",
+                    interval_field
+                )));
+            }
+            let value = interval_value.trim();
+            let value = match value.parse::<u64>() {
+                Ok(n) if n > 0 => n.to_string(),
+                Ok(_) => {
+                    return Err(AppError::Validation(
+                        "Interval must be positive".to_string(),
+                    ))
+                }
+                // Compound units take a quoted value such as '1:30'
+                Err(_) if !value.is_empty() && field.contains('_') => quote_string(value),
+                Err(_) => {
+                    return Err(AppError::Validation(format!(
+                        "Invalid interval: {}",
+                        interval_value
+                    )))
+                }
+            };
+
+            let mut clause = format!("EVERY {} {}", value, field);
+            if let Some(starts) = starts.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+                clause.push_str(&format!(" STARTS {}", quote_string(starts)));
+            }
+            if let Some(ends) = ends.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+                clause.push_str(&format!(" ENDS {}", quote_string(ends)));
+            }
+            Ok(clause)
+        }
+    }
+}
+
 /// Convert JSON value to string for CSV
 fn json_to_string(value: &JsonValue) -> String {
     match value {
@@ -2097,41 +2751,126 @@ fn json_to_string(value: &JsonValue) -> String {
 fn json_to_sql_value(value: Option<&JsonValue>) -> String {
     match value {
         None | Some(JsonValue::Null) => "NULL".to_string(),
-        Some(JsonValue::String(s)) => format!("'{}'", s.replace('\'', "\\'")),
+        Some(JsonValue::String(s)) => quote_string(s),
         Some(JsonValue::Number(n)) => n.to_string(),
         Some(JsonValue::Bool(b)) => if *b { "1".to_string() } else { "0".to_string() },
-        Some(v) => format!("'{}'", v.to_string().replace('\'', "\\'")),
+        Some(v) => quote_string(&v.to_string()),
     }
 }
 
-/// Detect the type of SQL query
-fn detect_query_type(query: &str) -> String {
-    let trimmed = query.trim().to_lowercase();
-    if trimmed.starts_with("select") {
-        "select".to_string()
-    } else if trimmed.starts_with("insert") {
-        "insert".to_string()
-    } else if trimmed.starts_with("update") {
-        "update".to_string()
-    } else if trimmed.starts_with("delete") {
-        "delete".to_string()
-    } else if trimmed.starts_with("show") {
-        "show".to_string()
-    } else if trimmed.starts_with("describe") || trimmed.starts_with("desc") {
-        "describe".to_string()
-    } else if trimmed.starts_with("create") {
-        "create".to_string()
-    } else if trimmed.starts_with("drop") {
-        "drop".to_string()
-    } else if trimmed.starts_with("alter") {
-        "alter".to_string()
-    } else {
-        "other".to_string()
+/// Run a query and collect up to `max_rows` rows.
+/// Returns the rows, the affected row count and whether rows were cut off.
+pub(crate) async fn fetch_limited<'q, E>(
+    conn: &mut MySqlConnection,
+    query: E,
+    max_rows: u64,
+) -> Result<(Vec<MySqlRow>, u64, bool), sqlx::Error>
+where
+    E: 'q + Execute<'q, MySql>,
+{
+    let mut stream = conn.fetch_many(query);
+    let mut rows = Vec::new();
+    let mut rows_affected = 0u64;
+    let mut truncated = false;
+
+    // Unread rows left behind are drained by sqlx before the next command
+    while let Some(item) = stream.try_next().await? {
+        match item {
+            Either::Left(done) => rows_affected += done.rows_affected(),
+            Either::Right(row) => {
+                if rows.len() as u64 >= max_rows {
+                    truncated = true;
+                    break;
+                }
+                rows.push(row);
+            }
+        }
+    }
+
+    Ok((rows, rows_affected, truncated))
+}
+
+/// Split a script and run its statements in order on one connection.
+///
+/// In stop mode the statements after a failure are skipped; a cancelled
+/// execution skips the rest in either mode.
+pub(crate) async fn run_script(
+    conn: &mut MySqlConnection,
+    script: &str,
+    mode: ScriptErrorMode,
+    max_rows: u64,
+    execution: &ExecutionHandle,
+) -> MysqlScriptResult {
+    let start = Instant::now();
+    let mut results = Vec::new();
+    let mut halted = false;
+
+    for (index, statement) in split_statements(script).into_iter().enumerate() {
+        let mut result = MysqlStatementResult {
+            index,
+            line: statement.line,
+            sql: statement.sql,
+            query_type: statement.query_type,
+            status: "skipped".to_string(),
+            columns: vec![],
+            rows: vec![],
+            affected_rows: 0,
+            execution_time_ms: 0,
+            truncated: false,
+            error: None,
+        };
+        if halted || execution.is_cancelled() {
+            results.push(result);
+            continue;
+        }
+
+        let statement_start = Instant::now();
+        let outcome = fetch_limited(conn, sqlx::raw_sql(&result.sql), max_rows.max(1)).await;
+        result.execution_time_ms = statement_start.elapsed().as_millis() as u64;
+
+        match outcome {
+            Ok((rows, rows_affected, truncated)) => {
+                let (columns, rows) = mysql_rows_to_json(&rows);
+                result.affected_rows = if columns.is_empty() {
+                    rows_affected
+                } else {
+                    rows.len() as u64
+                };
+                result.columns = columns;
+                result.rows = rows;
+                result.truncated = truncated;
+                result.status = "success".to_string();
+            }
+            Err(e) if execution.is_cancelled() => {
+                result.status = "cancelled".to_string();
+                result.error = Some(e.to_string());
+                halted = true;
+            }
+            Err(e) => {
+                result.status = "error".to_string();
+                result.error = Some(e.to_string());
+                halted = mode == ScriptErrorMode::Stop;
+            }
+        }
+        results.push(result);
+    }
+
+    let count = |status: &str| results.iter().filter(|r| r.status == status).count();
+    MysqlScriptResult {
+        succeeded: count("success"),
+        failed: count("error"),
+        skipped: count("skipped"),
+        cancelled: execution.is_cancelled(),
+        execution_time_ms: start.elapsed().as_millis() as u64,
+        execution_id: Some(execution.id().to_string()),
+        statements: results,
     }
 }
 
 /// Convert MySQL rows to JSON format (returns objects with column names as keys)
-fn mysql_rows_to_json(rows: &[MySqlRow]) -> (Vec<String>, Vec<HashMap<String, JsonValue>>) {
+pub(crate) fn mysql_rows_to_json(
+    rows: &[MySqlRow],
+) -> (Vec<String>, Vec<HashMap<String, JsonValue>>) {
     if rows.is_empty() {
         return (vec![], vec![]);
     }
@@ -2157,6 +2896,39 @@ fn mysql_rows_to_json(rows: &[MySqlRow]) -> (Vec<String>, Vec<HashMap<String, Js
     (columns, json_rows)
 }
 
+/// Drain a row stream into JSON, converting in chunks so the raw rows and
+/// their JSON form are never both held in full
+async fn collect_json_rows(
+    mut stream: BoxStream<'_, Result<MySqlRow, sqlx::Error>>,
+) -> AppResult<(Vec<String>, Vec<HashMap<String, JsonValue>>)> {
+    let mut columns = Vec::new();
+    let mut json_rows = Vec::new();
+    let mut chunk = Vec::with_capacity(ROW_CHUNK_SIZE);
+
+    loop {
+        let row = stream
+            .try_next()
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let done = row.is_none();
+        chunk.extend(row);
+
+        if chunk.len() >= ROW_CHUNK_SIZE || (done && !chunk.is_empty()) {
+            let (chunk_columns, chunk_rows) = mysql_rows_to_json(&chunk);
+            if columns.is_empty() {
+                columns = chunk_columns;
+            }
+            json_rows.extend(chunk_rows);
+            chunk.clear();
+        }
+        if done {
+            break;
+        }
+    }
+
+    Ok((columns, json_rows))
+}
+
 /// Convert a single MySQL column value to JSON
 fn mysql_value_to_json(row: &MySqlRow, col: &sqlx::mysql::MySqlColumn) -> JsonValue {
     let type_name = col.type_info().name();
@@ -2230,3 +3002,59 @@ fn bind_json_value<'q>(
         JsonValue::Array(_) | JsonValue::Object(_) => query.bind(value.to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn every(value: &str, field: &str) -> EventSchedule {
+        EventSchedule::Recurring {
+            interval_value: value.to_string(),
+            interval_field: field.to_string(),
+            starts: None,
+            ends: None,
+        }
+    }
+
+    #[test]
+    fn test_render_event_schedule() {
+        assert_eq!(
+            render_event_schedule(&every("5", "minute")).unwrap(),
+            "EVERY 5 MINUTE"
+        );
+        assert_eq!(
+            render_event_schedule(&every("1:30", "hour_minute")).unwrap(),
+            "EVERY '1:30' HOUR_MINUTE"
+        );
+        assert_eq!(
+            render_event_schedule(&EventSchedule::Recurring {
+                interval_value: "1".to_string(),
+                interval_field: "DAY".to_string(),
+                starts: Some("2026-01-01 00:00:00".to_string()),
+                ends: Some(" ".to_string()),
+            })
+            .unwrap(),
+            "EVERY 1 DAY STARTS '2026-01-01 00:00:00'"
+        );
+        assert_eq!(
+            render_event_schedule(&EventSchedule::OneTime {
+                execute_at: "2026-01-01 03:00:00".to_string(),
+            })
+            .unwrap(),
+            "AT '2026-01-01 03:00:00'"
+        );
+    }
+
+    #[test]
+    fn test_render_event_schedule_rejects_invalid() {
+        // Only compound units take a quoted value
+        assert!(render_event_schedule(&every("1:30", "MINUTE")).is_err());
+        assert!(render_event_schedule(&every("0", "SECOND")).is_err());
+        assert!(render_event_schedule(&every("1", "FORTNIGHT")).is_err());
+        assert!(render_event_schedule(&every("", "DAY_HOUR")).is_err());
+        assert!(render_event_schedule(&EventSchedule::OneTime {
+            execute_at: " ".to_string(),
+        })
+        .is_err());
+    }
+}
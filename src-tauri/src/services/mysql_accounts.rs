@@ -0,0 +1,697 @@
+//! MySQL accounts, roles and privileges
+//!
+//! Parses `SHOW GRANTS` output into a privilege matrix of account × object
+//! × privilege, manages MySQL 8 roles (CREATE ROLE, GRANT role TO user,
+//! SET DEFAULT ROLE), account locking, password expiry and resource limits,
+//! and clones the grants of one account onto another.
+
+use std::collections::{BTreeSet, HashSet};
+
+use sqlx::Row;
+
+use crate::db::models::{
+    AccountDetails, AccountPrivileges, AlterAccountRequest, ClonePrivilegesRequest,
+    ClonePrivilegesResult, MysqlUserInfo, PasswordExpiry, PrivilegeGrant, PrivilegeLevel,
+    PrivilegeMatrix, RoleGrant, RoleGrantRequest, RoleRequest, SetDefaultRolesRequest,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::mysql::MysqlService;
+use crate::services::query_params::quote_plain_string;
+
+/// Accounts the server creates for itself
+const SYSTEM_ACCOUNTS: &[&str] = &["mysql.infoschema", "mysql.session", "mysql.sys"];
+
+/// Manages accounts, roles and their privileges
+pub struct MysqlAccountService {
+    mysql: MysqlService,
+}
+
+impl MysqlAccountService {
+    /// Create an account service on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// Parsed grants of every account
+    pub async fn privilege_matrix(&self, include_system: bool) -> AppResult<PrivilegeMatrix> {
+        let roles = self.role_accounts().await?;
+        let mut accounts = Vec::new();
+        for user in self.mysql.list_users().await? {
+            if !include_system && SYSTEM_ACCOUNTS.contains(&user.user.as_str()) {
+                continue;
+            }
+            let is_role = roles.contains(&(user.user.clone(), user.host.clone()));
+            accounts.push(
+                self.account_privileges(&user.user, &user.host, is_role)
+                    .await?,
+            );
+        }
+
+        let mut objects = BTreeSet::new();
+        let mut privileges = BTreeSet::new();
+        for grant in accounts.iter().flat_map(|account| &account.privileges) {
+            objects.insert(grant.object.clone());
+            privileges.insert(grant.privilege.clone());
+        }
+
+        Ok(PrivilegeMatrix {
+            accounts,
+            objects: objects.into_iter().collect(),
+            privileges: privileges.into_iter().collect(),
+        })
+    }
+
+    /// Locking, password and resource limit settings of an account with
+    /// its grants
+    pub async fn account_details(&self, user: &str, host: &str) -> AppResult<AccountDetails> {
+        let row = sqlx::query(
+            "SELECT account_locked = 'Y' AS account_locked, \
+             password_expired = 'Y' AS password_expired, \
+             CAST(password_lifetime AS UNSIGNED) AS password_lifetime, \
+             CAST(password_last_changed AS CHAR) AS password_last_changed, \
+             CAST(max_questions AS UNSIGNED) AS max_questions, \
+             CAST(max_updates AS UNSIGNED) AS max_updates, \
+             CAST(max_connections AS UNSIGNED) AS max_connections, \
+             CAST(max_user_connections AS UNSIGNED) AS max_user_connections \
+             FROM mysql.user WHERE User = ? AND Host = ?",
+        )
+        .bind(user)
+        .bind(host)
+        .fetch_optional(self.mysql.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(format!("Account {} not found", account(user, host))))?;
+
+        let flag = |column: &str| row.try_get::<i64, _>(column).unwrap_or(0) != 0;
+        let count = |column: &str| row.try_get::<u64, _>(column).unwrap_or(0);
+        let is_role = self
+            .role_accounts()
+            .await?
+            .contains(&(user.to_string(), host.to_string()));
+
+        Ok(AccountDetails {
+            user: user.to_string(),
+            host: host.to_string(),
+            is_role,
+            account_locked: flag("account_locked"),
+            password_expired: flag("password_expired"),
+            password_lifetime_days: row
+                .try_get::<Option<u64>, _>("password_lifetime")
+                .ok()
+                .flatten()
+                .map(|days| days as u32),
+            password_last_changed: MysqlService::get_optional_string_from_row(
+                &row,
+                "password_last_changed",
+            ),
+            max_queries_per_hour: count("max_questions"),
+            max_updates_per_hour: count("max_updates"),
+            max_connections_per_hour: count("max_connections"),
+            max_user_connections: count("max_user_connections"),
+            default_roles: self.default_roles(user, host).await,
+            privileges: self.account_privileges(user, host, is_role).await?,
+        })
+    }
+
+    /// Create a role
+    pub async fn create_role(&self, req: &RoleRequest) -> AppResult<()> {
+        self.execute(&format!("CREATE ROLE {}", role_account(req)?))
+            .await
+    }
+
+    /// Drop a role, revoking it from every account it was granted to
+    pub async fn drop_role(&self, req: &RoleRequest) -> AppResult<()> {
+        self.execute(&format!("DROP ROLE {}", role_account(req)?))
+            .await
+    }
+
+    /// Grant a role to an account
+    pub async fn grant_role(&self, req: &RoleGrantRequest) -> AppResult<()> {
+        let mut sql = format!(
+            "GRANT {} TO {}",
+            account(&req.role.user, &req.role.host),
+            account(&req.username, &req.host)
+        );
+        if req.with_admin_option {
+            sql.push_str(" WITH ADMIN OPTION");
+        }
+        self.execute(&sql).await
+    }
+
+    /// Revoke a role from an account
+    pub async fn revoke_role(&self, req: &RoleGrantRequest) -> AppResult<()> {
+        self.execute(&format!(
+            "REVOKE {} FROM {}",
+            account(&req.role.user, &req.role.host),
+            account(&req.username, &req.host)
+        ))
+        .await
+    }
+
+    /// Set the roles activated when an account logs in
+    pub async fn set_default_roles(&self, req: &SetDefaultRolesRequest) -> AppResult<()> {
+        let roles = match (req.all, req.roles.is_empty()) {
+            (true, true) => "ALL".to_string(),
+            (true, false) => {
+                return Err(AppError::Validation(
+                    "Either activate all roles or list them".to_string(),
+                ))
+            }
+            (false, true) => "NONE".to_string(),
+            (false, false) => req
+                .roles
+                .iter()
+                .map(|role| account(&role.user, &role.host))
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        self.execute(&format!(
+            "SET DEFAULT ROLE {} TO {}",
+            roles,
+            account(&req.username, &req.host)
+        ))
+        .await
+    }
+
+    /// Change account locking, password expiry or resource limits
+    pub async fn alter_account(&self, req: &AlterAccountRequest) -> AppResult<()> {
+        log::info!("Altering account {}@{}", req.username, req.host);
+        self.execute(&alter_account_sql(req)?).await
+    }
+
+    /// Grant the target account every privilege and role of the source
+    /// account. Privileges the target already has are kept.
+    pub async fn clone_privileges(
+        &self,
+        req: &ClonePrivilegesRequest,
+    ) -> AppResult<ClonePrivilegesResult> {
+        if req.source_user == req.target_user && req.source_host == req.target_host {
+            return Err(AppError::Validation(
+                "Source and target account are the same".to_string(),
+            ));
+        }
+        let exists: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM mysql.user WHERE User = ? AND Host = ?")
+                .bind(&req.target_user)
+                .bind(&req.target_host)
+                .fetch_one(self.mysql.pool())
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?;
+        let target = account(&req.target_user, &req.target_host);
+        if exists == 0 {
+            return Err(AppError::NotFound(format!("Account {} not found", target)));
+        }
+
+        let grants = self
+            .mysql
+            .show_grants(&req.source_user, &req.source_host)
+            .await?;
+        let mut statements: Vec<String> = grants
+            .grants
+            .iter()
+            .filter_map(|grant| retarget_grant(&grant.grant_statement, &target))
+            .collect();
+        let default_roles = self.default_roles(&req.source_user, &req.source_host).await;
+        if !default_roles.is_empty() {
+            let roles: Vec<String> = default_roles
+                .iter()
+                .map(|role| account(&role.user, &role.host))
+                .collect();
+            statements.push(format!(
+                "SET DEFAULT ROLE {} TO {}",
+                roles.join(", "),
+                target
+            ));
+        }
+
+        if !req.dry_run {
+            log::info!(
+                "Cloning privileges of {}@{} to {}",
+                req.source_user,
+                req.source_host,
+                target
+            );
+            for statement in &statements {
+                sqlx::raw_sql(statement)
+                    .execute(self.mysql.pool())
+                    .await
+                    .map_err(|e| AppError::Database(format!("{}: {}", statement, e)))?;
+            }
+        }
+
+        Ok(ClonePrivilegesResult {
+            statements,
+            executed: !req.dry_run,
+        })
+    }
+
+    async fn account_privileges(
+        &self,
+        user: &str,
+        host: &str,
+        is_role: bool,
+    ) -> AppResult<AccountPrivileges> {
+        let grants: Vec<String> = self
+            .mysql
+            .show_grants(user, host)
+            .await?
+            .grants
+            .into_iter()
+            .map(|grant| grant.grant_statement)
+            .collect();
+
+        let mut privileges = Vec::new();
+        let mut roles = Vec::new();
+        for grant in &grants {
+            match parse_grant(grant) {
+                ParsedGrant::Privileges(parsed) => privileges.extend(parsed),
+                ParsedGrant::Roles(parsed) => roles.extend(parsed),
+                ParsedGrant::Other => {}
+            }
+        }
+
+        Ok(AccountPrivileges {
+            user: user.to_string(),
+            host: host.to_string(),
+            is_role,
+            privileges,
+            roles,
+            grants,
+        })
+    }
+
+    /// Accounts that are roles: granted to someone (MySQL 8.0 role_edges)
+    /// or created by CREATE ROLE (locked, expired and without a password)
+    async fn role_accounts(&self) -> AppResult<HashSet<(String, String)>> {
+        let mut roles: HashSet<(String, String)> = sqlx::query(
+            "SELECT CAST(User AS CHAR) AS user, CAST(Host AS CHAR) AS host FROM mysql.user \
+             WHERE account_locked = 'Y' AND password_expired = 'Y' \
+             AND authentication_string = ''",
+        )
+        .fetch_all(self.mysql.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .iter()
+        .map(|row| {
+            (
+                MysqlService::get_string_from_row(row, "user"),
+                MysqlService::get_string_from_row(row, "host"),
+            )
+        })
+        .collect();
+
+        if let Ok(rows) = sqlx::query(
+            "SELECT DISTINCT CAST(FROM_USER AS CHAR) AS user, CAST(FROM_HOST AS CHAR) AS host \
+             FROM mysql.role_edges",
+        )
+        .fetch_all(self.mysql.pool())
+        .await
+        {
+            roles.extend(rows.iter().map(|row| {
+                (
+                    MysqlService::get_string_from_row(row, "user"),
+                    MysqlService::get_string_from_row(row, "host"),
+                )
+            }));
+        }
+        Ok(roles)
+    }
+
+    /// Default roles of an account, empty before MySQL 8.0
+    async fn default_roles(&self, user: &str, host: &str) -> Vec<MysqlUserInfo> {
+        sqlx::query(
+            "SELECT CAST(DEFAULT_ROLE_USER AS CHAR) AS user, \
+             CAST(DEFAULT_ROLE_HOST AS CHAR) AS host \
+             FROM mysql.default_roles WHERE USER = ? AND HOST = ?",
+        )
+        .bind(user)
+        .bind(host)
+        .fetch_all(self.mysql.pool())
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|row| MysqlUserInfo {
+            user: MysqlService::get_string_from_row(row, "user"),
+            host: MysqlService::get_string_from_row(row, "host"),
+        })
+        .collect()
+    }
+
+    async fn execute(&self, statement: &str) -> AppResult<()> {
+        sqlx::raw_sql(statement)
+            .execute(self.mysql.pool())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
+
+fn account(user: &str, host: &str) -> String {
+    format!("{}@{}", quote_plain_string(user), quote_plain_string(host))
+}
+
+fn role_account(req: &RoleRequest) -> AppResult<String> {
+    if req.role.trim().is_empty() {
+        return Err(AppError::Validation("Role name is required".to_string()));
+    }
+    Ok(account(&req.role, req.host.as_deref().unwrap_or("%")))
+}
+
+fn alter_account_sql(req: &AlterAccountRequest) -> AppResult<String> {
+    let mut options = Vec::new();
+
+    let limits: Vec<String> = [
+        ("MAX_QUERIES_PER_HOUR", req.max_queries_per_hour),
+        ("MAX_UPDATES_PER_HOUR", req.max_updates_per_hour),
+        ("MAX_CONNECTIONS_PER_HOUR", req.max_connections_per_hour),
+        ("MAX_USER_CONNECTIONS", req.max_user_connections),
+    ]
+    .iter()
+    .filter_map(|(option, value)| value.map(|value| format!("{} {}", option, value)))
+    .collect();
+    if !limits.is_empty() {
+        options.push(format!("WITH {}", limits.join(" ")));
+    }
+
+    match req.password_expiry {
+        Some(PasswordExpiry::Now) => options.push("PASSWORD EXPIRE".to_string()),
+        Some(PasswordExpiry::Default) => options.push("PASSWORD EXPIRE DEFAULT".to_string()),
+        Some(PasswordExpiry::Never) => options.push("PASSWORD EXPIRE NEVER".to_string()),
+        Some(PasswordExpiry::Days(0)) => {
+            return Err(AppError::Validation(
+                "Password lifetime must be at least one day".to_string(),
+            ))
+        }
+        Some(PasswordExpiry::Days(days)) => {
+            options.push(format!("PASSWORD EXPIRE INTERVAL {} DAY", days))
+        }
+        None => {}
+    }
+
+    match req.account_locked {
+        Some(true) => options.push("ACCOUNT LOCK".to_string()),
+        Some(false) => options.push("ACCOUNT UNLOCK".to_string()),
+        None => {}
+    }
+
+    if options.is_empty() {
+        return Err(AppError::Validation("No account changes given".to_string()));
+    }
+    Ok(format!(
+        "ALTER USER {} {}",
+        account(&req.username, &req.host),
+        options.join(" ")
+    ))
+}
+
+enum ParsedGrant {
+    Privileges(Vec<PrivilegeGrant>),
+    Roles(Vec<RoleGrant>),
+    /// Partial revokes and statements that could not be parsed
+    Other,
+}
+
+/// Byte offset of `keyword` outside quotes and parentheses, ignoring case
+fn find_top_level(text: &str, keyword: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => {
+                if depth == 0
+                    && text
+                        .get(i..i + keyword.len())
+                        .is_some_and(|s| s.eq_ignore_ascii_case(keyword))
+                {
+                    return Some(i);
+                }
+                match c {
+                    '\'' | '"' | '`' => quote = Some(c),
+                    '(' => depth += 1,
+                    ')' => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Split on `separator` outside quotes and parentheses
+fn split_top_level(text: &str, separator: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(i) = find_top_level(rest, separator) {
+        parts.push(rest[..i].trim().to_string());
+        rest = &rest[i + separator.len()..];
+    }
+    parts.push(rest.trim().to_string());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+fn unquote(text: &str) -> String {
+    let text = text.trim();
+    for q in ['`', '\'', '"'] {
+        if text.len() >= 2 && text.starts_with(q) && text.ends_with(q) {
+            let doubled: String = [q, q].iter().collect();
+            return text[1..text.len() - 1].replace(&doubled, &q.to_string());
+        }
+    }
+    text.to_string()
+}
+
+fn parse_account(text: &str) -> MysqlUserInfo {
+    match find_top_level(text, "@") {
+        Some(i) => MysqlUserInfo {
+            user: unquote(&text[..i]),
+            host: unquote(&text[i + 1..]),
+        },
+        None => MysqlUserInfo {
+            user: unquote(text),
+            host: "%".to_string(),
+        },
+    }
+}
+
+/// Level, database and table or routine name of a grant object
+fn parse_object(object: &str) -> (PrivilegeLevel, Option<String>, Option<String>) {
+    let upper = object.to_ascii_uppercase();
+    let (routine, path) = if upper.starts_with("PROCEDURE ") || upper.starts_with("FUNCTION ") {
+        let (_, path) = object.split_once(' ').unwrap_or(("", object));
+        (true, path.trim())
+    } else if upper.starts_with("TABLE ") {
+        (false, object[6..].trim())
+    } else {
+        (false, object)
+    };
+
+    let parts = split_top_level(path, ".");
+    let name = |part: &str| (part != "*").then(|| unquote(part));
+    match parts.as_slice() {
+        [db, object] if db == "*" && object == "*" => (PrivilegeLevel::Global, None, None),
+        [db, object] if object == "*" => (PrivilegeLevel::Database, name(db), None),
+        [db, object] if routine => (PrivilegeLevel::Routine, name(db), name(object)),
+        [db, object] => (PrivilegeLevel::Table, name(db), name(object)),
+        // A single name refers to the default database
+        [object] if object == "*" => (PrivilegeLevel::Database, None, None),
+        [object] if routine => (PrivilegeLevel::Routine, None, name(object)),
+        [object] => (PrivilegeLevel::Table, None, name(object)),
+        _ => (PrivilegeLevel::Global, None, None),
+    }
+}
+
+fn parse_grant(statement: &str) -> ParsedGrant {
+    let statement = statement.trim();
+    let Some(body) = statement
+        .get(..6)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("GRANT "))
+        .map(|_| &statement[6..])
+    else {
+        return ParsedGrant::Other;
+    };
+    let Some(to) = find_top_level(body, " TO ") else {
+        return ParsedGrant::Other;
+    };
+    let head = &body[..to];
+    let tail = body[to + 4..].to_ascii_uppercase();
+
+    let Some(on) = find_top_level(head, " ON ") else {
+        // GRANT role[, role] TO account
+        let with_admin_option = tail.contains(" WITH ADMIN OPTION");
+        return ParsedGrant::Roles(
+            split_top_level(head, ",")
+                .iter()
+                .map(|role| RoleGrant {
+                    role: parse_account(role),
+                    with_admin_option,
+                })
+                .collect(),
+        );
+    };
+
+    let privileges = &head[..on];
+    let object = head[on + 4..].trim();
+    let grantable = tail.contains(" WITH GRANT OPTION");
+    let (level, database, name) = if privileges.trim().eq_ignore_ascii_case("PROXY") {
+        (PrivilegeLevel::Proxy, None, None)
+    } else {
+        parse_object(object)
+    };
+
+    ParsedGrant::Privileges(
+        split_top_level(privileges, ",")
+            .iter()
+            .filter_map(|privilege| {
+                let (privilege, columns) = match privilege.split_once('(') {
+                    Some((privilege, columns)) => (
+                        privilege,
+                        split_top_level(columns.trim_end_matches(')'), ",")
+                            .iter()
+                            .map(|column| unquote(column))
+                            .collect(),
+                    ),
+                    None => (privilege.as_str(), Vec::new()),
+                };
+                let privilege = privilege.trim().to_ascii_uppercase();
+                // USAGE means "no privileges"
+                (privilege != "USAGE").then(|| PrivilegeGrant {
+                    privilege,
+                    level,
+                    object: object.to_string(),
+                    database: database.clone(),
+                    name: name.clone(),
+                    columns,
+                    grantable,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Rewrite a SHOW GRANTS statement for another account. Passwords and
+/// other account options in old-style grants are dropped; the USAGE grant
+/// every account has is skipped.
+fn retarget_grant(statement: &str, target: &str) -> Option<String> {
+    let statement = statement.trim();
+    let upper = statement.to_ascii_uppercase();
+    if upper.starts_with("GRANT USAGE ON *.* TO ") {
+        return None;
+    }
+    let keyword = if upper.starts_with("GRANT ") {
+        " TO "
+    } else if upper.starts_with("REVOKE ") {
+        " FROM "
+    } else {
+        return None;
+    };
+
+    let at = find_top_level(statement, keyword)?;
+    let mut retargeted = format!("{}{}{}", &statement[..at], keyword, target);
+    if upper.contains(" WITH GRANT OPTION") {
+        retargeted.push_str(" WITH GRANT OPTION");
+    } else if upper.contains(" WITH ADMIN OPTION") {
+        retargeted.push_str(" WITH ADMIN OPTION");
+    }
+    Some(retargeted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_privilege_and_role_grants() {
+        let ParsedGrant::Privileges(grants) = parse_grant(
+            "GRANT SELECT, UPDATE (`name`, `e,mail`) ON `shop`.`customers` TO `app`@`%` \
+             WITH GRANT OPTION",
+        ) else {
+            panic!("expected privileges");
+        };
+        assert_eq!(grants.len(), 2);
+        assert_eq!(grants[0].privilege, "SELECT");
+        assert_eq!(grants[0].level, PrivilegeLevel::Table);
+        assert_eq!(grants[0].database.as_deref(), Some("shop"));
+        assert_eq!(grants[0].name.as_deref(), Some("customers"));
+        assert_eq!(grants[1].columns, vec!["name", "e,mail"]);
+        assert!(grants[1].grantable);
+
+        let ParsedGrant::Privileges(grants) =
+            parse_grant("GRANT EXECUTE ON PROCEDURE `shop`.`refund` TO 'app'@'10.%'")
+        else {
+            panic!("expected privileges");
+        };
+        assert_eq!(grants[0].level, PrivilegeLevel::Routine);
+        assert_eq!(grants[0].object, "PROCEDURE `shop`.`refund`");
+
+        let ParsedGrant::Privileges(grants) = parse_grant("GRANT USAGE ON *.* TO `app`@`%`") else {
+            panic!("expected privileges");
+        };
+        assert!(grants.is_empty());
+
+        let ParsedGrant::Roles(roles) =
+            parse_grant("GRANT `reader`@`%`,`ops`@`localhost` TO `app`@`%` WITH ADMIN OPTION")
+        else {
+            panic!("expected roles");
+        };
+        assert_eq!(roles.len(), 2);
+        assert_eq!(roles[1].role.user, "ops");
+        assert_eq!(roles[1].role.host, "localhost");
+        assert!(roles[0].with_admin_option);
+
+        assert!(matches!(
+            parse_grant("REVOKE INSERT ON `mysql`.* FROM `app`@`%`"),
+            ParsedGrant::Other
+        ));
+    }
+
+    #[test]
+    fn builds_clone_and_account_statements() {
+        let target = account("copy", "%");
+        assert_eq!(
+            retarget_grant(
+                "GRANT SELECT ON `shop`.* TO 'app'@'%' WITH GRANT OPTION",
+                &target
+            )
+            .as_deref(),
+            Some("GRANT SELECT ON `shop`.* TO 'copy'@'%' WITH GRANT OPTION")
+        );
+        assert_eq!(
+            retarget_grant(
+                "GRANT USAGE ON *.* TO 'app'@'%' IDENTIFIED BY PASSWORD '*AB'",
+                &target
+            ),
+            None
+        );
+        assert_eq!(
+            retarget_grant("REVOKE INSERT ON `mysql`.* FROM `app`@`%`", &target).as_deref(),
+            Some("REVOKE INSERT ON `mysql`.* FROM 'copy'@'%'")
+        );
+
+        let mut req = AlterAccountRequest {
+            username: "app".to_string(),
+            host: "%".to_string(),
+            account_locked: Some(true),
+            password_expiry: Some(PasswordExpiry::Days(90)),
+            max_queries_per_hour: None,
+            max_updates_per_hour: Some(100),
+            max_connections_per_hour: None,
+            max_user_connections: Some(5),
+        };
+        assert_eq!(
+            alter_account_sql(&req).unwrap(),
+            "ALTER USER 'app'@'%' WITH MAX_UPDATES_PER_HOUR 100 MAX_USER_CONNECTIONS 5 \
+             PASSWORD EXPIRE INTERVAL 90 DAY ACCOUNT LOCK"
+        );
+        req = AlterAccountRequest {
+            account_locked: None,
+            password_expiry: None,
+            max_updates_per_hour: None,
+            max_user_connections: None,
+            ..req
+        };
+        assert!(alter_account_sql(&req).is_err());
+    }
+}
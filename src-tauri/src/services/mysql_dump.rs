@@ -0,0 +1,874 @@
+//! Logical MySQL dump and restore
+//!
+//! Dumps a database to a plain or gzipped SQL file the way `mysqldump` does:
+//! `SHOW CREATE` DDL and batched extended INSERTs per table, followed by
+//! routines, views, triggers and events. Restoring streams the file through
+//! `StatementReader`, so dumps larger than memory can be replayed.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::TryStreamExt;
+use sqlx::mysql::{MySql, MySqlConnection, MySqlRow};
+use sqlx::{Executor, Row};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::db::models::{
+    MysqlDumpRequest, MysqlDumpResult, MysqlRestoreError, MysqlRestoreRequest, MysqlRestoreResult,
+    ScriptErrorMode,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::jobs::JobContext;
+use crate::services::mysql::MysqlService;
+use crate::services::mysql_export::{text_row_values, ExportValue};
+use crate::services::schema_diff::{dependency_order, normalize_definition};
+use crate::services::sql_identifiers::quote_name;
+use crate::services::sql_splitter::{SqlStatement, StatementReader};
+
+/// Default rows per extended INSERT
+const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// Extended INSERTs are split before reaching this size, well below the
+/// default max_allowed_packet
+const MAX_INSERT_BYTES: usize = 1024 * 1024;
+
+/// Failed statements reported in detail by a restore
+const MAX_RESTORE_ERRORS: usize = 100;
+
+/// Length of the statement excerpt in restore errors
+const ERROR_STATEMENT_CHARS: usize = 200;
+
+/// Dump text handed to the writer thread at a time
+const WRITE_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Chunks or statement batches queued between the runtime and a file thread
+const FILE_QUEUE: usize = 8;
+
+/// Output file, optionally gzipped
+enum DumpFile {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl DumpFile {
+    fn write(&mut self, text: &str) -> AppResult<()> {
+        match self {
+            DumpFile::Plain(w) => w.write_all(text.as_bytes())?,
+            DumpFile::Gzip(w) => w.write_all(text.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    /// Flush buffers and write the gzip trailer
+    fn finish(self) -> AppResult<()> {
+        match self {
+            DumpFile::Plain(mut w) => w.flush()?,
+            DumpFile::Gzip(w) => w.finish()?.flush()?,
+        }
+        Ok(())
+    }
+}
+
+/// Buffers dump text and hands it to a blocking thread, which compresses it
+/// and writes the file
+struct DumpWriter {
+    buffer: String,
+    output: Option<mpsc::Sender<String>>,
+    worker: JoinHandle<AppResult<()>>,
+}
+
+impl DumpWriter {
+    fn create(path: &str, gzip: bool) -> AppResult<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut file = if gzip {
+            DumpFile::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            DumpFile::Plain(file)
+        };
+        let (tx, mut rx) = mpsc::channel::<String>(FILE_QUEUE);
+        let worker = tokio::task::spawn_blocking(move || {
+            while let Some(chunk) = rx.blocking_recv() {
+                file.write(&chunk)?;
+            }
+            file.finish()
+        });
+        Ok(Self {
+            buffer: String::with_capacity(WRITE_CHUNK_BYTES),
+            output: Some(tx),
+            worker,
+        })
+    }
+
+    async fn write(&mut self, text: &str) -> AppResult<()> {
+        self.buffer.push_str(text);
+        if self.buffer.len() >= WRITE_CHUNK_BYTES {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> AppResult<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, String::with_capacity(WRITE_CHUNK_BYTES));
+        let sent = match &self.output {
+            Some(tx) => tx.send(chunk).await.is_ok(),
+            None => false,
+        };
+        if sent {
+            return Ok(());
+        }
+        // The writer thread stopped early; report why
+        self.output = None;
+        match (&mut self.worker).await {
+            Ok(Err(e)) => Err(e),
+            Ok(Ok(())) => Err(AppError::Internal("Dump writer stopped".to_string())),
+            Err(e) => Err(AppError::Internal(e.to_string())),
+        }
+    }
+
+    /// Write the remaining text and wait for the file to be complete
+    async fn finish(mut self) -> AppResult<()> {
+        self.flush().await?;
+        self.output = None;
+        self.worker
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?
+    }
+}
+
+/// Counts the bytes read from the file, before decompression
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    count: Arc<AtomicU64>,
+}
+
+impl<R> CountingReader<R> {
+    pub(crate) fn new(inner: R, count: Arc<AtomicU64>) -> Self {
+        Self { inner, count }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+struct TableEntry {
+    name: String,
+    references: Vec<String>,
+}
+
+/// Dumps and restores MySQL databases
+pub struct MysqlDumpService {
+    mysql: MysqlService,
+}
+
+impl MysqlDumpService {
+    /// Create a dump service on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// Write a dump of the requested database or tables to a file
+    pub async fn dump(
+        &self,
+        req: &MysqlDumpRequest,
+        ctx: &JobContext,
+    ) -> AppResult<MysqlDumpResult> {
+        let database = req.database.as_str();
+        let whole_database = req.tables.is_empty();
+
+        ctx.set_message("Reading schema").await;
+        let all_tables = self.mysql.list_tables(database).await?;
+        let selected: Vec<_> = all_tables
+            .into_iter()
+            .filter(|t| whole_database || req.tables.contains(&t.name))
+            .collect();
+        if let Some(missing) = req
+            .tables
+            .iter()
+            .find(|name| !selected.iter().any(|t| &t.name == *name))
+        {
+            return Err(AppError::NotFound(format!("Table not found: {}", missing)));
+        }
+        let estimated_rows: i64 = selected.iter().map(|t| t.row_count.max(0)).sum();
+
+        // Tables are written referenced-first; foreign key checks are off
+        // while loading, so cycles only affect readability
+        let mut entries = Vec::with_capacity(selected.len());
+        for table in &selected {
+            let references = self
+                .mysql
+                .list_foreign_keys(database, &table.name)
+                .await?
+                .into_iter()
+                .map(|fk| fk.ref_table)
+                .collect();
+            entries.push(TableEntry {
+                name: table.name.clone(),
+                references,
+            });
+        }
+        let entry_refs: Vec<&TableEntry> = entries.iter().collect();
+        let (mut tables, cycles) = dependency_order(&entry_refs, |table, other| {
+            table.name != other.name && table.references.contains(&other.name)
+        });
+        tables.extend(cycles);
+
+        let mut writer = DumpWriter::create(&req.file_path, req.gzip)?;
+        writer
+            .write(&format!(
+                "-- InfraDesk dump of database {}\n-- Created {}\n\n\
+             /*!40101 SET NAMES utf8mb4 */;\n\
+             /*!40103 SET @OLD_TIME_ZONE=@@TIME_ZONE */;\n\
+             /*!40103 SET TIME_ZONE='+00:00' */;\n\
+             /*!40014 SET @OLD_FOREIGN_KEY_CHECKS=@@FOREIGN_KEY_CHECKS, FOREIGN_KEY_CHECKS=0 */;\n\
+             /*!40101 SET @OLD_SQL_MODE=@@SQL_MODE, SQL_MODE='NO_AUTO_VALUE_ON_ZERO' */;\n\n",
+                quote_name(database),
+                chrono::Utc::now().to_rfc3339()
+            ))
+            .await?;
+
+        let mut result = MysqlDumpResult {
+            file_path: req.file_path.clone(),
+            tables: 0,
+            views: 0,
+            routines: 0,
+            triggers: 0,
+            events: 0,
+            rows: 0,
+            bytes: 0,
+        };
+
+        // Like mysqldump --single-transaction: every table is read on one
+        // connection inside a consistent snapshot, so the dump shows a single
+        // point in time. TIMESTAMP values are read in UTC to match the
+        // TIME_ZONE set in the header. The connection is discarded afterwards.
+        let mut snapshot = self
+            .mysql
+            .pool()
+            .acquire()
+            .await
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        snapshot.close_on_drop();
+        snapshot
+            .execute(sqlx::raw_sql(
+                "SET SESSION time_zone = '+00:00'; \
+                 SET SESSION TRANSACTION ISOLATION LEVEL REPEATABLE READ; \
+                 START TRANSACTION WITH CONSISTENT SNAPSHOT",
+            ))
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        for table in &tables {
+            ctx.check_cancelled()?;
+            ctx.set_message(format!("Dumping table {}", table.name))
+                .await;
+
+            let create = show_create(
+                &mut *snapshot,
+                &format!("TABLE {}", qualified(database, &table.name)),
+                1,
+            )
+            .await?;
+            writer
+                .write(&format!(
+                    "--\n-- Table structure for {}\n--\n\n",
+                    quote_name(&table.name)
+                ))
+                .await?;
+            if req.add_drop_statements {
+                writer
+                    .write(&format!(
+                        "DROP TABLE IF EXISTS {};\n",
+                        quote_name(&table.name)
+                    ))
+                    .await?;
+            }
+            writer.write(&format!("{};\n\n", create)).await?;
+
+            if req.include_data {
+                result.rows += self
+                    .dump_rows(
+                        &mut snapshot,
+                        &mut writer,
+                        req,
+                        &table.name,
+                        result.rows,
+                        estimated_rows,
+                        ctx,
+                    )
+                    .await?;
+            }
+            result.tables += 1;
+        }
+        // The other objects are read in the same snapshot, so they match the
+        // tables dumped above
+        let routines = if whole_database && req.include_routines {
+            ctx.check_cancelled()?;
+            ctx.set_message("Reading routines").await;
+            read_objects(
+                &mut snapshot,
+                "SELECT ROUTINE_TYPE AS kind, ROUTINE_NAME AS name, NULL AS table_name \
+                 FROM information_schema.ROUTINES WHERE ROUTINE_SCHEMA = ? \
+                 ORDER BY ROUTINE_TYPE, ROUTINE_NAME",
+                database,
+            )
+            .await?
+        } else {
+            Vec::new()
+        };
+        let views = if whole_database {
+            ctx.set_message("Reading views").await;
+            read_objects(
+                &mut snapshot,
+                "SELECT 'VIEW' AS kind, TABLE_NAME AS name, NULL AS table_name \
+                 FROM information_schema.VIEWS WHERE TABLE_SCHEMA = ? ORDER BY TABLE_NAME",
+                database,
+            )
+            .await?
+        } else {
+            Vec::new()
+        };
+        let triggers = if req.include_triggers {
+            ctx.check_cancelled()?;
+            ctx.set_message("Reading triggers").await;
+            read_objects(
+                &mut snapshot,
+                "SELECT 'TRIGGER' AS kind, TRIGGER_NAME AS name, EVENT_OBJECT_TABLE AS table_name \
+                 FROM information_schema.TRIGGERS WHERE TRIGGER_SCHEMA = ? \
+                 ORDER BY EVENT_OBJECT_TABLE, ACTION_ORDER",
+                database,
+            )
+            .await?
+        } else {
+            Vec::new()
+        };
+        let events = if whole_database && req.include_routines {
+            ctx.check_cancelled()?;
+            ctx.set_message("Reading events").await;
+            read_objects(
+                &mut snapshot,
+                "SELECT 'EVENT' AS kind, EVENT_NAME AS name, NULL AS table_name \
+                 FROM information_schema.EVENTS WHERE EVENT_SCHEMA = ? ORDER BY EVENT_NAME",
+                database,
+            )
+            .await?
+        } else {
+            Vec::new()
+        };
+        snapshot
+            .execute(sqlx::raw_sql("COMMIT"))
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // Views may call functions, which must exist when the view is created
+        for routine in &routines {
+            if req.add_drop_statements {
+                writer
+                    .write(&format!(
+                        "DROP {} IF EXISTS {};\n",
+                        routine.kind,
+                        quote_name(&routine.name)
+                    ))
+                    .await?;
+            }
+            writer
+                .write(&compound(&normalize_definition(
+                    &routine.definition,
+                    database,
+                )))
+                .await?;
+            result.routines += 1;
+        }
+
+        let views: Vec<(String, String)> = views
+            .into_iter()
+            .map(|view| {
+                let definition = normalize_definition(&view.definition, database);
+                (view.name, definition)
+            })
+            .collect();
+        let view_refs: Vec<&(String, String)> = views.iter().collect();
+        let (mut ordered, cycles) = dependency_order(&view_refs, |view, other| {
+            view.0 != other.0 && view.1.contains(&quote_name(&other.0))
+        });
+        ordered.extend(cycles);
+        for (name, definition) in ordered {
+            if req.add_drop_statements {
+                writer
+                    .write(&format!("DROP VIEW IF EXISTS {};\n", quote_name(name)))
+                    .await?;
+            }
+            writer.write(&format!("{};\n\n", definition)).await?;
+            result.views += 1;
+        }
+
+        for trigger in &triggers {
+            if !tables
+                .iter()
+                .any(|t| Some(&t.name) == trigger.table.as_ref())
+            {
+                continue;
+            }
+            if req.add_drop_statements {
+                writer
+                    .write(&format!(
+                        "DROP TRIGGER IF EXISTS {};\n",
+                        quote_name(&trigger.name)
+                    ))
+                    .await?;
+            }
+            writer
+                .write(&compound(&normalize_definition(
+                    &trigger.definition,
+                    database,
+                )))
+                .await?;
+            result.triggers += 1;
+        }
+
+        for event in &events {
+            if req.add_drop_statements {
+                writer
+                    .write(&format!(
+                        "DROP EVENT IF EXISTS {};\n",
+                        quote_name(&event.name)
+                    ))
+                    .await?;
+            }
+            writer
+                .write(&compound(&normalize_definition(
+                    &event.definition,
+                    database,
+                )))
+                .await?;
+            result.events += 1;
+        }
+
+        writer
+            .write(
+                "/*!40101 SET SQL_MODE=@OLD_SQL_MODE */;\n\
+             /*!40014 SET FOREIGN_KEY_CHECKS=@OLD_FOREIGN_KEY_CHECKS */;\n\
+             /*!40103 SET TIME_ZONE=@OLD_TIME_ZONE */;\n\n\
+             -- Dump completed\n",
+            )
+            .await?;
+        writer.finish().await?;
+        result.bytes = std::fs::metadata(&req.file_path)?.len();
+
+        Ok(result)
+    }
+
+    /// Stream the rows of a table as extended INSERTs; returns the row count
+    #[allow(clippy::too_many_arguments)]
+    async fn dump_rows(
+        &self,
+        conn: &mut MySqlConnection,
+        writer: &mut DumpWriter,
+        req: &MysqlDumpRequest,
+        table: &str,
+        rows_before: u64,
+        estimated_rows: i64,
+        ctx: &JobContext,
+    ) -> AppResult<u64> {
+        let database = req.database.as_str();
+        let columns = insertable_columns(&self.mysql, database, table).await?;
+        if columns.is_empty() {
+            return Ok(0);
+        }
+        let query = format!(
+            "SELECT {} FROM {}",
+            column_list(&columns),
+            qualified(database, table)
+        );
+        let mut inserts = InsertBatcher::new(&quote_name(table), &columns, "", req.batch_size);
+
+        let mut rows = 0u64;
+        let mut stream = conn.fetch(sqlx::raw_sql(&query));
+        while let Some(row) = stream
+            .try_next()
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?
+        {
+            rows += 1;
+            if let Some(insert) = inserts.push(&row) {
+                writer.write(&insert.sql).await?;
+                writer.write(";\n").await?;
+                ctx.check_cancelled()?;
+                ctx.set_progress(rows_before + rows, Some(estimated_rows.max(0) as u64))
+                    .await;
+            }
+        }
+        if let Some(insert) = inserts.finish() {
+            writer.write(&insert.sql).await?;
+            writer.write(";\n").await?;
+        }
+        if rows > 0 {
+            writer.write("\n").await?;
+        }
+
+        Ok(rows)
+    }
+
+    /// Run a dump file against a database, statement by statement
+    pub async fn restore(
+        &self,
+        req: &MysqlRestoreRequest,
+        ctx: &JobContext,
+    ) -> AppResult<MysqlRestoreResult> {
+        let start = Instant::now();
+        let file_size = std::fs::metadata(&req.file_path)?.len();
+        let bytes_read = Arc::new(AtomicU64::new(0));
+        let mut statements = read_statements(&req.file_path, bytes_read.clone())?;
+
+        // Session settings from the dump (foreign key checks, SQL mode) must
+        // apply to every statement, so all of them run on one connection
+        let mut conn = self
+            .mysql
+            .pool()
+            .acquire()
+            .await
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        conn.execute(sqlx::raw_sql(&format!("USE {}", quote_name(&req.database))))
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        // Statements such as SET FOREIGN_KEY_CHECKS outlive the restore
+        conn.close_on_drop();
+
+        let mut result = MysqlRestoreResult {
+            statements: 0,
+            succeeded: 0,
+            failed: 0,
+            errors: Vec::new(),
+            execution_time_ms: 0,
+        };
+
+        ctx.set_message("Restoring").await;
+        while let Some(batch) = statements.recv().await {
+            let batch = batch?;
+            for statement in batch {
+                ctx.check_cancelled()?;
+                result.statements += 1;
+                match conn.execute(sqlx::raw_sql(&statement.sql)).await {
+                    Ok(_) => result.succeeded += 1,
+                    Err(e) => {
+                        result.failed += 1;
+                        if result.errors.len() < MAX_RESTORE_ERRORS {
+                            result.errors.push(MysqlRestoreError {
+                                line: statement.line,
+                                statement: statement
+                                    .sql
+                                    .chars()
+                                    .take(ERROR_STATEMENT_CHARS)
+                                    .collect(),
+                                error: e.to_string(),
+                            });
+                        }
+                        if req.mode == ScriptErrorMode::Stop {
+                            result.execution_time_ms = start.elapsed().as_millis() as u64;
+                            return Ok(result);
+                        }
+                    }
+                }
+
+                if result.statements % 100 == 0 {
+                    ctx.set_progress(bytes_read.load(Ordering::Relaxed), Some(file_size))
+                        .await;
+                    ctx.set_message(format!(
+                        "Executed {} statements ({} failed)",
+                        result.statements, result.failed
+                    ))
+                    .await;
+                }
+            }
+        }
+
+        ctx.set_progress(file_size, Some(file_size)).await;
+        result.execution_time_ms = start.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+}
+
+/// Column `index` of a SHOW CREATE statement
+pub(crate) async fn show_create<'c, E>(executor: E, object: &str, index: usize) -> AppResult<String>
+where
+    E: Executor<'c, Database = MySql>,
+{
+    let row = sqlx::query(&format!("SHOW CREATE {}", object))
+        .fetch_one(executor)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    row.try_get::<String, _>(index)
+        .or_else(|_| {
+            row.try_get::<Vec<u8>, _>(index)
+                .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+        })
+        .map_err(|e| AppError::Database(e.to_string()))
+}
+
+/// Columns of a table that an INSERT can write; generated columns cannot
+/// be inserted into
+pub(crate) async fn insertable_columns(
+    mysql: &MysqlService,
+    database: &str,
+    table: &str,
+) -> AppResult<Vec<String>> {
+    Ok(mysql
+        .get_table_schema(database, table)
+        .await?
+        .columns
+        .into_iter()
+        .filter(|c| {
+            let extra = c.extra.as_deref().unwrap_or_default().to_uppercase();
+            !extra.contains("VIRTUAL GENERATED") && !extra.contains("STORED GENERATED")
+        })
+        .map(|c| c.name)
+        .collect())
+}
+
+pub(crate) fn column_list(columns: &[String]) -> String {
+    columns
+        .iter()
+        .map(|c| quote_name(c))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// An extended INSERT and the number of rows it carries
+pub(crate) struct InsertStatement {
+    pub(crate) sql: String,
+    pub(crate) rows: u64,
+}
+
+/// Turns rows read with `sqlx::raw_sql` into extended INSERTs of at most
+/// `batch_size` rows and about MAX_INSERT_BYTES. The text protocol returns
+/// every value in its textual form, which keeps DECIMAL, BIGINT UNSIGNED and
+/// temporal values exact.
+pub(crate) struct InsertBatcher {
+    prefix: String,
+    suffix: String,
+    batch_size: usize,
+    tuples: Vec<String>,
+    bytes: usize,
+}
+
+impl InsertBatcher {
+    /// Batch INSERTs into `table`, a quoted name, followed by `suffix`
+    pub(crate) fn new(
+        table: &str,
+        columns: &[String],
+        suffix: &str,
+        batch_size: Option<usize>,
+    ) -> Self {
+        Self {
+            prefix: format!("INSERT INTO {} ({}) VALUES\n", table, column_list(columns)),
+            suffix: suffix.to_string(),
+            batch_size: batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1),
+            tuples: Vec::new(),
+            bytes: 0,
+        }
+    }
+
+    /// Add a row; returns the INSERT once the batch is full
+    pub(crate) fn push(&mut self, row: &MySqlRow) -> Option<InsertStatement> {
+        let tuple = format!(
+            "({})",
+            text_row_values(row)
+                .iter()
+                .map(ExportValue::sql_literal)
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        self.bytes += tuple.len();
+        self.tuples.push(tuple);
+        if self.tuples.len() >= self.batch_size || self.bytes >= MAX_INSERT_BYTES {
+            self.finish()
+        } else {
+            None
+        }
+    }
+
+    /// The INSERT for the rows added since the last one, if any
+    pub(crate) fn finish(&mut self) -> Option<InsertStatement> {
+        if self.tuples.is_empty() {
+            return None;
+        }
+        let insert = InsertStatement {
+            sql: format!("{}{}{}", self.prefix, self.tuples.join(",\n"), self.suffix),
+            rows: self.tuples.len() as u64,
+        };
+        self.tuples.clear();
+        self.bytes = 0;
+        Some(insert)
+    }
+}
+
+/// Open a dump file, decompressing it if it starts with the gzip magic bytes
+fn open_dump(path: &str, bytes_read: Arc<AtomicU64>) -> AppResult<Box<dyn BufRead + Send>> {
+    let mut file = BufReader::new(CountingReader::new(File::open(path)?, bytes_read));
+    let gzipped = file.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+    Ok(if gzipped {
+        Box::new(BufReader::new(GzDecoder::new(file)))
+    } else {
+        Box::new(file)
+    })
+}
+
+/// Read and split a dump file on a blocking thread. A read error ends the
+/// stream with the error as its last item
+fn read_statements(
+    path: &str,
+    bytes_read: Arc<AtomicU64>,
+) -> AppResult<mpsc::Receiver<AppResult<Vec<SqlStatement>>>> {
+    let mut reader = open_dump(path, bytes_read)?;
+    let (tx, rx) = mpsc::channel(FILE_QUEUE);
+    tokio::task::spawn_blocking(move || {
+        let mut statements = StatementReader::new();
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let batch = match reader.read_until(b'\n', &mut line) {
+                Ok(0) => {
+                    let last: Vec<_> = statements.finish().into_iter().collect();
+                    if !last.is_empty() {
+                        let _ = tx.blocking_send(Ok(last));
+                    }
+                    return;
+                }
+                Ok(_) => statements.push_line(&String::from_utf8_lossy(&line)),
+                Err(e) => {
+                    let _ = tx.blocking_send(Err(e.into()));
+                    return;
+                }
+            };
+            // A closed receiver means the restore stopped
+            if !batch.is_empty() && tx.blocking_send(Ok(batch)).is_err() {
+                return;
+            }
+        }
+    });
+    Ok(rx)
+}
+
+/// A routine, view, trigger or event read inside the dump snapshot
+struct DumpObject {
+    /// PROCEDURE, FUNCTION, VIEW, TRIGGER or EVENT
+    kind: String,
+    name: String,
+    /// Table a trigger belongs to
+    table: Option<String>,
+    definition: String,
+}
+
+/// The objects listed by an information_schema query selecting `kind`,
+/// `name` and `table_name`, with their SHOW CREATE statements
+async fn read_objects(
+    conn: &mut MySqlConnection,
+    query: &str,
+    database: &str,
+) -> AppResult<Vec<DumpObject>> {
+    let rows = sqlx::query(query)
+        .bind(database)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let mut objects = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let kind = MysqlService::get_string_from_row(row, "kind").to_uppercase();
+        let name = MysqlService::get_string_from_row(row, "name");
+        // SHOW CREATE VIEW has the statement in its second column, SHOW
+        // CREATE EVENT in its fourth and the others in their third
+        let index = match kind.as_str() {
+            "VIEW" => 1,
+            "EVENT" => 3,
+            _ => 2,
+        };
+        let definition = show_create(
+            &mut *conn,
+            &format!("{} {}", kind, qualified(database, &name)),
+            index,
+        )
+        .await?;
+        objects.push(DumpObject {
+            table: row
+                .try_get::<Option<String>, _>("table_name")
+                .ok()
+                .flatten(),
+            kind,
+            name,
+            definition,
+        });
+    }
+    Ok(objects)
+}
+
+/// A routine, trigger or event body wrapped in DELIMITER lines
+fn compound(definition: &str) -> String {
+    format!("DELIMITER ;;\n{};;\nDELIMITER ;\n\n", definition)
+}
+
+fn qualified(database: &str, name: &str) -> String {
+    format!("{}.{}", quote_name(database), quote_name(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::sql_splitter::split_statements;
+
+    #[test]
+    fn test_dump_statements_round_trip() {
+        let mut dump = String::from("/*!40101 SET NAMES utf8mb4 */;\n");
+        dump.push_str("INSERT INTO `t` (`a`) VALUES\n('x;\\n'),\n('y');\n");
+        dump.push_str(&compound(
+            "CREATE TRIGGER `tr` BEFORE INSERT ON `t` FOR EACH ROW BEGIN SET NEW.a = ';'; END",
+        ));
+
+        let statements = split_statements(&dump);
+        let types: Vec<&str> = statements.iter().map(|s| s.query_type.as_str()).collect();
+        assert_eq!(types, vec!["other", "insert", "create"]);
+        assert_eq!(statements[1].line, 2);
+        assert!(statements[2].sql.ends_with("END"));
+    }
+
+    #[tokio::test]
+    async fn test_dump_writer_round_trip_gzip() {
+        let path = std::env::temp_dir().join(format!("dump-test-{}.sql.gz", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut writer = DumpWriter::create(path, true).unwrap();
+        for i in 0..3000 {
+            writer
+                .write(&format!("INSERT INTO `t` VALUES ({});\n", i))
+                .await
+                .unwrap();
+        }
+        writer.finish().await.unwrap();
+
+        let bytes_read = Arc::new(AtomicU64::new(0));
+        let mut batches = read_statements(path, bytes_read.clone()).unwrap();
+        let mut statements = Vec::new();
+        while let Some(batch) = batches.recv().await {
+            statements.extend(batch.unwrap());
+        }
+        let file_size = std::fs::metadata(path).unwrap().len();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(statements.len(), 3000);
+        assert_eq!(statements[2999].sql, "INSERT INTO `t` VALUES (2999)");
+        assert_eq!(bytes_read.load(Ordering::Relaxed), file_size);
+    }
+}
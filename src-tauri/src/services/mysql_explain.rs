@@ -0,0 +1,654 @@
+//! Structured query plans
+//!
+//! Runs `EXPLAIN FORMAT=JSON`, or `EXPLAIN ANALYZE` on MySQL 8.0.18+, and
+//! parses the output into a `PlanNode` tree. Both the classic JSON layout
+//! (`query_block`) and the 8.3+ tree layout (`explain_json_format_version=2`)
+//! are understood. Nodes are flagged for full table scans, filesorts and
+//! temporary tables so plans can be rendered and compared.
+
+use serde_json::Value as JsonValue;
+use sqlx::{Executor, Row};
+
+use crate::db::models::{ExplainFormat, ExplainPlan, ExplainPlanRequest, PlanFlag, PlanNode};
+use crate::error::{AppError, AppResult};
+use crate::services::mysql::MysqlService;
+use crate::services::sql_identifiers::quote_name;
+use crate::services::sql_splitter::{classify_statement, split_statements};
+
+/// Arrays of subqueries attached to a query block or table in JSON plans
+const SUBQUERY_KEYS: &[&str] = &[
+    "attached_subqueries",
+    "optimized_away_subqueries",
+    "select_list_subqueries",
+    "having_subqueries",
+    "order_by_subqueries",
+    "group_by_subqueries",
+];
+
+/// Operation prefixes of tree plans and the access type they stand for
+const TREE_ACCESS_TYPES: &[(&str, &str)] = &[
+    ("Table scan on ", "ALL"),
+    ("Index scan on ", "index"),
+    ("Covering index scan on ", "index"),
+    ("Index range scan on ", "range"),
+    ("Covering index range scan on ", "range"),
+    ("Single-row index lookup on ", "eq_ref"),
+    ("Single-row covering index lookup on ", "eq_ref"),
+    ("Index lookup on ", "ref"),
+    ("Covering index lookup on ", "ref"),
+    ("Full-text index search on ", "fulltext"),
+    ("Constant row from ", "const"),
+];
+
+/// Estimates this far off the measured row count get a warning
+const ROW_ESTIMATE_FACTOR: f64 = 10.0;
+
+/// Produces typed plan trees
+pub struct MysqlExplainService {
+    mysql: MysqlService,
+}
+
+impl MysqlExplainService {
+    /// Create an explain service on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// EXPLAIN a statement and parse the plan
+    pub async fn explain_plan(&self, req: &ExplainPlanRequest) -> AppResult<ExplainPlan> {
+        let query = single_statement(&req.query)?;
+
+        let format = if req.analyze {
+            // EXPLAIN ANALYZE executes the statement
+            if classify_statement(query) != "select" {
+                return Err(AppError::Validation(
+                    "EXPLAIN ANALYZE runs the statement, only SELECT queries are allowed"
+                        .to_string(),
+                ));
+            }
+            let version = self.mysql.get_info().await?.version;
+            if !supports_explain_analyze(&version) {
+                return Err(AppError::Validation(format!(
+                    "EXPLAIN ANALYZE needs MySQL 8.0.18 or later, the server runs {}",
+                    version
+                )));
+            }
+            ExplainFormat::Analyze
+        } else {
+            ExplainFormat::Json
+        };
+
+        // USE, EXPLAIN and SHOW WARNINGS must share a connection
+        let mut conn = self
+            .mysql
+            .pool()
+            .acquire()
+            .await
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        conn.execute(sqlx::raw_sql(&format!("USE {}", quote_name(&req.database))))
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let explain = match format {
+            ExplainFormat::Json => format!("EXPLAIN FORMAT=JSON {}", query),
+            ExplainFormat::Analyze => format!("EXPLAIN ANALYZE {}", query),
+        };
+        let rows = conn
+            .fetch_all(sqlx::raw_sql(&explain))
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let raw = rows
+            .iter()
+            .map(|row| MysqlService::get_string_from_row(row, "EXPLAIN"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let warnings = conn
+            .fetch_all(sqlx::raw_sql("SHOW WARNINGS"))
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .iter()
+            .map(|row| {
+                format!(
+                    "{} {}: {}",
+                    MysqlService::get_string_from_row(row, "Level"),
+                    row.try_get::<u32, _>("Code").unwrap_or_default(),
+                    MysqlService::get_string_from_row(row, "Message")
+                )
+            })
+            .collect();
+
+        let root = match format {
+            ExplainFormat::Json => parse_json_plan(&raw)?,
+            ExplainFormat::Analyze => parse_tree_plan(&raw)?,
+        };
+        let mut flags = Vec::new();
+        collect_flags(&root, &mut flags);
+
+        Ok(ExplainPlan {
+            query: query.to_string(),
+            format,
+            root,
+            flags,
+            warnings,
+            raw,
+        })
+    }
+}
+
+/// The statement to explain, without its terminator. The EXPLAIN is sent as
+/// raw SQL, so a second statement in the input would really run.
+fn single_statement(query: &str) -> AppResult<&str> {
+    let query = query.trim().trim_end_matches(';').trim_end();
+    match split_statements(query).len() {
+        0 => Err(AppError::Validation("Query is empty".to_string())),
+        1 => Ok(query),
+        _ => Err(AppError::Validation(
+            "Only a single statement can be explained".to_string(),
+        )),
+    }
+}
+
+/// EXPLAIN ANALYZE exists from MySQL 8.0.18; MariaDB has a different syntax
+fn supports_explain_analyze(version: &str) -> bool {
+    if version.to_ascii_lowercase().contains("mariadb") {
+        return false;
+    }
+    let mut parts = version
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|part| part.parse::<u32>().unwrap_or(0));
+    let mut next = || parts.next().unwrap_or(0);
+    (next(), next(), next()) >= (8, 0, 18)
+}
+
+/// Parse EXPLAIN FORMAT=JSON output in either layout
+fn parse_json_plan(raw: &str) -> AppResult<PlanNode> {
+    let value: JsonValue = serde_json::from_str(raw)?;
+    if let Some(block) = value.get("query_block") {
+        Ok(query_block_node(block))
+    } else if value.get("operation").is_some() {
+        Ok(tree_json_node(&value))
+    } else {
+        Err(AppError::Internal(
+            "Unrecognized EXPLAIN FORMAT=JSON output".to_string(),
+        ))
+    }
+}
+
+fn query_block_node(block: &JsonValue) -> PlanNode {
+    let mut node = PlanNode {
+        operation: match block.get("select_id") {
+            Some(id) => format!("Query block #{}", id),
+            None => "Query block".to_string(),
+        },
+        cost: cost(block, "query_cost"),
+        ..Default::default()
+    };
+    add_message(&mut node, block);
+    node.children = operation_children(block);
+    node
+}
+
+/// Operations, tables and subqueries nested in a query block or operation
+fn operation_children(value: &JsonValue) -> Vec<PlanNode> {
+    let mut children = Vec::new();
+    for (key, label) in [
+        ("ordering_operation", "Sort"),
+        ("grouping_operation", "Group"),
+        ("duplicates_removal", "Remove duplicates"),
+        ("windowing", "Window"),
+    ] {
+        if let Some(operation) = value.get(key) {
+            children.push(operation_node(label, operation));
+        }
+    }
+    if let Some(table) = value.get("table") {
+        children.push(table_node(table));
+    }
+    if let Some(tables) = value.get("nested_loop").and_then(JsonValue::as_array) {
+        children.push(PlanNode {
+            operation: "Nested loop".to_string(),
+            children: tables
+                .iter()
+                .filter_map(|entry| entry.get("table"))
+                .map(table_node)
+                .collect(),
+            ..Default::default()
+        });
+    }
+    if let Some(union) = value.get("union_result") {
+        children.push(union_node(union));
+    }
+    children.extend(subquery_children(value));
+    children
+}
+
+fn operation_node(label: &str, operation: &JsonValue) -> PlanNode {
+    let mut node = PlanNode {
+        operation: label.to_string(),
+        cost: cost(operation, "sort_cost"),
+        ..Default::default()
+    };
+    add_sort_flags(&mut node, operation);
+    for window in operation
+        .get("windows")
+        .and_then(JsonValue::as_array)
+        .into_iter()
+        .flatten()
+    {
+        add_sort_flags(&mut node, window);
+    }
+    node.children = operation_children(operation);
+    node
+}
+
+fn table_node(table: &JsonValue) -> PlanNode {
+    let name = text(table, "table_name");
+    let access_type = text(table, "access_type");
+    let label = match access_type.as_deref() {
+        Some("ALL") => "Table scan",
+        Some("index") => "Index scan",
+        Some("range") => "Index range scan",
+        Some("const") | Some("system") => "Constant row",
+        Some(_) => "Index lookup",
+        None => "Table",
+    };
+    let cost = match (cost(table, "read_cost"), cost(table, "eval_cost")) {
+        (Some(read), Some(eval)) => Some(read + eval),
+        (read, eval) => read.or(eval),
+    };
+
+    let mut node = PlanNode {
+        operation: match &name {
+            Some(name) => format!("{} on {}", label, name),
+            None => label.to_string(),
+        },
+        table: name,
+        key: text(table, "key"),
+        condition: text(table, "attached_condition"),
+        cost,
+        estimated_rows: number(table.get("rows_examined_per_scan")),
+        ..Default::default()
+    };
+    if access_type.as_deref() == Some("ALL") {
+        node.flags.push(PlanFlag::FullTableScan);
+    }
+    node.access_type = access_type;
+    add_sort_flags(&mut node, table);
+    add_message(&mut node, table);
+    if let Some(buffer) = text(table, "using_join_buffer") {
+        node.warnings.push(format!("Join buffer ({})", buffer));
+    }
+
+    if let Some(derived) = table.get("materialized_from_subquery") {
+        let mut materialize = PlanNode {
+            operation: "Materialize".to_string(),
+            ..Default::default()
+        };
+        add_sort_flags(&mut materialize, derived);
+        if let Some(block) = derived.get("query_block") {
+            materialize.children.push(query_block_node(block));
+        }
+        node.children.push(materialize);
+    }
+    node.children.extend(subquery_children(table));
+    node
+}
+
+fn union_node(union: &JsonValue) -> PlanNode {
+    let mut node = PlanNode {
+        operation: "Union".to_string(),
+        table: text(union, "table_name"),
+        access_type: text(union, "access_type"),
+        children: union
+            .get("query_specifications")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|spec| spec.get("query_block"))
+            .map(query_block_node)
+            .collect(),
+        ..Default::default()
+    };
+    add_sort_flags(&mut node, union);
+    node
+}
+
+fn subquery_children(value: &JsonValue) -> Vec<PlanNode> {
+    let mut children = Vec::new();
+    for key in SUBQUERY_KEYS {
+        for subquery in value
+            .get(*key)
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let Some(block) = subquery.get("query_block") else {
+                continue;
+            };
+            let mut node = PlanNode {
+                operation: "Subquery".to_string(),
+                children: vec![query_block_node(block)],
+                ..Default::default()
+            };
+            if subquery.get("dependent").and_then(JsonValue::as_bool) == Some(true) {
+                node.warnings
+                    .push("Dependent subquery, evaluated again for each outer row".to_string());
+            }
+            add_sort_flags(&mut node, subquery);
+            children.push(node);
+        }
+    }
+    children
+}
+
+/// Node of the version 2 JSON layout, which mirrors the tree format
+fn tree_json_node(value: &JsonValue) -> PlanNode {
+    let mut node = PlanNode {
+        operation: text(value, "operation").unwrap_or_default(),
+        table: text(value, "table_name"),
+        key: text(value, "index_name"),
+        condition: text(value, "condition"),
+        cost: number(value.get("estimated_total_cost")),
+        estimated_rows: number(value.get("estimated_rows")),
+        children: value
+            .get("inputs")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+            .map(tree_json_node)
+            .collect(),
+        ..Default::default()
+    };
+    classify_tree_operation(&mut node);
+    node
+}
+
+/// Parse the indented `-> operation (cost=..) (actual time=..)` text of
+/// EXPLAIN ANALYZE (and EXPLAIN FORMAT=TREE)
+fn parse_tree_plan(raw: &str) -> AppResult<PlanNode> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for line in raw.lines() {
+        let trimmed = line.trim_start();
+        if let Some(operation) = trimmed.strip_prefix("-> ") {
+            lines.push((line.len() - trimmed.len(), operation.trim_end().to_string()));
+        } else if let Some((_, last)) = lines.last_mut() {
+            // Long conditions can wrap onto following lines
+            if !trimmed.is_empty() {
+                last.push(' ');
+                last.push_str(trimmed.trim_end());
+            }
+        }
+    }
+    if lines.is_empty() {
+        return Err(AppError::Internal(
+            "Unrecognized EXPLAIN ANALYZE output".to_string(),
+        ));
+    }
+
+    let mut stack: Vec<(usize, PlanNode)> = Vec::new();
+    let mut roots = Vec::new();
+    for (indent, line) in lines {
+        while stack.last().is_some_and(|(top, _)| *top >= indent) {
+            let (_, done) = stack.pop().unwrap();
+            attach(&mut stack, &mut roots, done);
+        }
+        stack.push((indent, tree_line_node(&line)));
+    }
+    while let Some((_, done)) = stack.pop() {
+        attach(&mut stack, &mut roots, done);
+    }
+
+    Ok(if roots.len() == 1 {
+        roots.remove(0)
+    } else {
+        PlanNode {
+            operation: "Plan".to_string(),
+            children: roots,
+            ..Default::default()
+        }
+    })
+}
+
+fn attach(stack: &mut [(usize, PlanNode)], roots: &mut Vec<PlanNode>, node: PlanNode) {
+    match stack.last_mut() {
+        Some((_, parent)) => parent.children.push(node),
+        None => roots.push(node),
+    }
+}
+
+fn tree_line_node(line: &str) -> PlanNode {
+    let end = ["  (cost=", " (cost=", " (actual time=", " (never executed)"]
+        .iter()
+        .filter_map(|marker| line.find(marker))
+        .min()
+        .unwrap_or(line.len());
+    let metrics = &line[end..];
+
+    let mut node = PlanNode {
+        operation: line[..end].trim().to_string(),
+        ..Default::default()
+    };
+    if let Some(estimate) = paren_group(metrics, "(cost=") {
+        // 8.1+ prints a startup..total cost range
+        node.cost = metric(estimate, "cost=")
+            .and_then(|cost| cost.rsplit("..").next())
+            .and_then(|cost| cost.parse().ok());
+        node.estimated_rows = metric(estimate, "rows=").and_then(|rows| rows.parse().ok());
+    }
+    if let Some(actual) = paren_group(metrics, "(actual time=") {
+        if let Some((first, last)) = metric(actual, "time=").and_then(|t| t.split_once("..")) {
+            node.first_row_ms = first.parse().ok();
+            node.last_row_ms = last.parse().ok();
+        }
+        node.actual_rows = metric(actual, "rows=").and_then(|rows| rows.parse().ok());
+        node.loops = metric(actual, "loops=").and_then(|loops| loops.parse().ok());
+    } else if metrics.contains("(never executed)") {
+        node.loops = Some(0);
+        node.warnings.push("Never executed".to_string());
+    }
+
+    if let (Some(estimated), Some(actual)) = (node.estimated_rows, node.actual_rows) {
+        let (low, high) = if estimated < actual {
+            (estimated, actual)
+        } else {
+            (actual, estimated)
+        };
+        if high >= 100.0 && high > low.max(1.0) * ROW_ESTIMATE_FACTOR {
+            node.warnings
+                .push(format!("Estimated {} rows, read {}", estimated, actual));
+        }
+    }
+
+    classify_tree_operation(&mut node);
+    node
+}
+
+/// Derive table, key, access type and flags from a tree operation label
+fn classify_tree_operation(node: &mut PlanNode) {
+    let operation = node.operation.clone();
+    for (prefix, access_type) in TREE_ACCESS_TYPES {
+        let Some(rest) = operation.strip_prefix(prefix) else {
+            continue;
+        };
+        if node.table.is_none() {
+            node.table = rest.split_whitespace().next().map(str::to_string);
+        }
+        if node.key.is_none() {
+            node.key = rest
+                .split_once(" using ")
+                .and_then(|(_, key)| key.split_whitespace().next())
+                .map(str::to_string);
+        }
+        node.access_type = Some(access_type.to_string());
+        break;
+    }
+
+    if let Some(condition) = operation.strip_prefix("Filter: ") {
+        node.condition.get_or_insert_with(|| condition.to_string());
+    }
+    if node.access_type.as_deref() == Some("ALL") {
+        add_flag(node, PlanFlag::FullTableScan);
+    }
+    if operation.starts_with("Sort") {
+        add_flag(node, PlanFlag::Filesort);
+    }
+    if operation.starts_with("Materialize") || operation.to_lowercase().contains("temporary table")
+    {
+        add_flag(node, PlanFlag::TemporaryTable);
+    }
+}
+
+/// `(marker ... )` group of a tree line, without the parentheses
+fn paren_group<'a>(text: &'a str, marker: &str) -> Option<&'a str> {
+    let start = text.find(marker)? + 1;
+    let end = text[start..].find(')')? + start;
+    Some(&text[start..end])
+}
+
+/// Value of `name=value` inside a metrics group
+fn metric<'a>(group: &'a str, name: &str) -> Option<&'a str> {
+    group
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix(name))
+}
+
+fn add_sort_flags(node: &mut PlanNode, value: &JsonValue) {
+    if value.get("using_filesort").and_then(JsonValue::as_bool) == Some(true) {
+        add_flag(node, PlanFlag::Filesort);
+    }
+    if value
+        .get("using_temporary_table")
+        .and_then(JsonValue::as_bool)
+        == Some(true)
+    {
+        add_flag(node, PlanFlag::TemporaryTable);
+    }
+}
+
+fn add_message(node: &mut PlanNode, value: &JsonValue) {
+    if let Some(message) = text(value, "message") {
+        node.warnings.push(message);
+    }
+}
+
+fn add_flag(node: &mut PlanNode, flag: PlanFlag) {
+    if !node.flags.contains(&flag) {
+        node.flags.push(flag);
+    }
+}
+
+fn collect_flags(node: &PlanNode, flags: &mut Vec<PlanFlag>) {
+    for flag in &node.flags {
+        if !flags.contains(flag) {
+            flags.push(*flag);
+        }
+    }
+    for child in &node.children {
+        collect_flags(child, flags);
+    }
+}
+
+fn cost(value: &JsonValue, name: &str) -> Option<f64> {
+    number(value.get("cost_info")?.get(name))
+}
+
+/// JSON plans print most numbers as strings
+fn number(value: Option<&JsonValue>) -> Option<f64> {
+    match value? {
+        JsonValue::Number(n) => n.as_f64(),
+        JsonValue::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn text(value: &JsonValue, name: &str) -> Option<String> {
+    match value.get(name)? {
+        JsonValue::String(s) => Some(s.clone()),
+        JsonValue::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_json_plan() {
+        let raw = r#"{
+          "query_block": {
+            "select_id": 1,
+            "cost_info": {"query_cost": "12.50"},
+            "ordering_operation": {
+              "using_filesort": true,
+              "nested_loop": [
+                {"table": {"table_name": "o", "access_type": "ALL",
+                           "rows_examined_per_scan": 100,
+                           "cost_info": {"read_cost": "1.00", "eval_cost": "10.00"},
+                           "attached_condition": "(`shop`.`o`.`total` > 10)"}},
+                {"table": {"table_name": "c", "access_type": "eq_ref", "key": "PRIMARY",
+                           "rows_examined_per_scan": 1}}
+              ]
+            }
+          }
+        }"#;
+
+        let root = parse_json_plan(raw).unwrap();
+        assert_eq!(root.operation, "Query block #1");
+        assert_eq!(root.cost, Some(12.5));
+
+        let sort = &root.children[0];
+        assert_eq!(sort.flags, vec![PlanFlag::Filesort]);
+        let join = &sort.children[0];
+        assert_eq!(join.operation, "Nested loop");
+        assert_eq!(join.children[0].operation, "Table scan on o");
+        assert_eq!(join.children[0].flags, vec![PlanFlag::FullTableScan]);
+        assert_eq!(join.children[0].cost, Some(11.0));
+        assert_eq!(join.children[1].key.as_deref(), Some("PRIMARY"));
+
+        let mut flags = Vec::new();
+        collect_flags(&root, &mut flags);
+        assert_eq!(flags, vec![PlanFlag::Filesort, PlanFlag::FullTableScan]);
+    }
+
+    #[test]
+    fn rejects_multiple_statements() {
+        assert_eq!(
+            single_statement(" SELECT ';' FROM t; ").unwrap(),
+            "SELECT ';' FROM t"
+        );
+        assert!(single_statement("SELECT 1; DELETE FROM t").is_err());
+        assert!(single_statement(" ; ").is_err());
+    }
+
+    #[test]
+    fn parses_analyze_tree() {
+        let raw = "-> Sort: o.total DESC  (cost=105 rows=1000) (actual time=5.1..5.3 rows=12 loops=1)\n    -> Filter: (o.total > 10)  (cost=105 rows=1000) (actual time=0.05..4.9 rows=12 loops=1)\n        -> Table scan on o  (cost=105 rows=1000) (actual time=0.04..3.2 rows=1000 loops=1)\n    -> Index lookup on c using idx_name (name='x')  (cost=0.35 rows=1) (never executed)";
+
+        let root = parse_tree_plan(raw).unwrap();
+        assert_eq!(root.flags, vec![PlanFlag::Filesort]);
+        assert_eq!(root.last_row_ms, Some(5.3));
+        assert_eq!(
+            root.warnings,
+            vec!["Estimated 1000 rows, read 12".to_string()]
+        );
+        assert_eq!(root.children.len(), 2);
+
+        let filter = &root.children[0];
+        assert_eq!(filter.condition.as_deref(), Some("(o.total > 10)"));
+        let scan = &filter.children[0];
+        assert_eq!(scan.table.as_deref(), Some("o"));
+        assert_eq!(scan.flags, vec![PlanFlag::FullTableScan]);
+        assert_eq!(scan.actual_rows, Some(1000.0));
+
+        let lookup = &root.children[1];
+        assert_eq!(lookup.access_type.as_deref(), Some("ref"));
+        assert_eq!(lookup.key.as_deref(), Some("idx_name"));
+        assert_eq!(lookup.loops, Some(0));
+
+        assert!(supports_explain_analyze("8.0.35-0ubuntu0.22.04.1"));
+        assert!(!supports_explain_analyze("8.0.17"));
+        assert!(!supports_explain_analyze("10.11.6-MariaDB"));
+    }
+}
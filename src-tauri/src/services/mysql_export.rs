@@ -0,0 +1,1482 @@
+//! Streaming MySQL exports
+//!
+//! Writes table contents or query results to a file or an HTTP download
+//! without holding them in memory. Tables are read page by page in key order
+//! (keyset pagination); tables without a usable key and query results are
+//! streamed from a single result set.
+
+use std::fs::File;
+use std::future::Future;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow_array::builder::{BinaryBuilder, Decimal128Builder, PrimitiveBuilder, StringBuilder};
+use arrow_array::types::{
+    Date32Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    TimestampMicrosecondType, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{ArrayRef, ArrowPrimitiveType, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use base64::Engine;
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use futures::TryStreamExt;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rust_xlsxwriter::{Format, Workbook};
+use sqlx::mysql::MySqlRow;
+use sqlx::{Column, Executor, Row, TypeInfo};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::db::models::{
+    ExportFormat, ExportQueryRequest, ExportSummary, ExportTableRequest, MysqlColumn, SqlInsertMode,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::jobs::{JobContext, JobService, JobStarted};
+use crate::services::mysql::{escape_csv_field, MysqlService};
+use crate::services::query_params::quote_string;
+use crate::services::sql_identifiers::quote_name;
+use crate::services::sql_splitter::{returns_rows, split_statements};
+
+/// Rows fetched per keyset page
+const PAGE_SIZE: usize = 5000;
+
+/// Output is handed to the sink in chunks of about this size
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Chunks buffered for a download before the export waits for the client
+const DOWNLOAD_BUFFER: usize = 16;
+
+/// Rows handed to the encoder thread at a time
+const ENCODER_BATCH_ROWS: usize = 1000;
+
+/// Row batches queued for the encoder thread before reading waits for it
+const ENCODER_QUEUE: usize = 4;
+
+/// Rows per INSERT statement of SQL exports, unless requested otherwise
+const DEFAULT_ROWS_PER_INSERT: usize = 100;
+
+/// Rows per Parquet row group
+const PARQUET_ROW_GROUP: usize = 16 * 1024;
+
+/// Rows of an XLSX worksheet, the header included
+const XLSX_MAX_ROWS: u32 = 1_048_576;
+
+/// A value read with the text protocol, which returns every value in its
+/// textual form and so keeps DECIMAL, BIGINT UNSIGNED and temporal values
+/// exact
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportValue {
+    Null,
+    /// Numeric value in MySQL's own notation
+    Number(String),
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl ExportValue {
+    /// Classify raw text-protocol bytes by column type
+    pub fn from_text(type_name: &str, bytes: Option<&[u8]>) -> Self {
+        let Some(bytes) = bytes else {
+            return ExportValue::Null;
+        };
+        match type_name.trim_end_matches(" UNSIGNED") {
+            // YEAR stays text: the zero year reads as 0000
+            "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "BIGINT" | "DECIMAL" | "FLOAT"
+            | "DOUBLE" | "BOOLEAN" => {
+                ExportValue::Number(String::from_utf8_lossy(bytes).to_string())
+            }
+            "BIT" | "BINARY" | "VARBINARY" | "TINYBLOB" | "BLOB" | "MEDIUMBLOB" | "LONGBLOB"
+            | "GEOMETRY" => ExportValue::Binary(bytes.to_vec()),
+            _ => ExportValue::Text(String::from_utf8_lossy(bytes).to_string()),
+        }
+    }
+
+    /// SQL literal: numbers as they are, binary data as hex and everything
+    /// else as a quoted string
+    pub fn sql_literal(&self) -> String {
+        match self {
+            ExportValue::Null => "NULL".to_string(),
+            ExportValue::Number(n) => n.clone(),
+            ExportValue::Text(s) => quote_string(s),
+            ExportValue::Binary(b) if b.is_empty() => "''".to_string(),
+            ExportValue::Binary(b) => {
+                let hex: String = b.iter().map(|byte| format!("{:02X}", byte)).collect();
+                format!("0x{}", hex)
+            }
+        }
+    }
+
+    /// JSON value; binary data is base64 encoded, and numbers JSON cannot
+    /// spell are written as strings
+    pub fn json(&self) -> String {
+        match self {
+            ExportValue::Null => "null".to_string(),
+            ExportValue::Number(n) if is_json_number(n) => n.clone(),
+            ExportValue::Number(n) => serde_json::Value::from(n.as_str()).to_string(),
+            ExportValue::Text(s) => serde_json::Value::from(s.as_str()).to_string(),
+            ExportValue::Binary(b) => {
+                serde_json::Value::from(base64::engine::general_purpose::STANDARD.encode(b))
+                    .to_string()
+            }
+        }
+    }
+
+    /// Plain text; empty for NULL, base64 for binary data
+    pub fn text(&self) -> String {
+        match self {
+            ExportValue::Null => String::new(),
+            ExportValue::Number(n) => n.clone(),
+            ExportValue::Text(s) => s.clone(),
+            ExportValue::Binary(b) => base64::engine::general_purpose::STANDARD.encode(b),
+        }
+    }
+}
+
+/// Whether number text is valid JSON as it is
+fn is_json_number(text: &str) -> bool {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let (mantissa, exponent) = match digits.find(['e', 'E']) {
+        Some(i) => (&digits[..i], Some(&digits[i + 1..])),
+        None => (digits, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+    let all_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    all_digits(integer)
+        && (integer == "0" || !integer.starts_with('0'))
+        && fraction.map_or(true, all_digits)
+        && exponent.map_or(true, |e| {
+            all_digits(e.strip_prefix(['+', '-']).unwrap_or(e))
+        })
+}
+
+/// Values of a row fetched with `sqlx::raw_sql`
+pub fn text_row_values(row: &MySqlRow) -> Vec<ExportValue> {
+    row.columns()
+        .iter()
+        .map(|column| {
+            // Text-protocol values are raw bytes whatever the column type
+            let bytes = row
+                .try_get_unchecked::<Option<Vec<u8>>, _>(column.ordinal())
+                .ok()
+                .flatten();
+            ExportValue::from_text(column.type_info().name(), bytes.as_deref())
+        })
+        .collect()
+}
+
+/// Exported column and its MySQL type, as shown by `SHOW COLUMNS`
+/// (`decimal(10,2)`) or reported for a result set (`BIGINT UNSIGNED`)
+#[derive(Debug, Clone)]
+pub struct ExportColumn {
+    pub name: String,
+    pub column_type: String,
+}
+
+/// Typed formats map MySQL column types onto these
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnKind {
+    Int {
+        bits: u8,
+        unsigned: bool,
+    },
+    Float,
+    Double,
+    /// Precision and scale, when the type states them
+    Decimal(Option<(u8, i8)>),
+    Date,
+    DateTime,
+    Binary,
+    Text,
+}
+
+impl ColumnKind {
+    fn of(column_type: &str) -> Self {
+        let lower = column_type.to_ascii_lowercase();
+        let base = lower.split(['(', ' ']).next().unwrap_or_default();
+        let unsigned = lower.contains("unsigned");
+        match base {
+            "tinyint" | "bool" | "boolean" => ColumnKind::Int { bits: 8, unsigned },
+            "smallint" => ColumnKind::Int { bits: 16, unsigned },
+            "year" => ColumnKind::Int {
+                bits: 16,
+                unsigned: false,
+            },
+            "mediumint" | "int" | "integer" => ColumnKind::Int { bits: 32, unsigned },
+            "bigint" => ColumnKind::Int { bits: 64, unsigned },
+            "float" => ColumnKind::Float,
+            "double" | "real" => ColumnKind::Double,
+            "decimal" | "numeric" => {
+                let args = lower
+                    .split_once('(')
+                    .and_then(|(_, rest)| rest.split_once(')'))
+                    .map(|(args, _)| args);
+                ColumnKind::Decimal(args.and_then(|args| {
+                    let mut parts = args.split(',').map(|p| p.trim().parse::<u8>());
+                    let precision = parts.next()?.ok()?;
+                    let scale = parts.next().unwrap_or(Ok(0)).ok()?;
+                    Some((precision, scale as i8))
+                }))
+            }
+            "date" => ColumnKind::Date,
+            "datetime" | "timestamp" => ColumnKind::DateTime,
+            "bit" | "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob"
+            | "geometry" | "point" | "linestring" | "polygon" | "multipoint"
+            | "multilinestring" | "multipolygon" | "geometrycollection" => ColumnKind::Binary,
+            _ => ColumnKind::Text,
+        }
+    }
+
+    /// Arrow type of a Parquet column. DECIMAL beyond 38 digits, or of
+    /// unknown precision, is kept exact as text.
+    fn arrow_type(self) -> DataType {
+        match self {
+            ColumnKind::Int { bits: 8, unsigned } => {
+                pick(unsigned, DataType::UInt8, DataType::Int8)
+            }
+            ColumnKind::Int { bits: 16, unsigned } => {
+                pick(unsigned, DataType::UInt16, DataType::Int16)
+            }
+            ColumnKind::Int { bits: 32, unsigned } => {
+                pick(unsigned, DataType::UInt32, DataType::Int32)
+            }
+            ColumnKind::Int { unsigned, .. } => pick(unsigned, DataType::UInt64, DataType::Int64),
+            ColumnKind::Float => DataType::Float32,
+            ColumnKind::Double => DataType::Float64,
+            ColumnKind::Decimal(Some((precision, scale))) if precision <= 38 => {
+                DataType::Decimal128(precision, scale)
+            }
+            ColumnKind::Date => DataType::Date32,
+            ColumnKind::DateTime => DataType::Timestamp(TimeUnit::Microsecond, None),
+            ColumnKind::Binary => DataType::Binary,
+            ColumnKind::Decimal(_) | ColumnKind::Text => DataType::Utf8,
+        }
+    }
+}
+
+fn pick(unsigned: bool, if_unsigned: DataType, if_signed: DataType) -> DataType {
+    if unsigned {
+        if_unsigned
+    } else {
+        if_signed
+    }
+}
+
+/// Encodes rows in an export format, one piece at a time
+pub struct RowEncoder {
+    format: ExportFormat,
+    columns: Vec<ExportColumn>,
+    table: String,
+    include_headers: bool,
+    insert_mode: SqlInsertMode,
+    rows_per_insert: usize,
+    rows: u64,
+    /// Rows written to the SQL statement still open
+    pending: usize,
+    xlsx: Option<XlsxSheet>,
+    parquet: Option<ParquetFile>,
+}
+
+impl RowEncoder {
+    /// `table` names the target table of SQL INSERT statements and the
+    /// XLSX worksheet
+    pub fn new(
+        format: ExportFormat,
+        columns: Vec<ExportColumn>,
+        table: &str,
+        include_headers: bool,
+        insert_mode: SqlInsertMode,
+        rows_per_insert: Option<usize>,
+    ) -> Self {
+        Self {
+            format,
+            columns,
+            table: table.to_string(),
+            include_headers,
+            insert_mode,
+            rows_per_insert: rows_per_insert.unwrap_or(DEFAULT_ROWS_PER_INSERT).max(1),
+            rows: 0,
+            pending: 0,
+            xlsx: None,
+            parquet: None,
+        }
+    }
+
+    /// Start the output once the columns are known
+    pub fn header(&mut self, out: &mut Vec<u8>) -> AppResult<()> {
+        match self.format {
+            ExportFormat::Csv if self.include_headers && !self.columns.is_empty() => {
+                let header: Vec<String> = self
+                    .columns
+                    .iter()
+                    .map(|c| escape_csv_field(&c.name))
+                    .collect();
+                out.extend_from_slice(header.join(",").as_bytes());
+                out.push(b'\n');
+            }
+            ExportFormat::Json => out.push(b'['),
+            ExportFormat::Markdown if !self.columns.is_empty() => {
+                let names: Vec<String> = self
+                    .columns
+                    .iter()
+                    .map(|c| markdown_cell(&c.name))
+                    .collect();
+                let rule = vec!["---"; self.columns.len()];
+                out.extend_from_slice(
+                    format!("| {} |\n| {} |\n", names.join(" | "), rule.join(" | ")).as_bytes(),
+                );
+            }
+            ExportFormat::Xlsx => {
+                self.xlsx = Some(XlsxSheet::new(
+                    &self.table,
+                    &self.columns,
+                    self.include_headers,
+                )?)
+            }
+            ExportFormat::Parquet => self.parquet = Some(ParquetFile::new(&self.columns)?),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn row(&mut self, values: &[ExportValue], out: &mut Vec<u8>) -> AppResult<()> {
+        match self.format {
+            ExportFormat::Csv => {
+                let fields: Vec<String> =
+                    values.iter().map(|v| escape_csv_field(&v.text())).collect();
+                out.extend_from_slice(fields.join(",").as_bytes());
+                out.push(b'\n');
+            }
+            ExportFormat::Json => {
+                if self.rows > 0 {
+                    out.push(b',');
+                }
+                out.extend_from_slice(format!("\n  {}", self.json_object(values)).as_bytes());
+            }
+            ExportFormat::Ndjson => {
+                out.extend_from_slice(self.json_object(values).as_bytes());
+                out.push(b'\n');
+            }
+            ExportFormat::Markdown => {
+                let cells: Vec<String> = values
+                    .iter()
+                    .map(|v| match v {
+                        ExportValue::Null => "NULL".to_string(),
+                        v => markdown_cell(&v.text()),
+                    })
+                    .collect();
+                out.extend_from_slice(format!("| {} |\n", cells.join(" | ")).as_bytes());
+            }
+            ExportFormat::Sql => self.sql_row(values, out),
+            ExportFormat::Xlsx => self.xlsx_sheet()?.row(values)?,
+            ExportFormat::Parquet => {
+                let parquet = self
+                    .parquet
+                    .as_mut()
+                    .ok_or_else(|| AppError::Internal("Parquet header not written".to_string()))?;
+                parquet.row(values, out)?;
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    pub fn footer(&mut self, out: &mut Vec<u8>) -> AppResult<()> {
+        match self.format {
+            ExportFormat::Json => {
+                if self.rows > 0 {
+                    out.push(b'\n');
+                }
+                out.extend_from_slice(b"]\n");
+            }
+            ExportFormat::Sql => self.end_insert(out),
+            ExportFormat::Xlsx => out.extend(self.xlsx_sheet()?.finish()?),
+            ExportFormat::Parquet => {
+                if let Some(parquet) = self.parquet.take() {
+                    parquet.finish(out)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn json_object(&self, values: &[ExportValue]) -> String {
+        let fields: Vec<String> = self
+            .columns
+            .iter()
+            .zip(values)
+            .map(|(column, value)| {
+                format!(
+                    "{}: {}",
+                    serde_json::Value::from(column.name.as_str()),
+                    value.json()
+                )
+            })
+            .collect();
+        format!("{{{}}}", fields.join(", "))
+    }
+
+    /// Add a row to the open INSERT statement, starting one if needed
+    fn sql_row(&mut self, values: &[ExportValue], out: &mut Vec<u8>) {
+        let literals: Vec<String> = values.iter().map(ExportValue::sql_literal).collect();
+        if self.pending == 0 {
+            let verb = match self.insert_mode {
+                SqlInsertMode::InsertIgnore => "INSERT IGNORE",
+                SqlInsertMode::Insert | SqlInsertMode::Upsert => "INSERT",
+            };
+            out.extend_from_slice(
+                format!(
+                    "{} INTO {} ({}) VALUES\n({})",
+                    verb,
+                    quote_name(&self.table),
+                    self.column_list().join(", "),
+                    literals.join(", ")
+                )
+                .as_bytes(),
+            );
+        } else {
+            out.extend_from_slice(format!(",\n({})", literals.join(", ")).as_bytes());
+        }
+        self.pending += 1;
+        if self.pending >= self.rows_per_insert {
+            self.end_insert(out);
+        }
+    }
+
+    fn end_insert(&mut self, out: &mut Vec<u8>) {
+        if self.pending == 0 {
+            return;
+        }
+        if self.insert_mode == SqlInsertMode::Upsert {
+            let updates: Vec<String> = self
+                .column_list()
+                .iter()
+                .map(|c| format!("{} = VALUES({})", c, c))
+                .collect();
+            out.extend_from_slice(
+                format!("\nON DUPLICATE KEY UPDATE {}", updates.join(", ")).as_bytes(),
+            );
+        }
+        out.extend_from_slice(b";\n");
+        self.pending = 0;
+    }
+
+    fn column_list(&self) -> Vec<String> {
+        self.columns.iter().map(|c| quote_name(&c.name)).collect()
+    }
+
+    fn xlsx_sheet(&mut self) -> AppResult<&mut XlsxSheet> {
+        self.xlsx
+            .as_mut()
+            .ok_or_else(|| AppError::Internal("XLSX header not written".to_string()))
+    }
+}
+
+/// Escape a value for a Markdown table cell
+fn markdown_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+fn format_error(e: impl std::fmt::Display) -> AppError {
+    AppError::Internal(e.to_string())
+}
+
+/// Worksheet written in constant memory mode, which moves each finished row
+/// to a temporary file
+struct XlsxSheet {
+    workbook: Workbook,
+    kinds: Vec<ColumnKind>,
+    row: u32,
+    date: Format,
+    datetime: Format,
+}
+
+impl XlsxSheet {
+    fn new(table: &str, columns: &[ExportColumn], include_headers: bool) -> AppResult<Self> {
+        // Sheet names are limited to 31 characters and exclude []:*?/\
+        let mut name: String = table
+            .chars()
+            .map(|c| if "[]:*?/\\".contains(c) { '_' } else { c })
+            .take(31)
+            .collect();
+        if name.is_empty() {
+            name = "Export".to_string();
+        }
+
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet_with_constant_memory();
+        sheet.set_name(name).map_err(format_error)?;
+        let mut row = 0;
+        if include_headers {
+            let bold = Format::new().set_bold();
+            for (col, column) in columns.iter().enumerate() {
+                sheet
+                    .write_string_with_format(0, col as u16, &column.name, &bold)
+                    .map_err(format_error)?;
+            }
+            row = 1;
+        }
+
+        Ok(Self {
+            workbook,
+            kinds: columns
+                .iter()
+                .map(|c| ColumnKind::of(&c.column_type))
+                .collect(),
+            row,
+            date: Format::new().set_num_format("yyyy-mm-dd"),
+            datetime: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+        })
+    }
+
+    fn row(&mut self, values: &[ExportValue]) -> AppResult<()> {
+        if self.row >= XLSX_MAX_ROWS {
+            return Err(AppError::Validation(format!(
+                "XLSX sheets hold at most {} rows; export to CSV or Parquet instead",
+                XLSX_MAX_ROWS
+            )));
+        }
+        let row = self.row;
+        let sheet = self
+            .workbook
+            .worksheet_from_index(0)
+            .map_err(format_error)?;
+        for (col, (value, kind)) in values.iter().zip(&self.kinds).enumerate() {
+            let col = col as u16;
+            let text = value.text();
+            let serial = match kind {
+                ColumnKind::Date => parse_date(&text).and_then(excel_date),
+                ColumnKind::DateTime => parse_datetime(&text).and_then(excel_datetime),
+                _ => None,
+            };
+            let written = match (value, serial) {
+                (ExportValue::Null, _) => continue,
+                (_, Some(serial)) if *kind == ColumnKind::Date => {
+                    sheet.write_number_with_format(row, col, serial, &self.date)
+                }
+                (_, Some(serial)) => {
+                    sheet.write_number_with_format(row, col, serial, &self.datetime)
+                }
+                (ExportValue::Number(n), _) if exact_in_f64(n) => match n.parse::<f64>() {
+                    Ok(number) => sheet.write_number(row, col, number),
+                    Err(_) => sheet.write_string(row, col, n),
+                },
+                // Binary data is written base64 encoded, numbers Excel would
+                // round as text
+                _ => sheet.write_string(row, col, &text),
+            };
+            written.map_err(format_error)?;
+        }
+        self.row += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> AppResult<Vec<u8>> {
+        self.workbook.save_to_buffer().map_err(format_error)
+    }
+}
+
+/// Whether a number has few enough significant digits to survive the trip
+/// through an Excel double
+fn exact_in_f64(number: &str) -> bool {
+    let mantissa = number.split(['e', 'E']).next().unwrap_or_default();
+    let digits = mantissa
+        .chars()
+        .filter(char::is_ascii_digit)
+        .skip_while(|&c| c == '0')
+        .count();
+    digits <= 15
+}
+
+/// Zero dates (`0000-00-00`) don't parse and are left as text or null
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").ok()
+}
+
+/// Excel serial day, counted from 1899-12-30 (dates before March 1900 are
+/// off by Excel's 1900 leap year bug, so they stay text)
+fn excel_date(date: NaiveDate) -> Option<f64> {
+    excel_datetime(date.and_hms_opt(0, 0, 0)?)
+}
+
+fn excel_datetime(datetime: NaiveDateTime) -> Option<f64> {
+    if datetime.year() < 1900 || (datetime.year() == 1900 && datetime.month() < 3) {
+        return None;
+    }
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_hms_opt(0, 0, 0)?;
+    let micros = (datetime - epoch).num_microseconds()?;
+    Some(micros as f64 / 86_400_000_000.0)
+}
+
+/// Parquet output, written one row group at a time
+struct ParquetFile {
+    schema: SchemaRef,
+    writer: ArrowWriter<Vec<u8>>,
+    batch: Vec<Vec<ExportValue>>,
+}
+
+impl ParquetFile {
+    fn new(columns: &[ExportColumn]) -> AppResult<Self> {
+        let fields: Vec<Field> = columns
+            .iter()
+            .map(|c| Field::new(&c.name, ColumnKind::of(&c.column_type).arrow_type(), true))
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(Vec::new(), schema.clone(), Some(properties))
+            .map_err(format_error)?;
+        Ok(Self {
+            schema,
+            writer,
+            batch: Vec::with_capacity(PARQUET_ROW_GROUP),
+        })
+    }
+
+    fn row(&mut self, values: &[ExportValue], out: &mut Vec<u8>) -> AppResult<()> {
+        self.batch.push(values.to_vec());
+        if self.batch.len() >= PARQUET_ROW_GROUP {
+            self.write_row_group()?;
+            // Hand over what the writer has produced so far
+            out.append(self.writer.inner_mut());
+        }
+        Ok(())
+    }
+
+    fn finish(mut self, out: &mut Vec<u8>) -> AppResult<()> {
+        if !self.batch.is_empty() {
+            self.write_row_group()?;
+        }
+        out.extend(self.writer.into_inner().map_err(format_error)?);
+        Ok(())
+    }
+
+    fn write_row_group(&mut self) -> AppResult<()> {
+        let rows = std::mem::take(&mut self.batch);
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let values: Vec<&ExportValue> = rows.iter().map(|row| &row[i]).collect();
+                arrow_array(field.data_type(), &values)
+                    .map_err(|e| AppError::Validation(format!("Column {}: {}", field.name(), e)))
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+        let batch = RecordBatch::try_new(self.schema.clone(), columns).map_err(format_error)?;
+        self.writer.write(&batch).map_err(format_error)?;
+        self.writer.flush().map_err(format_error)
+    }
+}
+
+/// Build an Arrow array of the given type from exported values
+fn arrow_array(data_type: &DataType, values: &[&ExportValue]) -> Result<ArrayRef, String> {
+    Ok(match data_type {
+        DataType::Int8 => primitive_array::<Int8Type>(values, parsed)?,
+        DataType::Int16 => primitive_array::<Int16Type>(values, parsed)?,
+        DataType::Int32 => primitive_array::<Int32Type>(values, parsed)?,
+        DataType::Int64 => primitive_array::<Int64Type>(values, parsed)?,
+        DataType::UInt8 => primitive_array::<UInt8Type>(values, parsed)?,
+        DataType::UInt16 => primitive_array::<UInt16Type>(values, parsed)?,
+        DataType::UInt32 => primitive_array::<UInt32Type>(values, parsed)?,
+        DataType::UInt64 => primitive_array::<UInt64Type>(values, parsed)?,
+        DataType::Float32 => primitive_array::<Float32Type>(values, parsed)?,
+        DataType::Float64 => primitive_array::<Float64Type>(values, parsed)?,
+        DataType::Decimal128(precision, scale) => {
+            let mut builder = Decimal128Builder::with_capacity(values.len())
+                .with_precision_and_scale(*precision, *scale)
+                .map_err(|e| e.to_string())?;
+            for value in values {
+                match value {
+                    ExportValue::Null => builder.append_null(),
+                    value => {
+                        let text = value.text();
+                        let decimal = parse_decimal(&text, *scale)
+                            .ok_or_else(|| format!("invalid decimal {}", text))?;
+                        builder.append_value(decimal);
+                    }
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        DataType::Date32 => primitive_array::<Date32Type>(values, |text| {
+            Ok(parse_date(text).map(|date| {
+                (date - NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default()).num_days() as i32
+            }))
+        })?,
+        DataType::Timestamp(_, _) => primitive_array::<TimestampMicrosecondType>(values, |text| {
+            Ok(parse_datetime(text).map(|datetime| datetime.and_utc().timestamp_micros()))
+        })?,
+        DataType::Binary => {
+            let mut builder = BinaryBuilder::with_capacity(values.len(), 0);
+            for value in values {
+                match value {
+                    ExportValue::Null => builder.append_null(),
+                    ExportValue::Binary(bytes) => builder.append_value(bytes),
+                    value => builder.append_value(value.text()),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        _ => {
+            let mut builder = StringBuilder::with_capacity(values.len(), 0);
+            for value in values {
+                match value {
+                    ExportValue::Null => builder.append_null(),
+                    value => builder.append_value(value.text()),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+    })
+}
+
+/// Primitive array from values parsed by `parse`; `Ok(None)` becomes null
+fn primitive_array<T: ArrowPrimitiveType>(
+    values: &[&ExportValue],
+    parse: impl Fn(&str) -> Result<Option<T::Native>, String>,
+) -> Result<ArrayRef, String> {
+    let mut builder = PrimitiveBuilder::<T>::with_capacity(values.len());
+    for value in values {
+        match value {
+            ExportValue::Null => builder.append_null(),
+            value => builder.append_option(parse(&value.text())?),
+        }
+    }
+    Ok(Arc::new(builder.finish()))
+}
+
+fn parsed<N: FromStr>(text: &str) -> Result<Option<N>, String> {
+    text.parse()
+        .map(Some)
+        .map_err(|_| format!("invalid number {}", text))
+}
+
+/// Unscaled value of a decimal with `scale` fractional digits
+fn parse_decimal(text: &str, scale: i8) -> Option<i128> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let scale = scale.max(0) as usize;
+    if fraction.len() > scale
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let unscaled: i128 = format!(
+        "{}{}{}",
+        whole,
+        fraction,
+        "0".repeat(scale - fraction.len())
+    )
+    .parse()
+    .ok()?;
+    Some(if negative { -unscaled } else { unscaled })
+}
+
+/// Where an export is written
+pub enum ExportSink {
+    File {
+        path: String,
+        writer: BufWriter<File>,
+    },
+    /// Chunks of a streamed HTTP response
+    Channel(mpsc::Sender<std::io::Result<Vec<u8>>>),
+}
+
+impl ExportSink {
+    /// Create (or truncate) the destination file
+    pub fn file(path: &str) -> AppResult<Self> {
+        Ok(ExportSink::File {
+            path: path.to_string(),
+            writer: BufWriter::new(File::create(path)?),
+        })
+    }
+
+    /// Run an export as a job feeding the returned receiver, for use as a
+    /// response body. A failure ends the stream with an error; the export
+    /// stops when the job is cancelled or the client disconnects.
+    pub async fn download<F, Fut>(
+        jobs: &JobService,
+        kind: &str,
+        export: F,
+    ) -> (JobStarted, mpsc::Receiver<std::io::Result<Vec<u8>>>)
+    where
+        F: FnOnce(ExportSink, JobContext) -> Fut + Send + 'static,
+        Fut: Future<Output = AppResult<ExportSummary>> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(DOWNLOAD_BUFFER);
+        let started = jobs
+            .spawn(kind, move |ctx| async move {
+                let errors = tx.clone();
+                // A slow query may not produce output for a while, so the
+                // disconnect is watched rather than noticed on the next write
+                let result = tokio::select! {
+                    result = export(ExportSink::Channel(tx), ctx) => result,
+                    _ = errors.closed() => Err(AppError::Cancelled(
+                        "Download closed by the client".to_string(),
+                    )),
+                };
+                if let Err(e) = &result {
+                    let _ = errors.send(Err(std::io::Error::other(e.to_string()))).await;
+                }
+                result
+            })
+            .await;
+        (started, rx)
+    }
+
+    /// Write a chunk; runs on the encoder thread, so it may block
+    fn write(&mut self, bytes: Vec<u8>) -> AppResult<()> {
+        match self {
+            ExportSink::File { writer, .. } => writer.write_all(&bytes)?,
+            ExportSink::Channel(tx) => tx
+                .blocking_send(Ok(bytes))
+                .map_err(|_| AppError::Cancelled("Download closed by the client".to_string()))?,
+        }
+        Ok(())
+    }
+}
+
+/// Input for the encoder thread of an export
+enum EncoderInput {
+    /// Start the output, with the columns if they were not known up front
+    Header(Option<Vec<ExportColumn>>),
+    Rows(Vec<Vec<ExportValue>>),
+}
+
+/// Front of a running export. Encoding and writing happen on a blocking
+/// thread, so large XLSX, Parquet or file output does not stall the runtime.
+struct ExportWriter {
+    input: Option<mpsc::Sender<EncoderInput>>,
+    worker: JoinHandle<AppResult<ExportSummary>>,
+    batch: Vec<Vec<ExportValue>>,
+    /// Rows handed to the encoder so far
+    rows: u64,
+}
+
+impl ExportWriter {
+    /// Writer whose header is written once the columns are known
+    fn new(sink: ExportSink, encoder: RowEncoder) -> Self {
+        let (tx, rx) = mpsc::channel(ENCODER_QUEUE);
+        Self {
+            input: Some(tx),
+            worker: tokio::task::spawn_blocking(move || encode(rx, encoder, sink)),
+            batch: Vec::with_capacity(ENCODER_BATCH_ROWS),
+            rows: 0,
+        }
+    }
+
+    async fn header(&mut self, columns: Option<Vec<ExportColumn>>) -> AppResult<()> {
+        self.send(EncoderInput::Header(columns)).await
+    }
+
+    async fn row(&mut self, values: &[ExportValue]) -> AppResult<()> {
+        self.batch.push(values.to_vec());
+        self.rows += 1;
+        if self.batch.len() >= ENCODER_BATCH_ROWS {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> AppResult<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        let batch = std::mem::replace(&mut self.batch, Vec::with_capacity(ENCODER_BATCH_ROWS));
+        self.send(EncoderInput::Rows(batch)).await
+    }
+
+    async fn send(&mut self, input: EncoderInput) -> AppResult<()> {
+        let sent = match &self.input {
+            Some(tx) => tx.send(input).await.is_ok(),
+            None => false,
+        };
+        if sent {
+            return Ok(());
+        }
+        // The encoder thread stopped early; report why
+        self.input = None;
+        match (&mut self.worker).await {
+            Ok(Err(e)) => Err(e),
+            Ok(Ok(_)) => Err(AppError::Internal("Export encoder stopped".to_string())),
+            Err(e) => Err(AppError::Internal(e.to_string())),
+        }
+    }
+
+    async fn finish(mut self) -> AppResult<ExportSummary> {
+        self.flush().await?;
+        // Closing the input lets the encoder write the footer
+        self.input = None;
+        self.worker
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?
+    }
+}
+
+/// Encoder thread: encode incoming rows and write them to the sink in
+/// chunks of about CHUNK_BYTES
+fn encode(
+    mut input: mpsc::Receiver<EncoderInput>,
+    mut encoder: RowEncoder,
+    mut sink: ExportSink,
+) -> AppResult<ExportSummary> {
+    let mut buffer = Vec::with_capacity(CHUNK_BYTES);
+    let mut bytes = 0u64;
+    while let Some(item) = input.blocking_recv() {
+        match item {
+            EncoderInput::Header(columns) => {
+                if let Some(columns) = columns {
+                    encoder.columns = columns;
+                }
+                encoder.header(&mut buffer)?;
+            }
+            EncoderInput::Rows(rows) => {
+                for values in &rows {
+                    encoder.row(values, &mut buffer)?;
+                }
+            }
+        }
+        if buffer.len() >= CHUNK_BYTES {
+            bytes += buffer.len() as u64;
+            sink.write(std::mem::replace(
+                &mut buffer,
+                Vec::with_capacity(CHUNK_BYTES),
+            ))?;
+        }
+    }
+
+    encoder.footer(&mut buffer)?;
+    bytes += buffer.len() as u64;
+    sink.write(buffer)?;
+    let file_path = match sink {
+        ExportSink::File { path, mut writer } => {
+            writer.flush()?;
+            Some(path)
+        }
+        ExportSink::Channel(_) => None,
+    };
+    Ok(ExportSummary {
+        file_path,
+        format: format_name(&encoder.format).to_string(),
+        row_count: encoder.rows,
+        bytes,
+    })
+}
+
+/// Streams tables and query results to files or downloads
+pub struct MysqlExportService {
+    mysql: MysqlService,
+}
+
+impl MysqlExportService {
+    /// Create an export service on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// Export a table, page by page in primary key (or NOT NULL unique key)
+    /// order. Progress and cancellation go through `ctx` when run as a job.
+    pub async fn export_table(
+        &self,
+        database: &str,
+        table: &str,
+        options: &ExportTableRequest,
+        sink: ExportSink,
+        ctx: Option<&JobContext>,
+    ) -> AppResult<ExportSummary> {
+        let schema = self.mysql.get_table_schema(database, table).await?;
+        if schema.columns.is_empty() {
+            return Err(AppError::NotFound(format!("Table not found: {}", table)));
+        }
+        let columns: Vec<String> = match &options.columns {
+            Some(selected) if !selected.is_empty() => {
+                if let Some(unknown) = selected
+                    .iter()
+                    .find(|c| !schema.columns.iter().any(|s| &s.name == *c))
+                {
+                    return Err(AppError::Validation(format!("Unknown column: {}", unknown)));
+                }
+                selected.clone()
+            }
+            _ => schema.columns.iter().map(|c| c.name.clone()).collect(),
+        };
+
+        let key = self
+            .keyset_columns(database, table, &schema.columns)
+            .await?;
+        // Key columns are fetched even when not exported, to page on them
+        let mut fetched = columns.clone();
+        fetched.extend(key.iter().filter(|k| !columns.contains(k)).cloned());
+        let key_positions: Vec<usize> = key
+            .iter()
+            .filter_map(|k| fetched.iter().position(|c| c == k))
+            .collect();
+
+        let total = match options.limit {
+            Some(limit) => Some(limit as u64),
+            None => self
+                .mysql
+                .list_tables(database)
+                .await?
+                .into_iter()
+                .find(|t| t.name == table)
+                .map(|t| t.row_count.max(0) as u64),
+        };
+
+        let select = format!(
+            "SELECT {} FROM {}.{}",
+            fetched
+                .iter()
+                .map(|c| quote_name(c))
+                .collect::<Vec<_>>()
+                .join(", "),
+            quote_name(database),
+            quote_name(table)
+        );
+        let filter = options
+            .where_clause
+            .as_deref()
+            .map(str::trim)
+            .filter(|w| !w.is_empty())
+            .map(|w| format!("({})", w));
+
+        let export_columns = columns
+            .iter()
+            .filter_map(|name| schema.columns.iter().find(|c| &c.name == name))
+            .map(|c| ExportColumn {
+                name: c.name.clone(),
+                column_type: c.column_type.clone(),
+            })
+            .collect();
+        let encoder = RowEncoder::new(
+            options.format.clone(),
+            export_columns,
+            table,
+            options.include_headers,
+            options.insert_mode,
+            options.rows_per_insert,
+        );
+        let mut writer = ExportWriter::new(sink, encoder);
+        writer.header(None).await?;
+
+        if key.is_empty() {
+            let mut query = select;
+            if let Some(filter) = &filter {
+                query.push_str(&format!(" WHERE {}", filter));
+            }
+            if let Some(limit) = options.limit {
+                query.push_str(&format!(" LIMIT {}", limit));
+            }
+            self.stream_query(None, &query, &mut writer, Some(columns.len()), total, ctx)
+                .await?;
+            return writer.finish().await;
+        }
+
+        let key_list = key
+            .iter()
+            .map(|k| quote_name(k))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut last_key: Option<Vec<String>> = None;
+        loop {
+            let page_size = match options.limit {
+                Some(limit) => {
+                    let remaining = (limit as u64).saturating_sub(writer.rows);
+                    if remaining == 0 {
+                        break;
+                    }
+                    (remaining as usize).min(PAGE_SIZE)
+                }
+                None => PAGE_SIZE,
+            };
+
+            let mut conditions: Vec<String> = filter.iter().cloned().collect();
+            if let Some(last) = &last_key {
+                conditions.push(format!("({}) > ({})", key_list, last.join(", ")));
+            }
+            let mut query = select.clone();
+            if !conditions.is_empty() {
+                query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+            }
+            query.push_str(&format!(" ORDER BY {} LIMIT {}", key_list, page_size));
+
+            let rows = self
+                .mysql
+                .pool()
+                .fetch_all(sqlx::raw_sql(&query))
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            for row in &rows {
+                let values = text_row_values(row);
+                last_key = Some(
+                    key_positions
+                        .iter()
+                        .map(|&i| values[i].sql_literal())
+                        .collect(),
+                );
+                writer.row(&values[..columns.len()]).await?;
+            }
+
+            if let Some(ctx) = ctx {
+                ctx.check_cancelled()?;
+                ctx.set_progress(writer.rows, total).await;
+            }
+            if rows.len() < page_size {
+                break;
+            }
+        }
+
+        writer.finish().await
+    }
+
+    /// Export the rows of a single query
+    pub async fn export_query(
+        &self,
+        req: &ExportQueryRequest,
+        sink: ExportSink,
+        ctx: Option<&JobContext>,
+    ) -> AppResult<ExportSummary> {
+        let statements = split_statements(&req.query);
+        let [statement] = statements.as_slice() else {
+            return Err(AppError::Validation(
+                "Exactly one statement can be exported".to_string(),
+            ));
+        };
+        if !returns_rows(&statement.query_type) {
+            return Err(AppError::Validation(
+                "Only statements returning rows can be exported".to_string(),
+            ));
+        }
+
+        // Columns are only known once the first row arrives
+        let encoder = RowEncoder::new(
+            req.format.clone(),
+            Vec::new(),
+            req.table_name.as_deref().unwrap_or("query_result"),
+            req.include_headers,
+            req.insert_mode,
+            req.rows_per_insert,
+        );
+        let mut writer = ExportWriter::new(sink, encoder);
+        self.stream_query(
+            Some(&req.database),
+            &statement.sql,
+            &mut writer,
+            None,
+            None,
+            ctx,
+        )
+        .await?;
+        if writer.rows == 0 {
+            writer.header(None).await?;
+        }
+        writer.finish().await
+    }
+
+    /// Stream one result set into the writer. With `visible` set, only that
+    /// many leading columns are exported; otherwise the header is written
+    /// from the first row's columns.
+    async fn stream_query(
+        &self,
+        database: Option<&str>,
+        query: &str,
+        writer: &mut ExportWriter,
+        visible: Option<usize>,
+        total: Option<u64>,
+        ctx: Option<&JobContext>,
+    ) -> AppResult<()> {
+        let mut conn = self
+            .mysql
+            .pool()
+            .acquire()
+            .await
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        if let Some(database) = database {
+            conn.execute(sqlx::raw_sql(&format!("USE {}", quote_name(database))))
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+
+        let outcome: AppResult<()> = async {
+            let mut stream = conn.fetch(sqlx::raw_sql(query));
+            while let Some(row) = stream
+                .try_next()
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?
+            {
+                if visible.is_none() && writer.rows == 0 {
+                    let columns = row
+                        .columns()
+                        .iter()
+                        .map(|c| ExportColumn {
+                            name: c.name().to_string(),
+                            column_type: c.type_info().name().to_string(),
+                        })
+                        .collect();
+                    writer.header(Some(columns)).await?;
+                }
+                let values = text_row_values(&row);
+                let count = visible.unwrap_or(values.len()).min(values.len());
+                writer.row(&values[..count]).await?;
+
+                if writer.rows % PAGE_SIZE as u64 == 0 {
+                    if let Some(ctx) = ctx {
+                        ctx.check_cancelled()?;
+                        ctx.set_progress(writer.rows, total).await;
+                    }
+                }
+            }
+            Ok(())
+        }
+        .await;
+
+        if outcome.is_err() {
+            // The result set may be half read
+            conn.close_on_drop();
+        }
+        outcome
+    }
+
+    /// Primary key columns, or those of a unique index over NOT NULL
+    /// columns; empty if the table has neither
+    async fn keyset_columns(
+        &self,
+        database: &str,
+        table: &str,
+        columns: &[MysqlColumn],
+    ) -> AppResult<Vec<String>> {
+        let indexes = self.mysql.list_indexes(database, table).await?;
+        if let Some(primary) = indexes.iter().find(|i| i.is_primary) {
+            return Ok(primary.columns.clone());
+        }
+        Ok(indexes
+            .iter()
+            .find(|i| {
+                i.unique
+                    && i.columns
+                        .iter()
+                        .all(|name| columns.iter().any(|c| &c.name == name && !c.nullable))
+            })
+            .map(|i| i.columns.clone())
+            .unwrap_or_default())
+    }
+}
+
+/// Format name, as reported in export summaries
+pub fn format_name(format: &ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Csv => "csv",
+        ExportFormat::Json => "json",
+        ExportFormat::Ndjson => "ndjson",
+        ExportFormat::Markdown => "markdown",
+        ExportFormat::Sql => "sql",
+        ExportFormat::Xlsx => "xlsx",
+        ExportFormat::Parquet => "parquet",
+    }
+}
+
+/// File extension of an export
+pub fn file_extension(format: &ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Markdown => "md",
+        format => format_name(format),
+    }
+}
+
+/// Content type of a download
+pub fn content_type(format: &ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Csv => "text/csv; charset=utf-8",
+        ExportFormat::Json => "application/json",
+        ExportFormat::Ndjson => "application/x-ndjson",
+        ExportFormat::Markdown => "text/markdown; charset=utf-8",
+        ExportFormat::Sql => "application/sql",
+        ExportFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        ExportFormat::Parquet => "application/vnd.apache.parquet",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_value() {
+        let unsigned = ExportValue::from_text("BIGINT UNSIGNED", Some(b"18446744073709551615"));
+        assert_eq!(unsigned.sql_literal(), "18446744073709551615");
+        assert_eq!(unsigned.json(), "18446744073709551615");
+        assert_eq!(
+            ExportValue::from_text("DECIMAL", Some(b"12.3400")).sql_literal(),
+            "12.3400"
+        );
+
+        let binary = ExportValue::from_text("VARBINARY", Some(&[0x00, 0xff]));
+        assert_eq!(binary.sql_literal(), "0x00FF");
+        assert_eq!(binary.json(), "\"AP8=\"");
+
+        let text = ExportValue::from_text("VARCHAR", Some("it's \"x\"".as_bytes()));
+        assert_eq!(text.sql_literal(), "'it''s \"x\"'");
+        assert_eq!(text.json(), "\"it's \\\"x\\\"\"");
+        assert_eq!(
+            ExportValue::from_text("DATETIME", None).sql_literal(),
+            "NULL"
+        );
+
+        let year = ExportValue::from_text("YEAR", Some(b"0000"));
+        assert_eq!(year.json(), "\"0000\"");
+        assert_eq!(year.sql_literal(), "'0000'");
+        assert_eq!(ExportValue::Number("-1.5E+3".to_string()).json(), "-1.5E+3");
+        assert_eq!(ExportValue::Number("007".to_string()).json(), "\"007\"");
+        assert_eq!(ExportValue::Number("1.".to_string()).json(), "\"1.\"");
+    }
+
+    fn encode(format: ExportFormat, mode: SqlInsertMode, values: &[Vec<ExportValue>]) -> Vec<u8> {
+        let columns = vec![
+            ExportColumn {
+                name: "id".to_string(),
+                column_type: "bigint(20) unsigned".to_string(),
+            },
+            ExportColumn {
+                name: "name".to_string(),
+                column_type: "varchar(20)".to_string(),
+            },
+        ];
+        let mut out = Vec::new();
+        let mut encoder = RowEncoder::new(format, columns, "t", true, mode, Some(2));
+        encoder.header(&mut out).unwrap();
+        for row in values {
+            encoder.row(row, &mut out).unwrap();
+        }
+        encoder.footer(&mut out).unwrap();
+        out
+    }
+
+    #[tokio::test]
+    async fn test_export_writer_encodes_on_thread() {
+        let (tx, mut rx) = mpsc::channel(DOWNLOAD_BUFFER);
+        let encoder = RowEncoder::new(
+            ExportFormat::Csv,
+            Vec::new(),
+            "t",
+            true,
+            SqlInsertMode::Insert,
+            None,
+        );
+        let mut writer = ExportWriter::new(ExportSink::Channel(tx), encoder);
+        writer
+            .header(Some(vec![ExportColumn {
+                name: "id".to_string(),
+                column_type: "INT".to_string(),
+            }]))
+            .await
+            .unwrap();
+        for i in 0..2500 {
+            writer
+                .row(&[ExportValue::Number(i.to_string())])
+                .await
+                .unwrap();
+        }
+        let summary = writer.finish().await.unwrap();
+        assert_eq!(summary.row_count, 2500);
+
+        let mut out = Vec::new();
+        while let Some(chunk) = rx.recv().await {
+            out.extend(chunk.unwrap());
+        }
+        assert_eq!(out.len() as u64, summary.bytes);
+        assert!(out.starts_with(b"id\n0\n1\n"));
+        assert!(out.ends_with(b"\n2499\n"));
+    }
+
+    #[test]
+    fn test_row_encoder() {
+        let row = vec![
+            ExportValue::Number("1".to_string()),
+            ExportValue::Text("a,b|\\".to_string()),
+        ];
+        let rows = vec![row.clone(), row.clone(), row];
+
+        let csv = encode(ExportFormat::Csv, SqlInsertMode::Insert, &rows[..1]);
+        assert_eq!(String::from_utf8(csv).unwrap(), "id,name\n1,\"a,b|\\\"\n");
+
+        let json = encode(ExportFormat::Json, SqlInsertMode::Insert, &rows);
+        let parsed: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed[2]["name"], "a,b|\\");
+        assert_eq!(parsed[2]["id"], 1);
+
+        let ndjson = encode(ExportFormat::Ndjson, SqlInsertMode::Insert, &rows);
+        assert_eq!(String::from_utf8(ndjson).unwrap().lines().count(), 3);
+
+        let markdown = encode(ExportFormat::Markdown, SqlInsertMode::Insert, &rows[..1]);
+        assert_eq!(
+            String::from_utf8(markdown).unwrap(),
+            "| id | name |\n| --- | --- |\n| 1 | a,b\\|\\\\ |\n"
+        );
+
+        // Two rows per statement, backslashes hex-encoded
+        let sql = encode(ExportFormat::Sql, SqlInsertMode::Upsert, &rows);
+        assert_eq!(
+            String::from_utf8(sql).unwrap(),
+            "INSERT INTO `t` (`id`, `name`) VALUES\n\
+             (1, _utf8mb4 X'612C627C5C'),\n(1, _utf8mb4 X'612C627C5C')\n\
+             ON DUPLICATE KEY UPDATE `id` = VALUES(`id`), `name` = VALUES(`name`);\n\
+             INSERT INTO `t` (`id`, `name`) VALUES\n(1, _utf8mb4 X'612C627C5C')\n\
+             ON DUPLICATE KEY UPDATE `id` = VALUES(`id`), `name` = VALUES(`name`);\n"
+        );
+        let ignore = encode(ExportFormat::Sql, SqlInsertMode::InsertIgnore, &rows[..1]);
+        assert!(String::from_utf8(ignore)
+            .unwrap()
+            .starts_with("INSERT IGNORE INTO `t`"));
+
+        let parquet = encode(ExportFormat::Parquet, SqlInsertMode::Insert, &rows);
+        assert!(parquet.starts_with(b"PAR1") && parquet.ends_with(b"PAR1"));
+        let xlsx = encode(ExportFormat::Xlsx, SqlInsertMode::Insert, &rows);
+        assert!(xlsx.starts_with(b"PK"));
+    }
+
+    #[test]
+    fn test_column_types() {
+        assert_eq!(
+            ColumnKind::of("decimal(10,2)").arrow_type(),
+            DataType::Decimal128(10, 2)
+        );
+        assert_eq!(ColumnKind::of("DECIMAL").arrow_type(), DataType::Utf8);
+        assert_eq!(
+            ColumnKind::of("int(10) unsigned").arrow_type(),
+            DataType::UInt32
+        );
+        assert_eq!(ColumnKind::of("BIGINT").arrow_type(), DataType::Int64);
+        assert_eq!(ColumnKind::of("longblob").arrow_type(), DataType::Binary);
+        assert_eq!(parse_decimal("-12.3", 2), Some(-1230));
+        assert_eq!(parse_decimal("1.234", 2), None);
+
+        assert!(exact_in_f64("123456789012345"));
+        assert!(!exact_in_f64("18446744073709551615"));
+        let datetime = parse_datetime("2024-01-01 12:00:00").and_then(excel_datetime);
+        assert_eq!(datetime, Some(45292.5));
+        assert_eq!(parse_date("0000-00-00"), None);
+    }
+}
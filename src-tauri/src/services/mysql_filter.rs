@@ -0,0 +1,450 @@
+//! Table row filters
+//!
+//! Compiles structured filters, sort orders and column projections into the
+//! clauses of a table browsing query. Column names are checked against the
+//! table and values are written as quoted literals. Keyset pages are ordered
+//! and split by the primary key, with opaque cursors holding key values.
+
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine};
+use serde_json::Value as JsonValue;
+
+use crate::db::models::{
+    FilterGroup, FilterLogic, FilterNode, FilterOperator, MysqlColumn, SortDirection,
+    TableRowsOptions,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::query_params::quote_string;
+use crate::services::sql_identifiers::quote_name;
+
+/// Deepest nesting of filter groups accepted
+const MAX_FILTER_DEPTH: usize = 16;
+
+/// SQL clauses of a table browsing query
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowClauses {
+    /// Select list, `*` unless columns were chosen
+    pub select: String,
+    /// ` WHERE ...`, or empty
+    pub filter: String,
+    /// ` ORDER BY ...`, or empty
+    pub order_by: String,
+}
+
+impl RowClauses {
+    /// Compile browsing options against the columns of the table
+    pub fn new(options: &TableRowsOptions, columns: &[MysqlColumn]) -> AppResult<Self> {
+        let select = match options.columns.as_deref() {
+            Some(chosen) if !chosen.is_empty() => chosen
+                .iter()
+                .map(|name| resolve_column(name, columns))
+                .collect::<AppResult<Vec<_>>>()?
+                .join(", "),
+            _ => "*".to_string(),
+        };
+
+        let filter = match &options.filter {
+            Some(group) => compile_group(group, columns, 0)?
+                .map(|condition| format!(" WHERE {}", condition))
+                .unwrap_or_default(),
+            None => String::new(),
+        };
+
+        let order_by = if options.order_by.is_empty() {
+            String::new()
+        } else {
+            let terms = options
+                .order_by
+                .iter()
+                .map(|sort| {
+                    let direction = match sort.direction {
+                        SortDirection::Asc => "ASC",
+                        SortDirection::Desc => "DESC",
+                    };
+                    Ok(format!(
+                        "{} {}",
+                        resolve_column(&sort.column, columns)?,
+                        direction
+                    ))
+                })
+                .collect::<AppResult<Vec<_>>>()?;
+            format!(" ORDER BY {}", terms.join(", "))
+        };
+
+        Ok(Self {
+            select,
+            filter,
+            order_by,
+        })
+    }
+
+    /// WHERE clause of the filter with another condition added
+    pub fn filter_and(&self, condition: &str) -> String {
+        if self.filter.is_empty() {
+            format!(" WHERE {}", condition)
+        } else {
+            format!("{} AND {}", self.filter, condition)
+        }
+    }
+}
+
+/// Whether the options change anything about a plain `SELECT *`
+pub fn has_options(options: &TableRowsOptions) -> bool {
+    options.filter.is_some() || !options.order_by.is_empty() || options.columns.is_some()
+}
+
+/// How a key value is read back and written into a cursor condition
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KeyKind {
+    Number,
+    Binary,
+    Text,
+}
+
+/// Orders and splits table rows into pages by the primary key
+#[derive(Debug, Clone)]
+pub struct Keyset {
+    /// Quoted column names and their kinds
+    columns: Vec<(String, KeyKind)>,
+    descending: bool,
+}
+
+impl Keyset {
+    pub fn new(columns: &[&MysqlColumn], descending: bool) -> Self {
+        let columns = columns
+            .iter()
+            .map(|c| {
+                let ty = c.column_type.to_ascii_lowercase();
+                let kind = if ty.contains("int")
+                    || ty.starts_with("decimal")
+                    || ty.starts_with("float")
+                    || ty.starts_with("double")
+                {
+                    KeyKind::Number
+                } else if ty.contains("binary") || ty.contains("blob") {
+                    KeyKind::Binary
+                } else {
+                    KeyKind::Text
+                };
+                (quote_name(&c.name), kind)
+            })
+            .collect();
+        Self {
+            columns,
+            descending,
+        }
+    }
+
+    /// Select list entries reading the key as text, so cursors hold exact values
+    pub fn select(&self) -> String {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, (name, kind))| match kind {
+                KeyKind::Binary => format!(", HEX({}) AS `{}`", name, key_alias(i)),
+                _ => format!(", CAST({} AS CHAR) AS `{}`", name, key_alias(i)),
+            })
+            .collect()
+    }
+
+    /// Rows after the cursor, or before it when paging backward
+    pub fn condition(&self, cursor: &str, backward: bool) -> AppResult<String> {
+        let invalid = || AppError::Validation("Invalid page cursor".to_string());
+        let bytes = BASE64.decode(cursor).map_err(|_| invalid())?;
+        let values: Vec<String> = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        if values.len() != self.columns.len() {
+            return Err(invalid());
+        }
+        let literals = values
+            .iter()
+            .zip(&self.columns)
+            .map(|(value, (_, kind))| match kind {
+                KeyKind::Number => value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|_| value.chars().all(|c| "0123456789+-.eE".contains(c)))
+                    .map(|_| value.clone())
+                    .ok_or_else(invalid),
+                KeyKind::Binary if value.chars().all(|c| c.is_ascii_hexdigit()) => {
+                    Ok(format!("X'{}'", value))
+                }
+                KeyKind::Binary => Err(invalid()),
+                KeyKind::Text => Ok(quote_string(value)),
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+        let names: Vec<&str> = self.columns.iter().map(|(name, _)| name.as_str()).collect();
+        let operator = if self.descending != backward {
+            "<"
+        } else {
+            ">"
+        };
+        Ok(format!(
+            "({}) {} ({})",
+            names.join(", "),
+            operator,
+            literals.join(", ")
+        ))
+    }
+
+    pub fn order_by(&self, backward: bool) -> String {
+        let direction = if self.descending != backward {
+            "DESC"
+        } else {
+            "ASC"
+        };
+        let terms: Vec<String> = self
+            .columns
+            .iter()
+            .map(|(name, _)| format!("{} {}", name, direction))
+            .collect();
+        format!(" ORDER BY {}", terms.join(", "))
+    }
+
+    /// Take the key values selected by `select` out of a row
+    pub fn take_key(&self, row: &mut HashMap<String, JsonValue>) -> Vec<String> {
+        (0..self.columns.len())
+            .map(|i| match row.remove(&key_alias(i)) {
+                Some(JsonValue::String(s)) => s,
+                Some(other) => other.to_string(),
+                None => String::new(),
+            })
+            .collect()
+    }
+
+    /// Names of the key value columns added by `select`
+    pub fn aliases(&self) -> Vec<String> {
+        (0..self.columns.len()).map(key_alias).collect()
+    }
+}
+
+fn key_alias(i: usize) -> String {
+    format!("__keyset_{}", i)
+}
+
+/// Cursor pointing at a row with the given key
+pub fn encode_cursor(key: &[String]) -> String {
+    BASE64.encode(serde_json::to_vec(key).unwrap_or_default())
+}
+
+/// Quoted name of a table column, matched exactly or else ignoring case
+fn resolve_column(name: &str, columns: &[MysqlColumn]) -> AppResult<String> {
+    columns
+        .iter()
+        .find(|c| c.name == name)
+        .or_else(|| columns.iter().find(|c| c.name.eq_ignore_ascii_case(name)))
+        .map(|c| quote_name(&c.name))
+        .ok_or_else(|| AppError::Validation(format!("Unknown column: {}", name)))
+}
+
+/// Condition of a group, or None when the group holds no conditions
+fn compile_group(
+    group: &FilterGroup,
+    columns: &[MysqlColumn],
+    depth: usize,
+) -> AppResult<Option<String>> {
+    if depth > MAX_FILTER_DEPTH {
+        return Err(AppError::Validation(
+            "Filter groups are nested too deeply".to_string(),
+        ));
+    }
+    let mut parts = Vec::new();
+    for node in &group.conditions {
+        let part = match node {
+            FilterNode::Condition {
+                column,
+                operator,
+                value,
+            } => Some(compile_condition(column, *operator, value, columns)?),
+            FilterNode::Group(inner) => compile_group(inner, columns, depth + 1)?,
+        };
+        parts.extend(part);
+    }
+    let joiner = match group.logic {
+        FilterLogic::And => " AND ",
+        FilterLogic::Or => " OR ",
+    };
+    Ok(match parts.len() {
+        0 => None,
+        1 => parts.pop(),
+        _ => Some(format!("({})", parts.join(joiner))),
+    })
+}
+
+fn compile_condition(
+    column: &str,
+    operator: FilterOperator,
+    value: &JsonValue,
+    columns: &[MysqlColumn],
+) -> AppResult<String> {
+    let name = resolve_column(column, columns)?;
+    let single = || literal(value, column);
+    let list = || match value {
+        JsonValue::Array(items) if !items.is_empty() => items
+            .iter()
+            .map(|item| literal(item, column))
+            .collect::<AppResult<Vec<_>>>(),
+        _ => Err(AppError::Validation(format!(
+            "Filter on {} needs a list of values",
+            column
+        ))),
+    };
+    let pattern = |prefix: &str, suffix: &str| {
+        let text = match value {
+            JsonValue::String(s) => s.clone(),
+            JsonValue::Number(n) => n.to_string(),
+            _ => {
+                return Err(AppError::Validation(format!(
+                    "Filter on {} needs a text value",
+                    column
+                )))
+            }
+        };
+        let escaped = text
+            .replace('!', "!!")
+            .replace('%', "!%")
+            .replace('_', "!_");
+        Ok(format!(
+            "{} LIKE {} ESCAPE '!'",
+            name,
+            quote_string(&format!("{}{}{}", prefix, escaped, suffix))
+        ))
+    };
+
+    Ok(match operator {
+        FilterOperator::Eq => format!("{} = {}", name, single()?),
+        FilterOperator::Ne => format!("{} <> {}", name, single()?),
+        FilterOperator::Lt => format!("{} < {}", name, single()?),
+        FilterOperator::Le => format!("{} <= {}", name, single()?),
+        FilterOperator::Gt => format!("{} > {}", name, single()?),
+        FilterOperator::Ge => format!("{} >= {}", name, single()?),
+        FilterOperator::Like => format!("{} LIKE {}", name, single()?),
+        FilterOperator::NotLike => format!("{} NOT LIKE {}", name, single()?),
+        FilterOperator::Contains => pattern("%", "%")?,
+        FilterOperator::StartsWith => pattern("", "%")?,
+        FilterOperator::EndsWith => pattern("%", "")?,
+        FilterOperator::In => format!("{} IN ({})", name, list()?.join(", ")),
+        FilterOperator::NotIn => format!("{} NOT IN ({})", name, list()?.join(", ")),
+        FilterOperator::Between => match list()?.as_slice() {
+            [low, high] => format!("{} BETWEEN {} AND {}", name, low, high),
+            _ => {
+                return Err(AppError::Validation(format!(
+                    "Filter on {} needs a lower and an upper bound",
+                    column
+                )))
+            }
+        },
+        FilterOperator::IsNull => format!("{} IS NULL", name),
+        FilterOperator::IsNotNull => format!("{} IS NOT NULL", name),
+    })
+}
+
+/// SQL literal of a filter value
+fn literal(value: &JsonValue, column: &str) -> AppResult<String> {
+    match value {
+        JsonValue::Number(n) => Ok(n.to_string()),
+        JsonValue::String(s) => Ok(quote_string(s)),
+        JsonValue::Bool(b) => Ok(if *b { "1" } else { "0" }.to_string()),
+        JsonValue::Null => Err(AppError::Validation(format!(
+            "Filter on {} needs a value; use is_null to match NULL",
+            column
+        ))),
+        _ => Err(AppError::Validation(format!(
+            "Filter on {} needs a single value",
+            column
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::SortColumn;
+    use serde_json::json;
+
+    fn columns() -> Vec<MysqlColumn> {
+        ["id", "name", "created_at"]
+            .iter()
+            .map(|name| MysqlColumn {
+                name: name.to_string(),
+                column_type: "varchar(255)".to_string(),
+                nullable: true,
+                key: None,
+                default: None,
+                extra: None,
+                comment: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_row_clauses() {
+        let options: TableRowsOptions = serde_json::from_value(json!({
+            "filter": {
+                "logic": "and",
+                "conditions": [
+                    {"kind": "condition", "column": "NAME", "operator": "contains", "value": "50%_o'k"},
+                    {"kind": "group", "logic": "or", "conditions": [
+                        {"kind": "condition", "column": "id", "operator": "in", "value": [1, 2]},
+                        {"kind": "condition", "column": "created_at", "operator": "is_null"}
+                    ]},
+                    {"kind": "group", "conditions": []}
+                ]
+            },
+            "order_by": [{"column": "created_at", "direction": "desc"}, {"column": "id"}],
+            "columns": ["id", "name"]
+        }))
+        .unwrap();
+        let clauses = RowClauses::new(&options, &columns()).unwrap();
+        assert_eq!(clauses.select, "`id`, `name`");
+        assert_eq!(
+            clauses.filter,
+            " WHERE (`name` LIKE '%50!%!_o''k%' ESCAPE '!' AND (`id` IN (1, 2) OR `created_at` IS NULL))"
+        );
+        assert_eq!(clauses.order_by, " ORDER BY `created_at` DESC, `id` ASC");
+
+        let unknown = TableRowsOptions {
+            order_by: vec![SortColumn {
+                column: "id`; DROP TABLE t; --".to_string(),
+                direction: SortDirection::Asc,
+            }],
+            ..Default::default()
+        };
+        assert!(RowClauses::new(&unknown, &columns()).is_err());
+
+        let between: TableRowsOptions = serde_json::from_value(json!({
+            "filter": {"conditions": [
+                {"kind": "condition", "column": "id", "operator": "between", "value": [1]}
+            ]}
+        }))
+        .unwrap();
+        assert!(RowClauses::new(&between, &columns()).is_err());
+    }
+
+    #[test]
+    fn test_keyset() {
+        let columns = columns();
+        let mut id = columns[0].clone();
+        id.column_type = "bigint unsigned".to_string();
+        let keyset = Keyset::new(&[&id, &columns[1]], false);
+        assert_eq!(
+            keyset.select(),
+            ", CAST(`id` AS CHAR) AS `__keyset_0`, CAST(`name` AS CHAR) AS `__keyset_1`"
+        );
+
+        let cursor = encode_cursor(&["18446744073709551615".to_string(), "o'k".to_string()]);
+        assert_eq!(
+            keyset.condition(&cursor, false).unwrap(),
+            "(`id`, `name`) > (18446744073709551615, 'o''k')"
+        );
+        assert_eq!(
+            keyset.condition(&cursor, true).unwrap(),
+            "(`id`, `name`) < (18446744073709551615, 'o''k')"
+        );
+        assert_eq!(keyset.order_by(true), " ORDER BY `id` DESC, `name` DESC");
+
+        let injected = encode_cursor(&["1) OR (1".to_string(), "x".to_string()]);
+        assert!(keyset.condition(&injected, false).is_err());
+        assert!(keyset.condition("not a cursor", false).is_err());
+    }
+}
@@ -0,0 +1,1740 @@
+//! Bulk file imports into MySQL
+//!
+//! Streams CSV, JSON, NDJSON, XLSX and Parquet files into a table. Values are
+//! coerced to the target column types and written with batched multi-row
+//! INSERTs inside transactions; a dry run only validates. The schema of a new
+//! table can be inferred from the file.
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, ArrayRef, RecordBatch};
+use arrow_cast::display::{ArrayFormatter, FormatOptions};
+use arrow_schema::DataType;
+use calamine::{open_workbook_auto, Data, Range, Reader};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use sqlx::mysql::MySqlConnection;
+use sqlx::Executor;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::db::models::{
+    ImportFileFormat, ImportFileRequest, ImportFileResult, ImportRowError, InferredColumn,
+    InferredTable, MysqlColumn, ScriptErrorMode, SqlInsertMode,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::jobs::JobContext;
+use crate::services::mysql::MysqlService;
+use crate::services::mysql_dump::CountingReader;
+use crate::services::mysql_export::ExportValue;
+use crate::services::sql_identifiers::quote_name;
+
+/// Rows per INSERT, unless requested otherwise
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// INSERTs are sent before reaching this size, well below the default
+/// max_allowed_packet
+const MAX_INSERT_BYTES: usize = 1024 * 1024;
+
+/// Rows between commits when bad rows are skipped
+const COMMIT_ROWS: u64 = 10_000;
+
+/// Bad rows reported in detail by an import
+const MAX_IMPORT_ERRORS: usize = 100;
+
+/// Rows read by a schema preview
+pub const INFER_SAMPLE_ROWS: u64 = 10_000;
+
+/// Rows decoded at a time from Parquet files
+const PARQUET_BATCH_ROWS: usize = 4096;
+
+/// A record of an import file
+struct Record {
+    line: u64,
+    /// Values in file column order, or why the record could not be read
+    values: Result<Vec<ExportValue>, String>,
+}
+
+/// Position in a JSON array of objects
+#[derive(Clone, Copy, PartialEq)]
+enum JsonState {
+    Start,
+    First,
+    Next,
+    Done,
+}
+
+enum Source {
+    Csv(csv::Reader<BufReader<CountingReader<File>>>),
+    Json {
+        reader: BufReader<CountingReader<File>>,
+        state: JsonState,
+    },
+    Ndjson(BufReader<CountingReader<File>>),
+    Xlsx {
+        range: Range<Data>,
+        row: usize,
+    },
+    Parquet {
+        reader: ParquetRecordBatchReader,
+        batch: Option<RecordBatch>,
+        row: usize,
+    },
+}
+
+/// Reads the records of an import file one at a time
+struct ImportReader {
+    source: Source,
+    /// File column names
+    columns: Vec<String>,
+    /// Whether columns have no names and map to table columns by position
+    positional: bool,
+    null_value: Option<String>,
+    /// Keys of JSON objects that the first object lacks
+    extra_keys: BTreeSet<String>,
+    /// Records read so far
+    records: u64,
+    /// Lines read from an NDJSON file
+    lines: u64,
+    /// First JSON object, read ahead to learn the columns
+    pending: Option<Record>,
+    bytes_read: Arc<AtomicU64>,
+    file_size: u64,
+    /// Rows of XLSX and Parquet files
+    total_rows: Option<u64>,
+}
+
+impl ImportReader {
+    fn open(req: &ImportFileRequest) -> AppResult<Self> {
+        let format = match req.format {
+            Some(format) => format,
+            None => format_from_path(&req.file_path)?,
+        };
+        let file_size = std::fs::metadata(&req.file_path)?.len();
+        let bytes_read = Arc::new(AtomicU64::new(0));
+        let text_file = || -> AppResult<BufReader<CountingReader<File>>> {
+            Ok(BufReader::new(CountingReader::new(
+                File::open(&req.file_path)?,
+                bytes_read.clone(),
+            )))
+        };
+
+        let mut columns = Vec::new();
+        let mut total_rows = None;
+        let mut positional = false;
+        let source = match format {
+            ImportFileFormat::Csv => {
+                let delimiter = req.delimiter.unwrap_or(',');
+                if !delimiter.is_ascii() {
+                    return Err(AppError::Validation(
+                        "The CSV delimiter must be an ASCII character".to_string(),
+                    ));
+                }
+                let mut reader = csv::ReaderBuilder::new()
+                    .delimiter(delimiter as u8)
+                    .has_headers(req.has_header)
+                    .flexible(true)
+                    .from_reader(text_file()?);
+                let headers = reader
+                    .headers()
+                    .map_err(|e| AppError::Validation(format!("CSV header error: {}", e)))?;
+                columns = column_names(headers.iter().map(str::to_string), req.has_header);
+                positional = !req.has_header;
+                Source::Csv(reader)
+            }
+            ImportFileFormat::Json => Source::Json {
+                reader: text_file()?,
+                state: JsonState::Start,
+            },
+            ImportFileFormat::Ndjson => Source::Ndjson(text_file()?),
+            ImportFileFormat::Xlsx => {
+                let mut workbook = open_workbook_auto(&req.file_path)
+                    .map_err(|e| AppError::Validation(format!("Cannot open workbook: {}", e)))?;
+                let sheet = match &req.sheet {
+                    Some(sheet) => sheet.clone(),
+                    None => workbook.sheet_names().first().cloned().ok_or_else(|| {
+                        AppError::Validation("The workbook has no worksheets".to_string())
+                    })?,
+                };
+                let range = workbook
+                    .worksheet_range(&sheet)
+                    .map_err(|e| AppError::Validation(format!("Cannot read {}: {}", sheet, e)))?;
+                let (height, width) = range.get_size();
+                let mut row = 0;
+                let names = (0..width).map(|col| {
+                    range
+                        .get((0, col))
+                        .map(|cell| cell.to_string())
+                        .unwrap_or_default()
+                });
+                columns = column_names(names, req.has_header && height > 0);
+                if req.has_header && height > 0 {
+                    row = 1;
+                }
+                positional = !req.has_header;
+                total_rows = Some((height - row) as u64);
+                Source::Xlsx { range, row }
+            }
+            ImportFileFormat::Parquet => {
+                let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&req.file_path)?)
+                    .map_err(|e| {
+                        AppError::Validation(format!("Cannot read Parquet file: {}", e))
+                    })?;
+                total_rows = Some(builder.metadata().file_metadata().num_rows().max(0) as u64);
+                columns = builder
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|f| f.name().clone())
+                    .collect();
+                let reader = builder
+                    .with_batch_size(PARQUET_BATCH_ROWS)
+                    .build()
+                    .map_err(|e| {
+                        AppError::Validation(format!("Cannot read Parquet file: {}", e))
+                    })?;
+                Source::Parquet {
+                    reader,
+                    batch: None,
+                    row: 0,
+                }
+            }
+        };
+
+        let mut reader = Self {
+            source,
+            columns,
+            positional,
+            null_value: req.null_value.clone(),
+            extra_keys: BTreeSet::new(),
+            records: 0,
+            lines: 0,
+            pending: None,
+            bytes_read,
+            file_size,
+            total_rows,
+        };
+        if matches!(format, ImportFileFormat::Json | ImportFileFormat::Ndjson) {
+            // JSON files name their columns in the first object
+            reader.pending = reader.read()?;
+        }
+        Ok(reader)
+    }
+
+    fn next(&mut self) -> AppResult<Option<Record>> {
+        match self.pending.take() {
+            Some(record) => Ok(Some(record)),
+            None => self.read(),
+        }
+    }
+
+    fn read(&mut self) -> AppResult<Option<Record>> {
+        let number = self.records + 1;
+        let null_value = self.null_value.as_deref().unwrap_or("");
+        let record = match &mut self.source {
+            Source::Csv(reader) => {
+                let mut record = csv::StringRecord::new();
+                match reader.read_record(&mut record) {
+                    Ok(false) => None,
+                    Ok(true) => {
+                        let values = record
+                            .iter()
+                            .map(|field| {
+                                if field == null_value {
+                                    ExportValue::Null
+                                } else {
+                                    ExportValue::Text(field.to_string())
+                                }
+                            })
+                            .collect();
+                        Some(Record {
+                            line: record.position().map_or(number, |p| p.line()),
+                            values: Ok(values),
+                        })
+                    }
+                    Err(e) if e.is_io_error() => return Err(AppError::Io(e.to_string())),
+                    Err(e) => Some(Record {
+                        line: e.position().map_or(number, |p| p.line()),
+                        values: Err(e.to_string()),
+                    }),
+                }
+            }
+            Source::Json { reader, state } => {
+                next_json_element(reader, state, number)?.map(|object| Record {
+                    line: number,
+                    values: Ok(vec![object]),
+                })
+            }
+            Source::Ndjson(reader) => {
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    if reader.read_line(&mut line)? == 0 {
+                        break None;
+                    }
+                    self.lines += 1;
+                    if !line.trim().is_empty() {
+                        let value = serde_json::from_str::<JsonValue>(&line)
+                            .map_err(|e| format!("Invalid JSON: {}", e));
+                        break Some(Record {
+                            line: self.lines,
+                            values: value.map(|v| vec![ExportValue::Text(v.to_string())]),
+                        });
+                    }
+                }
+            }
+            Source::Xlsx { range, row } => {
+                let (height, width) = range.get_size();
+                if *row >= height {
+                    None
+                } else {
+                    let values = (0..width)
+                        .map(|col| cell_value(range.get((*row, col)), null_value))
+                        .collect();
+                    let first_row = range.start().map_or(0, |(r, _)| r as u64);
+                    *row += 1;
+                    Some(Record {
+                        line: first_row + *row as u64,
+                        values,
+                    })
+                }
+            }
+            Source::Parquet { reader, batch, row } => loop {
+                if let Some(current) = batch {
+                    if *row < current.num_rows() {
+                        let values = current
+                            .columns()
+                            .iter()
+                            .map(|column| arrow_value(column, *row))
+                            .collect();
+                        *row += 1;
+                        break Some(Record {
+                            line: number,
+                            values,
+                        });
+                    }
+                }
+                match reader.next() {
+                    Some(next) => {
+                        *batch = Some(next.map_err(|e| AppError::Validation(e.to_string()))?);
+                        *row = 0;
+                    }
+                    None => break None,
+                }
+            },
+        };
+
+        let Some(mut record) = record else {
+            return Ok(None);
+        };
+        self.records += 1;
+        if matches!(self.source, Source::Json { .. } | Source::Ndjson(_)) {
+            record.values = record.values.and_then(|values| self.object_values(values));
+        }
+        Ok(Some(record))
+    }
+
+    /// Values of a JSON object in column order. NDJSON records arrive as
+    /// the object's text, JSON array elements already parsed.
+    fn object_values(&mut self, values: Vec<ExportValue>) -> Result<Vec<ExportValue>, String> {
+        let object = match values.into_iter().next() {
+            Some(ExportValue::Text(text)) => serde_json::from_str::<JsonValue>(&text)
+                .map_err(|e| format!("Invalid JSON: {}", e))?,
+            _ => return Err("Expected a JSON object".to_string()),
+        };
+        let JsonValue::Object(mut object) = object else {
+            return Err("Expected a JSON object".to_string());
+        };
+        if self.records == 1 {
+            self.columns = object.keys().cloned().collect();
+        }
+        let values = self
+            .columns
+            .iter()
+            .map(|c| object.remove(c).map_or(ExportValue::Null, json_value))
+            .collect();
+        self.extra_keys
+            .extend(object.into_iter().map(|(key, _)| key));
+        Ok(values)
+    }
+
+    /// Progress in rows when the file states its row count, in bytes otherwise
+    fn progress(&self) -> (u64, Option<u64>) {
+        match self.total_rows {
+            Some(total) => (self.records, Some(total)),
+            None => (
+                self.bytes_read.load(Ordering::Relaxed),
+                Some(self.file_size),
+            ),
+        }
+    }
+}
+
+/// Records read at a time by the reader thread
+const READ_BATCH_ROWS: usize = 1000;
+
+/// Record batches queued ahead of the import
+const READ_QUEUE: usize = 4;
+
+/// Records read on the reader thread, and the progress after reading them
+struct ReadBatch {
+    records: Vec<Record>,
+    progress: (u64, Option<u64>),
+}
+
+/// Reads an import file on a blocking thread, a batch of records ahead of
+/// the import
+struct RecordStream {
+    columns: Vec<String>,
+    positional: bool,
+    batches: mpsc::Receiver<AppResult<ReadBatch>>,
+    current: std::vec::IntoIter<Record>,
+    progress: (u64, Option<u64>),
+    /// Returns the JSON keys that the first object lacks
+    worker: JoinHandle<BTreeSet<String>>,
+}
+
+impl RecordStream {
+    async fn open(req: &ImportFileRequest) -> AppResult<Self> {
+        let req = req.clone();
+        let mut reader = tokio::task::spawn_blocking(move || ImportReader::open(&req))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+        let columns = reader.columns.clone();
+        let positional = reader.positional;
+        let progress = reader.progress();
+
+        let (tx, rx) = mpsc::channel(READ_QUEUE);
+        let worker = tokio::task::spawn_blocking(move || loop {
+            let mut records = Vec::with_capacity(READ_BATCH_ROWS);
+            let mut done = false;
+            while records.len() < READ_BATCH_ROWS {
+                match reader.next() {
+                    Ok(Some(record)) => records.push(record),
+                    Ok(None) => {
+                        done = true;
+                        break;
+                    }
+                    Err(e) => {
+                        let _ = tx.blocking_send(Err(e));
+                        return reader.extra_keys;
+                    }
+                }
+            }
+            let batch = ReadBatch {
+                records,
+                progress: reader.progress(),
+            };
+            // A closed receiver means the import stopped
+            if tx.blocking_send(Ok(batch)).is_err() || done {
+                return reader.extra_keys;
+            }
+        });
+
+        Ok(Self {
+            columns,
+            positional,
+            batches: rx,
+            current: Vec::new().into_iter(),
+            progress,
+            worker,
+        })
+    }
+
+    async fn next(&mut self) -> AppResult<Option<Record>> {
+        loop {
+            if let Some(record) = self.current.next() {
+                return Ok(Some(record));
+            }
+            match self.batches.recv().await {
+                Some(batch) => {
+                    let batch = batch?;
+                    self.progress = batch.progress;
+                    self.current = batch.records.into_iter();
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn progress(&self) -> (u64, Option<u64>) {
+        self.progress
+    }
+
+    /// Stop reading and return the JSON keys that the first object lacks
+    async fn extra_keys(self) -> AppResult<BTreeSet<String>> {
+        drop(self.batches);
+        self.worker
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))
+    }
+}
+
+fn format_from_path(path: &str) -> AppResult<ImportFileFormat> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "csv" | "tsv" | "txt" => Ok(ImportFileFormat::Csv),
+        "json" => Ok(ImportFileFormat::Json),
+        "ndjson" | "jsonl" => Ok(ImportFileFormat::Ndjson),
+        "xlsx" | "xlsm" | "xls" | "ods" => Ok(ImportFileFormat::Xlsx),
+        "parquet" => Ok(ImportFileFormat::Parquet),
+        _ => Err(AppError::Validation(format!(
+            "Cannot tell the format of {}; please choose one",
+            path
+        ))),
+    }
+}
+
+/// Header names, or `column_N` where a file has none
+fn column_names(names: impl Iterator<Item = String>, has_header: bool) -> Vec<String> {
+    names
+        .enumerate()
+        .map(|(i, name)| {
+            let name = name.trim();
+            if has_header && !name.is_empty() {
+                name.to_string()
+            } else {
+                format!("column_{}", i + 1)
+            }
+        })
+        .collect()
+}
+
+/// Read the next object of a JSON array. Elements must be objects, which
+/// end on a closing brace, so the parser never reads past one.
+fn next_json_element(
+    reader: &mut BufReader<CountingReader<File>>,
+    state: &mut JsonState,
+    number: u64,
+) -> AppResult<Option<ExportValue>> {
+    let invalid =
+        |message: &str| AppError::Validation(format!("JSON element {}: {}", number, message));
+    if *state == JsonState::Start {
+        if skip_whitespace(reader)? != Some(b'[') {
+            return Err(AppError::Validation(
+                "A JSON import file must hold an array of objects".to_string(),
+            ));
+        }
+        reader.consume(1);
+        *state = JsonState::First;
+    }
+    match (*state, skip_whitespace(reader)?) {
+        (JsonState::Done, _) => return Ok(None),
+        (_, Some(b']')) => {
+            *state = JsonState::Done;
+            return Ok(None);
+        }
+        (JsonState::Next, Some(b',')) => reader.consume(1),
+        (JsonState::First, _) => {}
+        _ => return Err(invalid("expected ',' or ']'")),
+    }
+    if skip_whitespace(reader)? != Some(b'{') {
+        return Err(invalid("array elements must be objects"));
+    }
+    let mut deserializer = serde_json::Deserializer::from_reader(&mut *reader);
+    let object = JsonValue::deserialize(&mut deserializer).map_err(|e| invalid(&e.to_string()))?;
+    *state = JsonState::Next;
+    Ok(Some(ExportValue::Text(object.to_string())))
+}
+
+/// Skip whitespace and peek at the next byte
+fn skip_whitespace(reader: &mut impl BufRead) -> AppResult<Option<u8>> {
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(None);
+        }
+        let spaces = buffer
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count();
+        if spaces < buffer.len() {
+            let next = buffer[spaces];
+            reader.consume(spaces);
+            return Ok(Some(next));
+        }
+        reader.consume(spaces);
+    }
+}
+
+fn json_value(value: JsonValue) -> ExportValue {
+    match value {
+        JsonValue::Null => ExportValue::Null,
+        JsonValue::Bool(b) => ExportValue::Number(if b { "1" } else { "0" }.to_string()),
+        JsonValue::Number(n) => ExportValue::Number(n.to_string()),
+        JsonValue::String(s) => ExportValue::Text(s),
+        other => ExportValue::Text(other.to_string()),
+    }
+}
+
+fn cell_value(cell: Option<&Data>, null_value: &str) -> Result<ExportValue, String> {
+    Ok(match cell {
+        None | Some(Data::Empty) => ExportValue::Null,
+        Some(Data::String(s)) if s == null_value => ExportValue::Null,
+        Some(Data::String(s)) => ExportValue::Text(s.clone()),
+        Some(Data::Int(i)) => ExportValue::Number(i.to_string()),
+        Some(Data::Float(f)) => ExportValue::Number(float_text(*f)),
+        Some(Data::Bool(b)) => ExportValue::Number(if *b { "1" } else { "0" }.to_string()),
+        Some(Data::DateTime(value)) if value.is_duration() => {
+            let seconds = (value.as_f64() * 86_400.0).round() as i64;
+            let sign = if seconds < 0 { "-" } else { "" };
+            let seconds = seconds.abs();
+            ExportValue::Text(format!(
+                "{}{:02}:{:02}:{:02}",
+                sign,
+                seconds / 3600,
+                seconds / 60 % 60,
+                seconds % 60
+            ))
+        }
+        Some(Data::DateTime(value)) => match value.as_datetime() {
+            Some(datetime) if datetime.time() == NaiveTime::MIN => {
+                ExportValue::Text(datetime.format("%Y-%m-%d").to_string())
+            }
+            Some(datetime) => {
+                ExportValue::Text(datetime.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+            }
+            None => ExportValue::Number(float_text(value.as_f64())),
+        },
+        Some(Data::DateTimeIso(s)) | Some(Data::DurationIso(s)) => ExportValue::Text(s.clone()),
+        Some(Data::Error(e)) => return Err(format!("Cell error {}", e)),
+    })
+}
+
+/// Spreadsheets store every number as a double; whole numbers lose the
+/// `.0` Rust would print
+fn float_text(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        value.to_string()
+    }
+}
+
+fn arrow_value(array: &ArrayRef, row: usize) -> Result<ExportValue, String> {
+    if array.is_null(row) {
+        return Ok(ExportValue::Null);
+    }
+    Ok(match array.data_type() {
+        DataType::Binary => ExportValue::Binary(array.as_binary::<i32>().value(row).to_vec()),
+        DataType::LargeBinary => ExportValue::Binary(array.as_binary::<i64>().value(row).to_vec()),
+        DataType::FixedSizeBinary(_) => {
+            ExportValue::Binary(array.as_fixed_size_binary().value(row).to_vec())
+        }
+        DataType::Boolean => ExportValue::Number(
+            if array.as_boolean().value(row) {
+                "1"
+            } else {
+                "0"
+            }
+            .to_string(),
+        ),
+        data_type => {
+            let text = ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default())
+                .map_err(|e| e.to_string())?
+                .value(row)
+                .to_string();
+            if data_type.is_numeric() {
+                ExportValue::Number(text)
+            } else {
+                ExportValue::Text(text)
+            }
+        }
+    })
+}
+
+/// Dates as written by MySQL or with slashes; a midnight datetime also
+/// counts as a date
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    ["%Y-%m-%d", "%Y/%m/%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+        .or_else(|| {
+            parse_datetime(text)
+                .filter(|datetime| datetime.time() == NaiveTime::MIN)
+                .map(|datetime| datetime.date())
+        })
+}
+
+/// Datetimes in MySQL or ISO 8601 notation; offsets are converted to UTC
+fn parse_datetime(text: &str) -> Option<NaiveDateTime> {
+    [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y/%m/%d %H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    .or_else(|| {
+        DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|datetime| datetime.naive_utc())
+    })
+    .or_else(|| {
+        ["%Y-%m-%d", "%Y/%m/%d"]
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+            .map(|date| date.and_time(NaiveTime::MIN))
+    })
+}
+
+/// Integer and fraction digit counts of a plain decimal number
+fn decimal_digits(text: &str) -> Option<(usize, usize)> {
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let valid = !(whole.is_empty() && fraction.is_empty())
+        && whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit());
+    valid.then(|| (whole.trim_start_matches('0').len(), fraction.len()))
+}
+
+/// How values are converted for a target column
+#[derive(Debug, Clone, PartialEq)]
+enum TargetKind {
+    Int {
+        min: i128,
+        max: i128,
+    },
+    Float,
+    Decimal {
+        integer_digits: usize,
+    },
+    Date,
+    DateTime,
+    Year,
+    Bit,
+    Enum(Vec<String>),
+    Set(Vec<String>),
+    Text {
+        max_chars: Option<usize>,
+        max_bytes: Option<usize>,
+    },
+    Binary {
+        max_bytes: Option<usize>,
+    },
+    Json,
+    /// TIME, spatial types and anything else, passed on as text
+    Other,
+}
+
+impl TargetKind {
+    fn of(column_type: &str) -> Self {
+        let lower = column_type.to_ascii_lowercase();
+        let base = lower.split(['(', ' ']).next().unwrap_or_default();
+        // Arguments keep their case, for ENUM and SET members
+        let args = column_type
+            .split_once('(')
+            .and_then(|(_, rest)| rest.rsplit_once(')'))
+            .map(|(args, _)| args);
+        let numbers: Vec<usize> = args
+            .map(|args| {
+                args.split(',')
+                    .filter_map(|a| a.trim().parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let length = numbers.first().copied();
+        let unsigned = lower.contains("unsigned");
+        let int = |bits: u32| {
+            if unsigned {
+                TargetKind::Int {
+                    min: 0,
+                    max: (1i128 << bits) - 1,
+                }
+            } else {
+                TargetKind::Int {
+                    min: -(1i128 << (bits - 1)),
+                    max: (1i128 << (bits - 1)) - 1,
+                }
+            }
+        };
+        let text = |max_chars: Option<usize>, max_bytes: Option<usize>| TargetKind::Text {
+            max_chars,
+            max_bytes,
+        };
+        match base {
+            "tinyint" | "bool" | "boolean" => int(8),
+            "smallint" => int(16),
+            "mediumint" => int(24),
+            "int" | "integer" => int(32),
+            "bigint" => int(64),
+            "float" | "double" | "real" => TargetKind::Float,
+            "decimal" | "numeric" | "dec" | "fixed" => {
+                let precision = length.unwrap_or(10);
+                let scale = numbers.get(1).copied().unwrap_or(0);
+                TargetKind::Decimal {
+                    integer_digits: precision.saturating_sub(scale),
+                }
+            }
+            "date" => TargetKind::Date,
+            "datetime" | "timestamp" => TargetKind::DateTime,
+            "year" => TargetKind::Year,
+            "bit" => TargetKind::Bit,
+            "enum" => TargetKind::Enum(quoted_members(args.unwrap_or_default())),
+            "set" => TargetKind::Set(quoted_members(args.unwrap_or_default())),
+            "char" | "varchar" => text(length, None),
+            "tinytext" => text(None, Some(255)),
+            "text" => text(None, Some(65_535)),
+            "mediumtext" => text(None, Some(16_777_215)),
+            "longtext" => text(None, None),
+            "binary" | "varbinary" => TargetKind::Binary { max_bytes: length },
+            "tinyblob" => TargetKind::Binary {
+                max_bytes: Some(255),
+            },
+            "blob" => TargetKind::Binary {
+                max_bytes: Some(65_535),
+            },
+            "mediumblob" => TargetKind::Binary {
+                max_bytes: Some(16_777_215),
+            },
+            "longblob" => TargetKind::Binary { max_bytes: None },
+            "json" => TargetKind::Json,
+            _ => TargetKind::Other,
+        }
+    }
+}
+
+/// Members of an ENUM or SET definition (`'a','it''s'`)
+fn quoted_members(args: &str) -> Vec<String> {
+    let mut members = Vec::new();
+    let mut chars = args.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            continue;
+        }
+        let mut member = String::new();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            member.push(c);
+        }
+        members.push(member);
+    }
+    members
+}
+
+/// A table column rows are imported into
+struct TargetColumn {
+    name: String,
+    kind: TargetKind,
+    /// NULL is accepted, which includes AUTO_INCREMENT columns
+    nullable: bool,
+}
+
+impl TargetColumn {
+    fn new(column: &MysqlColumn) -> Self {
+        Self {
+            name: column.name.clone(),
+            kind: TargetKind::of(&column.column_type),
+            nullable: column.nullable || extra_has(column, "auto_increment"),
+        }
+    }
+}
+
+fn extra_has(column: &MysqlColumn, flag: &str) -> bool {
+    column
+        .extra
+        .as_deref()
+        .is_some_and(|extra| extra.to_ascii_lowercase().contains(flag))
+}
+
+/// Virtual and stored generated columns can't be written
+fn is_generated(column: &MysqlColumn) -> bool {
+    extra_has(column, "virtual generated") || extra_has(column, "stored generated")
+}
+
+/// Convert a file value for a column, or explain why it doesn't fit
+fn coerce(value: &ExportValue, column: &TargetColumn) -> Result<ExportValue, String> {
+    let text = match value {
+        ExportValue::Null if column.nullable => return Ok(ExportValue::Null),
+        ExportValue::Null => return Err("cannot be NULL".to_string()),
+        ExportValue::Binary(bytes) => match (&column.kind, String::from_utf8(bytes.clone())) {
+            (TargetKind::Binary { .. }, _) => String::new(),
+            (_, Ok(text)) => text,
+            (_, Err(_)) => return Err("binary data for a text column".to_string()),
+        },
+        value => value.text(),
+    };
+    let trimmed = text.trim();
+    let excerpt: String = trimmed.chars().take(40).collect();
+
+    match &column.kind {
+        TargetKind::Int { min, max } => {
+            let number =
+                parse_integer(trimmed).ok_or_else(|| format!("'{}' is not an integer", excerpt))?;
+            if number < *min || number > *max {
+                return Err(format!("{} is out of range", number));
+            }
+            Ok(ExportValue::Number(number.to_string()))
+        }
+        TargetKind::Float => match trimmed.parse::<f64>() {
+            Ok(number) if number.is_finite() => Ok(ExportValue::Number(trimmed.to_string())),
+            _ => Err(format!("'{}' is not a number", excerpt)),
+        },
+        TargetKind::Decimal { integer_digits } => {
+            // Exponent notation is spelled out, which DECIMAL columns need
+            let plain = match decimal_digits(trimmed) {
+                Some(_) => trimmed.to_string(),
+                None => match trimmed.parse::<f64>() {
+                    Ok(number) if number.is_finite() => number.to_string(),
+                    _ => return Err(format!("'{}' is not a number", excerpt)),
+                },
+            };
+            match decimal_digits(&plain) {
+                Some((digits, _)) if digits <= *integer_digits => Ok(ExportValue::Number(plain)),
+                _ => Err(format!("{} is out of range", excerpt)),
+            }
+        }
+        TargetKind::Date => parse_date(trimmed)
+            .map(|date| ExportValue::Text(date.format("%Y-%m-%d").to_string()))
+            .ok_or_else(|| format!("'{}' is not a date", excerpt)),
+        TargetKind::DateTime => parse_datetime(trimmed)
+            .map(|datetime| ExportValue::Text(datetime.format("%Y-%m-%d %H:%M:%S%.f").to_string()))
+            .ok_or_else(|| format!("'{}' is not a date and time", excerpt)),
+        TargetKind::Year => match parse_integer(trimmed) {
+            Some(year) if (0..=99).contains(&year) || (1901..=2155).contains(&year) => {
+                Ok(ExportValue::Number(year.to_string()))
+            }
+            _ => Err(format!("'{}' is not a year", excerpt)),
+        },
+        TargetKind::Bit => match parse_integer(trimmed) {
+            Some(bits) if bits >= 0 && bits <= u64::MAX as i128 => {
+                Ok(ExportValue::Number(bits.to_string()))
+            }
+            _ => Err(format!("'{}' is not a bit value", excerpt)),
+        },
+        TargetKind::Enum(members) => members
+            .iter()
+            .find(|member| member.eq_ignore_ascii_case(trimmed))
+            .map(|member| ExportValue::Text(member.clone()))
+            .ok_or_else(|| format!("'{}' is not one of {}", excerpt, members.join(", "))),
+        TargetKind::Set(members) => {
+            let mut chosen = Vec::new();
+            for part in trimmed.split(',').filter(|p| !p.trim().is_empty()) {
+                let member = members
+                    .iter()
+                    .find(|member| member.eq_ignore_ascii_case(part.trim()))
+                    .ok_or_else(|| format!("'{}' is not one of {}", part, members.join(", ")))?;
+                chosen.push(member.as_str());
+            }
+            Ok(ExportValue::Text(chosen.join(",")))
+        }
+        TargetKind::Text {
+            max_chars,
+            max_bytes,
+        } => {
+            if max_chars.is_some_and(|max| text.chars().count() > max) {
+                return Err(format!("longer than {} characters", max_chars.unwrap_or(0)));
+            }
+            if max_bytes.is_some_and(|max| text.len() > max) {
+                return Err(format!("longer than {} bytes", max_bytes.unwrap_or(0)));
+            }
+            Ok(ExportValue::Text(text))
+        }
+        TargetKind::Binary { max_bytes } => {
+            let bytes = match value {
+                ExportValue::Binary(bytes) => bytes.clone(),
+                _ => text.into_bytes(),
+            };
+            if max_bytes.is_some_and(|max| bytes.len() > max) {
+                return Err(format!("longer than {} bytes", max_bytes.unwrap_or(0)));
+            }
+            Ok(ExportValue::Binary(bytes))
+        }
+        TargetKind::Json => serde_json::from_str::<JsonValue>(&text)
+            .map(|_| ExportValue::Text(text.clone()))
+            .map_err(|e| format!("invalid JSON: {}", e)),
+        TargetKind::Other => Ok(ExportValue::Text(text)),
+    }
+}
+
+/// Integers, including booleans and whole numbers written as decimals
+fn parse_integer(text: &str) -> Option<i128> {
+    if text.eq_ignore_ascii_case("true") {
+        return Some(1);
+    }
+    if text.eq_ignore_ascii_case("false") {
+        return Some(0);
+    }
+    text.parse::<i128>().ok().or_else(|| {
+        text.parse::<f64>()
+            .ok()
+            .filter(|f| f.fract() == 0.0 && f.abs() < 9.2e18)
+            .map(|f| f as i128)
+    })
+}
+
+/// What the values of a file column have in common
+#[derive(Debug, Clone)]
+struct ColumnProfile {
+    nulls: u64,
+    values: u64,
+    integers: bool,
+    min: i128,
+    max: i128,
+    decimals: bool,
+    integer_digits: usize,
+    scale: usize,
+    floats: bool,
+    dates: bool,
+    datetimes: bool,
+    fractional_seconds: bool,
+    json: bool,
+    binary: bool,
+    max_chars: usize,
+    max_bytes: usize,
+}
+
+impl Default for ColumnProfile {
+    fn default() -> Self {
+        Self {
+            nulls: 0,
+            values: 0,
+            integers: true,
+            min: i128::MAX,
+            max: i128::MIN,
+            decimals: true,
+            integer_digits: 0,
+            scale: 0,
+            floats: true,
+            dates: true,
+            datetimes: true,
+            fractional_seconds: false,
+            json: true,
+            binary: false,
+            max_chars: 0,
+            max_bytes: 0,
+        }
+    }
+}
+
+impl ColumnProfile {
+    fn add(&mut self, value: &ExportValue) {
+        let text = match value {
+            ExportValue::Null => {
+                self.nulls += 1;
+                return;
+            }
+            ExportValue::Binary(bytes) => {
+                self.binary = true;
+                self.values += 1;
+                self.max_bytes = self.max_bytes.max(bytes.len());
+                return;
+            }
+            value => value.text(),
+        };
+        self.values += 1;
+        self.max_chars = self.max_chars.max(text.chars().count());
+        self.max_bytes = self.max_bytes.max(text.len());
+        let trimmed = text.trim();
+
+        if self.integers {
+            match trimmed.parse::<i128>() {
+                Ok(number) => {
+                    self.min = self.min.min(number);
+                    self.max = self.max.max(number);
+                }
+                Err(_) => self.integers = false,
+            }
+        }
+        if self.decimals {
+            match decimal_digits(trimmed) {
+                Some((digits, scale)) => {
+                    self.integer_digits = self.integer_digits.max(digits);
+                    self.scale = self.scale.max(scale);
+                }
+                None => self.decimals = false,
+            }
+        }
+        self.floats &= trimmed.parse::<f64>().is_ok_and(f64::is_finite);
+        self.dates &= ["%Y-%m-%d", "%Y/%m/%d"]
+            .iter()
+            .any(|format| NaiveDate::parse_from_str(trimmed, format).is_ok());
+        if self.datetimes {
+            self.datetimes = parse_datetime(trimmed).is_some();
+            self.fractional_seconds |= trimmed.contains('.');
+        }
+        self.json &= (trimmed.starts_with('{') || trimmed.starts_with('['))
+            && serde_json::from_str::<JsonValue>(trimmed).is_ok();
+    }
+
+    fn column_type(&self) -> String {
+        if self.values == 0 {
+            return "VARCHAR(255)".to_string();
+        }
+        if self.binary {
+            return match self.max_bytes {
+                0..=65_535 => "BLOB",
+                65_536..=16_777_215 => "MEDIUMBLOB",
+                _ => "LONGBLOB",
+            }
+            .to_string();
+        }
+        if self.integers {
+            let fits = |min: i128, max: i128| self.min >= min && self.max <= max;
+            if fits(i8::MIN.into(), i8::MAX.into()) {
+                return "TINYINT".to_string();
+            } else if fits(i16::MIN.into(), i16::MAX.into()) {
+                return "SMALLINT".to_string();
+            } else if fits(i32::MIN.into(), i32::MAX.into()) {
+                return "INT".to_string();
+            } else if fits(i64::MIN.into(), i64::MAX.into()) {
+                return "BIGINT".to_string();
+            } else if fits(0, u64::MAX.into()) {
+                return "BIGINT UNSIGNED".to_string();
+            }
+        }
+        if self.decimals && self.integer_digits + self.scale <= 65 && self.scale <= 30 {
+            return format!(
+                "DECIMAL({},{})",
+                (self.integer_digits + self.scale).max(1),
+                self.scale
+            );
+        }
+        if self.floats {
+            return "DOUBLE".to_string();
+        }
+        if self.dates {
+            return "DATE".to_string();
+        }
+        if self.datetimes {
+            return if self.fractional_seconds {
+                "DATETIME(6)"
+            } else {
+                "DATETIME"
+            }
+            .to_string();
+        }
+        if self.json {
+            return "JSON".to_string();
+        }
+        match (self.max_chars, self.max_bytes) {
+            (0..=255, _) => format!(
+                "VARCHAR({})",
+                self.max_chars.next_power_of_two().clamp(16, 255)
+            ),
+            (_, 0..=65_535) => "TEXT".to_string(),
+            (_, 65_536..=16_777_215) => "MEDIUMTEXT".to_string(),
+            _ => "LONGTEXT".to_string(),
+        }
+    }
+}
+
+/// Infer a table from the records of a file, reading at most `limit` of them
+pub async fn infer_table(req: &ImportFileRequest, limit: Option<u64>) -> AppResult<InferredTable> {
+    let req = req.clone();
+    tokio::task::spawn_blocking(move || read_inferred_table(&req, limit))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+fn read_inferred_table(req: &ImportFileRequest, limit: Option<u64>) -> AppResult<InferredTable> {
+    let mut reader = ImportReader::open(req)?;
+    let mut profiles: Vec<ColumnProfile> = Vec::new();
+    let mut rows = 0;
+    let mut skipped = 0;
+    while limit.map_or(true, |limit| rows < limit) {
+        let Some(record) = reader.next()? else {
+            break;
+        };
+        if skipped < req.skip_rows {
+            skipped += 1;
+            continue;
+        }
+        if let Ok(values) = record.values {
+            profiles.resize_with(reader.columns.len(), ColumnProfile::default);
+            for (profile, value) in profiles.iter_mut().zip(&values) {
+                profile.add(value);
+            }
+            rows += 1;
+        }
+    }
+    profiles.resize_with(reader.columns.len(), ColumnProfile::default);
+
+    let mut names: Vec<String> = Vec::new();
+    for name in &reader.columns {
+        let mut name = req
+            .column_mapping
+            .as_ref()
+            .and_then(|mapping| mapping.get(name))
+            .unwrap_or(name)
+            .clone();
+        if names.iter().any(|n| n.eq_ignore_ascii_case(&name)) {
+            name = format!("{}_{}", name, names.len() + 1);
+        }
+        names.push(name);
+    }
+    let columns: Vec<InferredColumn> = names
+        .into_iter()
+        .zip(&profiles)
+        .map(|(name, profile)| InferredColumn {
+            name,
+            column_type: profile.column_type(),
+            nullable: profile.nulls > 0 || profile.values == 0,
+        })
+        .collect();
+    let definitions: Vec<String> = columns
+        .iter()
+        .map(|c| {
+            format!(
+                "{} {} {}",
+                quote_name(&c.name),
+                c.column_type,
+                if c.nullable { "NULL" } else { "NOT NULL" }
+            )
+        })
+        .collect();
+    let create_sql = format!(
+        "CREATE TABLE {}.{} (\n  {}\n) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4",
+        quote_name(&req.database),
+        quote_name(&req.table),
+        definitions.join(",\n  ")
+    );
+
+    Ok(InferredTable {
+        columns,
+        rows_sampled: rows,
+        create_sql,
+    })
+}
+
+/// Table column of each file column, or None for ignored file columns
+fn map_columns(
+    file_columns: &[String],
+    table: &[MysqlColumn],
+    req: &ImportFileRequest,
+    positional: bool,
+) -> AppResult<Vec<Option<usize>>> {
+    let find = |name: &str| {
+        table
+            .iter()
+            .position(|c| c.name == name)
+            .or_else(|| table.iter().position(|c| c.name.eq_ignore_ascii_case(name)))
+    };
+    if let Some(mapping) = &req.column_mapping {
+        for (source, target) in mapping {
+            if !file_columns.contains(source) {
+                return Err(AppError::Validation(format!(
+                    "Mapped column {} is not in the file",
+                    source
+                )));
+            }
+            match find(target) {
+                None => return Err(AppError::Validation(format!("Unknown column: {}", target))),
+                Some(i) if is_generated(&table[i]) => {
+                    return Err(AppError::Validation(format!(
+                        "Column {} is generated and can't be imported into",
+                        target
+                    )))
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    let mut targets: Vec<Option<usize>> = Vec::with_capacity(file_columns.len());
+    for (i, name) in file_columns.iter().enumerate() {
+        let explicit = req.column_mapping.as_ref().and_then(|m| m.get(name));
+        let target = match explicit {
+            Some(target) => find(target),
+            None if positional => (i < table.len()).then_some(i),
+            None => find(name),
+        };
+        let target = target.filter(|&t| explicit.is_some() || !is_generated(&table[t]));
+        if let Some(t) = target {
+            if targets.contains(&Some(t)) {
+                return Err(AppError::Validation(format!(
+                    "Column {} is mapped more than once",
+                    table[t].name
+                )));
+            }
+        }
+        targets.push(target);
+    }
+
+    if targets.iter().all(Option::is_none) {
+        return Err(AppError::Validation(
+            "No file column matches a column of the table".to_string(),
+        ));
+    }
+    for (i, column) in table.iter().enumerate() {
+        let required = !column.nullable
+            && column.default.is_none()
+            && !extra_has(column, "auto_increment")
+            && !is_generated(column);
+        if required && !targets.contains(&Some(i)) {
+            return Err(AppError::Validation(format!(
+                "Column {} needs a value but no file column maps to it",
+                column.name
+            )));
+        }
+    }
+    Ok(targets)
+}
+
+/// Rows waiting to be sent as one INSERT
+struct InsertBatch {
+    prefix: String,
+    suffix: String,
+    /// Line and value tuple of each row
+    rows: Vec<(u64, String)>,
+    bytes: usize,
+}
+
+impl InsertBatch {
+    fn statement(&self, rows: &[(u64, String)]) -> String {
+        let tuples: Vec<&str> = rows.iter().map(|(_, tuple)| tuple.as_str()).collect();
+        format!("{}{}{}", self.prefix, tuples.join(",\n"), self.suffix)
+    }
+
+    /// Send the batch. When the multi-row INSERT fails the rows are retried
+    /// one at a time to find the bad ones; with `stop` set, retrying ends at
+    /// the first bad row. Returns whether the import may go on.
+    async fn flush(
+        &mut self,
+        conn: &mut MySqlConnection,
+        result: &mut ImportFileResult,
+        stop: bool,
+    ) -> AppResult<bool> {
+        let rows = std::mem::take(&mut self.rows);
+        self.bytes = 0;
+        if rows.is_empty() {
+            return Ok(true);
+        }
+        if conn
+            .execute(sqlx::raw_sql(&self.statement(&rows)))
+            .await
+            .is_ok()
+        {
+            result.imported += rows.len() as u64;
+            return Ok(true);
+        }
+        for row in &rows {
+            match conn
+                .execute(sqlx::raw_sql(&self.statement(std::slice::from_ref(row))))
+                .await
+            {
+                Ok(_) => result.imported += 1,
+                Err(e) => {
+                    record_error(result, row.0, None, e.to_string());
+                    if stop {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn record_error(result: &mut ImportFileResult, line: u64, column: Option<String>, error: String) {
+    result.failed += 1;
+    if result.errors.len() < MAX_IMPORT_ERRORS {
+        result.errors.push(ImportRowError {
+            line,
+            column,
+            error,
+        });
+    }
+}
+
+/// Imports files into MySQL tables
+pub struct MysqlImportService {
+    mysql: MysqlService,
+}
+
+impl MysqlImportService {
+    /// Create an import service on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// Import a file into a table, creating the table first if requested
+    pub async fn import_file(
+        &self,
+        req: &ImportFileRequest,
+        ctx: &JobContext,
+    ) -> AppResult<ImportFileResult> {
+        let start = Instant::now();
+        let mut result = ImportFileResult {
+            rows_read: 0,
+            imported: 0,
+            failed: 0,
+            dry_run: req.dry_run,
+            created_table: false,
+            ignored_columns: Vec::new(),
+            errors: Vec::new(),
+            execution_time_ms: 0,
+        };
+
+        let schema = self
+            .mysql
+            .get_table_schema(&req.database, &req.table)
+            .await?;
+        let mut table = schema.columns;
+        if table.is_empty() && !req.create_table {
+            return Err(AppError::NotFound(format!(
+                "Table not found: {}",
+                req.table
+            )));
+        }
+        // A table created from the file has no keys either
+        if req.insert_mode == SqlInsertMode::Upsert && !schema.indexes.iter().any(|i| i.unique) {
+            return Err(AppError::Validation(format!(
+                "Upsert needs a primary or unique key on {}",
+                req.table
+            )));
+        }
+        if table.is_empty() {
+            ctx.set_message("Inferring the table schema").await;
+            let inferred = infer_table(req, None).await?;
+            if inferred.columns.is_empty() {
+                return Err(AppError::Validation("The file has no columns".to_string()));
+            }
+            if req.dry_run {
+                table = inferred
+                    .columns
+                    .into_iter()
+                    .map(|c| MysqlColumn {
+                        name: c.name,
+                        column_type: c.column_type,
+                        nullable: c.nullable,
+                        key: None,
+                        default: None,
+                        extra: None,
+                        comment: None,
+                    })
+                    .collect();
+            } else {
+                self.mysql
+                    .pool()
+                    .execute(sqlx::raw_sql(&inferred.create_sql))
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                table = self
+                    .mysql
+                    .get_table_schema(&req.database, &req.table)
+                    .await?
+                    .columns;
+            }
+            result.created_table = true;
+        }
+
+        let mut reader = RecordStream::open(req).await?;
+        let column_mapping = map_columns(&reader.columns, &table, req, reader.positional)?;
+        let targets: Vec<TargetColumn> = table.iter().map(TargetColumn::new).collect();
+        let mapped: Vec<&TargetColumn> = column_mapping
+            .iter()
+            .flatten()
+            .map(|&i| &targets[i])
+            .collect();
+        result.ignored_columns = reader
+            .columns
+            .iter()
+            .zip(&column_mapping)
+            .filter(|(_, target)| target.is_none())
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        let column_list: Vec<String> = mapped.iter().map(|c| quote_name(&c.name)).collect();
+        let verb = match req.insert_mode {
+            SqlInsertMode::InsertIgnore => "INSERT IGNORE",
+            SqlInsertMode::Insert | SqlInsertMode::Upsert => "INSERT",
+        };
+        let suffix = match req.insert_mode {
+            SqlInsertMode::Upsert => {
+                let updates: Vec<String> = column_list
+                    .iter()
+                    .map(|c| format!("{} = VALUES({})", c, c))
+                    .collect();
+                format!("\nON DUPLICATE KEY UPDATE {}", updates.join(", "))
+            }
+            _ => String::new(),
+        };
+        let mut batch = InsertBatch {
+            prefix: format!(
+                "{} INTO {}.{} ({}) VALUES\n",
+                verb,
+                quote_name(&req.database),
+                quote_name(&req.table),
+                column_list.join(", ")
+            ),
+            suffix,
+            rows: Vec::new(),
+            bytes: 0,
+        };
+        let batch_size = req.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+        let stop = req.mode == ScriptErrorMode::Stop && !req.dry_run;
+
+        // Rows are written in transactions: one for the whole file when the
+        // first bad row stops the import, otherwise committed every
+        // COMMIT_ROWS rows
+        let mut conn = None;
+        if !req.dry_run {
+            let mut acquired = self
+                .mysql
+                .pool()
+                .acquire()
+                .await
+                .map_err(|e| AppError::Connection(e.to_string()))?;
+            // A failed or cancelled import must not hand back an open
+            // transaction or its session settings; closing the connection
+            // rolls it back. The SQL mode is set outright, like a dump does:
+            // an inherited NO_BACKSLASH_ESCAPES would misread the escaped
+            // literals, and explicit zero ids must not be renumbered
+            acquired.close_on_drop();
+            acquired
+                .execute(sqlx::raw_sql(
+                    "SET SESSION SQL_MODE = 'NO_AUTO_VALUE_ON_ZERO'; BEGIN",
+                ))
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            conn = Some(acquired);
+        }
+        let mut committed = 0;
+        let mut skipped = 0;
+
+        ctx.set_message("Importing").await;
+        let mut keep_going = true;
+        while keep_going {
+            let Some(record) = reader.next().await? else {
+                break;
+            };
+            if skipped < req.skip_rows {
+                skipped += 1;
+                continue;
+            }
+            result.rows_read += 1;
+
+            let tuple = record.values.map_err(|e| (None, e)).and_then(|values| {
+                if values.len() != column_mapping.len() {
+                    return Err((
+                        None,
+                        format!(
+                            "expected {} fields, found {}",
+                            column_mapping.len(),
+                            values.len()
+                        ),
+                    ));
+                }
+                let literals = values
+                    .iter()
+                    .zip(&column_mapping)
+                    .filter_map(|(value, target)| target.map(|t| (value, &targets[t])))
+                    .map(|(value, column)| {
+                        coerce(value, column)
+                            .map(|v| v.sql_literal())
+                            .map_err(|e| (Some(column.name.clone()), e))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(format!("({})", literals.join(", ")))
+            });
+            match tuple {
+                Ok(tuple) => {
+                    batch.bytes += tuple.len();
+                    batch.rows.push((record.line, tuple));
+                }
+                Err((column, error)) => {
+                    record_error(&mut result, record.line, column, error);
+                    keep_going = !stop;
+                }
+            }
+
+            if batch.rows.len() >= batch_size || batch.bytes >= MAX_INSERT_BYTES || !keep_going {
+                match conn.as_deref_mut() {
+                    Some(conn) if keep_going => {
+                        keep_going = batch.flush(conn, &mut result, stop).await?;
+                        if !stop && result.imported - committed >= COMMIT_ROWS {
+                            conn.execute(sqlx::raw_sql("COMMIT; BEGIN"))
+                                .await
+                                .map_err(|e| AppError::Database(e.to_string()))?;
+                            committed = result.imported;
+                        }
+                    }
+                    Some(_) => batch.rows.clear(),
+                    None => {
+                        result.imported += batch.rows.len() as u64;
+                        batch.rows.clear();
+                        batch.bytes = 0;
+                    }
+                }
+            }
+
+            if result.rows_read % 1000 == 0 {
+                ctx.check_cancelled()?;
+                let (processed, total) = reader.progress();
+                ctx.set_progress(processed, total).await;
+                ctx.set_message(format!(
+                    "Read {} rows ({} failed)",
+                    result.rows_read, result.failed
+                ))
+                .await;
+            }
+        }
+
+        match conn.as_deref_mut() {
+            Some(conn) if keep_going => {
+                keep_going = batch.flush(conn, &mut result, stop).await?;
+                let end = if keep_going { "COMMIT" } else { "ROLLBACK" };
+                conn.execute(sqlx::raw_sql(end))
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+            }
+            Some(conn) => {
+                conn.execute(sqlx::raw_sql("ROLLBACK"))
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+            }
+            None => result.imported += batch.rows.len() as u64,
+        }
+        if !keep_going {
+            // Everything was rolled back
+            result.imported = 0;
+        }
+
+        let (processed, total) = reader.progress();
+        result.ignored_columns.extend(reader.extra_keys().await?);
+        ctx.set_progress(total.unwrap_or(processed), total).await;
+        result.execution_time_ms = start.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(column_type: &str, nullable: bool) -> TargetColumn {
+        TargetColumn {
+            name: "c".to_string(),
+            kind: TargetKind::of(column_type),
+            nullable,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_stream_reads_ahead() {
+        let path = std::env::temp_dir().join(format!("import-test-{}.ndjson", std::process::id()));
+        let mut text = String::new();
+        for i in 0..2500 {
+            if i == 2000 {
+                text.push_str("{\"id\": 2000, \"z\": 1}\n");
+            } else {
+                text.push_str(&format!("{{\"id\": {}}}\n", i));
+            }
+        }
+        std::fs::write(&path, &text).unwrap();
+        let req: ImportFileRequest = serde_json::from_value(serde_json::json!({
+            "connection_id": 1,
+            "database": "app",
+            "table": "t",
+            "file_path": path.to_str().unwrap(),
+            "format": null,
+            "delimiter": null,
+            "sheet": null,
+            "null_value": null,
+            "column_mapping": null,
+            "batch_size": null,
+        }))
+        .unwrap();
+
+        let mut stream = RecordStream::open(&req).await.unwrap();
+        assert_eq!(stream.columns, vec!["id"]);
+        let mut ids = Vec::new();
+        while let Some(record) = stream.next().await.unwrap() {
+            ids.push(record.values.unwrap()[0].clone());
+        }
+        assert_eq!(
+            stream.progress(),
+            (text.len() as u64, Some(text.len() as u64))
+        );
+        let extra_keys = stream.extra_keys().await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(ids.len(), 2500);
+        assert_eq!(ids[2499], ExportValue::Number("2499".to_string()));
+        assert_eq!(extra_keys.into_iter().collect::<Vec<_>>(), vec!["z"]);
+    }
+
+    #[test]
+    fn test_coerce() {
+        let text = |s: &str| ExportValue::Text(s.to_string());
+        let tinyint = column("tinyint(3) unsigned", false);
+        assert_eq!(
+            coerce(&text(" 255 "), &tinyint),
+            Ok(ExportValue::Number("255".to_string()))
+        );
+        assert_eq!(
+            coerce(&text("true"), &tinyint),
+            Ok(ExportValue::Number("1".to_string()))
+        );
+        assert!(coerce(&text("256"), &tinyint).is_err());
+        assert!(coerce(&ExportValue::Null, &tinyint).is_err());
+
+        let decimal = column("decimal(5,2)", true);
+        assert!(coerce(&text("123.45"), &decimal).is_ok());
+        assert!(coerce(&text("1234.5"), &decimal).is_err());
+        assert_eq!(coerce(&ExportValue::Null, &decimal), Ok(ExportValue::Null));
+
+        let datetime = column("datetime(6)", true);
+        assert_eq!(
+            coerce(&text("2024-03-01T10:20:30.5Z"), &datetime),
+            Ok(text("2024-03-01 10:20:30.500"))
+        );
+        assert!(coerce(&text("yesterday"), &datetime).is_err());
+
+        let status = column("enum('new','it''s done')", true);
+        assert_eq!(coerce(&text("NEW"), &status), Ok(text("new")));
+        assert_eq!(coerce(&text("it's done"), &status), Ok(text("it's done")));
+        assert!(coerce(&text("old"), &status).is_err());
+
+        assert!(coerce(&text("abcd"), &column("varchar(3)", true)).is_err());
+        assert!(coerce(&text("{\"a\": 1}"), &column("json", true)).is_ok());
+    }
+
+    #[test]
+    fn test_column_profile() {
+        let infer = |values: &[&str]| {
+            let mut profile = ColumnProfile::default();
+            for value in values {
+                profile.add(&if value.is_empty() {
+                    ExportValue::Null
+                } else {
+                    ExportValue::Text(value.to_string())
+                });
+            }
+            profile.column_type()
+        };
+        assert_eq!(infer(&["1", "-5", ""]), "TINYINT");
+        assert_eq!(infer(&["1", "70000"]), "INT");
+        assert_eq!(infer(&["1.5", "-12.25"]), "DECIMAL(4,2)");
+        assert_eq!(infer(&["1e5", "2.5"]), "DOUBLE");
+        assert_eq!(infer(&["2024-01-01", "2024/02/03"]), "DATE");
+        assert_eq!(infer(&["2024-01-01 10:00:00", "2024-01-02"]), "DATETIME");
+        assert_eq!(infer(&["{\"a\": 1}", "[1]"]), "JSON");
+        assert_eq!(infer(&["abc", "12"]), "VARCHAR(16)");
+        assert_eq!(infer(&[]), "VARCHAR(255)");
+    }
+}
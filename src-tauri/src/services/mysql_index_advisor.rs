@@ -0,0 +1,820 @@
+//! Index advisor
+//!
+//! Reports indexes that can be dropped and indexes worth adding:
+//! - unused: never read according to `sys.schema_unused_indexes`, or the
+//!   performance_schema index I/O statistics it is built on
+//! - redundant: duplicates and leftmost prefixes of another index
+//! - missing: columns compared in statement digests that scan whole tables
+//!
+//! Each recommendation carries the DDL to apply it and a size estimate from
+//! information_schema row counts and column widths.
+
+use std::collections::{HashMap, HashSet};
+
+use sqlx::Row;
+
+use crate::db::models::{
+    ForeignKeyInfo, IndexAdvice, IndexAdviceKind, IndexAdvisorReport, IndexAdvisorRequest,
+    IndexInfo, MysqlTable,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::mysql::MysqlService;
+use crate::services::schema_diff::key_parts;
+use crate::services::sql_identifiers::quote_name;
+
+/// Tables smaller than this are cheap to scan; no missing index advice
+const MIN_SCAN_ROWS: i64 = 1000;
+
+/// Full-scan digests looked at for missing indexes
+const DIGEST_LIMIT: u32 = 50;
+
+/// Wider candidates rarely pay for their maintenance cost
+const MAX_CANDIDATE_COLUMNS: usize = 4;
+
+/// Per-entry bytes on top of the key columns (record header, page directory)
+const ENTRY_OVERHEAD: u64 = 8;
+
+/// Uptime below which unused index statistics are likely incomplete
+const SHORT_UPTIME_SECS: u64 = 7 * 24 * 3600;
+
+const MAX_INDEX_NAME_LEN: usize = 64;
+
+/// Indexes, constraints and column widths of one table
+struct TableIndexes {
+    table: MysqlTable,
+    indexes: Vec<IndexInfo>,
+    foreign_keys: Vec<ForeignKeyInfo>,
+    /// Column name and approximate stored width in bytes, in table order
+    columns: Vec<(String, u64)>,
+}
+
+/// Analyzes index usage and statement digests
+pub struct MysqlIndexAdvisorService {
+    mysql: MysqlService,
+}
+
+impl MysqlIndexAdvisorService {
+    /// Create an index advisor on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// Collect unused, redundant and missing index advice
+    pub async fn analyze(&self, req: &IndexAdvisorRequest) -> AppResult<IndexAdvisorReport> {
+        let database = req.database.as_str();
+        let tables = self.load_tables(database, req.table.as_deref()).await?;
+
+        let mut report = IndexAdvisorReport {
+            database: database.to_string(),
+            advice: Vec::new(),
+            uptime_secs: self.uptime_secs().await,
+            usage_source: None,
+            warnings: Vec::new(),
+        };
+
+        for table in &tables {
+            for (index, covered_by) in redundant_indexes(&table.indexes) {
+                report.advice.push(drop_advice(
+                    database,
+                    table,
+                    index,
+                    IndexAdviceKind::Redundant,
+                    format!("Columns are a leftmost prefix of index {}", covered_by.name),
+                    Some(covered_by.name.clone()),
+                ));
+            }
+        }
+
+        if !self.performance_schema_enabled().await {
+            report.warnings.push(
+                "performance_schema is disabled; unused and missing indexes were not checked"
+                    .to_string(),
+            );
+            return Ok(report);
+        }
+
+        let (source, unused) = self.unused_indexes(database).await?;
+        report.usage_source = Some(source.to_string());
+        if report
+            .uptime_secs
+            .is_some_and(|secs| secs < SHORT_UPTIME_SECS)
+        {
+            report.warnings.push(
+                "The server started less than a week ago; rarely used indexes may show as unused"
+                    .to_string(),
+            );
+        }
+        for table in &tables {
+            for index in &table.indexes {
+                let flagged = report
+                    .advice
+                    .iter()
+                    .any(|a| a.table == table.table.name && a.index_name == index.name);
+                if flagged
+                    || index.unique
+                    || index.is_primary
+                    || !unused.contains(&(table.table.name.clone(), index.name.clone()))
+                    || backs_foreign_key(index, &table.indexes, &table.foreign_keys)
+                {
+                    continue;
+                }
+                report.advice.push(drop_advice(
+                    database,
+                    table,
+                    index,
+                    IndexAdviceKind::Unused,
+                    "No reads recorded since the server started".to_string(),
+                    None,
+                ));
+            }
+        }
+
+        self.add_missing_indexes(database, &tables, &mut report)
+            .await?;
+        Ok(report)
+    }
+
+    async fn load_tables(
+        &self,
+        database: &str,
+        only: Option<&str>,
+    ) -> AppResult<Vec<TableIndexes>> {
+        let mut listed = self.mysql.list_tables(database).await?;
+        if let Some(only) = only {
+            listed.retain(|t| t.name == only);
+            if listed.is_empty() {
+                return Err(AppError::NotFound(format!("Table not found: {}", only)));
+            }
+        }
+
+        let mut columns = self.column_widths(database).await?;
+        let mut tables = Vec::with_capacity(listed.len());
+        for table in listed {
+            tables.push(TableIndexes {
+                indexes: self.mysql.list_indexes(database, &table.name).await?,
+                foreign_keys: self.mysql.list_foreign_keys(database, &table.name).await?,
+                columns: columns.remove(&table.name).unwrap_or_default(),
+                table,
+            });
+        }
+        Ok(tables)
+    }
+
+    /// Approximate stored width of every column in the database
+    async fn column_widths(
+        &self,
+        database: &str,
+    ) -> AppResult<HashMap<String, Vec<(String, u64)>>> {
+        let rows = sqlx::query(
+            "SELECT TABLE_NAME, COLUMN_NAME, DATA_TYPE, CHARACTER_OCTET_LENGTH, \
+             NUMERIC_PRECISION, DATETIME_PRECISION \
+             FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = ? \
+             ORDER BY TABLE_NAME, ORDINAL_POSITION",
+        )
+        .bind(database)
+        .fetch_all(self.mysql.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut widths: HashMap<String, Vec<(String, u64)>> = HashMap::new();
+        for row in &rows {
+            let width = column_width(
+                &MysqlService::get_string_from_row(row, "DATA_TYPE"),
+                row.try_get::<Option<u64>, _>("CHARACTER_OCTET_LENGTH")
+                    .ok()
+                    .flatten(),
+                row.try_get::<Option<u64>, _>("NUMERIC_PRECISION")
+                    .ok()
+                    .flatten(),
+                row.try_get::<Option<u64>, _>("DATETIME_PRECISION")
+                    .ok()
+                    .flatten(),
+            );
+            widths
+                .entry(MysqlService::get_string_from_row(row, "TABLE_NAME"))
+                .or_default()
+                .push((MysqlService::get_string_from_row(row, "COLUMN_NAME"), width));
+        }
+        Ok(widths)
+    }
+
+    async fn uptime_secs(&self) -> Option<u64> {
+        let row = sqlx::query("SHOW GLOBAL STATUS LIKE 'Uptime'")
+            .fetch_one(self.mysql.pool())
+            .await
+            .ok()?;
+        MysqlService::get_string_from_row(&row, "Value")
+            .parse()
+            .ok()
+    }
+
+    async fn performance_schema_enabled(&self) -> bool {
+        sqlx::query_scalar::<_, i64>("SELECT @@performance_schema + 0")
+            .fetch_one(self.mysql.pool())
+            .await
+            .is_ok_and(|enabled| enabled != 0)
+    }
+
+    /// (table, index) pairs without reads, from sys when installed
+    async fn unused_indexes(
+        &self,
+        database: &str,
+    ) -> AppResult<(&'static str, HashSet<(String, String)>)> {
+        let from_sys = sqlx::query(
+            "SELECT object_name AS table_name, index_name \
+             FROM sys.schema_unused_indexes WHERE object_schema = ?",
+        )
+        .bind(database)
+        .fetch_all(self.mysql.pool())
+        .await;
+
+        let (source, rows) = match from_sys {
+            Ok(rows) => ("sys", rows),
+            Err(_) => (
+                "performance_schema",
+                sqlx::query(
+                    "SELECT OBJECT_NAME AS table_name, INDEX_NAME AS index_name \
+                     FROM performance_schema.table_io_waits_summary_by_index_usage \
+                     WHERE OBJECT_SCHEMA = ? AND INDEX_NAME IS NOT NULL \
+                     AND INDEX_NAME <> 'PRIMARY' AND COUNT_STAR = 0",
+                )
+                .bind(database)
+                .fetch_all(self.mysql.pool())
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?,
+            ),
+        };
+
+        let unused = rows
+            .iter()
+            .map(|row| {
+                (
+                    MysqlService::get_string_from_row(row, "table_name"),
+                    MysqlService::get_string_from_row(row, "index_name"),
+                )
+            })
+            .collect();
+        Ok((source, unused))
+    }
+
+    /// Suggest indexes for columns compared by digests that used no index
+    async fn add_missing_indexes(
+        &self,
+        database: &str,
+        tables: &[TableIndexes],
+        report: &mut IndexAdvisorReport,
+    ) -> AppResult<()> {
+        let rows = sqlx::query(
+            "SELECT DIGEST, DIGEST_TEXT, SUM_NO_INDEX_USED \
+             FROM performance_schema.events_statements_summary_by_digest \
+             WHERE SCHEMA_NAME = ? AND SUM_NO_INDEX_USED > 0 AND DIGEST_TEXT IS NOT NULL \
+             ORDER BY SUM_NO_INDEX_USED DESC LIMIT ?",
+        )
+        .bind(database)
+        .bind(DIGEST_LIMIT)
+        .fetch_all(self.mysql.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let table_columns: HashMap<&str, Vec<&str>> = tables
+            .iter()
+            .map(|t| {
+                let columns = t.columns.iter().map(|(name, _)| name.as_str()).collect();
+                (t.table.name.as_str(), columns)
+            })
+            .collect();
+
+        for row in &rows {
+            let digest = MysqlService::get_string_from_row(row, "DIGEST");
+            let digest_text = MysqlService::get_string_from_row(row, "DIGEST_TEXT");
+            let full_scans = row
+                .try_get::<u64, _>("SUM_NO_INDEX_USED")
+                .unwrap_or_default();
+
+            for (table_name, columns) in candidate_indexes(&digest_text, database, &table_columns) {
+                let Some(table) = tables.iter().find(|t| t.table.name == table_name) else {
+                    continue;
+                };
+                // Already served by an index, or a unique key finds at most one row
+                if table.table.row_count < MIN_SCAN_ROWS
+                    || table.indexes.iter().any(|i| {
+                        has_prefix(&i.columns, &columns)
+                            || (i.unique
+                                && i.columns
+                                    .iter()
+                                    .all(|c| columns.iter().any(|k| k.eq_ignore_ascii_case(c))))
+                    })
+                {
+                    continue;
+                }
+                if let Some(existing) = report.advice.iter_mut().find(|a| {
+                    a.kind == IndexAdviceKind::Missing
+                        && a.table == table_name
+                        && a.columns == columns
+                }) {
+                    *existing.full_scans.get_or_insert(0) += full_scans;
+                    continue;
+                }
+
+                let index_name = index_name(&table_name, &columns, &table.indexes);
+                let width = entry_width(table, &columns);
+                report.advice.push(IndexAdvice {
+                    kind: IndexAdviceKind::Missing,
+                    statement: format!(
+                        "CREATE INDEX {} ON {}.{} ({})",
+                        quote_name(&index_name),
+                        quote_name(database),
+                        quote_name(&table_name),
+                        columns
+                            .iter()
+                            .map(|c| quote_name(c))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    reason: "Statement compares these columns but scans the table".to_string(),
+                    estimated_size_bytes: Some(
+                        table.table.row_count.max(0).saturating_mul(width as i64),
+                    ),
+                    covered_by: None,
+                    digest: Some(digest.clone()),
+                    digest_text: Some(digest_text.clone()),
+                    full_scans: Some(full_scans),
+                    table: table_name,
+                    index_name,
+                    columns,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn drop_advice(
+    database: &str,
+    table: &TableIndexes,
+    index: &IndexInfo,
+    kind: IndexAdviceKind,
+    reason: String,
+    covered_by: Option<String>,
+) -> IndexAdvice {
+    IndexAdvice {
+        kind,
+        table: table.table.name.clone(),
+        index_name: index.name.clone(),
+        columns: index.columns.clone(),
+        reason,
+        statement: format!(
+            "DROP INDEX {} ON {}.{}",
+            quote_name(&index.name),
+            quote_name(database),
+            quote_name(&table.table.name)
+        ),
+        estimated_size_bytes: Some(index_size(table, index)),
+        covered_by,
+        digest: None,
+        digest_text: None,
+        full_scans: None,
+    }
+}
+
+/// Indexes made unnecessary by another index, paired with that index.
+/// An index is redundant when its key parts equal, or are a leftmost prefix
+/// of, the key parts of another index of the same type; a column prefix
+/// only matches the same prefix length. Unique indexes are
+/// only redundant to an identical primary or unique key, since they
+/// enforce a constraint.
+fn redundant_indexes(indexes: &[IndexInfo]) -> Vec<(&IndexInfo, &IndexInfo)> {
+    let mut redundant = Vec::new();
+    for (i, index) in indexes.iter().enumerate() {
+        if index.is_primary {
+            continue;
+        }
+        let parts = key_parts(index);
+        let covered_by = indexes.iter().enumerate().find(|(j, other)| {
+            if i == *j || other.index_type != index.index_type {
+                return false;
+            }
+            let other_parts = key_parts(other);
+            if same_columns(&parts, &other_parts) {
+                match (index.unique, other.unique || other.is_primary) {
+                    (false, true) => true,
+                    (true, false) => false,
+                    // Keep the primary key, otherwise the first by name
+                    _ => other.is_primary || index.name > other.name,
+                }
+            } else {
+                !index.unique && index.index_type == "BTREE" && has_prefix(&other_parts, &parts)
+            }
+        });
+        if let Some((_, other)) = covered_by {
+            redundant.push((index, other));
+        }
+    }
+    redundant
+}
+
+/// Whether dropping `index` would leave a foreign key without an index
+fn backs_foreign_key(
+    index: &IndexInfo,
+    indexes: &[IndexInfo],
+    foreign_keys: &[ForeignKeyInfo],
+) -> bool {
+    foreign_keys.iter().any(|fk| {
+        has_prefix(&index.columns, &fk.columns)
+            && !indexes
+                .iter()
+                .any(|other| other.name != index.name && has_prefix(&other.columns, &fk.columns))
+    })
+}
+
+fn same_columns(a: &[String], b: &[String]) -> bool {
+    a.len() == b.len() && has_prefix(a, b)
+}
+
+/// Whether `columns` starts with all of `prefix`
+fn has_prefix(columns: &[String], prefix: &[String]) -> bool {
+    !prefix.is_empty()
+        && columns.len() >= prefix.len()
+        && columns
+            .iter()
+            .zip(prefix)
+            .all(|(a, b)| a.eq_ignore_ascii_case(b))
+}
+
+/// Estimated bytes of an existing index. InnoDB reports one index_length
+/// for all secondary indexes, which is shared by their entry widths.
+fn index_size(table: &TableIndexes, index: &IndexInfo) -> i64 {
+    let width = entry_width(table, &index.columns);
+    let secondary: u64 = table
+        .indexes
+        .iter()
+        .filter(|i| !i.is_primary)
+        .map(|i| entry_width(table, &i.columns))
+        .sum();
+    if table.table.index_size > 0 && secondary > 0 {
+        (table.table.index_size as f64 * width as f64 / secondary as f64) as i64
+    } else {
+        table.table.row_count.max(0).saturating_mul(width as i64)
+    }
+}
+
+/// Bytes per secondary index entry: key columns plus the primary key
+fn entry_width(table: &TableIndexes, columns: &[String]) -> u64 {
+    let width_of = |column: &String| {
+        table
+            .columns
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(column))
+            .map(|(_, width)| *width)
+            .unwrap_or(8)
+    };
+    let primary: u64 = table
+        .indexes
+        .iter()
+        .find(|i| i.is_primary)
+        .map(|pk| pk.columns.iter().map(width_of).sum())
+        .unwrap_or(6);
+    columns.iter().map(width_of).sum::<u64>() + primary + ENTRY_OVERHEAD
+}
+
+/// Approximate stored width of a column; variable-length strings are
+/// assumed half full
+fn column_width(
+    data_type: &str,
+    octet_length: Option<u64>,
+    numeric_precision: Option<u64>,
+    datetime_precision: Option<u64>,
+) -> u64 {
+    let fraction = datetime_precision.unwrap_or(0).div_ceil(2);
+    match data_type.to_ascii_lowercase().as_str() {
+        "tinyint" | "year" => 1,
+        "smallint" | "enum" => 2,
+        "mediumint" | "date" => 3,
+        "int" | "integer" | "float" => 4,
+        "bigint" | "double" | "real" | "set" => 8,
+        "decimal" | "numeric" => numeric_precision.unwrap_or(10) / 2 + 1,
+        "bit" => numeric_precision.unwrap_or(1).div_ceil(8),
+        "time" => 3 + fraction,
+        "timestamp" => 4 + fraction,
+        "datetime" => 5 + fraction,
+        "char" | "binary" => octet_length.unwrap_or(1).min(767),
+        // Longer values are indexed by prefix
+        _ => octet_length.map_or(8, |len| len.min(767) / 2 + 2),
+    }
+}
+
+/// Name for a new index that does not clash with the existing ones
+fn index_name(table: &str, columns: &[String], existing: &[IndexInfo]) -> String {
+    let base: String = format!("idx_{}_{}", table, columns.join("_"))
+        .chars()
+        .take(MAX_INDEX_NAME_LEN - 3)
+        .collect();
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while existing.iter().any(|i| i.name.eq_ignore_ascii_case(&name)) {
+        name = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    name
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Backquoted identifier; digests quote every identifier
+    Ident(String),
+    /// Keyword or function name, uppercased
+    Word(String),
+    /// Placeholder, literal or number
+    Value,
+    Symbol(String),
+}
+
+fn tokenize(sql: &str) -> Vec<Token> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '`' {
+            let mut ident = String::new();
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == '`' {
+                    if chars.get(i + 1) == Some(&'`') {
+                        ident.push('`');
+                        i += 2;
+                        continue;
+                    }
+                    break;
+                }
+                ident.push(chars[i]);
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token::Ident(ident));
+        } else if c == '\'' || c == '"' {
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i += 1;
+            tokens.push(Token::Value);
+        } else if c == '?' || c.is_ascii_digit() {
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Value);
+        } else if c.is_alphanumeric() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+            {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            tokens.push(Token::Word(word.to_ascii_uppercase()));
+        } else if "<>=!".contains(c) {
+            let start = i;
+            while i < chars.len() && "<>=!".contains(chars[i]) {
+                i += 1;
+            }
+            tokens.push(Token::Symbol(chars[start..i].iter().collect()));
+        } else {
+            tokens.push(Token::Symbol(c.to_string()));
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// Read `ident [. ident [. ident]]` at `i`, returning the parts and the
+/// index after them
+fn qualified_name(tokens: &[Token], mut i: usize) -> Option<(Vec<String>, usize)> {
+    let mut parts = Vec::new();
+    loop {
+        let Some(Token::Ident(name)) = tokens.get(i) else {
+            return None;
+        };
+        parts.push(name.clone());
+        i += 1;
+        if parts.len() < 3 && tokens.get(i) == Some(&Token::Symbol(".".to_string())) {
+            i += 1;
+        } else {
+            return Some((parts, i));
+        }
+    }
+}
+
+/// Columns a digest compares, grouped into one index candidate per table:
+/// equality columns first, then one range column
+fn candidate_indexes(
+    digest_text: &str,
+    database: &str,
+    table_columns: &HashMap<&str, Vec<&str>>,
+) -> Vec<(String, Vec<String>)> {
+    let tokens = tokenize(digest_text);
+    let is_word = |i: usize, word: &str| matches!(tokens.get(i), Some(Token::Word(w)) if w == word);
+
+    // Table references with their aliases
+    let mut refs: Vec<(String, Option<String>)> = Vec::new();
+    for i in 0..tokens.len() {
+        if !(is_word(i, "FROM") || is_word(i, "JOIN") || is_word(i, "UPDATE")) {
+            continue;
+        }
+        let mut next = i + 1;
+        while let Some((parts, after)) = qualified_name(&tokens, next) {
+            next = after;
+            if is_word(next, "AS") {
+                next += 1;
+            }
+            let alias = match tokens.get(next) {
+                Some(Token::Ident(alias)) => {
+                    next += 1;
+                    Some(alias.clone())
+                }
+                _ => None,
+            };
+            let in_database = parts.len() == 1 || parts[0] == database;
+            let table = parts.last().cloned().unwrap_or_default();
+            if in_database && table_columns.contains_key(table.as_str()) {
+                refs.push((table, alias));
+            }
+            // FROM a, b
+            if is_word(i, "FROM") && tokens.get(next) == Some(&Token::Symbol(",".to_string())) {
+                next += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    let resolve = |parts: &[String]| -> Option<(String, String)> {
+        let column = parts.last()?;
+        let has_column = |table: &str| {
+            table_columns
+                .get(table)
+                .is_some_and(|columns| columns.iter().any(|c| c.eq_ignore_ascii_case(column)))
+        };
+        let matches: Vec<&String> = match parts.len() {
+            1 => refs
+                .iter()
+                .map(|(t, _)| t)
+                .filter(|t| has_column(t))
+                .collect(),
+            _ => {
+                let qualifier = &parts[parts.len() - 2];
+                refs.iter()
+                    .filter(|(t, alias)| alias.as_ref().unwrap_or(t) == qualifier)
+                    .map(|(t, _)| t)
+                    .filter(|t| has_column(t))
+                    .collect()
+            }
+        };
+        match matches.as_slice() {
+            [table] => Some(((*table).clone(), column.clone())),
+            _ => None,
+        }
+    };
+
+    let mut equality: Vec<(String, String)> = Vec::new();
+    let mut range: Vec<(String, String)> = Vec::new();
+    let mut in_predicate = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Token::Word(word) = &tokens[i] {
+            match word.as_str() {
+                "WHERE" | "ON" => in_predicate = true,
+                "SELECT" | "FROM" | "JOIN" | "GROUP" | "ORDER" | "LIMIT" | "HAVING" | "UNION"
+                | "SET" => in_predicate = false,
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+        let Some((left, after)) = qualified_name(&tokens, i).filter(|_| in_predicate) else {
+            i += 1;
+            continue;
+        };
+        i = after;
+        let Some(column) = resolve(&left) else {
+            continue;
+        };
+        match tokens.get(after) {
+            Some(Token::Symbol(op)) if op == "=" || op == "<=>" => {
+                if let Some((right, next)) = qualified_name(&tokens, after + 1) {
+                    // Join condition: either side may be the inner table
+                    if let Some(other) = resolve(&right) {
+                        equality.push(other);
+                    }
+                    i = next;
+                }
+                equality.push(column);
+            }
+            Some(Token::Symbol(op)) if matches!(op.as_str(), "<" | ">" | "<=" | ">=") => {
+                range.push(column)
+            }
+            Some(Token::Word(word)) if word == "IN" || word == "IS" => equality.push(column),
+            Some(Token::Word(word)) if word == "BETWEEN" || word == "LIKE" => range.push(column),
+            _ => {}
+        }
+    }
+
+    let mut candidates: Vec<(String, Vec<String>)> = Vec::new();
+    for (table, column) in equality.into_iter().chain(range) {
+        let position = match candidates.iter().position(|(t, _)| *t == table) {
+            Some(position) => position,
+            None => {
+                candidates.push((table, Vec::new()));
+                candidates.len() - 1
+            }
+        };
+        let columns = &mut candidates[position].1;
+        if columns.len() < MAX_CANDIDATE_COLUMNS && !columns.contains(&column) {
+            columns.push(column);
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(name: &str, columns: &[&str], unique: bool) -> IndexInfo {
+        IndexInfo {
+            name: name.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            sub_parts: vec![None; columns.len()],
+            expressions: vec![None; columns.len()],
+            unique: unique || name == "PRIMARY",
+            index_type: "BTREE".to_string(),
+            is_primary: name == "PRIMARY",
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn finds_redundant_indexes() {
+        let indexes = vec![
+            index("PRIMARY", &["id"], true),
+            index("idx_id", &["id"], false),
+            index("idx_customer", &["customer_id"], false),
+            index("idx_customer_status", &["customer_id", "status"], false),
+            index("uq_email", &["email"], true),
+            index("idx_email", &["email"], false),
+            index("uq_code", &["code"], true),
+            index("uq_code_region", &["code", "region"], true),
+            IndexInfo {
+                sub_parts: vec![Some(10)],
+                ..index("idx_name_prefix", &["name"], false)
+            },
+            index("idx_name_region", &["name", "region"], false),
+        ];
+
+        let redundant: Vec<(&str, &str)> = redundant_indexes(&indexes)
+            .into_iter()
+            .map(|(index, by)| (index.name.as_str(), by.name.as_str()))
+            .collect();
+        assert_eq!(
+            redundant,
+            vec![
+                ("idx_id", "PRIMARY"),
+                ("idx_customer", "idx_customer_status"),
+                ("idx_email", "uq_email"),
+            ]
+        );
+    }
+
+    #[test]
+    fn suggests_columns_from_digest() {
+        let table_columns = HashMap::from([
+            ("orders", vec!["id", "customer_id", "status", "created_at"]),
+            ("customers", vec!["id", "name", "region"]),
+        ]);
+        let digest = "SELECT `o` . `id` FROM `orders` `o` JOIN `customers` AS `c` \
+                      ON `o` . `customer_id` = `c` . `id` WHERE `c` . `region` = ? \
+                      AND `status` IN (...) AND `created_at` >= ? ORDER BY `created_at`";
+
+        let candidates = candidate_indexes(digest, "shop", &table_columns);
+        assert_eq!(
+            candidates,
+            vec![
+                (
+                    "customers".to_string(),
+                    vec!["id".to_string(), "region".to_string()]
+                ),
+                (
+                    "orders".to_string(),
+                    vec![
+                        "customer_id".to_string(),
+                        "status".to_string(),
+                        "created_at".to_string()
+                    ]
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,470 @@
+//! InnoDB lock waits and deadlocks
+//!
+//! Builds a graph of waiting and blocking transactions from
+//! `performance_schema.data_lock_waits` (MySQL 8.0), falling back to
+//! `sys.innodb_lock_waits` on older servers, and parses the LATEST
+//! DETECTED DEADLOCK section of `SHOW ENGINE INNODB STATUS`.
+
+use std::collections::HashMap;
+
+use sqlx::mysql::MySqlRow;
+use sqlx::Row;
+
+use crate::db::models::{
+    DeadlockLock, DeadlockReport, DeadlockTransaction, LockGraph, LockTransaction, LockWait,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::mysql::MysqlService;
+
+/// Inspects InnoDB locking
+pub struct MysqlLockService {
+    mysql: MysqlService,
+}
+
+impl MysqlLockService {
+    /// Create a lock service on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// Current lock waits with the transactions on both ends
+    pub async fn lock_graph(&self) -> AppResult<LockGraph> {
+        let (source, waits, last_statements) = match self.performance_schema_waits().await {
+            Ok((waits, last_statements)) => ("performance_schema", waits, last_statements),
+            Err(_) => ("sys", self.sys_waits().await?, HashMap::new()),
+        };
+
+        let processes: HashMap<u64, _> = self
+            .mysql
+            .get_process_list()
+            .await?
+            .into_iter()
+            .map(|p| (p.id, p))
+            .collect();
+        let mut transactions: Vec<LockTransaction> = self
+            .innodb_transactions()
+            .await?
+            .into_iter()
+            .filter(|trx| {
+                waits
+                    .iter()
+                    .any(|w| w.waiting_trx_id == trx.trx_id || w.blocking_trx_id == trx.trx_id)
+            })
+            .collect();
+        for trx in &mut transactions {
+            if let Some(process) = trx.process_id.and_then(|id| processes.get(&id)) {
+                trx.user = Some(process.user.clone());
+                trx.host = Some(process.host.clone());
+                trx.db = process.db.clone();
+            }
+            if trx.query.is_none() {
+                trx.query = last_statements.get(&trx.trx_id).cloned();
+            }
+        }
+
+        Ok(LockGraph {
+            source: source.to_string(),
+            root_blockers: root_blockers(&waits),
+            transactions,
+            waits,
+            collected_at: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Kill the connection of a transaction that blocks others. The process
+    /// must still be blocking, so a recycled id is not killed by mistake.
+    pub async fn kill_blocker(&self, process_id: u64) -> AppResult<()> {
+        let graph = self.lock_graph().await?;
+        let blocking = graph.transactions.iter().any(|trx| {
+            trx.process_id == Some(process_id)
+                && graph.waits.iter().any(|w| w.blocking_trx_id == trx.trx_id)
+        });
+        if !blocking {
+            return Err(AppError::Validation(format!(
+                "Process {} is not blocking any transaction",
+                process_id
+            )));
+        }
+        self.mysql.kill_process(process_id).await
+    }
+
+    /// Most recent deadlock, None when there was none since startup
+    pub async fn latest_deadlock(&self) -> AppResult<Option<DeadlockReport>> {
+        let row = sqlx::query("SHOW ENGINE INNODB STATUS")
+            .fetch_one(self.mysql.pool())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let status = MysqlService::get_string_from_row(&row, "Status");
+        Ok(parse_latest_deadlock(&status))
+    }
+
+    /// Waits from performance_schema, plus the last statement of each
+    /// blocking transaction by trx id
+    async fn performance_schema_waits(
+        &self,
+    ) -> AppResult<(Vec<LockWait>, HashMap<String, String>)> {
+        let rows = sqlx::query(
+            "SELECT CAST(w.REQUESTING_ENGINE_TRANSACTION_ID AS CHAR) AS waiting_trx_id, \
+             CAST(w.BLOCKING_ENGINE_TRANSACTION_ID AS CHAR) AS blocking_trx_id, \
+             rl.OBJECT_SCHEMA AS object_schema, rl.OBJECT_NAME AS object_name, \
+             rl.INDEX_NAME AS index_name, rl.LOCK_TYPE AS lock_type, \
+             rl.LOCK_MODE AS waiting_lock_mode, bl.LOCK_MODE AS blocking_lock_mode, \
+             rl.LOCK_DATA AS lock_data, \
+             (SELECT s.SQL_TEXT FROM performance_schema.events_statements_current s \
+              WHERE s.THREAD_ID = w.BLOCKING_THREAD_ID ORDER BY s.EVENT_ID DESC LIMIT 1) \
+             AS blocking_statement \
+             FROM performance_schema.data_lock_waits w \
+             JOIN performance_schema.data_locks rl \
+             ON rl.ENGINE_LOCK_ID = w.REQUESTING_ENGINE_LOCK_ID \
+             JOIN performance_schema.data_locks bl \
+             ON bl.ENGINE_LOCK_ID = w.BLOCKING_ENGINE_LOCK_ID",
+        )
+        .fetch_all(self.mysql.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut last_statements = HashMap::new();
+        let waits = rows
+            .iter()
+            .map(|row| {
+                let wait = lock_wait_from_row(row);
+                if let Some(statement) =
+                    MysqlService::get_optional_string_from_row(row, "blocking_statement")
+                {
+                    last_statements.insert(wait.blocking_trx_id.clone(), statement);
+                }
+                LockWait {
+                    lock_data: MysqlService::get_optional_string_from_row(row, "lock_data"),
+                    ..wait
+                }
+            })
+            .collect();
+        Ok((waits, last_statements))
+    }
+
+    async fn sys_waits(&self) -> AppResult<Vec<LockWait>> {
+        let rows = sqlx::query(
+            "SELECT waiting_trx_id, blocking_trx_id, locked_table, \
+             locked_index AS index_name, locked_type AS lock_type, \
+             waiting_lock_mode, blocking_lock_mode \
+             FROM sys.innodb_lock_waits",
+        )
+        .fetch_all(self.mysql.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                // locked_table reads `schema`.`table`
+                let table = MysqlService::get_optional_string_from_row(row, "locked_table")
+                    .map(|t| t.replace('`', ""));
+                let (schema, name) = match table.as_deref().and_then(|t| t.split_once('.')) {
+                    Some((schema, name)) => (Some(schema.to_string()), Some(name.to_string())),
+                    None => (None, table),
+                };
+                LockWait {
+                    object_schema: schema,
+                    object_name: name,
+                    ..lock_wait_from_row(row)
+                }
+            })
+            .collect())
+    }
+
+    async fn innodb_transactions(&self) -> AppResult<Vec<LockTransaction>> {
+        let rows = sqlx::query(
+            "SELECT CAST(trx_id AS CHAR) AS trx_id, trx_state, trx_mysql_thread_id, trx_query, \
+             CAST(trx_started AS CHAR) AS trx_started, \
+             TIMESTAMPDIFF(SECOND, trx_started, NOW()) AS age_secs, \
+             TIMESTAMPDIFF(SECOND, trx_wait_started, NOW()) AS wait_secs, \
+             trx_rows_locked, trx_rows_modified \
+             FROM information_schema.INNODB_TRX",
+        )
+        .fetch_all(self.mysql.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| LockTransaction {
+                trx_id: MysqlService::get_string_from_row(row, "trx_id"),
+                process_id: row.try_get::<u64, _>("trx_mysql_thread_id").ok(),
+                state: MysqlService::get_optional_string_from_row(row, "trx_state"),
+                query: MysqlService::get_optional_string_from_row(row, "trx_query"),
+                started: MysqlService::get_optional_string_from_row(row, "trx_started"),
+                age_secs: row.try_get::<Option<i64>, _>("age_secs").ok().flatten(),
+                wait_secs: row.try_get::<Option<i64>, _>("wait_secs").ok().flatten(),
+                rows_locked: row.try_get::<u64, _>("trx_rows_locked").ok(),
+                rows_modified: row.try_get::<u64, _>("trx_rows_modified").ok(),
+                ..Default::default()
+            })
+            .collect())
+    }
+}
+
+fn lock_wait_from_row(row: &MySqlRow) -> LockWait {
+    LockWait {
+        waiting_trx_id: MysqlService::get_string_from_row(row, "waiting_trx_id"),
+        blocking_trx_id: MysqlService::get_string_from_row(row, "blocking_trx_id"),
+        object_schema: MysqlService::get_optional_string_from_row(row, "object_schema"),
+        object_name: MysqlService::get_optional_string_from_row(row, "object_name"),
+        index_name: MysqlService::get_optional_string_from_row(row, "index_name"),
+        lock_type: MysqlService::get_optional_string_from_row(row, "lock_type"),
+        waiting_lock_mode: MysqlService::get_optional_string_from_row(row, "waiting_lock_mode"),
+        blocking_lock_mode: MysqlService::get_optional_string_from_row(row, "blocking_lock_mode"),
+        lock_data: None,
+    }
+}
+
+/// Blocking transactions that do not wait on anything themselves
+fn root_blockers(waits: &[LockWait]) -> Vec<String> {
+    let mut roots: Vec<String> = Vec::new();
+    for wait in waits {
+        let waiting = waits
+            .iter()
+            .any(|w| w.waiting_trx_id == wait.blocking_trx_id);
+        if !waiting && !roots.contains(&wait.blocking_trx_id) {
+            roots.push(wait.blocking_trx_id.clone());
+        }
+    }
+    roots
+}
+
+/// Lines of the LATEST DETECTED DEADLOCK section, without its header
+fn deadlock_section(status: &str) -> Option<Vec<&str>> {
+    let lines: Vec<&str> = status.lines().collect();
+    let is_rule = |line: &str| line.len() > 3 && line.chars().all(|c| c == '-');
+    let start = lines
+        .iter()
+        .position(|line| line.trim() == "LATEST DETECTED DEADLOCK")?
+        + 2;
+
+    let mut end = lines.len();
+    for i in start..lines.len() {
+        // Sections start with a title between two dashed rules
+        if is_rule(lines[i])
+            && lines.get(i + 2).is_some_and(|line| is_rule(line))
+            && lines.get(i + 1).is_some_and(|line| !line.trim().is_empty())
+        {
+            end = i;
+            break;
+        }
+    }
+    lines.get(start..end).map(|section| section.to_vec())
+}
+
+fn parse_latest_deadlock(status: &str) -> Option<DeadlockReport> {
+    let section = deadlock_section(status)?;
+
+    #[derive(PartialEq)]
+    enum Part {
+        Header,
+        Query,
+        Holds,
+        WaitsFor,
+    }
+
+    let mut report = DeadlockReport {
+        detected_at: section
+            .iter()
+            .find(|line| !line.trim().is_empty())
+            .map(|line| {
+                line.split_whitespace()
+                    .take(2)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }),
+        transactions: Vec::new(),
+        raw: section.join("\n").trim().to_string(),
+    };
+    let mut part = Part::Header;
+    let mut query: Vec<&str> = Vec::new();
+
+    for line in &section {
+        let trimmed = line.trim();
+        if let Some(marker) = trimmed.strip_prefix("*** ") {
+            if let Some(trx) = report.transactions.last_mut() {
+                if !query.is_empty() {
+                    trx.query = Some(query.join("\n"));
+                    query.clear();
+                }
+            }
+            if let Some(number) = marker.strip_prefix("WE ROLL BACK TRANSACTION (") {
+                let number = number.trim_end_matches(')').parse().ok();
+                for trx in &mut report.transactions {
+                    trx.rolled_back = Some(trx.number) == number;
+                }
+                continue;
+            }
+            let number = marker
+                .strip_prefix('(')
+                .and_then(|rest| rest.split_once(')'))
+                .and_then(|(number, _)| number.parse::<u32>().ok());
+            if marker.ends_with("TRANSACTION:") {
+                report.transactions.push(DeadlockTransaction {
+                    number: number.unwrap_or(report.transactions.len() as u32 + 1),
+                    ..Default::default()
+                });
+                part = Part::Header;
+            } else if marker.contains("HOLDS THE LOCK") {
+                part = Part::Holds;
+            } else if marker.contains("WAITING FOR THIS LOCK") {
+                part = Part::WaitsFor;
+            }
+            continue;
+        }
+
+        let Some(trx) = report.transactions.last_mut() else {
+            continue;
+        };
+        match part {
+            Part::Header => {
+                if let Some(rest) = trimmed.strip_prefix("TRANSACTION ") {
+                    let (id, rest) = rest.split_once(',').unwrap_or((rest, ""));
+                    trx.trx_id = Some(id.trim().to_string());
+                    trx.active_secs = rest
+                        .split_once("ACTIVE ")
+                        .and_then(|(_, active)| active.split_whitespace().next())
+                        .and_then(|secs| secs.parse().ok());
+                } else if let Some(rest) = trimmed.strip_prefix("MySQL thread id ") {
+                    trx.thread_id = rest.split(',').next().and_then(|id| id.trim().parse().ok());
+                    // "..., query id 500 localhost root updating"
+                    if let Some((_, tail)) = rest.split_once("query id ") {
+                        let mut words = tail.split_whitespace().skip(1);
+                        trx.host = words.next().map(str::to_string);
+                        trx.user = words.next().map(str::to_string);
+                    }
+                    part = Part::Query;
+                }
+            }
+            Part::Query => {
+                if !trimmed.is_empty() {
+                    query.push(trimmed);
+                }
+            }
+            Part::Holds | Part::WaitsFor => {
+                if let Some(lock) = parse_deadlock_lock(trimmed) {
+                    if part == Part::Holds {
+                        trx.holds.push(lock);
+                    } else {
+                        trx.waits_for.push(lock);
+                    }
+                }
+            }
+        }
+    }
+    if let Some(trx) = report.transactions.last_mut() {
+        if !query.is_empty() {
+            trx.query = Some(query.join("\n"));
+        }
+    }
+
+    Some(report)
+}
+
+/// Parse a `RECORD LOCKS ...` or `TABLE LOCK table ...` line
+fn parse_deadlock_lock(line: &str) -> Option<DeadlockLock> {
+    let between = |start: &str, end: &str| {
+        let (_, rest) = line.split_once(start)?;
+        let value = rest.split_once(end).map_or(rest, |(value, _)| value);
+        Some(value.trim().replace('`', ""))
+    };
+    let lock_mode = line
+        .split_once("lock_mode ")
+        .or_else(|| line.split_once("lock mode "))
+        .map(|(_, mode)| mode.trim_end_matches(" waiting").trim().to_string());
+
+    if line.starts_with("RECORD LOCKS ") {
+        Some(DeadlockLock {
+            lock_type: "RECORD".to_string(),
+            table: between(" of table ", " trx id"),
+            index: between(" index ", " of table "),
+            lock_mode,
+            raw: line.to_string(),
+        })
+    } else if line.starts_with("TABLE LOCK ") {
+        Some(DeadlockLock {
+            lock_type: "TABLE".to_string(),
+            table: between("TABLE LOCK table ", " trx id"),
+            index: None,
+            lock_mode,
+            raw: line.to_string(),
+        })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_latest_deadlock() {
+        let status = "\
+=====================================
+2024-01-15 10:24:00 INNODB MONITOR OUTPUT
+=====================================
+------------------------
+LATEST DETECTED DEADLOCK
+------------------------
+2024-01-15 10:23:45 0x7f8b2c0f6700
+*** (1) TRANSACTION:
+TRANSACTION 12345, ACTIVE 5 sec starting index read
+mysql tables in use 1, locked 1
+LOCK WAIT 3 lock struct(s), heap size 1136, 2 row lock(s)
+MySQL thread id 10, OS thread handle 140236, query id 500 localhost app updating
+UPDATE accounts SET balance = balance - 100
+WHERE id = 2
+*** (1) HOLDS THE LOCK(S):
+RECORD LOCKS space id 2 page no 4 n bits 72 index PRIMARY of table `bank`.`accounts` trx id 12345 lock_mode X locks rec but not gap
+Record lock, heap no 2 PHYSICAL RECORD: n_fields 4; compact format; info bits 0
+*** (1) WAITING FOR THIS LOCK TO BE GRANTED:
+RECORD LOCKS space id 2 page no 4 n bits 72 index PRIMARY of table `bank`.`accounts` trx id 12345 lock_mode X locks rec but not gap waiting
+*** (2) TRANSACTION:
+TRANSACTION 12346, ACTIVE 3 sec starting index read
+MySQL thread id 11, OS thread handle 140237, query id 501 10.0.0.5 batch updating
+UPDATE accounts SET balance = balance + 100 WHERE id = 1
+*** (2) HOLDS THE LOCK(S):
+TABLE LOCK table `bank`.`accounts` trx id 12346 lock mode IX
+*** (2) WAITING FOR THIS LOCK TO BE GRANTED:
+RECORD LOCKS space id 2 page no 4 n bits 72 index PRIMARY of table `bank`.`accounts` trx id 12346 lock_mode X locks rec but not gap waiting
+*** WE ROLL BACK TRANSACTION (2)
+------------
+TRANSACTIONS
+------------
+Trx id counter 12350
+";
+
+        let report = parse_latest_deadlock(status).unwrap();
+        assert_eq!(report.detected_at.as_deref(), Some("2024-01-15 10:23:45"));
+        assert!(!report.raw.contains("Trx id counter"));
+        assert_eq!(report.transactions.len(), 2);
+
+        let first = &report.transactions[0];
+        assert_eq!(first.trx_id.as_deref(), Some("12345"));
+        assert_eq!(first.active_secs, Some(5));
+        assert_eq!(first.thread_id, Some(10));
+        assert_eq!(first.user.as_deref(), Some("app"));
+        assert_eq!(
+            first.query.as_deref(),
+            Some("UPDATE accounts SET balance = balance - 100\nWHERE id = 2")
+        );
+        assert_eq!(first.holds[0].table.as_deref(), Some("bank.accounts"));
+        assert_eq!(first.waits_for[0].index.as_deref(), Some("PRIMARY"));
+        assert_eq!(
+            first.waits_for[0].lock_mode.as_deref(),
+            Some("X locks rec but not gap")
+        );
+        assert!(!first.rolled_back);
+
+        let second = &report.transactions[1];
+        assert_eq!(second.host.as_deref(), Some("10.0.0.5"));
+        assert_eq!(second.holds[0].lock_type, "TABLE");
+        assert_eq!(second.holds[0].lock_mode.as_deref(), Some("IX"));
+        assert!(second.rolled_back);
+
+        assert!(parse_latest_deadlock("------------\nTRANSACTIONS\n------------\n").is_none());
+    }
+}
@@ -0,0 +1,464 @@
+//! Replication and cluster status
+//!
+//! Parses `SHOW REPLICA STATUS` (`SHOW SLAVE STATUS` before 8.0.22),
+//! `SHOW BINARY LOG STATUS` (`SHOW MASTER STATUS` before 8.2), GTID sets
+//! and Group Replication members into typed structures with a health
+//! summary. Replica lag can be polled into SQLite by a background job to
+//! keep a history per connection.
+
+use std::future::Future;
+
+use sqlx::mysql::MySqlRow;
+use sqlx::Row;
+
+use crate::db::models::{
+    BinaryLogStatus, GroupReplicationMember, GtidRange, GtidSet, GtidSetMember, ReplicaStatus,
+    ReplicationHealth, ReplicationLagPollRequest, ReplicationStatus,
+};
+use crate::db::SqlitePool;
+use crate::error::{AppError, AppResult};
+use crate::services::jobs::JobContext;
+use crate::services::mysql::MysqlService;
+
+/// Lag above which a channel is reported as an issue
+const LAG_WARNING_SECS: i64 = 60;
+
+/// Lag samples kept per connection; older ones are pruned
+const LAG_SAMPLES_PER_CONNECTION: i64 = 10_000;
+
+/// Consecutive failed polls after which lag polling gives up
+const MAX_POLL_FAILURES: u32 = 5;
+
+/// Reads replication state of a server
+pub struct MysqlReplicationService {
+    mysql: MysqlService,
+}
+
+impl MysqlReplicationService {
+    /// Create a replication service on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// Replica channels, binary log position, group members and health
+    pub async fn status(&self) -> AppResult<ReplicationStatus> {
+        let replicas = self.replicas().await?;
+        let binary_log = self.binary_log().await?;
+        let group_members = self.group_members().await;
+        let gtid_mode = sqlx::query("SELECT CAST(@@GLOBAL.gtid_mode AS CHAR) AS gtid_mode")
+            .fetch_one(self.mysql.pool())
+            .await
+            .ok()
+            .and_then(|row| MysqlService::get_optional_string_from_row(&row, "gtid_mode"));
+
+        let health = assess_health(&replicas, &group_members, binary_log.is_some());
+        Ok(ReplicationStatus {
+            gtid_mode,
+            binary_log,
+            replicas,
+            group_members,
+            health,
+            collected_at: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Record the lag of every replica channel every `interval_secs`,
+    /// returning the number of samples stored. After a failed poll the
+    /// server is reached again through `reconnect`, which re-establishes a
+    /// port forward that went away.
+    pub async fn poll_lag<F, Fut>(
+        &mut self,
+        sqlite: &SqlitePool,
+        req: &ReplicationLagPollRequest,
+        ctx: &JobContext,
+        reconnect: F,
+    ) -> AppResult<u64>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = AppResult<MysqlService>>,
+    {
+        if req.interval_secs == 0 || req.count == Some(0) {
+            return Err(AppError::Validation(
+                "Poll count and interval must be positive".to_string(),
+            ));
+        }
+
+        let total = req.count.map(u64::from);
+        let mut polls: u64 = 0;
+        let mut samples: u64 = 0;
+        let mut failures = 0;
+        ctx.set_progress(0, total).await;
+        while total.map_or(true, |total| polls < total) {
+            if polls > 0 {
+                ctx.sleep(req.interval_secs).await?;
+            }
+
+            match self.replicas().await {
+                Ok(replicas) if replicas.is_empty() && polls == 0 => {
+                    return Err(AppError::Validation(
+                        "Server has no replication channels".to_string(),
+                    ));
+                }
+                Ok(replicas) => {
+                    failures = 0;
+                    for replica in &replicas {
+                        sqlite
+                            .add_replication_lag_sample(
+                                req.connection_id,
+                                &replica.channel_name,
+                                replica.seconds_behind_source,
+                                replica.io_running == "Yes",
+                                replica.sql_running == "Yes",
+                                LAG_SAMPLES_PER_CONNECTION,
+                            )
+                            .await?;
+                        samples += 1;
+                    }
+                    ctx.set_message(format!("{} samples recorded", samples))
+                        .await;
+                }
+                Err(e) => {
+                    failures += 1;
+                    if failures >= MAX_POLL_FAILURES {
+                        return Err(e);
+                    }
+                    ctx.set_message(format!("Poll failed: {}", e)).await;
+                    match reconnect().await {
+                        Ok(mysql) => self.mysql = mysql,
+                        Err(e) => log::warn!("Replication lag poll could not reconnect: {}", e),
+                    }
+                }
+            }
+            polls += 1;
+            ctx.set_progress(polls, total).await;
+        }
+
+        Ok(samples)
+    }
+
+    async fn replicas(&self) -> AppResult<Vec<ReplicaStatus>> {
+        let rows = match sqlx::raw_sql("SHOW REPLICA STATUS")
+            .fetch_all(self.mysql.pool())
+            .await
+        {
+            Ok(rows) => rows,
+            Err(_) => sqlx::raw_sql("SHOW SLAVE STATUS")
+                .fetch_all(self.mysql.pool())
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?,
+        };
+        Ok(rows.iter().map(replica_from_row).collect())
+    }
+
+    async fn binary_log(&self) -> AppResult<Option<BinaryLogStatus>> {
+        let rows = match sqlx::raw_sql("SHOW BINARY LOG STATUS")
+            .fetch_all(self.mysql.pool())
+            .await
+        {
+            Ok(rows) => rows,
+            Err(_) => sqlx::raw_sql("SHOW MASTER STATUS")
+                .fetch_all(self.mysql.pool())
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?,
+        };
+        // No row when binary logging is disabled
+        Ok(rows.first().map(|row| BinaryLogStatus {
+            file: column(row, &["File"]).unwrap_or_default(),
+            position: number(row, &["Position"]).unwrap_or_default(),
+            do_db: column(row, &["Binlog_Do_DB"]),
+            ignore_db: column(row, &["Binlog_Ignore_DB"]),
+            executed_gtid_set: parse_gtid_set(
+                &column(row, &["Executed_Gtid_Set"]).unwrap_or_default(),
+            ),
+        }))
+    }
+
+    /// Group members, empty when Group Replication is not in use
+    async fn group_members(&self) -> Vec<GroupReplicationMember> {
+        const MYSQL_80: &str = "SELECT m.CHANNEL_NAME, m.MEMBER_ID, m.MEMBER_HOST, \
+             m.MEMBER_PORT, m.MEMBER_STATE, m.MEMBER_ROLE, m.MEMBER_VERSION, \
+             s.COUNT_TRANSACTIONS_IN_QUEUE, s.COUNT_TRANSACTIONS_REMOTE_IN_APPLIER_QUEUE \
+             FROM performance_schema.replication_group_members m \
+             LEFT JOIN performance_schema.replication_group_member_stats s \
+             ON s.MEMBER_ID = m.MEMBER_ID";
+        const MYSQL_57: &str = "SELECT m.CHANNEL_NAME, m.MEMBER_ID, m.MEMBER_HOST, \
+             m.MEMBER_PORT, m.MEMBER_STATE, NULL AS MEMBER_ROLE, NULL AS MEMBER_VERSION, \
+             s.COUNT_TRANSACTIONS_IN_QUEUE, \
+             NULL AS COUNT_TRANSACTIONS_REMOTE_IN_APPLIER_QUEUE \
+             FROM performance_schema.replication_group_members m \
+             LEFT JOIN performance_schema.replication_group_member_stats s \
+             ON s.MEMBER_ID = m.MEMBER_ID";
+
+        let rows = match sqlx::raw_sql(MYSQL_80).fetch_all(self.mysql.pool()).await {
+            Ok(rows) => rows,
+            Err(_) => sqlx::raw_sql(MYSQL_57)
+                .fetch_all(self.mysql.pool())
+                .await
+                .unwrap_or_default(),
+        };
+
+        rows.iter()
+            .filter_map(|row| {
+                // The table has one placeholder row while the plugin is stopped
+                let member_id = column(row, &["MEMBER_ID"])?;
+                Some(GroupReplicationMember {
+                    channel_name: column(row, &["CHANNEL_NAME"]).unwrap_or_default(),
+                    member_id,
+                    host: column(row, &["MEMBER_HOST"]).unwrap_or_default(),
+                    port: number(row, &["MEMBER_PORT"]),
+                    state: column(row, &["MEMBER_STATE"]).unwrap_or_default(),
+                    role: column(row, &["MEMBER_ROLE"]),
+                    version: column(row, &["MEMBER_VERSION"]),
+                    transactions_in_queue: number(row, &["COUNT_TRANSACTIONS_IN_QUEUE"]),
+                    applier_queue: number(row, &["COUNT_TRANSACTIONS_REMOTE_IN_APPLIER_QUEUE"]),
+                })
+            })
+            .collect()
+    }
+}
+
+/// First non-empty value of the given columns as text. SHOW REPLICA STATUS
+/// and SHOW SLAVE STATUS name the same column differently, so callers pass
+/// both names.
+fn column(row: &MySqlRow, names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| {
+        MysqlService::get_optional_string_from_row(row, name)
+            .or_else(|| {
+                row.try_get::<Option<i64>, _>(*name)
+                    .ok()
+                    .flatten()
+                    .map(|v| v.to_string())
+            })
+            .or_else(|| {
+                row.try_get::<Option<u64>, _>(*name)
+                    .ok()
+                    .flatten()
+                    .map(|v| v.to_string())
+            })
+            .filter(|value| !value.trim().is_empty())
+    })
+}
+
+fn number<T: std::str::FromStr>(row: &MySqlRow, names: &[&str]) -> Option<T> {
+    column(row, names).and_then(|value| value.trim().parse().ok())
+}
+
+fn replica_from_row(row: &MySqlRow) -> ReplicaStatus {
+    let last_errno = |names: &[&str]| number::<i64>(row, names).filter(|errno| *errno != 0);
+
+    ReplicaStatus {
+        channel_name: column(row, &["Channel_Name"]).unwrap_or_default(),
+        source_host: column(row, &["Source_Host", "Master_Host"]),
+        source_port: number(row, &["Source_Port", "Master_Port"]),
+        source_user: column(row, &["Source_User", "Master_User"]),
+        source_uuid: column(row, &["Source_UUID", "Master_UUID"]),
+        io_running: column(row, &["Replica_IO_Running", "Slave_IO_Running"])
+            .unwrap_or_else(|| "No".to_string()),
+        sql_running: column(row, &["Replica_SQL_Running", "Slave_SQL_Running"])
+            .unwrap_or_else(|| "No".to_string()),
+        io_state: column(row, &["Replica_IO_State", "Slave_IO_State"]),
+        sql_state: column(
+            row,
+            &["Replica_SQL_Running_State", "Slave_SQL_Running_State"],
+        ),
+        seconds_behind_source: number(row, &["Seconds_Behind_Source", "Seconds_Behind_Master"]),
+        sql_delay_secs: number(row, &["SQL_Delay"]),
+        source_log_file: column(row, &["Source_Log_File", "Master_Log_File"]),
+        read_source_log_pos: number(row, &["Read_Source_Log_Pos", "Read_Master_Log_Pos"]),
+        relay_source_log_file: column(row, &["Relay_Source_Log_File", "Relay_Master_Log_File"]),
+        exec_source_log_pos: number(row, &["Exec_Source_Log_Pos", "Exec_Master_Log_Pos"]),
+        last_io_errno: last_errno(&["Last_IO_Errno"]),
+        last_io_error: column(row, &["Last_IO_Error"]),
+        last_io_error_at: column(row, &["Last_IO_Error_Timestamp"]),
+        last_sql_errno: last_errno(&["Last_SQL_Errno"]),
+        last_sql_error: column(row, &["Last_SQL_Error"]),
+        last_sql_error_at: column(row, &["Last_SQL_Error_Timestamp"]),
+        auto_position: number::<i64>(row, &["Auto_Position"]) == Some(1),
+        retrieved_gtid_set: parse_gtid_set(
+            &column(row, &["Retrieved_Gtid_Set"]).unwrap_or_default(),
+        ),
+        executed_gtid_set: parse_gtid_set(&column(row, &["Executed_Gtid_Set"]).unwrap_or_default()),
+    }
+}
+
+/// Parse a GTID set such as `uuid:1-5:11,uuid2:tag:1-3` (tags are MySQL
+/// 8.3+). Members are split per source and tag; unparsable intervals are
+/// skipped.
+fn parse_gtid_set(raw: &str) -> GtidSet {
+    let raw = raw.trim();
+    let mut members: Vec<GtidSetMember> = Vec::new();
+
+    for part in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let mut segments = part.split(':').map(str::trim);
+        let Some(source_uuid) = segments.next() else {
+            continue;
+        };
+        let mut member = GtidSetMember {
+            source_uuid: source_uuid.to_string(),
+            tag: None,
+            ranges: Vec::new(),
+        };
+        for segment in segments {
+            if segment.starts_with(|c: char| c.is_ascii_digit()) {
+                let (start, end) = segment.split_once('-').unwrap_or((segment, segment));
+                if let (Ok(start), Ok(end)) = (start.parse(), end.parse()) {
+                    member.ranges.push(GtidRange { start, end });
+                }
+            } else {
+                // A tag applies to the intervals after it
+                let previous = std::mem::replace(
+                    &mut member,
+                    GtidSetMember {
+                        source_uuid: source_uuid.to_string(),
+                        tag: Some(segment.to_string()),
+                        ranges: Vec::new(),
+                    },
+                );
+                if !previous.ranges.is_empty() {
+                    members.push(previous);
+                }
+            }
+        }
+        if !member.ranges.is_empty() {
+            members.push(member);
+        }
+    }
+
+    let transaction_count = members
+        .iter()
+        .flat_map(|member| &member.ranges)
+        .map(|range| range.end.saturating_sub(range.start) + 1)
+        .sum();
+    GtidSet {
+        raw: raw.to_string(),
+        members,
+        transaction_count,
+    }
+}
+
+fn assess_health(
+    replicas: &[ReplicaStatus],
+    group_members: &[GroupReplicationMember],
+    binary_logging: bool,
+) -> ReplicationHealth {
+    let mut issues = Vec::new();
+
+    for replica in replicas {
+        let channel = if replica.channel_name.is_empty() {
+            "Default channel".to_string()
+        } else {
+            format!("Channel '{}'", replica.channel_name)
+        };
+        let with_error = |message: String, error: &Option<String>| match error {
+            Some(error) => format!("{}: {}", message, error),
+            None => message,
+        };
+
+        if replica.io_running != "Yes" {
+            issues.push(with_error(
+                format!("{}: IO thread is {}", channel, replica.io_running),
+                &replica.last_io_error,
+            ));
+        }
+        if replica.sql_running != "Yes" {
+            issues.push(with_error(
+                format!("{}: SQL thread is {}", channel, replica.sql_running),
+                &replica.last_sql_error,
+            ));
+        }
+        match replica.seconds_behind_source {
+            Some(lag) if lag > LAG_WARNING_SECS => {
+                issues.push(format!("{}: {}s behind the source", channel, lag));
+            }
+            None if replica.io_running == "Yes" && replica.sql_running == "Yes" => {
+                issues.push(format!("{}: lag is unknown", channel));
+            }
+            _ => {}
+        }
+    }
+
+    for member in group_members {
+        if member.state != "ONLINE" {
+            let port = member
+                .port
+                .map(|port| format!(":{}", port))
+                .unwrap_or_default();
+            issues.push(format!(
+                "Group member {}{} is {}",
+                member.host, port, member.state
+            ));
+        }
+    }
+
+    ReplicationHealth {
+        healthy: issues.is_empty(),
+        is_replica: !replicas.is_empty(),
+        is_group_member: !group_members.is_empty(),
+        binary_logging,
+        max_lag_secs: replicas
+            .iter()
+            .filter_map(|replica| replica.seconds_behind_source)
+            .max(),
+        issues,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gtid_sets_with_tags() {
+        let set = parse_gtid_set(
+            "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:11-18,\n\
+             4f22ab58-71ca-11e1-9e33-c80aa9429562:7:batch:1-3",
+        );
+
+        assert_eq!(set.members.len(), 3);
+        assert_eq!(
+            set.members[0].ranges,
+            vec![
+                GtidRange { start: 1, end: 5 },
+                GtidRange { start: 11, end: 18 }
+            ]
+        );
+        assert_eq!(set.members[1].tag, None);
+        assert_eq!(set.members[1].ranges, vec![GtidRange { start: 7, end: 7 }]);
+        assert_eq!(set.members[2].tag.as_deref(), Some("batch"));
+        assert_eq!(set.transaction_count, 5 + 8 + 1 + 3);
+        assert_eq!(parse_gtid_set("").members.len(), 0);
+    }
+
+    #[test]
+    fn reports_stopped_threads_and_lag() {
+        let stopped = ReplicaStatus {
+            io_running: "Yes".to_string(),
+            sql_running: "No".to_string(),
+            last_sql_error: Some("Duplicate entry '1' for key 'PRIMARY'".to_string()),
+            ..Default::default()
+        };
+        let lagging = ReplicaStatus {
+            channel_name: "eu".to_string(),
+            io_running: "Yes".to_string(),
+            sql_running: "Yes".to_string(),
+            seconds_behind_source: Some(120),
+            ..Default::default()
+        };
+
+        let health = assess_health(&[stopped, lagging], &[], true);
+        assert!(!health.healthy);
+        assert_eq!(health.max_lag_secs, Some(120));
+        assert_eq!(
+            health.issues,
+            vec![
+                "Default channel: SQL thread is No: Duplicate entry '1' for key 'PRIMARY'"
+                    .to_string(),
+                "Channel 'eu': 120s behind the source".to_string(),
+            ]
+        );
+    }
+}
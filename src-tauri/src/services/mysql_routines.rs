@@ -0,0 +1,766 @@
+//! Stored procedures, functions and triggers
+//!
+//! Creates stored programs from their full CREATE statements, with or
+//! without the `DELIMITER` lines a client script wraps them in. Replacing an
+//! existing program first compiles the new definition under a scratch name,
+//! then drops and recreates it, putting the previous definition back if the
+//! new one fails. Procedures are called with typed IN/OUT/INOUT arguments.
+
+use std::time::Instant;
+
+use futures::TryStreamExt;
+use sqlx::mysql::{MySql, MySqlConnection, MySqlRow};
+use sqlx::pool::PoolConnection;
+use sqlx::{Either, Executor, Row};
+
+use crate::db::models::{
+    MysqlCallRequest, MysqlCallResult, MysqlCallResultSet, ParameterMode, ProcedureCallArgument,
+    RoutineParameterInfo, SaveStoredProgramRequest, SaveStoredProgramResult, StoredProgramKind,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::mysql::{mysql_rows_to_json, MysqlService};
+use crate::services::query_execution::QueryExecutionService;
+use crate::services::query_params::{quote_string, typed_literal};
+use crate::services::sql_identifiers::quote_name;
+use crate::services::sql_splitter::{
+    is_dash_comment, parse_delimiter_line, skip_block_comment, skip_line_comment, skip_quoted,
+    split_statements,
+};
+
+/// Longest identifier MySQL accepts
+const MAX_IDENTIFIER_LEN: usize = 64;
+
+/// Name and position of the object created by a CREATE statement
+#[derive(Debug, Clone, PartialEq)]
+struct ProgramHeader {
+    kind: StoredProgramKind,
+    schema: Option<String>,
+    name: String,
+    /// Character range of the (possibly qualified) name
+    name_span: (usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Symbol(char),
+}
+
+impl Token {
+    fn is_word(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn identifier(&self) -> Option<&str> {
+        match self {
+            Token::Word(w) | Token::Quoted(w) => Some(w),
+            Token::Symbol(_) => None,
+        }
+    }
+}
+
+/// Reads the leading tokens of a statement, skipping whitespace and comments
+#[derive(Clone)]
+struct Tokens<'a> {
+    chars: &'a [char],
+    pos: usize,
+}
+
+impl Tokens<'_> {
+    /// Next token with its character range
+    fn next(&mut self) -> Option<(Token, usize, usize)> {
+        loop {
+            match self.chars.get(self.pos) {
+                Some(c) if c.is_whitespace() => self.pos += 1,
+                Some('#') => self.pos = skip_line_comment(self.chars, self.pos),
+                Some('-') if is_dash_comment(self.chars, self.pos) => {
+                    self.pos = skip_line_comment(self.chars, self.pos)
+                }
+                Some('/') if self.chars.get(self.pos + 1) == Some(&'*') => {
+                    self.pos = skip_block_comment(self.chars, self.pos)
+                }
+                _ => break,
+            }
+        }
+
+        let start = self.pos;
+        let c = *self.chars.get(start)?;
+        let token = if c == '`' || c == '\'' || c == '"' {
+            self.pos = skip_quoted(self.chars, start);
+            let inner: String = self.chars[start + 1..(self.pos - 1).max(start + 1)]
+                .iter()
+                .collect();
+            Token::Quoted(inner.replace(&format!("{}{}", c, c), &c.to_string()))
+        } else if is_word_char(c) {
+            while self.chars.get(self.pos).is_some_and(|c| is_word_char(*c)) {
+                self.pos += 1;
+            }
+            Token::Word(self.chars[start..self.pos].iter().collect())
+        } else {
+            self.pos += 1;
+            Token::Symbol(c)
+        };
+
+        Some((token, start, self.pos))
+    }
+}
+
+/// Manages and runs stored procedures, functions and triggers
+pub struct MysqlRoutineService {
+    mysql: MysqlService,
+}
+
+impl MysqlRoutineService {
+    /// Create a routine service on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// Create a stored program, replacing an existing one of the same kind
+    /// and name when `replace` is set
+    pub async fn create(
+        &self,
+        database: &str,
+        req: &SaveStoredProgramRequest,
+    ) -> AppResult<SaveStoredProgramResult> {
+        let (sql, header) = prepare_definition(database, &req.definition)?;
+        let mut conn = self.connect(database).await?;
+
+        let replaced = req.replace.unwrap_or(false)
+            && program_exists(&mut conn, database, header.kind, &header.name).await?;
+        if replaced {
+            replace_program(&mut conn, &header.name, &sql, &header).await?;
+        } else {
+            execute(&mut conn, &sql).await?;
+        }
+
+        Ok(SaveStoredProgramResult {
+            kind: header.kind,
+            name: header.name,
+            replaced,
+        })
+    }
+
+    /// Replace the definition of an existing stored program. The new
+    /// definition may give it a different name.
+    pub async fn alter(
+        &self,
+        database: &str,
+        name: &str,
+        req: &SaveStoredProgramRequest,
+    ) -> AppResult<SaveStoredProgramResult> {
+        let (sql, header) = prepare_definition(database, &req.definition)?;
+        let mut conn = self.connect(database).await?;
+
+        if !program_exists(&mut conn, database, header.kind, name).await? {
+            return Err(AppError::NotFound(format!(
+                "{} not found: {}",
+                keyword(header.kind).to_lowercase(),
+                name
+            )));
+        }
+        if !header.name.eq_ignore_ascii_case(name)
+            && program_exists(&mut conn, database, header.kind, &header.name).await?
+        {
+            return Err(AppError::Validation(format!(
+                "A {} named {} already exists",
+                keyword(header.kind).to_lowercase(),
+                header.name
+            )));
+        }
+
+        replace_program(&mut conn, name, &sql, &header).await?;
+
+        Ok(SaveStoredProgramResult {
+            kind: header.kind,
+            name: header.name,
+            replaced: true,
+        })
+    }
+
+    /// Declared parameters of a stored procedure, in order
+    pub async fn get_procedure_parameters(
+        &self,
+        database: &str,
+        name: &str,
+    ) -> AppResult<Vec<RoutineParameterInfo>> {
+        let query = format!(
+            "SELECT CAST(ORDINAL_POSITION AS SIGNED) AS POSITION, PARAMETER_MODE, PARAMETER_NAME, \
+             CAST(DTD_IDENTIFIER AS CHAR) AS DATA_TYPE \
+             FROM information_schema.PARAMETERS \
+             WHERE SPECIFIC_SCHEMA = {} AND SPECIFIC_NAME = {} AND ROUTINE_TYPE = 'PROCEDURE' \
+             ORDER BY ORDINAL_POSITION",
+            quote_string(database),
+            quote_string(name)
+        );
+
+        let rows = sqlx::raw_sql(&query)
+            .fetch_all(self.mysql.pool())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| RoutineParameterInfo {
+                position: row.try_get::<i64, _>("POSITION").unwrap_or_default() as u32,
+                name: row
+                    .try_get::<String, _>("PARAMETER_NAME")
+                    .unwrap_or_default(),
+                mode: parse_mode(
+                    &row.try_get::<Option<String>, _>("PARAMETER_MODE")
+                        .ok()
+                        .flatten()
+                        .unwrap_or_default(),
+                ),
+                data_type: row.try_get::<String, _>("DATA_TYPE").unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// CALL a stored procedure, returning every result set it produces and
+    /// the values of its OUT and INOUT parameters
+    pub async fn call(
+        &self,
+        executions: &QueryExecutionService,
+        req: &MysqlCallRequest,
+        max_rows: u64,
+    ) -> AppResult<MysqlCallResult> {
+        let start = Instant::now();
+        let declared = self
+            .get_procedure_parameters(&req.database, &req.procedure)
+            .await?;
+        let bindings = bind_arguments(&declared, &req.arguments)?;
+
+        let conn = self.connect(&req.database).await?;
+        let mut execution = executions
+            .register_connection(req.execution_id.clone(), self.mysql.pool().clone(), conn)
+            .await?;
+        let handle = execution.handle();
+        let conn = execution.conn()?;
+
+        // Arguments are passed through user variables so OUT values can be
+        // read back after the call
+        if !bindings.is_empty() {
+            let assignments: Vec<String> = bindings
+                .iter()
+                .map(|b| format!("{} = {}", b.variable, b.input.as_deref().unwrap_or("NULL")))
+                .collect();
+            execute(conn, &format!("SET {}", assignments.join(", "))).await?;
+        }
+
+        let variables: Vec<&str> = bindings.iter().map(|b| b.variable.as_str()).collect();
+        let call = format!(
+            "CALL {}({})",
+            quote_name(&req.procedure),
+            variables.join(", ")
+        );
+        let (result_sets, affected_rows) = fetch_result_sets(conn, &call, max_rows)
+            .await
+            .map_err(|e| handle.map_error(AppError::Database(e.to_string())))?;
+
+        let outputs: Vec<String> = bindings
+            .iter()
+            .filter(|b| b.mode != ParameterMode::In)
+            .map(|b| format!("{} AS {}", b.variable, quote_name(&b.name)))
+            .collect();
+        let out_values = if outputs.is_empty() {
+            Default::default()
+        } else {
+            let row = conn
+                .fetch_one(sqlx::raw_sql(&format!("SELECT {}", outputs.join(", "))))
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            let (_, mut rows) = mysql_rows_to_json(&[row]);
+            rows.pop().unwrap_or_default()
+        };
+
+        Ok(MysqlCallResult {
+            result_sets,
+            out_values,
+            affected_rows,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+            execution_id: Some(handle.id().to_string()),
+        })
+    }
+
+    /// Pooled connection with `database` as the default schema, so
+    /// unqualified names in definitions resolve to it
+    async fn connect(&self, database: &str) -> AppResult<PoolConnection<MySql>> {
+        let mut conn = self
+            .mysql
+            .pool()
+            .acquire()
+            .await
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        execute(&mut conn, &format!("USE {}", quote_name(database))).await?;
+        Ok(conn)
+    }
+}
+
+/// An argument bound to a user variable for a CALL
+#[derive(Debug, Clone, PartialEq)]
+struct CallBinding {
+    variable: String,
+    name: String,
+    mode: ParameterMode,
+    /// Literal assigned before the call; NULL when absent
+    input: Option<String>,
+}
+
+/// Match call arguments to the declared parameters by name and convert
+/// their values. IN parameters need a value; INOUT ones start as NULL
+/// without one.
+fn bind_arguments(
+    declared: &[RoutineParameterInfo],
+    arguments: &[ProcedureCallArgument],
+) -> AppResult<Vec<CallBinding>> {
+    if let Some(unknown) = arguments.iter().find(|a| {
+        !declared
+            .iter()
+            .any(|p| p.name.eq_ignore_ascii_case(&a.name))
+    }) {
+        return Err(AppError::Validation(format!(
+            "Unknown parameter: {}",
+            unknown.name
+        )));
+    }
+
+    declared
+        .iter()
+        .enumerate()
+        .map(|(index, param)| {
+            let argument = arguments
+                .iter()
+                .find(|a| a.name.eq_ignore_ascii_case(&param.name));
+            if let Some(argument) = argument.filter(|a| a.mode != param.mode) {
+                return Err(AppError::Validation(format!(
+                    "Parameter {} is declared {}, not {}",
+                    param.name,
+                    mode_keyword(param.mode),
+                    mode_keyword(argument.mode)
+                )));
+            }
+
+            let input = match (param.mode, argument) {
+                (ParameterMode::Out, _) => None,
+                (
+                    _,
+                    Some(ProcedureCallArgument {
+                        value: Some(value),
+                        param_type,
+                        ..
+                    }),
+                ) => Some(typed_literal(&param.name, *param_type, value)?),
+                (ParameterMode::In, _) => {
+                    return Err(AppError::Validation(format!(
+                        "Missing value for parameter: {}",
+                        param.name
+                    )))
+                }
+                (ParameterMode::InOut, _) => None,
+            };
+
+            Ok(CallBinding {
+                variable: format!("@__call_arg{}", index + 1),
+                name: param.name.clone(),
+                mode: param.mode,
+                input,
+            })
+        })
+        .collect()
+}
+
+/// Run a CALL and split its output into result sets.
+///
+/// Each result set is followed by a status packet and the call ends with
+/// its own status, which carries the affected row count. Empty result sets
+/// have no rows to take column names from and are skipped.
+async fn fetch_result_sets(
+    conn: &mut MySqlConnection,
+    sql: &str,
+    max_rows: u64,
+) -> Result<(Vec<MysqlCallResultSet>, u64), sqlx::Error> {
+    let mut stream = conn.fetch_many(sqlx::raw_sql(sql));
+    let mut result_sets = Vec::new();
+    let mut rows: Vec<MySqlRow> = Vec::new();
+    let mut truncated = false;
+    let mut affected_rows = 0;
+
+    while let Some(item) = stream.try_next().await? {
+        match item {
+            Either::Right(row) => {
+                if (rows.len() as u64) < max_rows {
+                    rows.push(row);
+                } else {
+                    truncated = true;
+                }
+            }
+            Either::Left(done) if rows.is_empty() => affected_rows = done.rows_affected(),
+            Either::Left(_) => {
+                let (columns, json_rows) = mysql_rows_to_json(&rows);
+                result_sets.push(MysqlCallResultSet {
+                    columns,
+                    rows: json_rows,
+                    truncated,
+                });
+                rows.clear();
+                truncated = false;
+            }
+        }
+    }
+
+    Ok((result_sets, affected_rows))
+}
+
+/// Drop a stored program and create it from `sql`, after compiling `sql`
+/// under a scratch name. If the final CREATE fails the previous definition
+/// is restored under the sql_mode it was created with.
+async fn replace_program(
+    conn: &mut PoolConnection<MySql>,
+    old_name: &str,
+    sql: &str,
+    header: &ProgramHeader,
+) -> AppResult<()> {
+    let kind = keyword(header.kind);
+    let row = conn
+        .fetch_one(sqlx::raw_sql(&format!(
+            "SHOW CREATE {} {}",
+            kind,
+            quote_name(old_name)
+        )))
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    // SHOW CREATE returns: name, sql_mode, definition, ...
+    let sql_mode = row.try_get::<String, _>(1).unwrap_or_default();
+    let original = row
+        .try_get::<Option<String>, _>(2)
+        .ok()
+        .flatten()
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "Cannot read the current definition of {}; is it owned by another user?",
+                old_name
+            ))
+        })?;
+
+    // Triggers are not test-created: a second trigger would fire on live
+    // writes while it exists
+    if header.kind != StoredProgramKind::Trigger {
+        let scratch = scratch_name(&header.name);
+        execute(conn, &rename_program(sql, header, &scratch)).await?;
+        execute(
+            conn,
+            &format!("DROP {} IF EXISTS {}", kind, quote_name(&scratch)),
+        )
+        .await?;
+    }
+
+    execute(
+        conn,
+        &format!("DROP {} IF EXISTS {}", kind, quote_name(old_name)),
+    )
+    .await?;
+    if let Err(err) = execute(conn, sql).await {
+        // The session sql_mode is changed for the restore; don't hand the
+        // connection back to the pool
+        conn.close_on_drop();
+        let restored = match execute(
+            conn,
+            &format!("SET SESSION sql_mode = {}", quote_string(&sql_mode)),
+        )
+        .await
+        {
+            Ok(()) => execute(conn, &original).await,
+            Err(e) => Err(e),
+        };
+        return Err(match restored {
+            Ok(()) => err,
+            Err(restore_err) => AppError::Database(format!(
+                "{}; restoring the previous definition also failed: {}",
+                err, restore_err
+            )),
+        });
+    }
+
+    Ok(())
+}
+
+async fn program_exists(
+    conn: &mut MySqlConnection,
+    database: &str,
+    kind: StoredProgramKind,
+    name: &str,
+) -> AppResult<bool> {
+    let query = match kind {
+        StoredProgramKind::Trigger => format!(
+            "SELECT COUNT(*) FROM information_schema.TRIGGERS \
+             WHERE TRIGGER_SCHEMA = {} AND TRIGGER_NAME = {}",
+            quote_string(database),
+            quote_string(name)
+        ),
+        _ => format!(
+            "SELECT COUNT(*) FROM information_schema.ROUTINES \
+             WHERE ROUTINE_SCHEMA = {} AND ROUTINE_NAME = {} AND ROUTINE_TYPE = {}",
+            quote_string(database),
+            quote_string(name),
+            quote_string(keyword(kind))
+        ),
+    };
+
+    let count: i64 = conn
+        .fetch_one(sqlx::raw_sql(&query))
+        .await
+        .and_then(|row| row.try_get(0))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(count > 0)
+}
+
+async fn execute(conn: &mut MySqlConnection, sql: &str) -> AppResult<()> {
+    conn.execute(sqlx::raw_sql(sql))
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
+/// Strip client `DELIMITER` lines and check the definition creates one
+/// program in `database`
+fn prepare_definition(database: &str, definition: &str) -> AppResult<(String, ProgramHeader)> {
+    let sql = normalize_definition(definition)?;
+    let header = parse_header(&sql)?;
+    if let Some(schema) = header.schema.as_deref().filter(|s| *s != database) {
+        return Err(AppError::Validation(format!(
+            "The definition creates {} in database {}, not {}",
+            header.name, schema, database
+        )));
+    }
+    Ok((sql, header))
+}
+
+/// Statement text without `DELIMITER` lines or a trailing delimiter
+fn normalize_definition(definition: &str) -> AppResult<String> {
+    let sql = if definition
+        .lines()
+        .any(|line| parse_delimiter_line(line).is_some())
+    {
+        let mut statements = split_statements(definition);
+        if statements.len() != 1 {
+            return Err(AppError::Validation(format!(
+                "Expected one CREATE statement, found {}",
+                statements.len()
+            )));
+        }
+        statements.remove(0).sql
+    } else {
+        // Without DELIMITER lines the whole text is one statement, including
+        // any semicolons inside a BEGIN ... END body
+        definition
+            .trim()
+            .trim_end_matches(';')
+            .trim_end()
+            .to_string()
+    };
+
+    if sql.is_empty() {
+        return Err(AppError::Validation("The definition is empty".to_string()));
+    }
+    Ok(sql)
+}
+
+/// Parse `CREATE [DEFINER = user] {PROCEDURE | FUNCTION | TRIGGER}
+/// [IF NOT EXISTS] [schema.]name`
+fn parse_header(sql: &str) -> AppResult<ProgramHeader> {
+    let invalid = || {
+        AppError::Validation(
+            "Expected a CREATE PROCEDURE, FUNCTION or TRIGGER statement".to_string(),
+        )
+    };
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Tokens {
+        chars: &chars,
+        pos: 0,
+    };
+
+    if !tokens.next().is_some_and(|(t, ..)| t.is_word("CREATE")) {
+        return Err(invalid());
+    }
+    let mut token = tokens.next().ok_or_else(invalid)?.0;
+    if token.is_word("DEFINER") {
+        if tokens.next().map(|(t, ..)| t) != Some(Token::Symbol('=')) {
+            return Err(invalid());
+        }
+        // user, user@host or CURRENT_USER[()]
+        tokens.next().ok_or_else(invalid)?;
+        token = tokens.next().ok_or_else(invalid)?.0;
+        // Skip the host after @, or the ) after CURRENT_USER(
+        if token == Token::Symbol('@') || token == Token::Symbol('(') {
+            tokens.next().ok_or_else(invalid)?;
+            token = tokens.next().ok_or_else(invalid)?.0;
+        }
+    }
+
+    let kind = if token.is_word("PROCEDURE") {
+        StoredProgramKind::Procedure
+    } else if token.is_word("FUNCTION") {
+        StoredProgramKind::Function
+    } else if token.is_word("TRIGGER") {
+        StoredProgramKind::Trigger
+    } else {
+        return Err(invalid());
+    };
+
+    let mut next = tokens.next().ok_or_else(invalid)?;
+    if next.0.is_word("IF") {
+        for expected in ["NOT", "EXISTS"] {
+            if !tokens.next().is_some_and(|(t, ..)| t.is_word(expected)) {
+                return Err(invalid());
+            }
+        }
+        next = tokens.next().ok_or_else(invalid)?;
+    }
+
+    let (mut token, start, mut end) = next;
+    let mut schema = None;
+    if tokens.clone().next().map(|(t, ..)| t) == Some(Token::Symbol('.')) {
+        tokens.next();
+        let (name, _, name_end) = tokens.next().ok_or_else(invalid)?;
+        schema = token.identifier().map(str::to_string);
+        token = name;
+        end = name_end;
+    }
+
+    Ok(ProgramHeader {
+        kind,
+        schema,
+        name: token.identifier().ok_or_else(invalid)?.to_string(),
+        name_span: (start, end),
+    })
+}
+
+/// The statement with the created object's name replaced
+fn rename_program(sql: &str, header: &ProgramHeader, name: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let (start, end) = header.name_span;
+    let mut renamed: String = chars[..start].iter().collect();
+    renamed.push_str(&quote_name(name));
+    renamed.extend(&chars[end..]);
+    renamed
+}
+
+/// Name used to test-compile a definition before replacing `name`
+fn scratch_name(name: &str) -> String {
+    const SUFFIX: &str = "__check";
+    let base: String = name
+        .chars()
+        .take(MAX_IDENTIFIER_LEN - SUFFIX.len())
+        .collect();
+    format!("{}{}", base, SUFFIX)
+}
+
+fn keyword(kind: StoredProgramKind) -> &'static str {
+    match kind {
+        StoredProgramKind::Procedure => "PROCEDURE",
+        StoredProgramKind::Function => "FUNCTION",
+        StoredProgramKind::Trigger => "TRIGGER",
+    }
+}
+
+fn parse_mode(mode: &str) -> ParameterMode {
+    match mode.to_uppercase().as_str() {
+        "OUT" => ParameterMode::Out,
+        "INOUT" => ParameterMode::InOut,
+        _ => ParameterMode::In,
+    }
+}
+
+fn mode_keyword(mode: ParameterMode) -> &'static str {
+    match mode {
+        ParameterMode::In => "IN",
+        ParameterMode::Out => "OUT",
+        ParameterMode::InOut => "INOUT",
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::QueryParameterType;
+
+    #[test]
+    fn parses_delimited_definition_and_renames_it() {
+        let definition = "DELIMITER $$\n\
+            CREATE DEFINER=`app`@`%` PROCEDURE `shop`.`close_order`(IN id INT)\n\
+            BEGIN\n  UPDATE orders SET closed = 1 WHERE order_id = id;\nEND$$\n\
+            DELIMITER ;\n";
+
+        let (sql, header) = prepare_definition("shop", definition).unwrap();
+        assert!(sql.ends_with("END"));
+        assert_eq!(header.kind, StoredProgramKind::Procedure);
+        assert_eq!(header.schema.as_deref(), Some("shop"));
+        assert_eq!(header.name, "close_order");
+        assert!(rename_program(&sql, &header, "close_order__check")
+            .contains("PROCEDURE `close_order__check`(IN id INT)"));
+
+        assert!(prepare_definition("other", definition).is_err());
+        assert!(prepare_definition("shop", "DROP PROCEDURE p").is_err());
+
+        let (sql, header) = prepare_definition(
+            "shop",
+            "CREATE TRIGGER IF NOT EXISTS trg BEFORE INSERT ON t FOR EACH ROW SET NEW.a = 1;",
+        )
+        .unwrap();
+        assert!(sql.ends_with("NEW.a = 1"));
+        assert_eq!(header.kind, StoredProgramKind::Trigger);
+        assert_eq!(header.name, "trg");
+    }
+
+    #[test]
+    fn binds_arguments_by_declared_mode() {
+        let param = |position, name: &str, mode| RoutineParameterInfo {
+            position,
+            name: name.to_string(),
+            mode,
+            data_type: "int".to_string(),
+        };
+        let declared = vec![
+            param(1, "id", ParameterMode::In),
+            param(2, "total", ParameterMode::Out),
+            param(3, "counter", ParameterMode::InOut),
+        ];
+        let argument = |name: &str, mode, value: Option<serde_json::Value>| ProcedureCallArgument {
+            name: name.to_string(),
+            mode,
+            param_type: QueryParameterType::Integer,
+            value,
+        };
+
+        let bindings = bind_arguments(
+            &declared,
+            &[
+                argument("ID", ParameterMode::In, Some(serde_json::json!("42"))),
+                argument("counter", ParameterMode::InOut, Some(serde_json::json!(1))),
+            ],
+        )
+        .unwrap();
+        assert_eq!(bindings[0].input.as_deref(), Some("42"));
+        assert_eq!(bindings[1].input, None);
+        assert_eq!(bindings[1].variable, "@__call_arg2");
+        assert_eq!(bindings[2].input.as_deref(), Some("1"));
+
+        assert!(bind_arguments(&declared, &[]).is_err());
+        assert!(bind_arguments(&declared, &[argument("id", ParameterMode::Out, None)]).is_err());
+        assert!(bind_arguments(
+            &declared,
+            &[argument(
+                "missing",
+                ParameterMode::In,
+                Some(serde_json::json!(1))
+            )]
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,476 @@
+//! Sticky MySQL sessions
+//!
+//! A session pins one dedicated connection so that transactions, session
+//! variables and temporary tables survive across editor runs. Sessions left
+//! idle longer than their timeout are rolled back and closed by a watchdog
+//! task.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+
+use sqlx::mysql::{MySqlConnection, MySqlPool, MySqlRow};
+use sqlx::{Connection as _, Executor, Row};
+use tokio::sync::Mutex;
+
+use crate::db::models::{MysqlQueryResult, MysqlScriptResult, MysqlSessionInfo, ScriptErrorMode};
+use crate::error::{AppError, AppResult};
+use crate::services::mysql::{fetch_limited, mysql_rows_to_json, run_script, MysqlService};
+use crate::services::query_execution::QueryExecutionService;
+use crate::services::sql_identifiers::quote_name;
+use crate::services::sql_splitter::classify_statement;
+
+struct Session {
+    /// None once the session has been closed
+    conn: Option<MySqlConnection>,
+    /// Pool of the same server, used to KILL QUERY from another connection
+    pool: MySqlPool,
+    info: MysqlSessionInfo,
+    /// Copy of `info` readable while the session is busy
+    snapshot: Arc<StdMutex<MysqlSessionInfo>>,
+    last_used: Instant,
+    /// A transaction was opened and nothing has run in it since. InnoDB
+    /// only lists a transaction once it reads or writes a table.
+    trx_pending: bool,
+}
+
+impl Session {
+    fn conn(&mut self) -> AppResult<&mut MySqlConnection> {
+        let session_id = &self.info.session_id;
+        self.conn
+            .as_mut()
+            .ok_or_else(|| AppError::NotFound(format!("Session closed: {}", session_id)))
+    }
+
+    fn touch(&mut self) {
+        self.last_used = Instant::now();
+        self.info.last_used_at = chrono::Utc::now().to_rfc3339();
+        self.publish();
+    }
+
+    fn publish(&self) {
+        if let Ok(mut snapshot) = self.snapshot.lock() {
+            *snapshot = self.info.clone();
+        }
+    }
+
+    fn set_transaction(&mut self, open: bool) {
+        if open && !self.info.in_transaction {
+            self.info.transaction_started_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+        if !open {
+            self.info.transaction_started_at = None;
+            self.info.transaction_rows_modified = None;
+        }
+        self.info.in_transaction = open;
+        self.trx_pending = open;
+    }
+
+    /// Re-read the default database and InnoDB transaction state
+    async fn refresh(&mut self) {
+        let Some(conn) = self.conn.as_mut() else {
+            return;
+        };
+
+        if let Ok(row) = conn.fetch_one(sqlx::raw_sql("SELECT DATABASE()")).await {
+            self.info.database = row_string(&row, 0);
+        }
+
+        // Needs the PROCESS privilege; keep the tracked state if unavailable
+        let trx = conn
+            .fetch_optional(sqlx::raw_sql(
+                "SELECT CAST(trx_started AS CHAR), trx_rows_modified \
+                 FROM information_schema.innodb_trx \
+                 WHERE trx_mysql_thread_id = CONNECTION_ID()",
+            ))
+            .await;
+        match trx {
+            Ok(Some(row)) => {
+                let started = row_string(&row, 0);
+                let rows_modified = row.try_get::<u64, _>(1).ok();
+                self.set_transaction(true);
+                self.trx_pending = false;
+                if self.info.transaction_started_at.is_none() {
+                    self.info.transaction_started_at = started;
+                }
+                self.info.transaction_rows_modified = rows_modified;
+            }
+            // Ended by a statement not recognised as ending it
+            Ok(None) if !self.trx_pending => self.set_transaction(false),
+            _ => {}
+        }
+    }
+
+    /// Run a transaction control statement
+    async fn control(&mut self, statement: &str, open: bool) -> AppResult<MysqlSessionInfo> {
+        self.conn()?
+            .execute(sqlx::raw_sql(statement))
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        self.set_transaction(open);
+        self.refresh().await;
+        self.touch();
+        Ok(self.info.clone())
+    }
+
+    /// Roll back any open transaction and close the connection
+    async fn shutdown(&mut self) {
+        let Some(mut conn) = self.conn.take() else {
+            return;
+        };
+        if self.info.in_transaction {
+            if let Err(e) = conn.execute(sqlx::raw_sql("ROLLBACK")).await {
+                log::warn!("Rollback of session {} failed: {}", self.info.session_id, e);
+            }
+            self.set_transaction(false);
+        }
+        let _ = conn.close().await;
+    }
+}
+
+#[derive(Clone)]
+struct SessionHandle {
+    session: Arc<Mutex<Session>>,
+    snapshot: Arc<StdMutex<MysqlSessionInfo>>,
+}
+
+type SessionMap = Arc<Mutex<HashMap<String, SessionHandle>>>;
+
+/// Registry of pinned MySQL sessions
+#[derive(Clone, Default)]
+pub struct MysqlSessionService {
+    sessions: SessionMap,
+}
+
+impl MysqlSessionService {
+    /// Create an empty session registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open a session on a dedicated connection taken out of the pool
+    pub async fn open(
+        &self,
+        mysql: &MysqlService,
+        connection_id: i64,
+        database: Option<&str>,
+        idle_timeout: Duration,
+    ) -> AppResult<MysqlSessionInfo> {
+        let pool = mysql.pool().clone();
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| AppError::Connection(e.to_string()))?
+            .detach();
+
+        if let Some(db) = database {
+            conn.execute(sqlx::raw_sql(&format!("USE {}", quote_name(db))))
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+        let thread_id: u64 = conn
+            .fetch_one(sqlx::raw_sql("SELECT CONNECTION_ID()"))
+            .await
+            .and_then(|row| row.try_get(0))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let info = MysqlSessionInfo {
+            session_id: uuid::Uuid::new_v4().to_string(),
+            connection_id,
+            database: database.map(|db| db.to_string()),
+            thread_id,
+            in_transaction: false,
+            transaction_started_at: None,
+            transaction_rows_modified: None,
+            created_at: now.clone(),
+            last_used_at: now,
+            idle_timeout_secs: idle_timeout.as_secs(),
+        };
+
+        let snapshot = Arc::new(StdMutex::new(info.clone()));
+        let session = Session {
+            conn: Some(conn),
+            pool,
+            info: info.clone(),
+            snapshot: snapshot.clone(),
+            last_used: Instant::now(),
+            trx_pending: false,
+        };
+        self.sessions.lock().await.insert(
+            info.session_id.clone(),
+            SessionHandle {
+                session: Arc::new(Mutex::new(session)),
+                snapshot,
+            },
+        );
+        tokio::spawn(watch_idle(
+            self.sessions.clone(),
+            info.session_id.clone(),
+            idle_timeout,
+        ));
+
+        Ok(info)
+    }
+
+    /// List open sessions. Sessions busy running a query report their state
+    /// as of their last refresh.
+    pub async fn list(&self) -> Vec<MysqlSessionInfo> {
+        let mut sessions: Vec<MysqlSessionInfo> = self
+            .sessions
+            .lock()
+            .await
+            .values()
+            .filter_map(|handle| handle.snapshot.lock().ok().map(|info| info.clone()))
+            .collect();
+        sessions.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        sessions
+    }
+
+    /// Get the current state of a session, including its open transaction
+    pub async fn get(&self, session_id: &str) -> AppResult<MysqlSessionInfo> {
+        let entry = self.entry(session_id).await?;
+        let mut session = entry.lock().await;
+        session.conn()?;
+        session.refresh().await;
+        session.publish();
+        Ok(session.info.clone())
+    }
+
+    /// Run a query on the session's connection.
+    ///
+    /// Results are not paged; at most `max_rows` rows are returned.
+    pub async fn execute(
+        &self,
+        executions: &QueryExecutionService,
+        session_id: &str,
+        execution_id: Option<String>,
+        query: &str,
+        max_rows: u64,
+    ) -> AppResult<MysqlQueryResult> {
+        let entry = self.entry(session_id).await?;
+        let mut session = entry.lock().await;
+        let execution =
+            executions.register(execution_id, session.pool.clone(), session.info.thread_id)?;
+        let handle = execution.handle();
+
+        let start = Instant::now();
+        let query_type = classify_statement(query);
+        session.trx_pending = false;
+        let outcome = fetch_limited(session.conn()?, sqlx::raw_sql(query), max_rows.max(1)).await;
+        let execution_time_ms = start.elapsed().as_millis() as u64;
+        // The connection stays in use, so a KILL in flight must land first
+        execution.finish().await;
+
+        if outcome.is_ok() {
+            if let Some(open) = transaction_effect(query) {
+                session.set_transaction(open);
+            }
+        }
+        session.refresh().await;
+        session.touch();
+
+        let (rows, rows_affected, truncated) =
+            outcome.map_err(|e| handle.map_error(AppError::Database(e.to_string())))?;
+        let (columns, rows) = mysql_rows_to_json(&rows);
+        let affected_rows = if columns.is_empty() {
+            rows_affected
+        } else {
+            rows.len() as u64
+        };
+
+        Ok(MysqlQueryResult {
+            columns,
+            rows,
+            affected_rows,
+            execution_time_ms,
+            query_type,
+            cursor_id: None,
+            has_more: false,
+            truncated,
+            execution_id: Some(handle.id().to_string()),
+        })
+    }
+
+    /// Run a multi-statement script on the session's connection
+    pub async fn execute_script(
+        &self,
+        executions: &QueryExecutionService,
+        session_id: &str,
+        execution_id: Option<String>,
+        script: &str,
+        mode: ScriptErrorMode,
+        max_rows: u64,
+    ) -> AppResult<MysqlScriptResult> {
+        let entry = self.entry(session_id).await?;
+        let mut session = entry.lock().await;
+        let execution =
+            executions.register(execution_id, session.pool.clone(), session.info.thread_id)?;
+
+        session.trx_pending = false;
+        let result = run_script(session.conn()?, script, mode, max_rows, &execution.handle()).await;
+        execution.finish().await;
+
+        // Statements that never ran cannot have changed the transaction state
+        for statement in result.statements.iter().filter(|r| r.status == "success") {
+            if let Some(open) = transaction_effect(&statement.sql) {
+                session.set_transaction(open);
+            }
+        }
+        session.refresh().await;
+        session.touch();
+
+        Ok(result)
+    }
+
+    /// Start a transaction
+    pub async fn begin(&self, session_id: &str) -> AppResult<MysqlSessionInfo> {
+        let entry = self.entry(session_id).await?;
+        let mut session = entry.lock().await;
+        if session.info.in_transaction {
+            // START TRANSACTION would implicitly commit the open one
+            return Err(AppError::Validation(
+                "Session already has an open transaction".to_string(),
+            ));
+        }
+        session.control("START TRANSACTION", true).await
+    }
+
+    /// Commit the open transaction
+    pub async fn commit(&self, session_id: &str) -> AppResult<MysqlSessionInfo> {
+        let entry = self.entry(session_id).await?;
+        let mut session = entry.lock().await;
+        session.control("COMMIT", false).await
+    }
+
+    /// Roll back the open transaction
+    pub async fn rollback(&self, session_id: &str) -> AppResult<MysqlSessionInfo> {
+        let entry = self.entry(session_id).await?;
+        let mut session = entry.lock().await;
+        session.control("ROLLBACK", false).await
+    }
+
+    /// Close a session, rolling back any open transaction.
+    /// Returns false if the session was not open.
+    pub async fn close(&self, session_id: &str) -> bool {
+        let Some(entry) = self.sessions.lock().await.remove(session_id) else {
+            return false;
+        };
+        entry.session.lock().await.shutdown().await;
+        true
+    }
+
+    async fn entry(&self, session_id: &str) -> AppResult<Arc<Mutex<Session>>> {
+        self.sessions
+            .lock()
+            .await
+            .get(session_id)
+            .map(|handle| handle.session.clone())
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Session not found or expired: {}", session_id))
+            })
+    }
+}
+
+/// Roll back and close a session once it has been idle for `idle_timeout`
+async fn watch_idle(sessions: SessionMap, session_id: String, idle_timeout: Duration) {
+    loop {
+        let Some(entry) = sessions
+            .lock()
+            .await
+            .get(&session_id)
+            .map(|handle| handle.session.clone())
+        else {
+            return;
+        };
+
+        let wait = match entry.try_lock() {
+            Ok(mut session) => {
+                let idle = session.last_used.elapsed();
+                if idle >= idle_timeout {
+                    sessions.lock().await.remove(&session_id);
+                    if session.info.in_transaction {
+                        log::info!(
+                            "Rolling back idle MySQL session {} after {}s",
+                            session_id,
+                            idle.as_secs()
+                        );
+                    }
+                    session.shutdown().await;
+                    return;
+                }
+                idle_timeout - idle
+            }
+            // Running a query, so not idle
+            Err(_) => idle_timeout,
+        };
+
+        drop(entry);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// How a statement changes the transaction state: Some(true) opens a
+/// transaction, Some(false) ends one (including implicit commits by DDL)
+fn transaction_effect(query: &str) -> Option<bool> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .take(3)
+        .map(|w| w.trim_end_matches(';').to_lowercase())
+        .collect();
+    let first = words.first()?.as_str();
+    let second = words.get(1).map(|w| w.as_str());
+
+    match (first, second) {
+        ("begin", _) => Some(true),
+        ("start", Some("transaction")) => Some(true),
+        ("commit", _) => Some(false),
+        ("rollback", Some("to")) => None,
+        ("rollback", Some("work")) if words.get(2).map(|w| w.as_str()) == Some("to") => None,
+        ("rollback", _) => Some(false),
+        // Temporary tables do not commit implicitly
+        ("create" | "drop", Some("temporary")) => None,
+        ("create" | "alter" | "drop" | "truncate" | "rename", _) => Some(false),
+        ("lock", Some("tables")) => Some(false),
+        _ => None,
+    }
+}
+
+/// Read a string column that may come back as VARBINARY
+fn row_string(row: &MySqlRow, index: usize) -> Option<String> {
+    row.try_get::<Option<String>, _>(index)
+        .ok()
+        .flatten()
+        .or_else(|| {
+            row.try_get::<Option<Vec<u8>>, _>(index)
+                .ok()
+                .flatten()
+                .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_effect() {
+        assert_eq!(transaction_effect("BEGIN"), Some(true));
+        assert_eq!(
+            transaction_effect("  start transaction read only"),
+            Some(true)
+        );
+        assert_eq!(transaction_effect("COMMIT;"), Some(false));
+        assert_eq!(transaction_effect("rollback"), Some(false));
+        assert_eq!(transaction_effect("ROLLBACK TO SAVEPOINT a"), None);
+        assert_eq!(transaction_effect("rollback work to a"), None);
+        assert_eq!(transaction_effect("ALTER TABLE t ADD c INT"), Some(false));
+        assert_eq!(
+            transaction_effect("CREATE TEMPORARY TABLE tmp (id INT)"),
+            None
+        );
+        assert_eq!(transaction_effect("drop temporary table tmp"), None);
+        assert_eq!(transaction_effect("UPDATE t SET c = 1"), None);
+        assert_eq!(transaction_effect(""), None);
+    }
+}
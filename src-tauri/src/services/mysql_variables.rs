@@ -0,0 +1,989 @@
+//! Server variable editing and configuration baselines
+//!
+//! Describes global variables with their scope, type and allowed range,
+//! changes them with `SET GLOBAL`, `SET SESSION` (on a pinned session),
+//! `SET PERSIST` or `SET PERSIST_ONLY` after validating the new value, and
+//! records every change with its old and new value in SQLite. Snapshots of
+//! the global variables can be stored as baselines and compared with the
+//! current configuration or with another server.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value as JsonValue;
+use sqlx::mysql::MySqlRow;
+use sqlx::{MySql, Row};
+
+use crate::db::models::{
+    CompareServerVariablesRequest, ServerVariable, ServerVariableChange, ServerVariableInfo,
+    SetServerVariableRequest, VariableBaselineInfo, VariableComparison, VariableDifference,
+    VariableScope, VariableSetMode, VariableType,
+};
+use crate::db::SqlitePool;
+use crate::error::{AppError, AppResult};
+use crate::services::mysql::MysqlService;
+use crate::services::mysql_session::MysqlSessionService;
+use crate::services::query_execution::QueryExecutionService;
+use crate::services::query_params::quote_string;
+
+/// Metadata of a well-known variable: name, scope, dynamic, type and
+/// allowed values
+type KnownVariable = (
+    &'static str,
+    VariableScope,
+    bool,
+    VariableType,
+    &'static [&'static str],
+);
+
+const KNOWN_VARIABLES: &[KnownVariable] = &[
+    (
+        "autocommit",
+        VariableScope::Both,
+        true,
+        VariableType::Boolean,
+        &[],
+    ),
+    (
+        "binlog_expire_logs_seconds",
+        VariableScope::Global,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "binlog_format",
+        VariableScope::Both,
+        true,
+        VariableType::Enumeration,
+        &["ROW", "STATEMENT", "MIXED"],
+    ),
+    (
+        "binlog_row_image",
+        VariableScope::Both,
+        true,
+        VariableType::Enumeration,
+        &["FULL", "MINIMAL", "NOBLOB"],
+    ),
+    (
+        "character_set_server",
+        VariableScope::Both,
+        true,
+        VariableType::String,
+        &[],
+    ),
+    (
+        "collation_server",
+        VariableScope::Both,
+        true,
+        VariableType::String,
+        &[],
+    ),
+    (
+        "connect_timeout",
+        VariableScope::Global,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "datadir",
+        VariableScope::Global,
+        false,
+        VariableType::String,
+        &[],
+    ),
+    (
+        "default_storage_engine",
+        VariableScope::Both,
+        true,
+        VariableType::String,
+        &[],
+    ),
+    (
+        "enforce_gtid_consistency",
+        VariableScope::Global,
+        true,
+        VariableType::Enumeration,
+        &["OFF", "ON", "WARN"],
+    ),
+    (
+        "event_scheduler",
+        VariableScope::Global,
+        true,
+        VariableType::Enumeration,
+        &["ON", "OFF"],
+    ),
+    (
+        "general_log",
+        VariableScope::Global,
+        true,
+        VariableType::Boolean,
+        &[],
+    ),
+    (
+        "gtid_mode",
+        VariableScope::Global,
+        true,
+        VariableType::Enumeration,
+        &["OFF", "OFF_PERMISSIVE", "ON_PERMISSIVE", "ON"],
+    ),
+    (
+        "innodb_buffer_pool_instances",
+        VariableScope::Global,
+        false,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "innodb_buffer_pool_size",
+        VariableScope::Global,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "innodb_flush_log_at_trx_commit",
+        VariableScope::Global,
+        true,
+        VariableType::Enumeration,
+        &["0", "1", "2"],
+    ),
+    (
+        "innodb_io_capacity",
+        VariableScope::Global,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "innodb_lock_wait_timeout",
+        VariableScope::Both,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "innodb_log_file_size",
+        VariableScope::Global,
+        false,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "innodb_print_all_deadlocks",
+        VariableScope::Global,
+        true,
+        VariableType::Boolean,
+        &[],
+    ),
+    (
+        "interactive_timeout",
+        VariableScope::Both,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "lock_wait_timeout",
+        VariableScope::Both,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "log_bin",
+        VariableScope::Global,
+        false,
+        VariableType::Boolean,
+        &[],
+    ),
+    (
+        "long_query_time",
+        VariableScope::Both,
+        true,
+        VariableType::Float,
+        &[],
+    ),
+    (
+        "lower_case_table_names",
+        VariableScope::Global,
+        false,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "max_allowed_packet",
+        VariableScope::Global,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "max_connections",
+        VariableScope::Global,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "max_execution_time",
+        VariableScope::Both,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "max_heap_table_size",
+        VariableScope::Both,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "net_read_timeout",
+        VariableScope::Both,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "net_write_timeout",
+        VariableScope::Both,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "port",
+        VariableScope::Global,
+        false,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "read_only",
+        VariableScope::Global,
+        true,
+        VariableType::Boolean,
+        &[],
+    ),
+    (
+        "server_id",
+        VariableScope::Global,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "slow_query_log",
+        VariableScope::Global,
+        true,
+        VariableType::Boolean,
+        &[],
+    ),
+    (
+        "sort_buffer_size",
+        VariableScope::Both,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "sql_mode",
+        VariableScope::Both,
+        true,
+        VariableType::Set,
+        &[
+            "ALLOW_INVALID_DATES",
+            "ANSI",
+            "ANSI_QUOTES",
+            "DB2",
+            "ERROR_FOR_DIVISION_BY_ZERO",
+            "HIGH_NOT_PRECEDENCE",
+            "IGNORE_SPACE",
+            "MAXDB",
+            "MSSQL",
+            "MYSQL323",
+            "MYSQL40",
+            "NO_AUTO_CREATE_USER",
+            "NO_AUTO_VALUE_ON_ZERO",
+            "NO_BACKSLASH_ESCAPES",
+            "NO_DIR_IN_CREATE",
+            "NO_ENGINE_SUBSTITUTION",
+            "NO_FIELD_OPTIONS",
+            "NO_KEY_OPTIONS",
+            "NO_TABLE_OPTIONS",
+            "NO_UNSIGNED_SUBTRACTION",
+            "NO_ZERO_DATE",
+            "NO_ZERO_IN_DATE",
+            "ONLY_FULL_GROUP_BY",
+            "ORACLE",
+            "PAD_CHAR_TO_FULL_LENGTH",
+            "PIPES_AS_CONCAT",
+            "POSTGRESQL",
+            "REAL_AS_FLOAT",
+            "STRICT_ALL_TABLES",
+            "STRICT_TRANS_TABLES",
+            "TIME_TRUNCATE_FRACTIONAL",
+            "TRADITIONAL",
+        ],
+    ),
+    (
+        "super_read_only",
+        VariableScope::Global,
+        true,
+        VariableType::Boolean,
+        &[],
+    ),
+    (
+        "table_open_cache",
+        VariableScope::Global,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "thread_cache_size",
+        VariableScope::Global,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "time_zone",
+        VariableScope::Both,
+        true,
+        VariableType::String,
+        &[],
+    ),
+    (
+        "tmp_table_size",
+        VariableScope::Both,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+    (
+        "transaction_isolation",
+        VariableScope::Both,
+        true,
+        VariableType::Enumeration,
+        &[
+            "READ-UNCOMMITTED",
+            "READ-COMMITTED",
+            "REPEATABLE-READ",
+            "SERIALIZABLE",
+        ],
+    ),
+    (
+        "version",
+        VariableScope::Global,
+        false,
+        VariableType::String,
+        &[],
+    ),
+    (
+        "wait_timeout",
+        VariableScope::Both,
+        true,
+        VariableType::Integer,
+        &[],
+    ),
+];
+
+/// Variables that identify a server or point at its files; they differ
+/// between any two servers and are skipped when comparing by default
+const SERVER_SPECIFIC_VARIABLES: &[&str] = &[
+    "datadir",
+    "general_log_file",
+    "gtid_executed",
+    "gtid_purged",
+    "hostname",
+    "innodb_data_home_dir",
+    "log_bin_basename",
+    "log_bin_index",
+    "log_error",
+    "mysqlx_socket",
+    "pid_file",
+    "relay_log",
+    "relay_log_basename",
+    "relay_log_index",
+    "report_host",
+    "server_id",
+    "server_uuid",
+    "slow_query_log_file",
+    "socket",
+    "timestamp",
+    "tmpdir",
+];
+
+/// Reads and changes server variables
+pub struct MysqlVariableService {
+    mysql: MysqlService,
+}
+
+impl MysqlVariableService {
+    /// Create a variable service on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// Global variables with metadata, optionally filtered by name
+    pub async fn list(&self, filter: Option<&str>) -> AppResult<Vec<ServerVariableInfo>> {
+        let query = match filter {
+            Some(f) => format!(
+                "SHOW GLOBAL VARIABLES LIKE {}",
+                quote_string(&format!("%{}%", f))
+            ),
+            None => "SHOW GLOBAL VARIABLES".to_string(),
+        };
+        let variables = self.show_variables(&query).await?;
+        let mut details = self.variables_info().await;
+
+        Ok(variables
+            .into_iter()
+            .map(|variable| {
+                let key = variable_key(&variable);
+                describe(variable, details.remove(&key))
+            })
+            .collect())
+    }
+
+    /// Validate and apply a variable change, recording the old and new value
+    pub async fn set_variable(
+        &self,
+        sqlite: &SqlitePool,
+        sessions: &MysqlSessionService,
+        executions: &QueryExecutionService,
+        req: &SetServerVariableRequest,
+    ) -> AppResult<ServerVariableChange> {
+        let name = req.name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        {
+            return Err(AppError::Validation(format!(
+                "Invalid variable name: {}",
+                req.name
+            )));
+        }
+
+        let show = if req.mode == VariableSetMode::Session {
+            "SHOW SESSION VARIABLES"
+        } else {
+            "SHOW GLOBAL VARIABLES"
+        };
+        let variable = self
+            .show_variables(&format!(
+                "{} WHERE Variable_name = {}",
+                show,
+                quote_string(name)
+            ))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::NotFound(format!("Unknown server variable: {}", name)))?;
+        let mut details = self.variables_info().await;
+        let info = describe(variable, details.remove(&name.to_lowercase()));
+
+        check_mode(&info, req.mode)?;
+        let statement = format!(
+            "SET {} {} = {}",
+            req.mode.keyword(),
+            info.name,
+            value_literal(&info, &req.value)?
+        );
+
+        let (old_value, new_value) = match req.mode {
+            VariableSetMode::Session => {
+                let session_id = req.session_id.as_deref().ok_or_else(|| {
+                    AppError::Validation(
+                        "Session variables can only be changed on a pinned session".to_string(),
+                    )
+                })?;
+                if sessions.get(session_id).await?.connection_id != req.connection_id {
+                    return Err(AppError::Validation(format!(
+                        "Session {} belongs to another connection",
+                        session_id
+                    )));
+                }
+                let read = format!("SELECT @@SESSION.{} AS value", info.name);
+                let old_value = session_value(sessions, executions, session_id, &read).await?;
+                sessions
+                    .execute(executions, session_id, None, &statement, 1)
+                    .await?;
+                let new_value = session_value(sessions, executions, session_id, &read).await?;
+                (old_value, new_value)
+            }
+            VariableSetMode::PersistOnly => {
+                let old_value = self.persisted_value(&info.name).await?;
+                self.execute(&statement).await?;
+                (old_value, self.persisted_value(&info.name).await?)
+            }
+            VariableSetMode::Global | VariableSetMode::Persist => {
+                let read = format!("SELECT @@GLOBAL.{} AS value", info.name);
+                let old_value = self.scalar(&read).await?;
+                self.execute(&statement).await?;
+                (old_value, self.scalar(&read).await?)
+            }
+        };
+
+        sqlite
+            .add_server_variable_change(
+                req.connection_id,
+                &info.name,
+                req.mode.keyword(),
+                old_value.as_deref(),
+                new_value.as_deref(),
+            )
+            .await
+    }
+
+    /// Store the current global variables as a baseline
+    pub async fn create_baseline(
+        &self,
+        sqlite: &SqlitePool,
+        connection_id: i64,
+        label: Option<&str>,
+    ) -> AppResult<VariableBaselineInfo> {
+        let variables = self.show_variables("SHOW GLOBAL VARIABLES").await?;
+        sqlite
+            .create_variable_baseline(
+                connection_id,
+                label,
+                variables.len(),
+                &serde_json::to_string(&variables)?,
+            )
+            .await
+    }
+
+    /// Compare the current global variables with a baseline or with the
+    /// global variables of `other`
+    pub async fn compare(
+        &self,
+        sqlite: &SqlitePool,
+        req: &CompareServerVariablesRequest,
+        other: Option<(&str, &MysqlService)>,
+    ) -> AppResult<VariableComparison> {
+        let (compared_with, other_variables) = match (req.baseline_id, other) {
+            (Some(baseline_id), None) => {
+                let (baseline, variables) = sqlite.get_variable_baseline(baseline_id).await?;
+                let variables: Vec<ServerVariable> = serde_json::from_str(&variables)?;
+                let label = baseline
+                    .label
+                    .unwrap_or_else(|| format!("Baseline {}", baseline.id));
+                (format!("{} ({})", label, baseline.created_at), variables)
+            }
+            (None, Some((name, other))) => (
+                name.to_string(),
+                fetch_variables(other, "SHOW GLOBAL VARIABLES").await?,
+            ),
+            _ => {
+                return Err(AppError::Validation(
+                    "Compare with either a baseline or another connection".to_string(),
+                ))
+            }
+        };
+        let current = self.show_variables("SHOW GLOBAL VARIABLES").await?;
+
+        let differences = diff_variables(&current, &other_variables, req.include_server_specific);
+        Ok(VariableComparison {
+            compared_with,
+            compared_count: current.len().max(other_variables.len()),
+            differences,
+        })
+    }
+
+    async fn show_variables(&self, query: &str) -> AppResult<Vec<ServerVariable>> {
+        fetch_variables(&self.mysql, query).await
+    }
+
+    /// performance_schema.variables_info by lowercase name; empty before
+    /// MySQL 8.0
+    async fn variables_info(&self) -> HashMap<String, VariableDetails> {
+        let rows = sqlx::query(
+            "SELECT VARIABLE_NAME, VARIABLE_SOURCE, MIN_VALUE, MAX_VALUE, \
+             CAST(SET_TIME AS CHAR) AS SET_TIME, SET_USER, SET_HOST \
+             FROM performance_schema.variables_info",
+        )
+        .fetch_all(self.mysql.pool())
+        .await
+        .unwrap_or_default();
+
+        rows.iter()
+            .map(|row| {
+                let user = MysqlService::get_optional_string_from_row(row, "SET_USER")
+                    .filter(|user| !user.is_empty());
+                let host = MysqlService::get_optional_string_from_row(row, "SET_HOST")
+                    .filter(|host| !host.is_empty());
+                let details = VariableDetails {
+                    source: MysqlService::get_optional_string_from_row(row, "VARIABLE_SOURCE"),
+                    min_value: MysqlService::get_optional_string_from_row(row, "MIN_VALUE"),
+                    max_value: MysqlService::get_optional_string_from_row(row, "MAX_VALUE"),
+                    set_by: user.map(|user| match host {
+                        Some(host) => format!("{}@{}", user, host),
+                        None => user,
+                    }),
+                    set_at: MysqlService::get_optional_string_from_row(row, "SET_TIME"),
+                };
+                (
+                    MysqlService::get_string_from_row(row, "VARIABLE_NAME").to_lowercase(),
+                    details,
+                )
+            })
+            .collect()
+    }
+
+    async fn persisted_value(&self, name: &str) -> AppResult<Option<String>> {
+        let row = sqlx::query(
+            "SELECT VARIABLE_VALUE FROM performance_schema.persisted_variables \
+             WHERE VARIABLE_NAME = ?",
+        )
+        .bind(name)
+        .fetch_optional(self.mysql.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(row.and_then(|row| MysqlService::get_optional_string_from_row(&row, "VARIABLE_VALUE")))
+    }
+
+    async fn scalar(&self, query: &str) -> AppResult<Option<String>> {
+        let row = sqlx::query(query)
+            .fetch_one(self.mysql.pool())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        // @@ variables come back as integers, doubles or strings
+        Ok(MysqlService::get_optional_string_from_row(&row, "value")
+            .or_else(|| number_text::<i64>(&row))
+            .or_else(|| number_text::<u64>(&row))
+            .or_else(|| number_text::<f64>(&row)))
+    }
+
+    async fn execute(&self, statement: &str) -> AppResult<()> {
+        sqlx::query(statement)
+            .execute(self.mysql.pool())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Run a SHOW VARIABLES statement
+async fn fetch_variables(mysql: &MysqlService, query: &str) -> AppResult<Vec<ServerVariable>> {
+    let rows = sqlx::query(query)
+        .fetch_all(mysql.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| ServerVariable {
+            name: MysqlService::get_string_from_row(row, "Variable_name"),
+            value: MysqlService::get_string_from_row(row, "Value"),
+        })
+        .collect())
+}
+
+/// Row of performance_schema.variables_info
+struct VariableDetails {
+    source: Option<String>,
+    min_value: Option<String>,
+    max_value: Option<String>,
+    set_by: Option<String>,
+    set_at: Option<String>,
+}
+
+fn number_text<T>(row: &MySqlRow) -> Option<String>
+where
+    T: ToString + for<'r> sqlx::Decode<'r, MySql> + sqlx::Type<MySql>,
+{
+    row.try_get::<Option<T>, _>("value")
+        .ok()
+        .flatten()
+        .map(|value| value.to_string())
+}
+
+fn variable_key(variable: &ServerVariable) -> String {
+    variable.name.to_lowercase()
+}
+
+async fn session_value(
+    sessions: &MysqlSessionService,
+    executions: &QueryExecutionService,
+    session_id: &str,
+    query: &str,
+) -> AppResult<Option<String>> {
+    let result = sessions
+        .execute(executions, session_id, None, query, 1)
+        .await?;
+    Ok(result
+        .rows
+        .first()
+        .and_then(|row| row.get("value"))
+        .and_then(|value| match value {
+            JsonValue::Null => None,
+            JsonValue::String(value) => Some(value.clone()),
+            value => Some(value.to_string()),
+        }))
+}
+
+fn describe(variable: ServerVariable, details: Option<VariableDetails>) -> ServerVariableInfo {
+    let known = KNOWN_VARIABLES
+        .iter()
+        .find(|(name, ..)| name.eq_ignore_ascii_case(&variable.name));
+    let var_type = match known {
+        Some((_, _, _, var_type, _)) => *var_type,
+        None => infer_type(&variable.value),
+    };
+    // variables_info reports 0..0 for variables without a numeric range
+    let numeric = matches!(var_type, VariableType::Integer | VariableType::Float);
+    let (min_value, max_value, source, set_by, set_at) = match details {
+        Some(details) => (
+            details.min_value.filter(|_| numeric),
+            details.max_value.filter(|_| numeric),
+            details.source,
+            details.set_by,
+            details.set_at,
+        ),
+        None => (None, None, None, None, None),
+    };
+
+    ServerVariableInfo {
+        scope: known.map(|(_, scope, ..)| *scope),
+        dynamic: known.map(|(_, _, dynamic, ..)| *dynamic),
+        var_type,
+        allowed_values: known
+            .map(|(.., allowed)| allowed.iter().map(|v| v.to_string()).collect())
+            .unwrap_or_default(),
+        min_value,
+        max_value,
+        source,
+        set_by,
+        set_at,
+        name: variable.name,
+        value: variable.value,
+    }
+}
+
+fn infer_type(value: &str) -> VariableType {
+    if value.eq_ignore_ascii_case("ON") || value.eq_ignore_ascii_case("OFF") {
+        VariableType::Boolean
+    } else if value.parse::<i128>().is_ok() {
+        VariableType::Integer
+    } else if !value.is_empty() && value.parse::<f64>().is_ok() {
+        VariableType::Float
+    } else {
+        VariableType::String
+    }
+}
+
+fn check_mode(info: &ServerVariableInfo, mode: VariableSetMode) -> AppResult<()> {
+    if info.dynamic == Some(false) && mode != VariableSetMode::PersistOnly {
+        return Err(AppError::Validation(format!(
+            "{} cannot be changed at runtime; use PERSIST_ONLY to change it at the next restart",
+            info.name
+        )));
+    }
+    match (info.scope, mode) {
+        (Some(VariableScope::Global), VariableSetMode::Session) => Err(AppError::Validation(
+            format!("{} is a global variable", info.name),
+        )),
+        (Some(VariableScope::Session), mode) if mode != VariableSetMode::Session => Err(
+            AppError::Validation(format!("{} is a session variable", info.name)),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// SQL literal of a new value, checked against the variable's type and range
+fn value_literal(info: &ServerVariableInfo, value: &str) -> AppResult<String> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("DEFAULT") {
+        return Ok("DEFAULT".to_string());
+    }
+    let invalid = |expected: &str| {
+        AppError::Validation(format!(
+            "Invalid value for {}: {} (expected {})",
+            info.name, value, expected
+        ))
+    };
+    let allowed = |candidate: &str| {
+        if info.allowed_values.is_empty() {
+            return Some(candidate.to_string());
+        }
+        info.allowed_values
+            .iter()
+            .find(|allowed| allowed.eq_ignore_ascii_case(candidate))
+            .cloned()
+    };
+
+    match info.var_type {
+        VariableType::Boolean => match value.to_ascii_uppercase().as_str() {
+            "ON" | "1" | "TRUE" => Ok("ON".to_string()),
+            "OFF" | "0" | "FALSE" => Ok("OFF".to_string()),
+            _ => Err(invalid("ON or OFF")),
+        },
+        VariableType::Integer => {
+            let number: i128 = value.parse().map_err(|_| invalid("an integer"))?;
+            let min = info
+                .min_value
+                .as_deref()
+                .and_then(|v| v.parse::<i128>().ok());
+            let max = info
+                .max_value
+                .as_deref()
+                .and_then(|v| v.parse::<i128>().ok());
+            if min.is_some_and(|min| number < min) || max.is_some_and(|max| number > max) {
+                return Err(invalid(&format!(
+                    "{} to {}",
+                    info.min_value.as_deref().unwrap_or("-"),
+                    info.max_value.as_deref().unwrap_or("-")
+                )));
+            }
+            Ok(number.to_string())
+        }
+        VariableType::Float => {
+            let number: f64 = value
+                .parse()
+                .ok()
+                .filter(|n: &f64| n.is_finite())
+                .ok_or_else(|| invalid("a number"))?;
+            let min = info
+                .min_value
+                .as_deref()
+                .and_then(|v| v.parse::<f64>().ok());
+            let max = info
+                .max_value
+                .as_deref()
+                .and_then(|v| v.parse::<f64>().ok());
+            if min.is_some_and(|min| number < min) || max.is_some_and(|max| number > max) {
+                return Err(invalid(&format!(
+                    "{} to {}",
+                    info.min_value.as_deref().unwrap_or("-"),
+                    info.max_value.as_deref().unwrap_or("-")
+                )));
+            }
+            Ok(value.to_string())
+        }
+        VariableType::Enumeration => allowed(value)
+            .map(|value| quote_string(&value))
+            .ok_or_else(|| invalid(&info.allowed_values.join(", "))),
+        VariableType::Set => {
+            let members = value
+                .split(',')
+                .map(str::trim)
+                .filter(|member| !member.is_empty())
+                .map(|member| {
+                    allowed(member).ok_or_else(|| invalid(&info.allowed_values.join(", ")))
+                })
+                .collect::<AppResult<Vec<_>>>()?;
+            Ok(quote_string(&members.join(",")))
+        }
+        VariableType::String => Ok(quote_string(value)),
+    }
+}
+
+/// Variables whose values differ, ordered by name
+fn diff_variables(
+    current: &[ServerVariable],
+    other: &[ServerVariable],
+    include_server_specific: bool,
+) -> Vec<VariableDifference> {
+    let mut merged: BTreeMap<String, (Option<&str>, Option<&str>)> = BTreeMap::new();
+    for variable in current {
+        merged.entry(variable_key(variable)).or_default().0 = Some(&variable.value);
+    }
+    for variable in other {
+        merged.entry(variable_key(variable)).or_default().1 = Some(&variable.value);
+    }
+
+    merged
+        .into_iter()
+        .filter(|(name, _)| {
+            include_server_specific || !SERVER_SPECIFIC_VARIABLES.contains(&name.as_str())
+        })
+        .filter(|(_, (current, other))| current != other)
+        .map(|(name, (current, other))| VariableDifference {
+            name,
+            current: current.map(str::to_string),
+            other: other.map(str::to_string),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(name: &str, value: &str) -> ServerVariable {
+        ServerVariable {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn validates_values_by_type() {
+        let sql_mode = describe(variable("sql_mode", ""), None);
+        assert_eq!(
+            value_literal(&sql_mode, "strict_trans_tables, ONLY_FULL_GROUP_BY").unwrap(),
+            "'STRICT_TRANS_TABLES,ONLY_FULL_GROUP_BY'"
+        );
+        assert!(value_literal(&sql_mode, "STRICT,NO_SUCH_MODE").is_err());
+
+        let mut connections = describe(variable("max_connections", "151"), None);
+        connections.min_value = Some("1".to_string());
+        connections.max_value = Some("100000".to_string());
+        assert_eq!(value_literal(&connections, " 500 ").unwrap(), "500");
+        assert!(value_literal(&connections, "0").is_err());
+        assert!(value_literal(&connections, "lots").is_err());
+        assert_eq!(value_literal(&connections, "default").unwrap(), "DEFAULT");
+
+        let unknown = describe(variable("some_plugin_flag", "OFF"), None);
+        assert_eq!(unknown.var_type, VariableType::Boolean);
+        assert_eq!(value_literal(&unknown, "1").unwrap(), "ON");
+
+        let log_bin = describe(variable("log_bin", "ON"), None);
+        assert!(check_mode(&log_bin, VariableSetMode::Global).is_err());
+        assert!(check_mode(&log_bin, VariableSetMode::PersistOnly).is_ok());
+        assert!(check_mode(&connections, VariableSetMode::Session).is_err());
+    }
+
+    #[test]
+    fn diffs_variables_by_name() {
+        let current = vec![
+            variable("server_uuid", "a"),
+            variable("max_connections", "151"),
+            variable("wait_timeout", "28800"),
+            variable("sql_mode", "ANSI"),
+        ];
+        let other = vec![
+            variable("server_uuid", "b"),
+            variable("max_connections", "500"),
+            variable("wait_timeout", "28800"),
+            variable("innodb_dedicated_server", "OFF"),
+        ];
+
+        assert_eq!(
+            diff_variables(&current, &other, false),
+            vec![
+                VariableDifference {
+                    name: "innodb_dedicated_server".to_string(),
+                    current: None,
+                    other: Some("OFF".to_string()),
+                },
+                VariableDifference {
+                    name: "max_connections".to_string(),
+                    current: Some("151".to_string()),
+                    other: Some("500".to_string()),
+                },
+                VariableDifference {
+                    name: "sql_mode".to_string(),
+                    current: Some("ANSI".to_string()),
+                    other: None,
+                },
+            ]
+        );
+        assert_eq!(diff_variables(&current, &other, true).len(), 4);
+    }
+}
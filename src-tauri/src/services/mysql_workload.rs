@@ -0,0 +1,489 @@
+//! Workload analysis from performance_schema statement digests
+//!
+//! Ranks normalized statements from
+//! `performance_schema.events_statements_summary_by_digest` by latency,
+//! rows examined per row sent, temporary tables and full scans. The digest
+//! counters are cumulative, so snapshots are stored in SQLite and compared
+//! to show what ran between two points in time. When the `sys` schema is
+//! installed its 95th percentile view flags the slowest statements.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use sqlx::mysql::MySqlRow;
+use sqlx::Row;
+
+use crate::db::models::{
+    CompareDigestSnapshotsRequest, DigestComparison, DigestRanking, DigestSnapshotInfo,
+    ExplainDigestRequest, ExplainResult, PeriodicDigestSnapshotsRequest, StatementDigest,
+    TopQueriesReport, TopQueriesRequest,
+};
+use crate::db::SqlitePool;
+use crate::error::{AppError, AppResult};
+use crate::services::jobs::JobContext;
+use crate::services::mysql::MysqlService;
+use crate::services::query_params::quote_string;
+
+/// Digests returned when the request sets no limit
+const DEFAULT_TOP_LIMIT: u32 = 50;
+
+/// Digests stored per snapshot, by total latency
+const SNAPSHOT_DIGEST_LIMIT: u32 = 5000;
+
+/// Snapshots kept per connection; older ones are pruned
+const SNAPSHOTS_PER_CONNECTION: i64 = 200;
+
+/// performance_schema timers count picoseconds
+const PICOS_PER_MS: f64 = 1_000_000_000.0;
+
+/// Reads and compares statement digest statistics
+pub struct MysqlWorkloadService {
+    mysql: MysqlService,
+}
+
+impl MysqlWorkloadService {
+    /// Create a workload service on a connected MySQL service
+    pub fn new(mysql: MysqlService) -> Self {
+        Self { mysql }
+    }
+
+    /// Most expensive statements by the requested ranking
+    pub async fn top_queries(&self, req: &TopQueriesRequest) -> AppResult<TopQueriesReport> {
+        let limit = req.limit.unwrap_or(DEFAULT_TOP_LIMIT).max(1);
+        let mut digests = self
+            .collect_digests(req.schema.as_deref(), req.rank_by, limit)
+            .await?;
+
+        let threshold_ms = self.percentile_95_ms().await;
+        if let Some(threshold_ms) = threshold_ms {
+            for digest in &mut digests {
+                digest.in_95th_percentile = Some(digest.avg_latency_ms >= threshold_ms);
+            }
+        }
+
+        Ok(TopQueriesReport {
+            rank_by: req.rank_by,
+            digests,
+            sys_schema: threshold_ms.is_some(),
+            collected_at: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Store the current digest counters
+    pub async fn take_snapshot(
+        &self,
+        sqlite: &SqlitePool,
+        connection_id: i64,
+        label: Option<&str>,
+    ) -> AppResult<DigestSnapshotInfo> {
+        let mut digests = self
+            .collect_digests(None, DigestRanking::TotalLatency, SNAPSHOT_DIGEST_LIMIT)
+            .await?;
+        // Samples are looked up live when explaining; keep snapshots small
+        for digest in &mut digests {
+            digest.sample_query = None;
+        }
+
+        sqlite
+            .create_digest_snapshot(
+                connection_id,
+                label,
+                digests.len(),
+                &serde_json::to_string(&digests)?,
+                SNAPSHOTS_PER_CONNECTION,
+            )
+            .await
+    }
+
+    /// Take `count` snapshots `interval_secs` apart, reporting progress on
+    /// the job
+    pub async fn take_periodic_snapshots(
+        &self,
+        sqlite: &SqlitePool,
+        req: &PeriodicDigestSnapshotsRequest,
+        ctx: &JobContext,
+    ) -> AppResult<Vec<DigestSnapshotInfo>> {
+        if req.count == 0 || req.interval_secs == 0 {
+            return Err(AppError::Validation(
+                "Snapshot count and interval must be positive".to_string(),
+            ));
+        }
+
+        let mut snapshots = Vec::with_capacity(req.count as usize);
+        ctx.set_progress(0, Some(req.count as u64)).await;
+        for index in 0..req.count {
+            if index > 0 {
+                ctx.sleep(req.interval_secs).await?;
+            }
+            ctx.check_cancelled()?;
+
+            let label = req
+                .label
+                .as_ref()
+                .map(|label| format!("{} #{}", label, index + 1));
+            snapshots.push(
+                self.take_snapshot(sqlite, req.connection_id, label.as_deref())
+                    .await?,
+            );
+            ctx.set_progress(index as u64 + 1, None).await;
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Statement activity between a snapshot and a later snapshot or now
+    pub async fn compare(
+        &self,
+        sqlite: &SqlitePool,
+        req: &CompareDigestSnapshotsRequest,
+    ) -> AppResult<DigestComparison> {
+        let (before, before_digests) =
+            load_snapshot(sqlite, req.connection_id, req.before_id).await?;
+        let (after, after_digests) = match req.after_id {
+            Some(after_id) => {
+                let (after, digests) = load_snapshot(sqlite, req.connection_id, after_id).await?;
+                (Some(after), digests)
+            }
+            None => (
+                None,
+                self.collect_digests(None, DigestRanking::TotalLatency, SNAPSHOT_DIGEST_LIMIT)
+                    .await?,
+            ),
+        };
+
+        let mut diff = diff_digests(&before_digests, &after_digests);
+        rank_digests(&mut diff.digests, req.rank_by);
+        diff.digests
+            .truncate(req.limit.unwrap_or(DEFAULT_TOP_LIMIT).max(1) as usize);
+
+        Ok(DigestComparison {
+            before,
+            after,
+            rank_by: req.rank_by,
+            digests: diff.digests,
+            new_digests: diff.new_digests,
+            counters_reset: diff.counters_reset,
+        })
+    }
+
+    /// EXPLAIN the sample statement recorded for a digest
+    pub async fn explain_digest(&self, req: &ExplainDigestRequest) -> AppResult<ExplainResult> {
+        if !self.has_sample_column().await? {
+            return Err(AppError::Validation(
+                "Statement samples need MySQL 8.0 or later".to_string(),
+            ));
+        }
+
+        let row = sqlx::query(
+            "SELECT SCHEMA_NAME, QUERY_SAMPLE_TEXT \
+             FROM performance_schema.events_statements_summary_by_digest \
+             WHERE DIGEST = ? AND (? IS NULL OR SCHEMA_NAME = ?) \
+             ORDER BY SUM_TIMER_WAIT DESC LIMIT 1",
+        )
+        .bind(&req.digest)
+        .bind(&req.schema)
+        .bind(&req.schema)
+        .fetch_optional(self.mysql.pool())
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound(format!("Digest not found: {}", req.digest)))?;
+
+        let schema = row
+            .try_get::<Option<String>, _>("SCHEMA_NAME")
+            .ok()
+            .flatten()
+            .ok_or_else(|| {
+                AppError::Validation("The statement ran without a default schema".to_string())
+            })?;
+        let sample = row
+            .try_get::<Option<String>, _>("QUERY_SAMPLE_TEXT")
+            .ok()
+            .flatten()
+            .filter(|s| !s.trim().is_empty())
+            .ok_or_else(|| {
+                AppError::Validation("No sample statement was recorded for this digest".to_string())
+            })?;
+
+        self.mysql.explain_query(&schema, &sample).await
+    }
+
+    /// Read digest rows ordered by `rank_by`
+    async fn collect_digests(
+        &self,
+        schema: Option<&str>,
+        rank_by: DigestRanking,
+        limit: u32,
+    ) -> AppResult<Vec<StatementDigest>> {
+        self.ensure_performance_schema().await?;
+
+        let sample_column = if self.has_sample_column().await? {
+            ", QUERY_SAMPLE_TEXT"
+        } else {
+            ""
+        };
+        let schema_filter = schema
+            .map(|schema| format!(" AND SCHEMA_NAME = {}", quote_string(schema)))
+            .unwrap_or_default();
+        let query = format!(
+            "SELECT SCHEMA_NAME, DIGEST, DIGEST_TEXT, COUNT_STAR, SUM_TIMER_WAIT, MAX_TIMER_WAIT, \
+             SUM_LOCK_TIME, SUM_ROWS_EXAMINED, SUM_ROWS_SENT, SUM_ROWS_AFFECTED, \
+             SUM_CREATED_TMP_TABLES, SUM_CREATED_TMP_DISK_TABLES, SUM_NO_INDEX_USED, SUM_ERRORS, \
+             CAST(FIRST_SEEN AS CHAR) AS FIRST_SEEN, CAST(LAST_SEEN AS CHAR) AS LAST_SEEN{} \
+             FROM performance_schema.events_statements_summary_by_digest \
+             WHERE DIGEST IS NOT NULL{} ORDER BY {} LIMIT {}",
+            sample_column,
+            schema_filter,
+            order_by(rank_by),
+            limit
+        );
+
+        let rows = sqlx::raw_sql(&query)
+            .fetch_all(self.mysql.pool())
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(rows.iter().map(digest_from_row).collect())
+    }
+
+    async fn ensure_performance_schema(&self) -> AppResult<()> {
+        let enabled: i64 = sqlx::raw_sql("SELECT @@performance_schema + 0")
+            .fetch_one(self.mysql.pool())
+            .await
+            .and_then(|row| row.try_get(0))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        if enabled == 0 {
+            return Err(AppError::Validation(
+                "performance_schema is disabled on this server".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// QUERY_SAMPLE_TEXT was added in MySQL 8.0.3
+    async fn has_sample_column(&self) -> AppResult<bool> {
+        let count: i64 = sqlx::raw_sql(
+            "SELECT COUNT(*) FROM information_schema.COLUMNS \
+             WHERE TABLE_SCHEMA = 'performance_schema' \
+             AND TABLE_NAME = 'events_statements_summary_by_digest' \
+             AND COLUMN_NAME = 'QUERY_SAMPLE_TEXT'",
+        )
+        .fetch_one(self.mysql.pool())
+        .await
+        .and_then(|row| row.try_get(0))
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(count > 0)
+    }
+
+    /// Average latency that separates the slowest 5% of digests, from the
+    /// sys schema. None when sys is not installed or not readable.
+    async fn percentile_95_ms(&self) -> Option<f64> {
+        let row = sqlx::query(
+            "SELECT CAST(avg_us AS CHAR) AS avg_us FROM sys.`x$ps_digest_95th_percentile_by_avg_us`",
+        )
+        .fetch_optional(self.mysql.pool())
+        .await
+        .ok()??;
+        let avg_us: f64 = row.try_get::<String, _>("avg_us").ok()?.parse().ok()?;
+        Some(avg_us / 1000.0)
+    }
+}
+
+/// Digest differences between two points in time
+#[derive(Debug, Default)]
+struct DigestDiff {
+    digests: Vec<StatementDigest>,
+    new_digests: Vec<String>,
+    counters_reset: bool,
+}
+
+/// Subtract the earlier counters from the later ones. Digests without new
+/// executions are left out; a digest whose count went backwards was reset
+/// and is reported with its later counters.
+fn diff_digests(before: &[StatementDigest], after: &[StatementDigest]) -> DigestDiff {
+    let earlier: HashMap<(Option<&str>, &str), &StatementDigest> = before
+        .iter()
+        .map(|d| ((d.schema_name.as_deref(), d.digest.as_str()), d))
+        .collect();
+
+    let mut diff = DigestDiff::default();
+    for later in after {
+        let key = (later.schema_name.as_deref(), later.digest.as_str());
+        let delta = match earlier.get(&key) {
+            None => {
+                diff.new_digests.push(later.digest.clone());
+                later.clone()
+            }
+            Some(earlier) if later.exec_count < earlier.exec_count => {
+                diff.counters_reset = true;
+                later.clone()
+            }
+            Some(earlier) if later.exec_count == earlier.exec_count => continue,
+            Some(earlier) => StatementDigest {
+                exec_count: later.exec_count - earlier.exec_count,
+                total_latency_ms: (later.total_latency_ms - earlier.total_latency_ms).max(0.0),
+                lock_latency_ms: (later.lock_latency_ms - earlier.lock_latency_ms).max(0.0),
+                rows_examined: later.rows_examined.saturating_sub(earlier.rows_examined),
+                rows_sent: later.rows_sent.saturating_sub(earlier.rows_sent),
+                rows_affected: later.rows_affected.saturating_sub(earlier.rows_affected),
+                tmp_tables: later.tmp_tables.saturating_sub(earlier.tmp_tables),
+                tmp_disk_tables: later
+                    .tmp_disk_tables
+                    .saturating_sub(earlier.tmp_disk_tables),
+                full_scans: later.full_scans.saturating_sub(earlier.full_scans),
+                errors: later.errors.saturating_sub(earlier.errors),
+                ..later.clone()
+            },
+        };
+        diff.digests.push(with_averages(delta));
+    }
+
+    diff
+}
+
+/// Sort digests, most expensive first
+fn rank_digests(digests: &mut [StatementDigest], rank_by: DigestRanking) {
+    let by_float = |a: f64, b: f64| b.partial_cmp(&a).unwrap_or(Ordering::Equal);
+    digests.sort_by(|a, b| match rank_by {
+        DigestRanking::TotalLatency => by_float(a.total_latency_ms, b.total_latency_ms),
+        DigestRanking::RowsExaminedRatio => {
+            by_float(a.rows_examined_per_sent, b.rows_examined_per_sent)
+        }
+        DigestRanking::TmpTables => {
+            (b.tmp_disk_tables, b.tmp_tables).cmp(&(a.tmp_disk_tables, a.tmp_tables))
+        }
+        DigestRanking::FullScans => b.full_scans.cmp(&a.full_scans),
+        DigestRanking::ExecCount => b.exec_count.cmp(&a.exec_count),
+    });
+}
+
+fn order_by(rank_by: DigestRanking) -> &'static str {
+    match rank_by {
+        DigestRanking::TotalLatency => "SUM_TIMER_WAIT DESC",
+        DigestRanking::RowsExaminedRatio => {
+            "SUM_ROWS_EXAMINED / GREATEST(SUM_ROWS_SENT, 1) DESC, SUM_TIMER_WAIT DESC"
+        }
+        DigestRanking::TmpTables => {
+            "SUM_CREATED_TMP_DISK_TABLES DESC, SUM_CREATED_TMP_TABLES DESC, SUM_TIMER_WAIT DESC"
+        }
+        DigestRanking::FullScans => "SUM_NO_INDEX_USED DESC, SUM_TIMER_WAIT DESC",
+        DigestRanking::ExecCount => "COUNT_STAR DESC",
+    }
+}
+
+fn digest_from_row(row: &MySqlRow) -> StatementDigest {
+    let count = |column: &str| row.try_get::<u64, _>(column).unwrap_or_default();
+    let millis = |column: &str| count(column) as f64 / PICOS_PER_MS;
+
+    with_averages(StatementDigest {
+        schema_name: row
+            .try_get::<Option<String>, _>("SCHEMA_NAME")
+            .ok()
+            .flatten(),
+        digest: row.try_get::<String, _>("DIGEST").unwrap_or_default(),
+        digest_text: row
+            .try_get::<Option<String>, _>("DIGEST_TEXT")
+            .ok()
+            .flatten()
+            .unwrap_or_default(),
+        exec_count: count("COUNT_STAR"),
+        total_latency_ms: millis("SUM_TIMER_WAIT"),
+        max_latency_ms: millis("MAX_TIMER_WAIT"),
+        lock_latency_ms: millis("SUM_LOCK_TIME"),
+        rows_examined: count("SUM_ROWS_EXAMINED"),
+        rows_sent: count("SUM_ROWS_SENT"),
+        rows_affected: count("SUM_ROWS_AFFECTED"),
+        tmp_tables: count("SUM_CREATED_TMP_TABLES"),
+        tmp_disk_tables: count("SUM_CREATED_TMP_DISK_TABLES"),
+        full_scans: count("SUM_NO_INDEX_USED"),
+        errors: count("SUM_ERRORS"),
+        first_seen: row
+            .try_get::<Option<String>, _>("FIRST_SEEN")
+            .ok()
+            .flatten(),
+        last_seen: row.try_get::<Option<String>, _>("LAST_SEEN").ok().flatten(),
+        sample_query: row
+            .try_get::<Option<String>, _>("QUERY_SAMPLE_TEXT")
+            .ok()
+            .flatten(),
+        ..Default::default()
+    })
+}
+
+/// Fill in the per-execution and per-row averages
+fn with_averages(mut digest: StatementDigest) -> StatementDigest {
+    digest.avg_latency_ms = if digest.exec_count > 0 {
+        digest.total_latency_ms / digest.exec_count as f64
+    } else {
+        0.0
+    };
+    digest.rows_examined_per_sent = digest.rows_examined as f64 / digest.rows_sent.max(1) as f64;
+    digest
+}
+
+async fn load_snapshot(
+    sqlite: &SqlitePool,
+    connection_id: i64,
+    id: i64,
+) -> AppResult<(DigestSnapshotInfo, Vec<StatementDigest>)> {
+    let (snapshot, digests) = sqlite.get_digest_snapshot(id).await?;
+    if snapshot.connection_id != connection_id {
+        return Err(AppError::NotFound(format!(
+            "Digest snapshot {} not found",
+            id
+        )));
+    }
+    Ok((snapshot, serde_json::from_str(&digests)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(
+        name: &str,
+        exec_count: u64,
+        total_latency_ms: f64,
+        rows_examined: u64,
+    ) -> StatementDigest {
+        with_averages(StatementDigest {
+            schema_name: Some("shop".to_string()),
+            digest: name.to_string(),
+            digest_text: format!("SELECT * FROM {}", name),
+            exec_count,
+            total_latency_ms,
+            rows_examined,
+            rows_sent: exec_count,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn diffs_and_ranks_snapshots() {
+        let before = vec![
+            digest("orders", 10, 100.0, 1000),
+            digest("users", 5, 50.0, 5),
+            digest("idle", 3, 30.0, 3),
+        ];
+        let after = vec![
+            digest("orders", 12, 180.0, 1400),
+            digest("users", 2, 10.0, 2),
+            digest("idle", 3, 30.0, 3),
+            digest("items", 1, 5.0, 900),
+        ];
+
+        let mut diff = diff_digests(&before, &after);
+        assert!(diff.counters_reset);
+        assert_eq!(diff.new_digests, vec!["items".to_string()]);
+        assert_eq!(diff.digests.len(), 3);
+
+        let orders = diff.digests.iter().find(|d| d.digest == "orders").unwrap();
+        assert_eq!(orders.exec_count, 2);
+        assert_eq!(orders.avg_latency_ms, 40.0);
+        assert_eq!(orders.rows_examined_per_sent, 200.0);
+
+        rank_digests(&mut diff.digests, DigestRanking::TotalLatency);
+        assert_eq!(diff.digests[0].digest, "orders");
+        rank_digests(&mut diff.digests, DigestRanking::RowsExaminedRatio);
+        assert_eq!(diff.digests[0].digest, "items");
+    }
+}
@@ -0,0 +1,340 @@
+//! Server-side cursors for streaming MySQL result sets
+//!
+//! A cursor owns a pooled connection in a background task that streams rows
+//! with `fetch` and hands them out one page at a time over a bounded channel,
+//! so at most a couple of pages are held in memory regardless of the result
+//! size. Idle cursors are dropped after `CURSOR_IDLE_TIMEOUT`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::TryStreamExt;
+use serde_json::Value as JsonValue;
+use sqlx::mysql::{MySql, MySqlRow};
+use sqlx::pool::PoolConnection;
+use sqlx::Executor;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::error::{AppError, AppResult};
+use crate::services::mysql::mysql_rows_to_json;
+use crate::services::query_execution::ExecutionGuard;
+use crate::services::sql_identifiers::quote_name;
+
+/// Cursors not read for this long are closed
+const CURSOR_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// One page of rows produced by a cursor
+#[derive(Debug, Clone)]
+pub struct CursorPage {
+    pub columns: Vec<String>,
+    pub rows: Vec<HashMap<String, JsonValue>>,
+    /// More pages can be fetched from the cursor
+    pub has_more: bool,
+    /// The row cap was reached before the result set ended
+    pub truncated: bool,
+}
+
+/// Paging limits for a streamed query
+#[derive(Debug, Clone, Copy)]
+pub struct QueryLimits {
+    /// Rows per page
+    pub page_size: usize,
+    /// Maximum rows delivered in total
+    pub max_rows: u64,
+}
+
+struct CursorEntry {
+    receiver: mpsc::Receiver<AppResult<CursorPage>>,
+    last_used: Instant,
+}
+
+type CursorMap = Arc<Mutex<HashMap<String, Arc<Mutex<CursorEntry>>>>>;
+
+/// Registry of open query cursors
+#[derive(Clone)]
+pub struct QueryCursorService {
+    cursors: CursorMap,
+    idle_timeout: Duration,
+}
+
+impl QueryCursorService {
+    /// Create an empty cursor registry
+    pub fn new() -> Self {
+        Self::with_idle_timeout(CURSOR_IDLE_TIMEOUT)
+    }
+
+    fn with_idle_timeout(idle_timeout: Duration) -> Self {
+        Self {
+            cursors: Arc::new(Mutex::new(HashMap::new())),
+            idle_timeout,
+        }
+    }
+
+    /// Start streaming a row-returning query on the connection of a
+    /// registered execution and return its first page. The execution stays
+    /// registered, and so cancellable, until the stream ends.
+    ///
+    /// A cursor id is returned only when more pages are available.
+    pub async fn open(
+        &self,
+        execution: ExecutionGuard,
+        database: &str,
+        query: &str,
+        limits: QueryLimits,
+    ) -> AppResult<(Option<String>, CursorPage)> {
+        let (tx, mut rx) = mpsc::channel(1);
+        tokio::spawn(stream_pages(
+            execution,
+            database.to_string(),
+            query.to_string(),
+            limits.page_size.max(1),
+            limits.max_rows.max(1),
+            tx,
+        ));
+
+        let first = rx
+            .recv()
+            .await
+            .ok_or_else(|| AppError::Internal("Query stream ended unexpectedly".to_string()))??;
+
+        if !first.has_more {
+            return Ok((None, first));
+        }
+
+        Ok((Some(self.register(rx).await), first))
+    }
+
+    /// Track a page channel under a new cursor id until it is closed or idle
+    async fn register(&self, receiver: mpsc::Receiver<AppResult<CursorPage>>) -> String {
+        let cursor_id = uuid::Uuid::new_v4().to_string();
+        self.cursors.lock().await.insert(
+            cursor_id.clone(),
+            Arc::new(Mutex::new(CursorEntry {
+                receiver,
+                last_used: Instant::now(),
+            })),
+        );
+        tokio::spawn(watch_idle(
+            self.cursors.clone(),
+            cursor_id.clone(),
+            self.idle_timeout,
+        ));
+        cursor_id
+    }
+
+    /// Fetch the next page of a cursor; exhausted cursors are closed
+    pub async fn fetch(&self, cursor_id: &str) -> AppResult<CursorPage> {
+        let entry = self
+            .cursors
+            .lock()
+            .await
+            .get(cursor_id)
+            .cloned()
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Cursor not found or expired: {}", cursor_id))
+            })?;
+
+        let page = {
+            let mut entry = entry.lock().await;
+            entry.last_used = Instant::now();
+            entry.receiver.recv().await
+        };
+
+        match page {
+            Some(Ok(page)) => {
+                if !page.has_more {
+                    self.close(cursor_id).await;
+                }
+                Ok(page)
+            }
+            Some(Err(e)) => {
+                self.close(cursor_id).await;
+                Err(e)
+            }
+            None => {
+                self.close(cursor_id).await;
+                Err(AppError::NotFound(format!(
+                    "Cursor exhausted: {}",
+                    cursor_id
+                )))
+            }
+        }
+    }
+
+    /// Close a cursor, stopping its query. Returns false if it was not open.
+    pub async fn close(&self, cursor_id: &str) -> bool {
+        self.cursors.lock().await.remove(cursor_id).is_some()
+    }
+}
+
+impl Default for QueryCursorService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Close a cursor once it has not been read for `idle_timeout`. Dropping the
+/// receiver makes the streaming task stop and close its connection.
+async fn watch_idle(cursors: CursorMap, cursor_id: String, idle_timeout: Duration) {
+    loop {
+        let Some(entry) = cursors.lock().await.get(&cursor_id).cloned() else {
+            return;
+        };
+
+        let wait = match entry.try_lock() {
+            Ok(cursor) => {
+                let idle = cursor.last_used.elapsed();
+                if idle >= idle_timeout {
+                    cursors.lock().await.remove(&cursor_id);
+                    log::info!(
+                        "Closing query cursor {} after {}s idle",
+                        cursor_id,
+                        idle.as_secs()
+                    );
+                    return;
+                }
+                idle_timeout - idle
+            }
+            // Being read right now
+            Err(_) => idle_timeout,
+        };
+
+        drop(entry);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Background task feeding a cursor's channel
+async fn stream_pages(
+    mut execution: ExecutionGuard,
+    database: String,
+    query: String,
+    page_size: usize,
+    max_rows: u64,
+    tx: mpsc::Sender<AppResult<CursorPage>>,
+) {
+    let outcome = match execution.conn() {
+        Ok(conn) => produce_pages(conn, &database, &query, page_size, max_rows, &tx).await,
+        Err(e) => Err(e),
+    };
+    match outcome {
+        // Rows were left unread, so the connection must not be reused
+        Ok(true) => {
+            if let Ok(conn) = execution.conn() {
+                conn.close_on_drop();
+            }
+        }
+        Ok(false) => {}
+        Err(e) => {
+            let _ = tx.send(Err(execution.map_error(e))).await;
+        }
+    }
+}
+
+/// Stream the query into pages until the result ends, the row cap is hit or
+/// the cursor is closed. Returns true if rows were left unread.
+async fn produce_pages(
+    conn: &mut PoolConnection<MySql>,
+    database: &str,
+    query: &str,
+    page_size: usize,
+    max_rows: u64,
+    tx: &mpsc::Sender<AppResult<CursorPage>>,
+) -> AppResult<bool> {
+    conn.execute(sqlx::raw_sql(&format!("USE {}", quote_name(database))))
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let mut stream = conn.fetch(sqlx::raw_sql(query));
+    let mut next: Option<MySqlRow> = stream
+        .try_next()
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    let mut columns: Option<Vec<String>> = None;
+    let mut delivered = 0u64;
+
+    loop {
+        let limit = (page_size as u64).min(max_rows - delivered) as usize;
+        let mut rows = Vec::with_capacity(limit);
+        while rows.len() < limit {
+            match next.take() {
+                Some(row) => {
+                    rows.push(row);
+                    next = stream
+                        .try_next()
+                        .await
+                        .map_err(|e| AppError::Database(e.to_string()))?;
+                }
+                None => break,
+            }
+        }
+        delivered += rows.len() as u64;
+
+        let more = next.is_some();
+        let truncated = more && delivered >= max_rows;
+        let has_more = more && !truncated;
+
+        let (page_columns, json_rows) = mysql_rows_to_json(&rows);
+        drop(rows);
+        let columns = columns.get_or_insert(page_columns).clone();
+
+        let page = CursorPage {
+            columns,
+            rows: json_rows,
+            has_more,
+            truncated,
+        };
+        if tx.send(Ok(page)).await.is_err() {
+            // Cursor closed or expired
+            return Ok(more);
+        }
+        if !has_more {
+            return Ok(truncated);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(has_more: bool) -> CursorPage {
+        CursorPage {
+            columns: vec!["id".to_string()],
+            rows: Vec::new(),
+            has_more,
+            truncated: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cursor_fetch_and_close() {
+        let service = QueryCursorService::new();
+        let (tx, rx) = mpsc::channel(1);
+        let cursor_id = service.register(rx).await;
+
+        tx.send(Ok(page(true))).await.unwrap();
+        assert!(service.fetch(&cursor_id).await.unwrap().has_more);
+
+        assert!(service.close(&cursor_id).await);
+        assert!(!service.close(&cursor_id).await);
+        // The streaming task sees the closed channel and stops
+        assert!(tx.is_closed());
+        assert!(matches!(
+            service.fetch(&cursor_id).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_idle_cursor_expires() {
+        let service = QueryCursorService::with_idle_timeout(Duration::from_millis(20));
+        let (tx, rx) = mpsc::channel::<AppResult<CursorPage>>(1);
+        let cursor_id = service.register(rx).await;
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(tx.is_closed());
+        assert!(!service.close(&cursor_id).await);
+    }
+}
@@ -0,0 +1,333 @@
+//! Registry of running MySQL query executions
+//!
+//! Each `execute_query` call registers the server thread id
+//! (`CONNECTION_ID()`) of the pooled connection it runs on, so the query can
+//! be aborted with `KILL QUERY` from a separate connection.
+//!
+//! A KILL must never reach a connection that has moved on to another query.
+//! Cancelling marks the execution under the registry lock, and the guard
+//! checks the mark only after unregistering under the same lock, so a
+//! connection is either never targeted or known to be. A targeted pooled
+//! connection is closed rather than returned to the pool; a session's
+//! connection waits for the KILL to finish before it is used again.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use sqlx::mysql::{MySql, MySqlPool};
+use sqlx::pool::PoolConnection;
+use sqlx::{Executor, Row};
+
+use crate::db::models::AddQueryHistoryRequest;
+use crate::db::SqlitePool;
+use crate::error::{AppError, AppResult};
+use crate::services::mysql::mysql_error_number;
+use crate::services::sql_splitter::classify_statement;
+
+/// MySQL error for a KILL of a thread that no longer exists
+const ER_NO_SUCH_THREAD: u16 = 1094;
+
+struct RunningQuery {
+    pool: MySqlPool,
+    thread_id: u64,
+    cancelled: Arc<AtomicBool>,
+    /// Held while a KILL QUERY is in flight
+    killing: Arc<tokio::sync::Mutex<()>>,
+}
+
+type ExecutionMap = Arc<Mutex<HashMap<String, RunningQuery>>>;
+
+/// Registry of queries that can be cancelled
+#[derive(Clone, Default)]
+pub struct QueryExecutionService {
+    executions: ExecutionMap,
+}
+
+/// Identity and cancellation state of a registered execution
+#[derive(Clone)]
+pub struct ExecutionHandle {
+    id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ExecutionHandle {
+    /// Execution identifier
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Whether the execution was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Replace an error caused by a cancellation with `AppError::Cancelled`
+    pub fn map_error(&self, err: AppError) -> AppError {
+        if self.is_cancelled() {
+            AppError::Cancelled(format!("Query {} was cancelled", self.id))
+        } else {
+            err
+        }
+    }
+}
+
+/// Registration of a running query; unregisters it when dropped
+pub struct ExecutionGuard {
+    handle: ExecutionHandle,
+    executions: ExecutionMap,
+    killing: Arc<tokio::sync::Mutex<()>>,
+    /// Pooled connection the query runs on, closed on drop if cancelled
+    conn: Option<PoolConnection<MySql>>,
+}
+
+impl ExecutionGuard {
+    /// Execution identifier
+    pub fn id(&self) -> &str {
+        self.handle.id()
+    }
+
+    /// Whether the execution was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.handle.is_cancelled()
+    }
+
+    /// Replace an error caused by a cancellation with `AppError::Cancelled`
+    pub fn map_error(&self, err: AppError) -> AppError {
+        self.handle.map_error(err)
+    }
+
+    /// Identity and cancellation state, usable while the connection is
+    /// borrowed
+    pub fn handle(&self) -> ExecutionHandle {
+        self.handle.clone()
+    }
+
+    /// The pooled connection the query runs on
+    pub fn conn(&mut self) -> AppResult<&mut PoolConnection<MySql>> {
+        let id = &self.handle.id;
+        self.conn
+            .as_mut()
+            .ok_or_else(|| AppError::Internal(format!("Execution {} has no connection", id)))
+    }
+
+    /// Unregister and wait for a KILL QUERY in flight, so that the
+    /// connection can safely run another query. Needed for connections
+    /// that outlive the execution, such as a session's.
+    pub async fn finish(mut self) {
+        self.unregister();
+        let _killed = self.killing.lock().await;
+    }
+
+    fn unregister(&mut self) {
+        if let Ok(mut executions) = self.executions.lock() {
+            executions.remove(&self.handle.id);
+        }
+    }
+}
+
+impl Drop for ExecutionGuard {
+    fn drop(&mut self) {
+        self.unregister();
+        // Checked after unregistering: a later cancel cannot find the query
+        if self.is_cancelled() {
+            if let Some(conn) = self.conn.as_mut() {
+                conn.close_on_drop();
+            }
+        }
+    }
+}
+
+impl QueryExecutionService {
+    /// Create an empty execution registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a query running on the given server thread.
+    ///
+    /// Uses the caller's execution id if given, so the client can cancel a
+    /// query before its result arrives; otherwise a new id is generated.
+    pub fn register(
+        &self,
+        execution_id: Option<String>,
+        pool: MySqlPool,
+        thread_id: u64,
+    ) -> AppResult<ExecutionGuard> {
+        let id = execution_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let killing = Arc::new(tokio::sync::Mutex::new(()));
+
+        let mut executions = self.lock()?;
+        if executions.contains_key(&id) {
+            return Err(AppError::Validation(format!(
+                "Execution id already in use: {}",
+                id
+            )));
+        }
+        executions.insert(
+            id.clone(),
+            RunningQuery {
+                pool,
+                thread_id,
+                cancelled: cancelled.clone(),
+                killing: killing.clone(),
+            },
+        );
+
+        Ok(ExecutionGuard {
+            handle: ExecutionHandle { id, cancelled },
+            executions: self.executions.clone(),
+            killing,
+            conn: None,
+        })
+    }
+
+    /// Register a query about to run on a pooled connection, which the guard
+    /// takes over and releases when dropped
+    pub async fn register_connection(
+        &self,
+        execution_id: Option<String>,
+        pool: MySqlPool,
+        mut conn: PoolConnection<MySql>,
+    ) -> AppResult<ExecutionGuard> {
+        let thread_id: u64 = conn
+            .fetch_one(sqlx::raw_sql("SELECT CONNECTION_ID()"))
+            .await
+            .and_then(|row| row.try_get(0))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let mut guard = self.register(execution_id, pool, thread_id)?;
+        guard.conn = Some(conn);
+        Ok(guard)
+    }
+
+    /// Ids of the executions currently running
+    pub fn running(&self) -> AppResult<Vec<String>> {
+        Ok(self.lock()?.keys().cloned().collect())
+    }
+
+    /// Abort a running query with `KILL QUERY` on a separate connection.
+    /// Returns false if the execution has already finished.
+    pub async fn cancel(&self, execution_id: &str) -> AppResult<bool> {
+        let (pool, thread_id, _killing) = {
+            let executions = self.lock()?;
+            let Some(running) = executions.get(execution_id) else {
+                return Ok(false);
+            };
+            running.cancelled.store(true, Ordering::Relaxed);
+            match running.killing.clone().try_lock_owned() {
+                Ok(killing) => (running.pool.clone(), running.thread_id, killing),
+                // Another cancel is sending the KILL
+                Err(_) => return Ok(true),
+            }
+        };
+
+        // The running query holds its own connection, so the pool hands out
+        // a different one here
+        match sqlx::query(&format!("KILL QUERY {}", thread_id))
+            .execute(&pool)
+            .await
+        {
+            Ok(_) => Ok(true),
+            // The query finished meanwhile and its connection was closed
+            Err(e) if mysql_error_number(&e) == Some(ER_NO_SUCH_THREAD) => Ok(true),
+            Err(e) => Err(AppError::Database(e.to_string())),
+        }
+    }
+
+    fn lock(&self) -> AppResult<std::sync::MutexGuard<'_, HashMap<String, RunningQuery>>> {
+        self.executions
+            .lock()
+            .map_err(|_| AppError::Internal("Query execution registry poisoned".to_string()))
+    }
+}
+
+/// Record a query stopped by a cancel in the query history, as distinct
+/// from a failed one
+pub async fn record_cancelled(
+    sqlite: &SqlitePool,
+    connection_id: i64,
+    database: &str,
+    query: &str,
+    duration_ms: u64,
+) -> AppResult<()> {
+    sqlite
+        .add_query_history(&AddQueryHistoryRequest {
+            connection_id,
+            database: database.to_string(),
+            query_type: classify_statement(query),
+            query_text: query.to_string(),
+            duration_ms: duration_ms as i64,
+            row_count: 0,
+            status: "cancelled".to_string(),
+            error_message: None,
+        })
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_register_and_unregister() {
+        let service = QueryExecutionService::new();
+        let pool = MySqlPool::connect_lazy("mysql://root@localhost:3306").unwrap();
+
+        let guard = service
+            .register(Some("exec-1".to_string()), pool.clone(), 42)
+            .unwrap();
+        assert_eq!(guard.id(), "exec-1");
+        assert_eq!(service.running().unwrap(), vec!["exec-1".to_string()]);
+        assert!(matches!(
+            service.register(Some("exec-1".to_string()), pool, 43),
+            Err(AppError::Validation(_))
+        ));
+
+        drop(guard);
+        assert!(service.running().unwrap().is_empty());
+        assert!(!service.cancel("exec-1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_execution_maps_errors() {
+        let service = QueryExecutionService::new();
+        let pool = MySqlPool::connect_lazy("mysql://root@localhost:3306").unwrap();
+        let guard = service.register(None, pool, 7).unwrap();
+
+        let err = guard.map_error(AppError::Database("Lost connection".to_string()));
+        assert!(matches!(err, AppError::Database(_)));
+
+        // What `cancel` does before sending KILL QUERY
+        service.executions.lock().unwrap()[guard.id()]
+            .cancelled
+            .store(true, Ordering::Relaxed);
+        assert!(guard.is_cancelled());
+        let err = guard.map_error(AppError::Database(
+            "Query execution was interrupted".to_string(),
+        ));
+        assert!(matches!(err, AppError::Cancelled(_)));
+    }
+
+    #[tokio::test]
+    async fn test_finish_waits_for_pending_kill() {
+        let service = QueryExecutionService::new();
+        let pool = MySqlPool::connect_lazy("mysql://root@localhost:3306").unwrap();
+        let guard = service.register(None, pool, 7).unwrap();
+        let id = guard.id().to_string();
+
+        // Stand in for a cancel whose KILL QUERY is still in flight
+        let killing = guard.killing.clone().try_lock_owned().unwrap();
+        assert!(service.cancel(&id).await.unwrap());
+        assert!(guard.is_cancelled());
+
+        let finish = tokio::spawn(guard.finish());
+        tokio::task::yield_now().await;
+        assert!(service.running().unwrap().is_empty());
+        assert!(!finish.is_finished());
+
+        drop(killing);
+        finish.await.unwrap();
+    }
+}
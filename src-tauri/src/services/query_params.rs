@@ -0,0 +1,450 @@
+//! Named query parameters
+//!
+//! Finds `:name` and `${name}` placeholders outside of string literals,
+//! identifiers and comments, and renders them either as `?` markers with
+//! typed bind values (for prepared statements) or as quoted literals for
+//! statements the server cannot prepare.
+
+use std::collections::HashMap;
+
+use serde_json::Value as JsonValue;
+
+use crate::db::models::{MysqlParameterizedQueryRequest, QueryParameter, QueryParameterType};
+use crate::db::SqlitePool;
+use crate::error::{AppError, AppResult};
+use crate::services::sql_identifiers::quote_identifier;
+use crate::services::sql_splitter::{
+    is_dash_comment, skip_block_comment, skip_line_comment, skip_quoted,
+};
+
+/// A value bound to a `?` marker
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindValue {
+    Null,
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Text(String),
+}
+
+/// A query with its placeholders substituted
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedQuery {
+    pub sql: String,
+    /// Values for the `?` markers, in order; empty when rendered inline
+    pub binds: Vec<BindValue>,
+}
+
+struct Placeholder {
+    name: String,
+    start: usize,
+    end: usize,
+}
+
+/// A parameterized query ready to run
+#[derive(Debug, Clone)]
+pub struct ResolvedQuery {
+    pub database: String,
+    pub query: String,
+    pub parameters: Vec<QueryParameter>,
+}
+
+/// Fill in the query text, database and declarations of a request from its
+/// saved query, if any. Values given in the request take precedence.
+pub async fn resolve_request(
+    pool: &SqlitePool,
+    req: &MysqlParameterizedQueryRequest,
+) -> AppResult<ResolvedQuery> {
+    let saved = match req.saved_query_id {
+        Some(id) => Some(pool.get_saved_query(id).await?),
+        None => None,
+    };
+
+    let query = req
+        .query
+        .clone()
+        .or_else(|| saved.as_ref().map(|s| s.query_text.clone()))
+        .ok_or_else(|| AppError::Validation("A query or saved query id is required".to_string()))?;
+    let database = req
+        .database
+        .clone()
+        .or_else(|| saved.as_ref().map(|s| s.database.clone()))
+        .ok_or_else(|| AppError::Validation("A database is required".to_string()))?;
+    let parameters = req
+        .parameters
+        .clone()
+        .or_else(|| saved.map(|s| s.parameters))
+        .unwrap_or_default();
+    validate_parameters(&parameters)?;
+
+    Ok(ResolvedQuery {
+        database,
+        query,
+        parameters,
+    })
+}
+
+/// Names of the placeholders used in a query, in order of first use
+pub fn extract_parameters(sql: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for placeholder in find_placeholders(sql) {
+        if !names.contains(&placeholder.name) {
+            names.push(placeholder.name);
+        }
+    }
+    names
+}
+
+/// Check declarations for invalid or duplicate names
+pub fn validate_parameters(parameters: &[QueryParameter]) -> AppResult<()> {
+    for (i, parameter) in parameters.iter().enumerate() {
+        let mut chars = parameter.name.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(AppError::Validation(format!(
+                "Invalid parameter name: {}",
+                parameter.name
+            )));
+        }
+        if parameters[..i].iter().any(|p| p.name == parameter.name) {
+            return Err(AppError::Validation(format!(
+                "Duplicate parameter: {}",
+                parameter.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Substitute placeholders with values, falling back to declared defaults.
+///
+/// With `inline` set every value is written as a quoted literal; otherwise
+/// values become `?` markers, except identifiers which are always inlined.
+/// Undeclared placeholders are treated as strings.
+pub fn render_query(
+    sql: &str,
+    parameters: &[QueryParameter],
+    values: &HashMap<String, JsonValue>,
+    inline: bool,
+) -> AppResult<RenderedQuery> {
+    let mut rendered = String::with_capacity(sql.len());
+    let mut binds = Vec::new();
+    let mut last = 0;
+
+    for placeholder in find_placeholders(sql) {
+        let declared = parameters.iter().find(|p| p.name == placeholder.name);
+        let param_type = declared.map(|p| p.param_type).unwrap_or_default();
+        let value = values
+            .get(&placeholder.name)
+            .or_else(|| declared.and_then(|p| p.default_value.as_ref()))
+            .ok_or_else(|| {
+                AppError::Validation(format!("Missing value for parameter: {}", placeholder.name))
+            })?;
+
+        rendered.push_str(&sql[last..placeholder.start]);
+        last = placeholder.end;
+
+        let bind_values = match param_type {
+            QueryParameterType::Identifier => {
+                let name = value
+                    .as_str()
+                    .ok_or_else(|| type_error(&placeholder.name, "an identifier string", value))?;
+                rendered.push_str(&quote_identifier(name));
+                continue;
+            }
+            QueryParameterType::List => {
+                let items = value
+                    .as_array()
+                    .filter(|items| !items.is_empty())
+                    .ok_or_else(|| type_error(&placeholder.name, "a non-empty array", value))?;
+                items
+                    .iter()
+                    .map(|item| list_item(&placeholder.name, item))
+                    .collect::<AppResult<Vec<_>>>()?
+            }
+            _ => vec![coerce(&placeholder.name, param_type, value)?],
+        };
+
+        let markers: Vec<String> = if inline {
+            bind_values.iter().map(literal).collect()
+        } else {
+            let markers = vec!["?".to_string(); bind_values.len()];
+            binds.extend(bind_values);
+            markers
+        };
+        rendered.push_str(&markers.join(", "));
+    }
+    rendered.push_str(&sql[last..]);
+
+    Ok(RenderedQuery {
+        sql: rendered,
+        binds,
+    })
+}
+
+/// Convert a JSON value to the declared parameter type
+fn coerce(name: &str, param_type: QueryParameterType, value: &JsonValue) -> AppResult<BindValue> {
+    if value.is_null() {
+        return Ok(BindValue::Null);
+    }
+
+    match param_type {
+        QueryParameterType::String => Ok(BindValue::Text(match value {
+            JsonValue::String(s) => s.clone(),
+            other => other.to_string(),
+        })),
+        QueryParameterType::Integer => value
+            .as_i64()
+            .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+            .map(BindValue::Integer)
+            .ok_or_else(|| type_error(name, "an integer", value)),
+        QueryParameterType::Decimal => match value {
+            JsonValue::Number(n) => n
+                .as_i64()
+                .map(BindValue::Integer)
+                .or_else(|| n.as_f64().map(BindValue::Float))
+                .ok_or_else(|| type_error(name, "a decimal", value)),
+            // Passed as text to keep the exact precision
+            JsonValue::String(s) if s.trim().parse::<f64>().is_ok() => {
+                Ok(BindValue::Text(s.trim().to_string()))
+            }
+            _ => Err(type_error(name, "a decimal", value)),
+        },
+        QueryParameterType::Boolean => match value {
+            JsonValue::Bool(b) => Ok(BindValue::Boolean(*b)),
+            JsonValue::Number(n) if n.as_i64() == Some(0) || n.as_i64() == Some(1) => {
+                Ok(BindValue::Boolean(n.as_i64() == Some(1)))
+            }
+            JsonValue::String(s) if s.eq_ignore_ascii_case("true") => Ok(BindValue::Boolean(true)),
+            JsonValue::String(s) if s.eq_ignore_ascii_case("false") => {
+                Ok(BindValue::Boolean(false))
+            }
+            _ => Err(type_error(name, "a boolean", value)),
+        },
+        QueryParameterType::Date => value
+            .as_str()
+            .filter(|s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok())
+            .map(|s| BindValue::Text(s.to_string()))
+            .ok_or_else(|| type_error(name, "a date (YYYY-MM-DD)", value)),
+        QueryParameterType::Datetime => value
+            .as_str()
+            .and_then(|s| {
+                [
+                    "%Y-%m-%d %H:%M:%S",
+                    "%Y-%m-%dT%H:%M:%S",
+                    "%Y-%m-%d %H:%M:%S%.f",
+                ]
+                .iter()
+                .find_map(|format| chrono::NaiveDateTime::parse_from_str(s, format).ok())
+            })
+            .map(|dt| BindValue::Text(dt.format("%Y-%m-%d %H:%M:%S%.f").to_string()))
+            .ok_or_else(|| type_error(name, "a datetime (YYYY-MM-DD HH:MM:SS)", value)),
+        QueryParameterType::Json => Ok(BindValue::Text(value.to_string())),
+        QueryParameterType::Identifier | QueryParameterType::List => {
+            Err(type_error(name, "a scalar", value))
+        }
+    }
+}
+
+/// SQL literal for a value converted to the given scalar type
+pub(crate) fn typed_literal(
+    name: &str,
+    param_type: QueryParameterType,
+    value: &JsonValue,
+) -> AppResult<String> {
+    coerce(name, param_type, value).map(|v| literal(&v))
+}
+
+/// Convert one element of a list parameter, keeping numbers numeric
+fn list_item(name: &str, value: &JsonValue) -> AppResult<BindValue> {
+    match value {
+        JsonValue::Number(n) => n
+            .as_i64()
+            .map(BindValue::Integer)
+            .or_else(|| n.as_f64().map(BindValue::Float))
+            .ok_or_else(|| type_error(name, "a list of scalars", value)),
+        JsonValue::Bool(b) => Ok(BindValue::Boolean(*b)),
+        JsonValue::String(s) => Ok(BindValue::Text(s.clone())),
+        JsonValue::Null => Ok(BindValue::Null),
+        _ => Err(type_error(name, "a list of scalars", value)),
+    }
+}
+
+fn type_error(name: &str, expected: &str, value: &JsonValue) -> AppError {
+    AppError::Validation(format!(
+        "Parameter {} must be {}, got {}",
+        name, expected, value
+    ))
+}
+
+/// SQL literal for a bind value
+fn literal(value: &BindValue) -> String {
+    match value {
+        BindValue::Null => "NULL".to_string(),
+        BindValue::Integer(i) => i.to_string(),
+        BindValue::Float(f) => f.to_string(),
+        BindValue::Boolean(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        BindValue::Text(s) => quote_string(s),
+    }
+}
+
+/// Quote a string literal that reads the same in every SQL mode. Quotes are
+/// doubled; a value with backslashes or control characters, which
+/// NO_BACKSLASH_ESCAPES would read differently, becomes a hex literal.
+pub fn quote_string(value: &str) -> String {
+    if value.chars().any(|c| c == '\\' || c.is_control()) {
+        let hex: String = value.bytes().map(|byte| format!("{:02X}", byte)).collect();
+        return format!("_utf8mb4 X'{}'", hex);
+    }
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Quote a string where the grammar only takes a plain literal, such as a
+/// COMMENT or an account name. Backslashes and control characters are
+/// escaped, which assumes NO_BACKSLASH_ESCAPES is off.
+pub fn quote_plain_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('\'');
+    for c in value.chars() {
+        match c {
+            '\'' => quoted.push_str("''"),
+            '\\' => quoted.push_str("\\\\"),
+            '\0' => quoted.push_str("\\0"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\x1a' => quoted.push_str("\\Z"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Locate placeholders outside of literals, quoted identifiers and comments
+fn find_placeholders(sql: &str) -> Vec<Placeholder> {
+    let chars: Vec<(usize, char)> = sql.char_indices().collect();
+    let plain: Vec<char> = chars.iter().map(|(_, c)| *c).collect();
+    let byte_at = |i: usize| chars.get(i).map(|(b, _)| *b).unwrap_or(sql.len());
+    let is_name_start = |c: char| c.is_ascii_alphabetic() || c == '_';
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let mut placeholders = Vec::new();
+    let mut i = 0;
+    while i < plain.len() {
+        let c = plain[i];
+        match c {
+            '\'' | '"' | '`' => i = skip_quoted(&plain, i),
+            '#' => i = skip_line_comment(&plain, i),
+            '-' if is_dash_comment(&plain, i) => i = skip_line_comment(&plain, i),
+            '/' if plain.get(i + 1) == Some(&'*') => i = skip_block_comment(&plain, i),
+            // `::` and `:=` are not placeholders
+            ':' if i > 0 && plain[i - 1] == ':' => i += 1,
+            ':' if plain.get(i + 1).is_some_and(|c| is_name_start(*c)) => {
+                let mut end = i + 1;
+                while end < plain.len() && is_name_char(plain[end]) {
+                    end += 1;
+                }
+                placeholders.push(Placeholder {
+                    name: plain[i + 1..end].iter().collect(),
+                    start: byte_at(i),
+                    end: byte_at(end),
+                });
+                i = end;
+            }
+            '$' if plain.get(i + 1) == Some(&'{') => {
+                let mut end = i + 2;
+                while end < plain.len() && is_name_char(plain[end]) {
+                    end += 1;
+                }
+                if end > i + 2 && plain.get(end) == Some(&'}') {
+                    placeholders.push(Placeholder {
+                        name: plain[i + 2..end].iter().collect(),
+                        start: byte_at(i),
+                        end: byte_at(end + 1),
+                    });
+                    i = end + 1;
+                } else {
+                    i += 1;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    placeholders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn param(name: &str, param_type: QueryParameterType) -> QueryParameter {
+        QueryParameter {
+            name: name.to_string(),
+            param_type,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_extract_parameters() {
+        let sql = "SELECT * FROM t WHERE a = :user_id AND b = '${skip}' -- :skip\n\
+                   AND c = ${tenant} AND d = :user_id AND e = x::int AND @v := 1";
+        assert_eq!(extract_parameters(sql), vec!["user_id", "tenant"]);
+    }
+
+    #[test]
+    fn test_render_query() {
+        let parameters = vec![
+            param("id", QueryParameterType::Integer),
+            param("ids", QueryParameterType::List),
+            param("tbl", QueryParameterType::Identifier),
+            QueryParameter {
+                default_value: Some(json!("it's")),
+                ..param("name", QueryParameterType::String)
+            },
+        ];
+        let values: HashMap<String, JsonValue> = [
+            ("id".to_string(), json!("42")),
+            ("ids".to_string(), json!([1, 2])),
+            ("tbl".to_string(), json!("app.us`ers")),
+        ]
+        .into_iter()
+        .collect();
+        let sql = "SELECT * FROM ${tbl} WHERE id = :id OR id IN (:ids) OR name = :name";
+
+        let bound = render_query(sql, &parameters, &values, false).unwrap();
+        assert_eq!(
+            bound.sql,
+            "SELECT * FROM `app`.`us``ers` WHERE id = ? OR id IN (?, ?) OR name = ?"
+        );
+        assert_eq!(
+            bound.binds,
+            vec![
+                BindValue::Integer(42),
+                BindValue::Integer(1),
+                BindValue::Integer(2),
+                BindValue::Text("it's".to_string()),
+            ]
+        );
+
+        let inline = render_query(sql, &parameters, &values, true).unwrap();
+        assert_eq!(
+            inline.sql,
+            "SELECT * FROM `app`.`us``ers` WHERE id = 42 OR id IN (1, 2) OR name = 'it''s'"
+        );
+
+        assert_eq!(quote_string("it's"), "'it''s'");
+        assert_eq!(quote_string("a\\b\n"), "_utf8mb4 X'615C620A'");
+        assert_eq!(quote_plain_string("it's\n"), "'it''s\\n'");
+
+        let missing = render_query("SELECT :other", &parameters, &values, false);
+        assert!(missing.is_err());
+        let bad = HashMap::from([("id".to_string(), json!("abc"))]);
+        assert!(render_query("SELECT :id", &parameters, &bad, false).is_err());
+    }
+}
@@ -5,17 +5,33 @@
 //! - Key management (CRUD operations)
 //! - TTL management
 //! - Export/Import
+//! - Key fingerprints and DUMP/RESTORE for instance comparison
+//! - ACL user management
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
 
 use redis::aio::ConnectionManager;
 use redis::{AsyncCommands, Client, Value as RedisValue};
 use serde_json::Value as JsonValue;
 
 use crate::db::models::{
-    Connection, RedisExportData, RedisKeyInfo, RedisKeyListResponse, RedisKeyValue,
-    RedisServerInfo, SetKeyRequest,
+    AclChannelPattern, AclCommandRule, AclKeyPattern, AclKeyPermission, Connection, RedisAclChange,
+    RedisAclDiff, RedisAclLogEntry, RedisAclUser, RedisAclUserSpec, RedisExportData, RedisKeyInfo,
+    RedisKeyListResponse, RedisKeyValue, RedisServerInfo, SetKeyRequest,
 };
 use crate::error::{AppError, AppResult};
 
+/// Type, TTL and value hash of a key, used to compare instances
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedisKeyFingerprint {
+    pub key_type: String,
+    pub ttl: i64,
+    /// Hash of the value, None when not computed or the type is unsupported
+    pub value_hash: Option<u64>,
+}
+
 /// Redis service for database operations
 pub struct RedisService {
     manager: ConnectionManager,
@@ -519,4 +535,942 @@ impl RedisService {
 
         Ok(imported)
     }
+
+    // ==================== Compare / Sync Helpers ====================
+
+    /// Scan all keys matching a pattern, returned sorted and de-duplicated
+    pub async fn scan_all_keys(&mut self, pattern: &str, count: u64) -> AppResult<Vec<String>> {
+        let pattern = if pattern.is_empty() { "*" } else { pattern };
+        let mut keys = BTreeSet::new();
+        let mut cursor: u64 = 0;
+
+        loop {
+            let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(count)
+                .query_async(&mut self.manager)
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?;
+
+            keys.extend(batch);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        Ok(keys.into_iter().collect())
+    }
+
+    /// Get type, TTL and (optionally) a hash of the key's value.
+    ///
+    /// Values are read as raw bytes and set/hash members are sorted, so the
+    /// hash is independent of the server's internal encoding.
+    pub async fn key_fingerprint(
+        &mut self,
+        key: &str,
+        with_value: bool,
+    ) -> AppResult<RedisKeyFingerprint> {
+        let key_type = self.get_key_type(key).await?;
+        let ttl = self.get_ttl(key).await.unwrap_or(-1);
+        let value_hash = if with_value && key_type != "none" {
+            self.value_hash(key, &key_type).await?
+        } else {
+            None
+        };
+
+        Ok(RedisKeyFingerprint {
+            key_type,
+            ttl,
+            value_hash,
+        })
+    }
+
+    /// Hash a key's value in a canonical form
+    async fn value_hash(&mut self, key: &str, key_type: &str) -> AppResult<Option<u64>> {
+        let mut hasher = DefaultHasher::new();
+
+        match key_type {
+            "string" => {
+                let v: Vec<u8> = redis::cmd("GET")
+                    .arg(key)
+                    .query_async(&mut self.manager)
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                v.hash(&mut hasher);
+            }
+            "list" => {
+                let v: Vec<Vec<u8>> = redis::cmd("LRANGE")
+                    .arg(key)
+                    .arg(0)
+                    .arg(-1)
+                    .query_async(&mut self.manager)
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                v.hash(&mut hasher);
+            }
+            "set" => {
+                let mut v: Vec<Vec<u8>> = redis::cmd("SMEMBERS")
+                    .arg(key)
+                    .query_async(&mut self.manager)
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                v.sort();
+                v.hash(&mut hasher);
+            }
+            "zset" => {
+                // ZRANGE order (score, then member) is already deterministic
+                let v: Vec<(Vec<u8>, f64)> = redis::cmd("ZRANGE")
+                    .arg(key)
+                    .arg(0)
+                    .arg(-1)
+                    .arg("WITHSCORES")
+                    .query_async(&mut self.manager)
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                for (member, score) in v {
+                    member.hash(&mut hasher);
+                    score.to_bits().hash(&mut hasher);
+                }
+            }
+            "hash" => {
+                let mut v: Vec<(Vec<u8>, Vec<u8>)> = redis::cmd("HGETALL")
+                    .arg(key)
+                    .query_async(&mut self.manager)
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                v.sort();
+                v.hash(&mut hasher);
+            }
+            "stream" => {
+                let v: RedisValue = redis::cmd("XRANGE")
+                    .arg(key)
+                    .arg("-")
+                    .arg("+")
+                    .query_async(&mut self.manager)
+                    .await
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                format!("{:?}", v).hash(&mut hasher);
+            }
+            _ => return Ok(None),
+        }
+
+        Ok(Some(hasher.finish()))
+    }
+
+    /// Serialize a key with DUMP, returns None if the key does not exist
+    pub async fn dump_key(&mut self, key: &str) -> AppResult<Option<Vec<u8>>> {
+        redis::cmd("DUMP")
+            .arg(key)
+            .query_async(&mut self.manager)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Get the remaining TTL of a key in milliseconds (-1 = no expiry, -2 = missing)
+    pub async fn get_pttl(&mut self, key: &str) -> AppResult<i64> {
+        redis::cmd("PTTL")
+            .arg(key)
+            .query_async(&mut self.manager)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Restore a key from a DUMP payload, replacing any existing value
+    pub async fn restore_key(&mut self, key: &str, ttl_ms: i64, payload: &[u8]) -> AppResult<()> {
+        let _: () = redis::cmd("RESTORE")
+            .arg(key)
+            .arg(ttl_ms.max(0))
+            .arg(payload)
+            .arg("REPLACE")
+            .query_async(&mut self.manager)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    // ==================== ACL Management ====================
+
+    /// List all ACL users (ACL LIST)
+    pub async fn acl_list(&mut self) -> AppResult<Vec<RedisAclUser>> {
+        let lines: Vec<String> = redis::cmd("ACL")
+            .arg("LIST")
+            .query_async(&mut self.manager)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        lines.iter().map(|line| parse_acl_list_line(line)).collect()
+    }
+
+    /// Get a single ACL user (ACL GETUSER)
+    pub async fn acl_get_user(&mut self, username: &str) -> AppResult<RedisAclUser> {
+        let value: RedisValue = redis::cmd("ACL")
+            .arg("GETUSER")
+            .arg(username)
+            .query_async(&mut self.manager)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        if matches!(value, RedisValue::Nil) {
+            return Err(AppError::NotFound(format!(
+                "ACL user not found: {}",
+                username
+            )));
+        }
+
+        Ok(parse_acl_getuser_reply(username, &value))
+    }
+
+    /// Diff the requested rules against the current user and apply them with
+    /// ACL SETUSER (skipped for dry runs or when nothing changed)
+    pub async fn acl_set_user(&mut self, spec: &RedisAclUserSpec) -> AppResult<RedisAclDiff> {
+        if spec.username.trim().is_empty() {
+            return Err(AppError::Validation("Username is required".to_string()));
+        }
+        if spec.nopass && !spec.add_passwords.is_empty() {
+            return Err(AppError::Validation(
+                "nopass cannot be combined with passwords".to_string(),
+            ));
+        }
+
+        let current = match self.acl_get_user(&spec.username).await {
+            Ok(user) => Some(user),
+            Err(AppError::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+
+        let (changes, rules) = diff_acl_user(current.as_ref(), spec);
+        let applied = !spec.dry_run && !rules.is_empty();
+
+        if applied {
+            let mut cmd = redis::cmd("ACL");
+            cmd.arg("SETUSER").arg(&spec.username);
+            for rule in &rules {
+                cmd.arg(rule);
+            }
+            let _: () = cmd
+                .query_async(&mut self.manager)
+                .await
+                .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+
+        Ok(RedisAclDiff {
+            username: spec.username.clone(),
+            user_exists: current.is_some(),
+            changes,
+            rules: rules.iter().map(|r| mask_acl_rule(r)).collect(),
+            applied,
+        })
+    }
+
+    /// Delete ACL users, returns the number of users deleted
+    pub async fn acl_del_user(&mut self, usernames: &[String]) -> AppResult<i64> {
+        if usernames.is_empty() {
+            return Ok(0);
+        }
+
+        redis::cmd("ACL")
+            .arg("DELUSER")
+            .arg(usernames)
+            .query_async(&mut self.manager)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Get the username of the current connection (ACL WHOAMI)
+    pub async fn acl_whoami(&mut self) -> AppResult<String> {
+        redis::cmd("ACL")
+            .arg("WHOAMI")
+            .query_async(&mut self.manager)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Get recent ACL security events (ACL LOG)
+    pub async fn acl_log(&mut self, count: Option<u64>) -> AppResult<Vec<RedisAclLogEntry>> {
+        let mut cmd = redis::cmd("ACL");
+        cmd.arg("LOG");
+        if let Some(count) = count {
+            cmd.arg(count);
+        }
+
+        let value: RedisValue = cmd
+            .query_async(&mut self.manager)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let entries = match value {
+            RedisValue::Array(items) | RedisValue::Set(items) => items,
+            _ => Vec::new(),
+        };
+
+        Ok(entries.iter().map(parse_acl_log_entry).collect())
+    }
+
+    /// Clear the ACL log (ACL LOG RESET)
+    pub async fn acl_log_reset(&mut self) -> AppResult<()> {
+        let _: () = redis::cmd("ACL")
+            .arg("LOG")
+            .arg("RESET")
+            .query_async(&mut self.manager)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
+
+// ==================== ACL Rule Parsing ====================
+
+/// Convert a scalar Redis reply into a string
+fn redis_value_to_string(value: &RedisValue) -> Option<String> {
+    match value {
+        RedisValue::BulkString(bytes) => Some(String::from_utf8_lossy(bytes).to_string()),
+        RedisValue::SimpleString(s) => Some(s.clone()),
+        RedisValue::VerbatimString { text, .. } => Some(text.clone()),
+        RedisValue::Okay => Some("OK".to_string()),
+        RedisValue::Int(i) => Some(i.to_string()),
+        RedisValue::Double(d) => Some(d.to_string()),
+        RedisValue::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Convert an array reply (or a single scalar) into a list of strings
+fn redis_value_strings(value: &RedisValue) -> Vec<String> {
+    match value {
+        RedisValue::Array(items) | RedisValue::Set(items) => {
+            items.iter().filter_map(redis_value_to_string).collect()
+        }
+        other => redis_value_to_string(other).into_iter().collect(),
+    }
+}
+
+/// Read a map reply, either a RESP3 map or a RESP2 flat key/value array
+fn redis_value_pairs(value: &RedisValue) -> Vec<(String, &RedisValue)> {
+    match value {
+        RedisValue::Map(pairs) => pairs
+            .iter()
+            .filter_map(|(k, v)| Some((redis_value_to_string(k)?, v)))
+            .collect(),
+        RedisValue::Array(items) => items
+            .chunks(2)
+            .filter_map(|chunk| match chunk {
+                [k, v] => Some((redis_value_to_string(k)?, v)),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Split an ACL rule string on whitespace, keeping selectors "(...)" intact
+fn tokenize_acl_rules(rules: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+
+    for ch in rules.chars() {
+        match ch {
+            '(' => {
+                depth += 1;
+                current.push(ch);
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                current.push(ch);
+            }
+            c if c.is_whitespace() && depth == 0 => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+/// Parse a key pattern rule: ~pattern, %R~pattern, %W~pattern or %RW~pattern
+fn parse_acl_key_pattern(token: &str) -> Option<AclKeyPattern> {
+    if let Some(pattern) = token.strip_prefix('~') {
+        return Some(AclKeyPattern {
+            pattern: pattern.to_string(),
+            permission: AclKeyPermission::All,
+        });
+    }
+
+    let (perm, pattern) = token.strip_prefix('%')?.split_once('~')?;
+    let permission = match perm.to_ascii_uppercase().as_str() {
+        "R" => AclKeyPermission::Read,
+        "W" => AclKeyPermission::Write,
+        "RW" | "WR" => AclKeyPermission::All,
+        _ => return None,
+    };
+
+    Some(AclKeyPattern {
+        pattern: pattern.to_string(),
+        permission,
+    })
+}
+
+/// Parse a command rule: +cmd, -cmd, +cmd|sub, +@category, -@category
+fn parse_acl_command_rule(token: &str) -> Option<AclCommandRule> {
+    let (allowed, rest) = match token.as_bytes().first()? {
+        b'+' => (true, &token[1..]),
+        b'-' => (false, &token[1..]),
+        _ => return None,
+    };
+    if rest.is_empty() {
+        return None;
+    }
+
+    Some(match rest.strip_prefix('@') {
+        Some(category) => AclCommandRule::Category {
+            category: category.to_lowercase(),
+            allowed,
+        },
+        None => AclCommandRule::Command {
+            command: rest.to_lowercase(),
+            allowed,
+        },
+    })
+}
+
+/// Parse an ACL rule string into a typed user
+fn parse_acl_rules(username: &str, rules: &str) -> RedisAclUser {
+    let mut user = RedisAclUser {
+        username: username.to_string(),
+        enabled: false,
+        nopass: false,
+        password_hashes: Vec::new(),
+        commands: Vec::new(),
+        keys: Vec::new(),
+        channels: Vec::new(),
+        flags: Vec::new(),
+        rules: rules.trim().to_string(),
+    };
+
+    for token in tokenize_acl_rules(rules) {
+        match token.to_ascii_lowercase().as_str() {
+            "on" => user.enabled = true,
+            "off" => user.enabled = false,
+            "nopass" => {
+                user.nopass = true;
+                user.password_hashes.clear();
+            }
+            "resetpass" => {
+                user.nopass = false;
+                user.password_hashes.clear();
+            }
+            "allkeys" => add_acl_key(
+                &mut user,
+                AclKeyPattern {
+                    pattern: "*".to_string(),
+                    permission: AclKeyPermission::All,
+                },
+            ),
+            "resetkeys" => user.keys.clear(),
+            "allchannels" => add_acl_channel(&mut user, "*"),
+            "resetchannels" => user.channels.clear(),
+            "allcommands" => user.commands.push(AclCommandRule::Category {
+                category: "all".to_string(),
+                allowed: true,
+            }),
+            "nocommands" => user.commands.push(AclCommandRule::Category {
+                category: "all".to_string(),
+                allowed: false,
+            }),
+            "reset" => {
+                user.enabled = false;
+                user.nopass = false;
+                user.password_hashes.clear();
+                user.commands.clear();
+                user.keys.clear();
+                user.channels.clear();
+                user.flags.clear();
+            }
+            _ => {
+                if let Some(hash) = token.strip_prefix('#') {
+                    user.nopass = false;
+                    user.password_hashes.push(hash.to_lowercase());
+                } else if let Some(hash) = token.strip_prefix('!') {
+                    let hash = hash.to_lowercase();
+                    user.password_hashes.retain(|h| *h != hash);
+                } else if token.starts_with('>') || token.starts_with('<') {
+                    // Plaintext passwords never appear in server output
+                } else if let Some(key) = parse_acl_key_pattern(&token) {
+                    add_acl_key(&mut user, key);
+                } else if let Some(channel) = token.strip_prefix('&') {
+                    add_acl_channel(&mut user, channel);
+                } else if let Some(rule) = parse_acl_command_rule(&token) {
+                    user.commands.push(rule);
+                } else {
+                    user.flags.push(token);
+                }
+            }
+        }
+    }
+
+    user
+}
+
+fn add_acl_key(user: &mut RedisAclUser, key: AclKeyPattern) {
+    if !user.keys.contains(&key) {
+        user.keys.push(key);
+    }
+}
+
+fn add_acl_channel(user: &mut RedisAclUser, pattern: &str) {
+    if !user.channels.iter().any(|c| c.pattern == pattern) {
+        user.channels.push(AclChannelPattern {
+            pattern: pattern.to_string(),
+        });
+    }
+}
+
+/// Parse one line of ACL LIST output ("user <name> <rules...>")
+fn parse_acl_list_line(line: &str) -> AppResult<RedisAclUser> {
+    let mut parts = line.trim().splitn(3, ' ');
+    match (parts.next(), parts.next()) {
+        (Some("user"), Some(name)) => Ok(parse_acl_rules(name, parts.next().unwrap_or(""))),
+        _ => Err(AppError::Internal(format!(
+            "Unexpected ACL LIST entry: {}",
+            line
+        ))),
+    }
+}
+
+/// Parse an ACL GETUSER reply.
+///
+/// Redis 6 returns keys and channels as arrays of bare patterns, Redis 7
+/// returns them as rule strings and adds selectors.
+fn parse_acl_getuser_reply(username: &str, value: &RedisValue) -> RedisAclUser {
+    let mut tokens: Vec<String> = Vec::new();
+
+    for (field, v) in redis_value_pairs(value) {
+        match field.as_str() {
+            // allkeys/allchannels/allcommands are also reported in their own fields
+            "flags" => tokens.extend(redis_value_strings(v).into_iter().filter(|f| {
+                !matches!(
+                    f.as_str(),
+                    "allkeys" | "allchannels" | "allcommands" | "nocommands"
+                )
+            })),
+            "passwords" => tokens.extend(
+                redis_value_strings(v)
+                    .into_iter()
+                    .map(|hash| format!("#{}", hash)),
+            ),
+            "commands" => tokens.extend(redis_value_strings(v)),
+            "keys" => match v {
+                RedisValue::Array(_) => tokens.extend(
+                    redis_value_strings(v)
+                        .into_iter()
+                        .map(|p| format!("~{}", p)),
+                ),
+                other => tokens.extend(redis_value_to_string(other)),
+            },
+            "channels" => match v {
+                RedisValue::Array(_) => tokens.extend(
+                    redis_value_strings(v)
+                        .into_iter()
+                        .map(|p| format!("&{}", p)),
+                ),
+                other => tokens.extend(redis_value_to_string(other)),
+            },
+            "selectors" => {
+                if let RedisValue::Array(selectors) = v {
+                    for selector in selectors {
+                        let parts: Vec<String> = redis_value_pairs(selector)
+                            .into_iter()
+                            .filter_map(|(_, rule)| redis_value_to_string(rule))
+                            .filter(|rule| !rule.is_empty())
+                            .collect();
+                        tokens.push(format!("({})", parts.join(" ")));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    parse_acl_rules(username, &tokens.join(" "))
+}
+
+/// Parse an ACL LOG entry
+fn parse_acl_log_entry(value: &RedisValue) -> RedisAclLogEntry {
+    let mut entry = RedisAclLogEntry::default();
+
+    for (field, v) in redis_value_pairs(value) {
+        let text = redis_value_to_string(v).unwrap_or_default();
+        match field.as_str() {
+            "count" => entry.count = text.parse().unwrap_or(0),
+            "reason" => entry.reason = text,
+            "context" => entry.context = text,
+            "object" => entry.object = text,
+            "username" => entry.username = text,
+            "age-seconds" => entry.age_seconds = text.parse().unwrap_or(0.0),
+            "client-info" => entry.client_info = text,
+            "entry-id" => entry.entry_id = text.parse().ok(),
+            "timestamp-created" => entry.timestamp_created = text.parse().ok(),
+            "timestamp-last-updated" => entry.timestamp_last_updated = text.parse().ok(),
+            _ => {}
+        }
+    }
+
+    entry
+}
+
+// ==================== ACL Rule Diff ====================
+
+fn acl_key_rule(key: &AclKeyPattern) -> String {
+    match key.permission {
+        AclKeyPermission::All => format!("~{}", key.pattern),
+        AclKeyPermission::Read => format!("%R~{}", key.pattern),
+        AclKeyPermission::Write => format!("%W~{}", key.pattern),
+    }
+}
+
+fn acl_channel_rule(channel: &AclChannelPattern) -> String {
+    format!("&{}", channel.pattern)
+}
+
+fn acl_command_rule(rule: &AclCommandRule) -> String {
+    match rule {
+        AclCommandRule::Category { category, allowed } => {
+            format!("{}@{}", if *allowed { '+' } else { '-' }, category)
+        }
+        AclCommandRule::Command { command, allowed } => {
+            format!("{}{}", if *allowed { '+' } else { '-' }, command)
+        }
+    }
+}
+
+/// Command rules as strings, dropping the leading -@all every user starts from
+fn normalized_acl_commands(rules: &[AclCommandRule]) -> Vec<String> {
+    let mut rules: Vec<String> = rules.iter().map(acl_command_rule).collect();
+    if rules.first().map(String::as_str) == Some("-@all") {
+        rules.remove(0);
+    }
+    rules
+}
+
+/// Hide plaintext passwords in rules returned to the caller
+fn mask_acl_rule(rule: &str) -> String {
+    match rule.chars().next() {
+        Some(prefix @ ('>' | '<')) => format!("{}***", prefix),
+        _ => rule.to_string(),
+    }
+}
+
+fn acl_change(
+    field: &str,
+    action: &str,
+    from: Option<String>,
+    to: Option<String>,
+) -> RedisAclChange {
+    RedisAclChange {
+        field: field.to_string(),
+        action: action.to_string(),
+        from,
+        to,
+    }
+}
+
+/// Diff key or channel patterns; only additions are applied incrementally,
+/// any removal resets the list and re-adds the requested patterns
+fn diff_acl_patterns(
+    field: &str,
+    reset_rule: &str,
+    current: &[String],
+    desired: &[String],
+    changes: &mut Vec<RedisAclChange>,
+    rules: &mut Vec<String>,
+) {
+    let added: Vec<&String> = desired.iter().filter(|p| !current.contains(p)).collect();
+    let removed: Vec<&String> = current.iter().filter(|p| !desired.contains(p)).collect();
+
+    for pattern in &removed {
+        changes.push(acl_change(field, "removed", Some((*pattern).clone()), None));
+    }
+    for pattern in &added {
+        changes.push(acl_change(field, "added", None, Some((*pattern).clone())));
+    }
+
+    if removed.is_empty() {
+        rules.extend(added.into_iter().cloned());
+    } else {
+        rules.push(reset_rule.to_string());
+        rules.extend(desired.iter().cloned());
+    }
+}
+
+/// Compute the changes and ACL SETUSER rules needed to move `current` to `spec`
+fn diff_acl_user(
+    current: Option<&RedisAclUser>,
+    spec: &RedisAclUserSpec,
+) -> (Vec<RedisAclChange>, Vec<String>) {
+    let mut changes = Vec::new();
+    let mut rules = Vec::new();
+
+    let desired_keys: Vec<String> = spec.keys.iter().map(acl_key_rule).collect();
+    let desired_channels: Vec<String> = spec.channels.iter().map(acl_channel_rule).collect();
+    let desired_commands = normalized_acl_commands(&spec.commands);
+
+    let Some(current) = current else {
+        // New user: start from a clean slate so server defaults don't leak in
+        rules.push("reset".to_string());
+        rules.push(if spec.enabled { "on" } else { "off" }.to_string());
+        changes.push(acl_change(
+            "enabled",
+            "added",
+            None,
+            Some(spec.enabled.to_string()),
+        ));
+        if spec.nopass {
+            rules.push("nopass".to_string());
+            changes.push(acl_change(
+                "nopass",
+                "added",
+                None,
+                Some("true".to_string()),
+            ));
+        }
+        for password in &spec.add_passwords {
+            rules.push(format!(">{}", password));
+            changes.push(acl_change(
+                "passwords",
+                "added",
+                None,
+                Some("***".to_string()),
+            ));
+        }
+        diff_acl_patterns(
+            "keys",
+            "resetkeys",
+            &[],
+            &desired_keys,
+            &mut changes,
+            &mut rules,
+        );
+        diff_acl_patterns(
+            "channels",
+            "resetchannels",
+            &[],
+            &desired_channels,
+            &mut changes,
+            &mut rules,
+        );
+        if !desired_commands.is_empty() {
+            changes.push(acl_change(
+                "commands",
+                "added",
+                None,
+                Some(desired_commands.join(" ")),
+            ));
+            rules.extend(desired_commands);
+        }
+        return (changes, rules);
+    };
+
+    if current.enabled != spec.enabled {
+        rules.push(if spec.enabled { "on" } else { "off" }.to_string());
+        changes.push(acl_change(
+            "enabled",
+            "changed",
+            Some(current.enabled.to_string()),
+            Some(spec.enabled.to_string()),
+        ));
+    }
+
+    if spec.nopass && !current.nopass {
+        rules.push("nopass".to_string());
+        changes.push(acl_change(
+            "nopass",
+            "added",
+            None,
+            Some("true".to_string()),
+        ));
+    } else if !spec.nopass && current.nopass {
+        rules.push("resetpass".to_string());
+        changes.push(acl_change(
+            "nopass",
+            "removed",
+            Some("true".to_string()),
+            None,
+        ));
+    }
+
+    for password in &spec.add_passwords {
+        rules.push(format!(">{}", password));
+        changes.push(acl_change(
+            "passwords",
+            "added",
+            None,
+            Some("***".to_string()),
+        ));
+    }
+    if !spec.nopass {
+        for hash in &spec.remove_password_hashes {
+            let hash = hash.to_lowercase();
+            if current.password_hashes.contains(&hash) {
+                rules.push(format!("!{}", hash));
+                changes.push(acl_change("passwords", "removed", Some(hash), None));
+            }
+        }
+    }
+
+    let current_keys: Vec<String> = current.keys.iter().map(acl_key_rule).collect();
+    diff_acl_patterns(
+        "keys",
+        "resetkeys",
+        &current_keys,
+        &desired_keys,
+        &mut changes,
+        &mut rules,
+    );
+
+    let current_channels: Vec<String> = current.channels.iter().map(acl_channel_rule).collect();
+    diff_acl_patterns(
+        "channels",
+        "resetchannels",
+        &current_channels,
+        &desired_channels,
+        &mut changes,
+        &mut rules,
+    );
+
+    let current_commands = normalized_acl_commands(&current.commands);
+    if current_commands != desired_commands {
+        changes.push(acl_change(
+            "commands",
+            "changed",
+            Some(current_commands.join(" ")),
+            Some(desired_commands.join(" ")),
+        ));
+        if desired_commands.starts_with(&current_commands) {
+            rules.extend(desired_commands[current_commands.len()..].iter().cloned());
+        } else {
+            rules.push("-@all".to_string());
+            rules.extend(desired_commands);
+        }
+    }
+
+    (changes, rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(username: &str) -> RedisAclUserSpec {
+        RedisAclUserSpec {
+            username: username.to_string(),
+            enabled: true,
+            nopass: false,
+            add_passwords: Vec::new(),
+            remove_password_hashes: Vec::new(),
+            commands: Vec::new(),
+            keys: Vec::new(),
+            channels: Vec::new(),
+            dry_run: true,
+        }
+    }
+
+    fn bulk(s: &str) -> RedisValue {
+        RedisValue::BulkString(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_parse_acl_list_line() {
+        let user = parse_acl_list_line(
+            "user app on #5e884898da sanitize-payload ~cache:* %R~report:* resetchannels &events:* -@all +@read +config|get (~tmp:* +set)",
+        )
+        .unwrap();
+
+        assert_eq!(user.username, "app");
+        assert!(user.enabled);
+        assert_eq!(user.password_hashes, vec!["5e884898da"]);
+        assert_eq!(user.keys.len(), 2);
+        assert_eq!(user.keys[1].permission, AclKeyPermission::Read);
+        assert_eq!(user.channels[0].pattern, "events:*");
+        assert_eq!(
+            user.commands[2],
+            AclCommandRule::Command {
+                command: "config|get".to_string(),
+                allowed: true
+            }
+        );
+        assert_eq!(user.flags, vec!["sanitize-payload", "(~tmp:* +set)"]);
+    }
+
+    #[test]
+    fn test_parse_getuser_resp2_reply() {
+        let reply = RedisValue::Array(vec![
+            bulk("flags"),
+            RedisValue::Array(vec![bulk("on"), bulk("allkeys")]),
+            bulk("passwords"),
+            RedisValue::Array(vec![]),
+            bulk("commands"),
+            bulk("+@all"),
+            bulk("keys"),
+            RedisValue::Array(vec![bulk("*")]),
+        ]);
+
+        let user = parse_acl_getuser_reply("default", &reply);
+        assert!(user.enabled);
+        assert_eq!(user.keys.len(), 1);
+        assert_eq!(user.keys[0].pattern, "*");
+        assert_eq!(normalized_acl_commands(&user.commands), vec!["+@all"]);
+    }
+
+    #[test]
+    fn test_diff_new_user_starts_from_reset() {
+        let mut desired = spec("app");
+        desired.add_passwords = vec!["secret".to_string()];
+        desired.keys = vec![AclKeyPattern {
+            pattern: "app:*".to_string(),
+            permission: AclKeyPermission::All,
+        }];
+
+        let (_, rules) = diff_acl_user(None, &desired);
+        assert_eq!(rules, vec!["reset", "on", ">secret", "~app:*"]);
+        assert_eq!(mask_acl_rule(&rules[2]), ">***");
+    }
+
+    #[test]
+    fn test_diff_existing_user() {
+        let current = parse_acl_rules("app", "on nopass ~a:* ~b:* resetchannels -@all +get");
+
+        // Identical state produces no rules
+        let mut same = spec("app");
+        same.nopass = true;
+        same.keys = current.keys.clone();
+        same.commands = vec![parse_acl_command_rule("+get").unwrap()];
+        let (changes, rules) = diff_acl_user(Some(&current), &same);
+        assert!(changes.is_empty());
+        assert!(rules.is_empty());
+
+        // Removing a key pattern resets keys, appending a command is incremental
+        let mut desired = same.clone();
+        desired.keys.truncate(1);
+        desired
+            .commands
+            .push(parse_acl_command_rule("+set").unwrap());
+        let (_, rules) = diff_acl_user(Some(&current), &desired);
+        assert_eq!(rules, vec!["resetkeys", "~a:*", "+set"]);
+
+        // Replacing commands rewrites them from -@all
+        let mut desired = same.clone();
+        desired.commands = vec![parse_acl_command_rule("+@read").unwrap()];
+        let (_, rules) = diff_acl_user(Some(&current), &desired);
+        assert_eq!(rules, vec!["-@all", "+@read"]);
+    }
 }
@@ -0,0 +1,302 @@
+//! Redis instance comparison service
+//!
+//! Compares the keys of two Redis instances (or two DBs on one instance) by
+//! type, TTL bucket and value hash, and applies the resulting sync plan by
+//! copying keys from source to target.
+
+use std::collections::HashSet;
+
+use crate::db::models::{
+    Connection, RedisApplySyncPlanRequest, RedisCompareReport, RedisCompareRequest,
+    RedisKeyDifference, RedisSyncFailure, RedisSyncPlan, RedisSyncResult, SetKeyRequest,
+};
+use crate::error::AppResult;
+use crate::services::jobs::JobContext;
+use crate::services::redis::{RedisKeyFingerprint, RedisService};
+
+/// Default SCAN COUNT hint
+const DEFAULT_SCAN_COUNT: u64 = 500;
+
+/// Default number of sample keys reported per category
+const DEFAULT_SAMPLE_LIMIT: usize = 20;
+
+/// How often (in keys) progress is reported
+const PROGRESS_INTERVAL: usize = 100;
+
+/// Compares and syncs two Redis databases
+pub struct RedisCompareService {
+    source: RedisService,
+    target: RedisService,
+}
+
+impl RedisCompareService {
+    /// Create a compare service from connected source and target services
+    pub fn new(source: RedisService, target: RedisService) -> Self {
+        Self { source, target }
+    }
+
+    /// Copy of a connection pointing at another DB index
+    pub fn with_database(conn: &Connection, db: Option<i64>) -> Connection {
+        let mut conn = conn.clone();
+        if let Some(db) = db {
+            conn.database_name = Some(db.to_string());
+        }
+        conn
+    }
+
+    /// Scan both sides and report missing, extra and differing keys
+    pub async fn compare(
+        &mut self,
+        req: &RedisCompareRequest,
+        ctx: &JobContext,
+    ) -> AppResult<RedisCompareReport> {
+        let count = req.scan_count.unwrap_or(DEFAULT_SCAN_COUNT).max(1);
+        let sample_limit = req.sample_limit.unwrap_or(DEFAULT_SAMPLE_LIMIT);
+
+        ctx.set_message("Scanning source keys").await;
+        let source_keys = self.source.scan_all_keys(&req.pattern, count).await?;
+        ctx.check_cancelled()?;
+
+        ctx.set_message("Scanning target keys").await;
+        let target_keys = self.target.scan_all_keys(&req.pattern, count).await?;
+        ctx.check_cancelled()?;
+
+        let source_set: HashSet<&String> = source_keys.iter().collect();
+        let target_set: HashSet<&String> = target_keys.iter().collect();
+
+        let missing: Vec<String> = source_keys
+            .iter()
+            .filter(|k| !target_set.contains(k))
+            .cloned()
+            .collect();
+        let extra: Vec<String> = target_keys
+            .iter()
+            .filter(|k| !source_set.contains(k))
+            .cloned()
+            .collect();
+        let common: Vec<&String> = source_keys
+            .iter()
+            .filter(|k| target_set.contains(k))
+            .collect();
+
+        ctx.set_message("Comparing keys").await;
+        ctx.set_progress(0, Some(common.len() as u64)).await;
+
+        let mut matching_count = 0u64;
+        let mut differing_keys = Vec::new();
+        let mut differing_samples = Vec::new();
+
+        for (i, key) in common.iter().enumerate() {
+            ctx.check_cancelled()?;
+
+            let source = self.source.key_fingerprint(key, !req.skip_values).await?;
+            let target = self.target.key_fingerprint(key, !req.skip_values).await?;
+            let differences = fingerprint_differences(&source, &target);
+
+            if differences.is_empty() {
+                matching_count += 1;
+            } else {
+                if differing_samples.len() < sample_limit {
+                    differing_samples.push(RedisKeyDifference {
+                        key: (*key).clone(),
+                        differences,
+                        source_type: source.key_type,
+                        target_type: target.key_type,
+                        source_ttl: source.ttl,
+                        target_ttl: target.ttl,
+                    });
+                }
+                differing_keys.push((*key).clone());
+            }
+
+            if (i + 1) % PROGRESS_INTERVAL == 0 {
+                ctx.set_progress((i + 1) as u64, None).await;
+            }
+        }
+        ctx.set_progress(common.len() as u64, None).await;
+
+        let sync_plan = req.include_sync_plan.then(|| RedisSyncPlan {
+            source_connection_id: req.source_connection_id,
+            target_connection_id: req.target_connection_id,
+            source_db: req.source_db,
+            target_db: req.target_db,
+            copy_keys: missing
+                .iter()
+                .chain(differing_keys.iter())
+                .cloned()
+                .collect(),
+            delete_keys: extra.clone(),
+        });
+
+        Ok(RedisCompareReport {
+            source_key_count: source_keys.len() as u64,
+            target_key_count: target_keys.len() as u64,
+            matching_count,
+            missing_count: missing.len() as u64,
+            extra_count: extra.len() as u64,
+            differing_count: differing_keys.len() as u64,
+            missing_samples: missing.into_iter().take(sample_limit).collect(),
+            extra_samples: extra.into_iter().take(sample_limit).collect(),
+            differing_samples,
+            sync_plan,
+        })
+    }
+
+    /// Copy the plan's keys to the target and optionally delete extra keys
+    pub async fn apply_sync_plan(
+        &mut self,
+        req: &RedisApplySyncPlanRequest,
+        ctx: &JobContext,
+    ) -> AppResult<RedisSyncResult> {
+        let plan = &req.plan;
+        let delete_count = if req.delete_extra {
+            plan.delete_keys.len()
+        } else {
+            0
+        };
+        let total = (plan.copy_keys.len() + delete_count) as u64;
+
+        let mut result = RedisSyncResult {
+            copied: 0,
+            deleted: 0,
+            skipped: 0,
+            failures: Vec::new(),
+        };
+        let mut processed = 0u64;
+
+        ctx.set_message("Copying keys").await;
+        ctx.set_progress(0, Some(total)).await;
+
+        for key in &plan.copy_keys {
+            ctx.check_cancelled()?;
+            match self.copy_key(key).await {
+                Ok(true) => result.copied += 1,
+                Ok(false) => result.skipped += 1,
+                Err(e) => result.failures.push(RedisSyncFailure {
+                    key: key.clone(),
+                    error: e.to_string(),
+                }),
+            }
+            processed += 1;
+            if processed % PROGRESS_INTERVAL as u64 == 0 {
+                ctx.set_progress(processed, None).await;
+            }
+        }
+
+        if req.delete_extra {
+            ctx.set_message("Deleting extra keys").await;
+            for key in &plan.delete_keys {
+                ctx.check_cancelled()?;
+                match self.target.delete_key(key).await {
+                    Ok(()) => result.deleted += 1,
+                    Err(e) => result.failures.push(RedisSyncFailure {
+                        key: key.clone(),
+                        error: e.to_string(),
+                    }),
+                }
+                processed += 1;
+                if processed % PROGRESS_INTERVAL as u64 == 0 {
+                    ctx.set_progress(processed, None).await;
+                }
+            }
+        }
+
+        ctx.set_progress(processed, None).await;
+        Ok(result)
+    }
+
+    /// Copy one key with DUMP/RESTORE, falling back to a value-level copy when
+    /// the target rejects the payload (e.g. an older RDB version).
+    /// Returns false if the key no longer exists on the source.
+    async fn copy_key(&mut self, key: &str) -> AppResult<bool> {
+        let Some(payload) = self.source.dump_key(key).await? else {
+            return Ok(false);
+        };
+        let ttl_ms = self.source.get_pttl(key).await?;
+        if ttl_ms == -2 {
+            return Ok(false);
+        }
+
+        match self.target.restore_key(key, ttl_ms, &payload).await {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                log::warn!(
+                    "RESTORE of {} failed ({}), falling back to value copy",
+                    key,
+                    e
+                );
+                let kv = self.source.get_key(key).await?;
+                self.target
+                    .set_key(&SetKeyRequest {
+                        key: kv.key,
+                        key_type: kv.key_type,
+                        value: kv.value,
+                        ttl: if kv.ttl > 0 { Some(kv.ttl) } else { None },
+                    })
+                    .await?;
+                Ok(true)
+            }
+        }
+    }
+}
+
+/// Bucket a TTL so expiries that drift between the two scans still match
+fn ttl_bucket(ttl: i64) -> &'static str {
+    match ttl {
+        i64::MIN..=-2 => "missing",
+        -1 => "persistent",
+        0..=59 => "<1m",
+        60..=3599 => "<1h",
+        3600..=86399 => "<1d",
+        86400..=604799 => "<7d",
+        _ => ">=7d",
+    }
+}
+
+/// List what differs between two fingerprints of the same key
+fn fingerprint_differences(
+    source: &RedisKeyFingerprint,
+    target: &RedisKeyFingerprint,
+) -> Vec<String> {
+    let mut differences = Vec::new();
+
+    if source.key_type != target.key_type {
+        differences.push("type".to_string());
+    }
+    if ttl_bucket(source.ttl) != ttl_bucket(target.ttl) {
+        differences.push("ttl".to_string());
+    }
+    if source.key_type == target.key_type && source.value_hash != target.value_hash {
+        differences.push("value".to_string());
+    }
+
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(key_type: &str, ttl: i64, value_hash: Option<u64>) -> RedisKeyFingerprint {
+        RedisKeyFingerprint {
+            key_type: key_type.to_string(),
+            ttl,
+            value_hash,
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_differences() {
+        // TTLs in the same bucket match
+        let a = fingerprint("hash", 3700, Some(1));
+        let b = fingerprint("hash", 3650, Some(1));
+        assert!(fingerprint_differences(&a, &b).is_empty());
+
+        let b = fingerprint("hash", -1, Some(2));
+        assert_eq!(fingerprint_differences(&a, &b), vec!["ttl", "value"]);
+
+        // Values are not compared across types
+        let b = fingerprint("string", 3700, Some(2));
+        assert_eq!(fingerprint_differences(&a, &b), vec!["type"]);
+    }
+}
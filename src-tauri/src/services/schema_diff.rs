@@ -0,0 +1,1190 @@
+//! MySQL schema comparison
+//!
+//! Snapshots the tables, views, routines and triggers of two databases,
+//! reports how they differ and generates a script that migrates the target
+//! to the source schema. Statements are ordered so that dependent objects
+//! are dropped before the objects they use and created after them.
+
+use std::collections::{HashMap, HashSet};
+
+use sqlx::Row;
+
+use crate::db::models::{
+    ForeignKeyInfo, IndexInfo, MysqlColumn, SchemaCompareOptions, SchemaCompareReport,
+    SchemaCompareRequest, SchemaElementChange, SchemaObjectChange, TableDiff,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::mysql::MysqlService;
+use crate::services::query_params::{quote_plain_string, quote_string};
+use crate::services::sql_identifiers::quote_name;
+
+/// A column with the attributes `get_table_schema` does not report
+#[derive(Debug, Clone)]
+pub struct ColumnSnapshot {
+    pub column: MysqlColumn,
+    pub charset: Option<String>,
+    pub collation: Option<String>,
+    pub generation_expression: Option<String>,
+}
+
+type ColumnExtras = (Option<String>, Option<String>, Option<String>);
+
+/// Structure and options of one table
+#[derive(Debug, Clone, Default)]
+pub struct TableSnapshot {
+    pub name: String,
+    pub columns: Vec<ColumnSnapshot>,
+    pub indexes: Vec<IndexInfo>,
+    pub foreign_keys: Vec<ForeignKeyInfo>,
+    pub engine: Option<String>,
+    pub collation: Option<String>,
+    pub comment: Option<String>,
+    pub auto_increment: Option<u64>,
+}
+
+/// A view, routine or trigger with its CREATE statement
+#[derive(Debug, Clone)]
+pub struct ObjectSnapshot {
+    pub name: String,
+    /// view, procedure, function or trigger
+    pub object_type: String,
+    /// CREATE statement without DEFINER and database qualifiers
+    pub definition: String,
+}
+
+/// Everything compared in one database
+#[derive(Debug, Clone, Default)]
+pub struct SchemaSnapshot {
+    pub database: String,
+    pub tables: Vec<TableSnapshot>,
+    pub objects: Vec<ObjectSnapshot>,
+}
+
+/// Compares the schemas of two MySQL databases
+pub struct SchemaCompareService {
+    source: MysqlService,
+    target: MysqlService,
+}
+
+impl SchemaCompareService {
+    /// Create a compare service from connected source and target services
+    pub fn new(source: MysqlService, target: MysqlService) -> Self {
+        Self { source, target }
+    }
+
+    /// Snapshot both databases and diff them
+    pub async fn compare(&self, req: &SchemaCompareRequest) -> AppResult<SchemaCompareReport> {
+        let source = snapshot(&self.source, &req.source_database).await?;
+        let target = snapshot(&self.target, &req.target_database).await?;
+        Ok(compare_snapshots(&source, &target, &req.options))
+    }
+}
+
+/// Read the schema of a database
+pub async fn snapshot(mysql: &MysqlService, database: &str) -> AppResult<SchemaSnapshot> {
+    let table_rows = sqlx::query(
+        "SELECT TABLE_NAME, TABLE_COLLATION, AUTO_INCREMENT FROM information_schema.TABLES \
+         WHERE TABLE_SCHEMA = ? AND TABLE_TYPE = 'BASE TABLE'",
+    )
+    .bind(database)
+    .fetch_all(mysql.pool())
+    .await
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    let table_options: HashMap<String, (Option<String>, Option<u64>)> = table_rows
+        .iter()
+        .map(|row| {
+            (
+                MysqlService::get_string_from_row(row, "TABLE_NAME"),
+                (
+                    MysqlService::get_optional_string_from_row(row, "TABLE_COLLATION"),
+                    row.try_get::<Option<u64>, _>("AUTO_INCREMENT")
+                        .ok()
+                        .flatten(),
+                ),
+            )
+        })
+        .collect();
+
+    let column_rows = sqlx::query(
+        "SELECT TABLE_NAME, COLUMN_NAME, CHARACTER_SET_NAME, COLLATION_NAME, GENERATION_EXPRESSION \
+         FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = ?",
+    )
+    .bind(database)
+    .fetch_all(mysql.pool())
+    .await
+    .map_err(|e| AppError::Database(e.to_string()))?;
+    // Character set, collation and generation expression by (table, column)
+    let mut column_extras: HashMap<(String, String), ColumnExtras> = column_rows
+        .iter()
+        .map(|row| {
+            (
+                (
+                    MysqlService::get_string_from_row(row, "TABLE_NAME"),
+                    MysqlService::get_string_from_row(row, "COLUMN_NAME"),
+                ),
+                (
+                    MysqlService::get_optional_string_from_row(row, "CHARACTER_SET_NAME"),
+                    MysqlService::get_optional_string_from_row(row, "COLLATION_NAME"),
+                    MysqlService::get_optional_string_from_row(row, "GENERATION_EXPRESSION")
+                        .filter(|e| !e.is_empty()),
+                ),
+            )
+        })
+        .collect();
+
+    let mut tables = Vec::new();
+    for table in mysql.list_tables(database).await? {
+        let schema = mysql.get_table_schema(database, &table.name).await?;
+        let columns = schema
+            .columns
+            .into_iter()
+            .map(|column| {
+                let (charset, collation, generation_expression) = column_extras
+                    .remove(&(table.name.clone(), column.name.clone()))
+                    .unwrap_or_default();
+                ColumnSnapshot {
+                    column,
+                    charset,
+                    collation,
+                    generation_expression,
+                }
+            })
+            .collect();
+        let (collation, auto_increment) =
+            table_options.get(&table.name).cloned().unwrap_or_default();
+
+        tables.push(TableSnapshot {
+            indexes: mysql.list_indexes(database, &table.name).await?,
+            foreign_keys: mysql.list_foreign_keys(database, &table.name).await?,
+            name: table.name,
+            columns,
+            engine: table.engine,
+            collation,
+            comment: table.comment.filter(|c| !c.is_empty()),
+            auto_increment,
+        });
+    }
+
+    let mut objects = Vec::new();
+    for view in mysql.list_views(database).await? {
+        let definition = mysql.get_view_definition(database, &view.name).await?;
+        objects.push(ObjectSnapshot {
+            name: view.name,
+            object_type: "view".to_string(),
+            definition: normalize_definition(&definition.definition, database),
+        });
+    }
+    for routine in mysql.list_procedures(database).await? {
+        let definition = mysql
+            .get_procedure_definition(database, &routine.name, &routine.routine_type)
+            .await?;
+        objects.push(ObjectSnapshot {
+            name: routine.name,
+            object_type: routine.routine_type.to_lowercase(),
+            definition: normalize_definition(&definition.definition, database),
+        });
+    }
+    for trigger in mysql.list_triggers(database).await? {
+        let definition = mysql
+            .get_trigger_definition(database, &trigger.name)
+            .await?;
+        objects.push(ObjectSnapshot {
+            name: trigger.name,
+            object_type: "trigger".to_string(),
+            definition: normalize_definition(&definition.definition, database),
+        });
+    }
+
+    Ok(SchemaSnapshot {
+        database: database.to_string(),
+        tables,
+        objects,
+    })
+}
+
+/// A migration statement; routine and trigger bodies need a custom delimiter
+struct Statement {
+    sql: String,
+    compound: bool,
+}
+
+impl Statement {
+    fn simple(sql: String) -> Self {
+        Self {
+            sql,
+            compound: false,
+        }
+    }
+}
+
+/// Diff two snapshots and build the migration script for the target
+pub fn compare_snapshots(
+    source: &SchemaSnapshot,
+    target: &SchemaSnapshot,
+    options: &SchemaCompareOptions,
+) -> SchemaCompareReport {
+    let source_tables: HashMap<&str, &TableSnapshot> =
+        source.tables.iter().map(|t| (t.name.as_str(), t)).collect();
+    let target_tables: HashMap<&str, &TableSnapshot> =
+        target.tables.iter().map(|t| (t.name.as_str(), t)).collect();
+
+    let mut added_tables: Vec<&TableSnapshot> = source
+        .tables
+        .iter()
+        .filter(|t| !target_tables.contains_key(t.name.as_str()))
+        .collect();
+    added_tables.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut removed_tables: Vec<&TableSnapshot> = target
+        .tables
+        .iter()
+        .filter(|t| !source_tables.contains_key(t.name.as_str()))
+        .collect();
+    removed_tables.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut modified_tables: Vec<(TableDiff, &TableSnapshot, &TableSnapshot)> = source
+        .tables
+        .iter()
+        .filter_map(|s| {
+            let t = target_tables.get(s.name.as_str())?;
+            diff_table(s, t, options).map(|diff| (diff, s, *t))
+        })
+        .collect();
+    modified_tables.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+
+    let source_objects: HashMap<(&str, &str), &ObjectSnapshot> = source
+        .objects
+        .iter()
+        .map(|o| ((o.object_type.as_str(), o.name.as_str()), o))
+        .collect();
+    let target_objects: HashMap<(&str, &str), &ObjectSnapshot> = target
+        .objects
+        .iter()
+        .map(|o| ((o.object_type.as_str(), o.name.as_str()), o))
+        .collect();
+
+    let mut objects = Vec::new();
+    let mut created_objects: Vec<&ObjectSnapshot> = Vec::new();
+    let mut dropped_objects: Vec<&ObjectSnapshot> = Vec::new();
+    let mut replaced_views: Vec<&ObjectSnapshot> = Vec::new();
+    for object in &source.objects {
+        let key = (object.object_type.as_str(), object.name.as_str());
+        let change = match target_objects.get(&key) {
+            None => "added",
+            Some(existing) if collapse(&existing.definition) != collapse(&object.definition) => {
+                if object.object_type == "view" {
+                    replaced_views.push(object);
+                } else {
+                    dropped_objects.push(existing);
+                }
+                "modified"
+            }
+            Some(_) => continue,
+        };
+        if object.object_type != "view" || change == "added" {
+            created_objects.push(object);
+        }
+        objects.push(SchemaObjectChange {
+            name: object.name.clone(),
+            object_type: object.object_type.clone(),
+            change: change.to_string(),
+        });
+    }
+    for object in &target.objects {
+        if !source_objects.contains_key(&(object.object_type.as_str(), object.name.as_str())) {
+            dropped_objects.push(object);
+            objects.push(SchemaObjectChange {
+                name: object.name.clone(),
+                object_type: object.object_type.clone(),
+                change: "removed".to_string(),
+            });
+        }
+    }
+    objects.sort_by(|a, b| (&a.object_type, &a.name).cmp(&(&b.object_type, &b.name)));
+
+    let mut statements: Vec<Statement> = Vec::new();
+
+    // Triggers and routines may reference anything, so they go first
+    for object in objects_of(&dropped_objects, &["trigger"]) {
+        statements.push(Statement::simple(format!(
+            "DROP TRIGGER IF EXISTS {}",
+            quote_name(&object.name)
+        )));
+    }
+    let dropped_views = objects_of(&dropped_objects, &["view"]);
+    for object in view_order(&dropped_views).into_iter().rev() {
+        statements.push(Statement::simple(format!(
+            "DROP VIEW IF EXISTS {}",
+            quote_name(&object.name)
+        )));
+    }
+    for object in objects_of(&dropped_objects, &["procedure", "function"]) {
+        statements.push(Statement::simple(format!(
+            "DROP {} IF EXISTS {}",
+            object.object_type.to_uppercase(),
+            quote_name(&object.name)
+        )));
+    }
+
+    // Foreign keys are dropped before the tables and indexes they use
+    let (removed_order, removed_cycles) = table_order(&removed_tables);
+    for (diff, _, existing) in &modified_tables {
+        for fk in &existing.foreign_keys {
+            if diff.removed_foreign_keys.contains(&fk.name)
+                || diff.modified_foreign_keys.iter().any(|c| c.name == fk.name)
+            {
+                statements.push(drop_foreign_key(&existing.name, &fk.name));
+            }
+        }
+    }
+    for table in &removed_cycles {
+        for fk in &table.foreign_keys {
+            statements.push(drop_foreign_key(&table.name, &fk.name));
+        }
+    }
+    for table in removed_order.iter().rev().chain(removed_cycles.iter()) {
+        statements.push(Statement::simple(format!(
+            "DROP TABLE IF EXISTS {}",
+            quote_name(&table.name)
+        )));
+    }
+
+    // New tables are created without foreign keys, which are added once
+    // every table exists
+    let (added_order, added_cycles) = table_order(&added_tables);
+    for table in added_order.iter().chain(added_cycles.iter()) {
+        statements.push(Statement::simple(create_table(table, options)));
+    }
+    for (diff, desired, existing) in &modified_tables {
+        if let Some(alter) = alter_table(diff, desired, existing, options) {
+            statements.push(Statement::simple(alter));
+        }
+    }
+    for table in added_order.iter().chain(added_cycles.iter()) {
+        for fk in &table.foreign_keys {
+            statements.push(add_foreign_key(&table.name, fk));
+        }
+    }
+    for (diff, desired, _) in &modified_tables {
+        for fk in &desired.foreign_keys {
+            if diff.added_foreign_keys.contains(&fk.name)
+                || diff.modified_foreign_keys.iter().any(|c| c.name == fk.name)
+            {
+                statements.push(add_foreign_key(&desired.name, fk));
+            }
+        }
+    }
+
+    let mut views = objects_of(&created_objects, &["view"]);
+    views.extend(replaced_views);
+    for view in view_order(&views) {
+        statements.push(Statement::simple(replace_create(
+            &view.definition,
+            "CREATE OR REPLACE ",
+        )));
+    }
+    for object in objects_of(&created_objects, &["procedure", "function", "trigger"]) {
+        statements.push(Statement {
+            sql: object.definition.clone(),
+            compound: true,
+        });
+    }
+
+    if !statements.is_empty() {
+        statements.insert(
+            0,
+            Statement::simple(format!("USE {}", quote_name(&target.database))),
+        );
+    }
+
+    SchemaCompareReport {
+        source_database: source.database.clone(),
+        target_database: target.database.clone(),
+        identical: statements.is_empty(),
+        added_tables: added_tables.iter().map(|t| t.name.clone()).collect(),
+        removed_tables: removed_tables.iter().map(|t| t.name.clone()).collect(),
+        modified_tables: modified_tables
+            .into_iter()
+            .map(|(diff, _, _)| diff)
+            .collect(),
+        objects,
+        script: render_script(&statements),
+        statements: statements.into_iter().map(|s| s.sql).collect(),
+    }
+}
+
+/// Differences between two versions of a table, or None if they match
+fn diff_table(
+    source: &TableSnapshot,
+    target: &TableSnapshot,
+    options: &SchemaCompareOptions,
+) -> Option<TableDiff> {
+    let mut diff = TableDiff {
+        name: source.name.clone(),
+        ..Default::default()
+    };
+
+    let (added, removed, modified) = diff_elements(
+        &source.columns,
+        &target.columns,
+        |c| c.column.name.as_str(),
+        |s, t| column_differences(s, t, options),
+    );
+    diff.added_columns = added;
+    diff.removed_columns = removed;
+    diff.modified_columns = modified;
+
+    let (added, removed, modified) = diff_elements(
+        &source.indexes,
+        &target.indexes,
+        |i| i.name.as_str(),
+        |s, t| index_differences(s, t, options),
+    );
+    diff.added_indexes = added;
+    diff.removed_indexes = removed;
+    diff.modified_indexes = modified;
+
+    let (added, removed, modified) = diff_elements(
+        &source.foreign_keys,
+        &target.foreign_keys,
+        |f| f.name.as_str(),
+        foreign_key_differences,
+    );
+    diff.added_foreign_keys = added;
+    diff.removed_foreign_keys = removed;
+    diff.modified_foreign_keys = modified;
+
+    let engine = |t: &TableSnapshot| t.engine.as_deref().map(str::to_lowercase);
+    push_difference(
+        &mut diff.option_changes,
+        "engine",
+        engine(source),
+        engine(target),
+    );
+    if !options.ignore_collation {
+        push_difference(
+            &mut diff.option_changes,
+            "collation",
+            source.collation.clone(),
+            target.collation.clone(),
+        );
+    }
+    if !options.ignore_comments {
+        push_difference(
+            &mut diff.option_changes,
+            "comment",
+            source.comment.clone(),
+            target.comment.clone(),
+        );
+    }
+    if !options.ignore_auto_increment && source.auto_increment.is_some() {
+        push_difference(
+            &mut diff.option_changes,
+            "auto_increment",
+            source.auto_increment,
+            target.auto_increment,
+        );
+    }
+
+    let unchanged = diff.added_columns.is_empty()
+        && diff.removed_columns.is_empty()
+        && diff.modified_columns.is_empty()
+        && diff.added_indexes.is_empty()
+        && diff.removed_indexes.is_empty()
+        && diff.modified_indexes.is_empty()
+        && diff.added_foreign_keys.is_empty()
+        && diff.removed_foreign_keys.is_empty()
+        && diff.modified_foreign_keys.is_empty()
+        && diff.option_changes.is_empty();
+    (!unchanged).then_some(diff)
+}
+
+/// Names added, removed and changed between two lists of named elements
+fn diff_elements<T>(
+    source: &[T],
+    target: &[T],
+    name: impl Fn(&T) -> &str,
+    differences: impl Fn(&T, &T) -> Vec<String>,
+) -> (Vec<String>, Vec<String>, Vec<SchemaElementChange>) {
+    let target_by_name: HashMap<&str, &T> = target.iter().map(|t| (name(t), t)).collect();
+    let source_names: HashSet<&str> = source.iter().map(&name).collect();
+
+    let mut added = Vec::new();
+    let mut modified = Vec::new();
+    for element in source {
+        match target_by_name.get(name(element)) {
+            None => added.push(name(element).to_string()),
+            Some(existing) => {
+                let differences = differences(element, existing);
+                if !differences.is_empty() {
+                    modified.push(SchemaElementChange {
+                        name: name(element).to_string(),
+                        differences,
+                    });
+                }
+            }
+        }
+    }
+    let removed = target
+        .iter()
+        .map(&name)
+        .filter(|n| !source_names.contains(n))
+        .map(str::to_string)
+        .collect();
+
+    (added, removed, modified)
+}
+
+fn column_differences(
+    source: &ColumnSnapshot,
+    target: &ColumnSnapshot,
+    options: &SchemaCompareOptions,
+) -> Vec<String> {
+    let (s, t) = (&source.column, &target.column);
+    let mut differences = Vec::new();
+    push_difference(
+        &mut differences,
+        "type",
+        s.column_type.to_lowercase(),
+        t.column_type.to_lowercase(),
+    );
+    push_difference(&mut differences, "nullable", s.nullable, t.nullable);
+    push_difference(
+        &mut differences,
+        "default",
+        s.default.clone(),
+        t.default.clone(),
+    );
+    push_difference(
+        &mut differences,
+        "extra",
+        column_extra(s).to_lowercase(),
+        column_extra(t).to_lowercase(),
+    );
+    push_difference(
+        &mut differences,
+        "generated",
+        source.generation_expression.clone(),
+        target.generation_expression.clone(),
+    );
+    if !options.ignore_collation {
+        push_difference(
+            &mut differences,
+            "collation",
+            source.collation.clone(),
+            target.collation.clone(),
+        );
+    }
+    if !options.ignore_comments {
+        let comment = |c: &MysqlColumn| c.comment.clone().filter(|c| !c.is_empty());
+        push_difference(&mut differences, "comment", comment(s), comment(t));
+    }
+    differences
+}
+
+fn index_differences(
+    source: &IndexInfo,
+    target: &IndexInfo,
+    options: &SchemaCompareOptions,
+) -> Vec<String> {
+    let mut differences = Vec::new();
+    push_difference(
+        &mut differences,
+        "columns",
+        key_parts(source).join(", "),
+        key_parts(target).join(", "),
+    );
+    push_difference(&mut differences, "unique", source.unique, target.unique);
+    push_difference(
+        &mut differences,
+        "type",
+        source.index_type.clone(),
+        target.index_type.clone(),
+    );
+    if !options.ignore_comments {
+        let comment = |i: &IndexInfo| i.comment.clone().filter(|c| !c.is_empty());
+        push_difference(
+            &mut differences,
+            "comment",
+            comment(source),
+            comment(target),
+        );
+    }
+    differences
+}
+
+fn foreign_key_differences(source: &ForeignKeyInfo, target: &ForeignKeyInfo) -> Vec<String> {
+    let mut differences = Vec::new();
+    push_difference(
+        &mut differences,
+        "columns",
+        source.columns.join(", "),
+        target.columns.join(", "),
+    );
+    push_difference(
+        &mut differences,
+        "references",
+        format!("{}({})", source.ref_table, source.ref_columns.join(", ")),
+        format!("{}({})", target.ref_table, target.ref_columns.join(", ")),
+    );
+    push_difference(
+        &mut differences,
+        "on_delete",
+        source.on_delete.clone(),
+        target.on_delete.clone(),
+    );
+    push_difference(
+        &mut differences,
+        "on_update",
+        source.on_update.clone(),
+        target.on_update.clone(),
+    );
+    differences
+}
+
+/// Record "<what>: <target> -> <source>" when the values differ
+fn push_difference<T: PartialEq + std::fmt::Debug>(
+    differences: &mut Vec<String>,
+    what: &str,
+    source: T,
+    target: T,
+) {
+    if source != target {
+        differences.push(format!("{}: {:?} -> {:?}", what, target, source));
+    }
+}
+
+fn create_table(table: &TableSnapshot, options: &SchemaCompareOptions) -> String {
+    let mut definitions: Vec<String> = table
+        .columns
+        .iter()
+        .map(|c| column_definition(c, !options.ignore_collation))
+        .collect();
+    definitions.extend(table.indexes.iter().map(index_definition));
+
+    let mut table_options = Vec::new();
+    if let Some(engine) = &table.engine {
+        table_options.push(format!("ENGINE={}", engine));
+    }
+    if !options.ignore_collation {
+        if let Some(collation) = &table.collation {
+            table_options.push(format!("COLLATE={}", collation));
+        }
+    }
+    if !options.ignore_auto_increment {
+        if let Some(auto_increment) = table.auto_increment.filter(|n| *n > 1) {
+            table_options.push(format!("AUTO_INCREMENT={}", auto_increment));
+        }
+    }
+    if let Some(comment) = &table.comment {
+        table_options.push(format!("COMMENT={}", quote_plain_string(comment)));
+    }
+
+    let mut sql = format!(
+        "CREATE TABLE {} (\n  {}\n)",
+        quote_name(&table.name),
+        definitions.join(",\n  ")
+    );
+    if !table_options.is_empty() {
+        sql.push(' ');
+        sql.push_str(&table_options.join(" "));
+    }
+    sql
+}
+
+/// One ALTER TABLE for all column, index and option changes of a table
+fn alter_table(
+    diff: &TableDiff,
+    source: &TableSnapshot,
+    target: &TableSnapshot,
+    options: &SchemaCompareOptions,
+) -> Option<String> {
+    let mut clauses = Vec::new();
+
+    for index in &target.indexes {
+        if diff.removed_indexes.contains(&index.name)
+            || diff.modified_indexes.iter().any(|c| c.name == index.name)
+        {
+            clauses.push(if index.is_primary {
+                "DROP PRIMARY KEY".to_string()
+            } else {
+                format!("DROP INDEX {}", quote_name(&index.name))
+            });
+        }
+    }
+    for name in &diff.removed_columns {
+        clauses.push(format!("DROP COLUMN {}", quote_name(name)));
+    }
+
+    let target_columns: HashMap<&str, &ColumnSnapshot> = target
+        .columns
+        .iter()
+        .map(|c| (c.column.name.as_str(), c))
+        .collect();
+    for (i, column) in source.columns.iter().enumerate() {
+        let position = match i {
+            0 => "FIRST".to_string(),
+            _ => format!("AFTER {}", quote_name(&source.columns[i - 1].column.name)),
+        };
+        if diff.added_columns.contains(&column.column.name) {
+            clauses.push(format!(
+                "ADD COLUMN {} {}",
+                column_definition(column, !options.ignore_collation),
+                position
+            ));
+        } else if diff
+            .modified_columns
+            .iter()
+            .any(|c| c.name == column.column.name)
+        {
+            // Ignored attributes keep their current value on the target
+            let mut merged = column.clone();
+            if let Some(existing) = target_columns.get(column.column.name.as_str()) {
+                if options.ignore_collation {
+                    merged.charset = existing.charset.clone();
+                    merged.collation = existing.collation.clone();
+                }
+                if options.ignore_comments {
+                    merged.column.comment = existing.column.comment.clone();
+                }
+            }
+            clauses.push(format!(
+                "MODIFY COLUMN {}",
+                column_definition(&merged, true)
+            ));
+        }
+    }
+
+    for index in &source.indexes {
+        if diff.added_indexes.contains(&index.name)
+            || diff.modified_indexes.iter().any(|c| c.name == index.name)
+        {
+            clauses.push(format!("ADD {}", index_definition(index)));
+        }
+    }
+
+    for change in &diff.option_changes {
+        match change.split(':').next().unwrap_or_default() {
+            "engine" => {
+                if let Some(engine) = &source.engine {
+                    clauses.push(format!("ENGINE={}", engine));
+                }
+            }
+            "collation" => {
+                if let Some(collation) = &source.collation {
+                    clauses.push(format!("COLLATE={}", collation));
+                }
+            }
+            "comment" => clauses.push(format!(
+                "COMMENT={}",
+                quote_plain_string(source.comment.as_deref().unwrap_or_default())
+            )),
+            "auto_increment" => {
+                if let Some(auto_increment) = source.auto_increment {
+                    clauses.push(format!("AUTO_INCREMENT={}", auto_increment));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (!clauses.is_empty()).then(|| {
+        format!(
+            "ALTER TABLE {}\n  {}",
+            quote_name(&source.name),
+            clauses.join(",\n  ")
+        )
+    })
+}
+
+fn column_definition(snapshot: &ColumnSnapshot, include_collation: bool) -> String {
+    let column = &snapshot.column;
+    let mut def = format!("{} {}", quote_name(&column.name), column.column_type);
+    if include_collation {
+        if let Some(charset) = &snapshot.charset {
+            def.push_str(&format!(" CHARACTER SET {}", charset));
+        }
+        if let Some(collation) = &snapshot.collation {
+            def.push_str(&format!(" COLLATE {}", collation));
+        }
+    }
+
+    let raw_extra = column.extra.as_deref().unwrap_or_default().to_uppercase();
+    if let Some(expression) = &snapshot.generation_expression {
+        let kind = if raw_extra.contains("STORED") {
+            "STORED"
+        } else {
+            "VIRTUAL"
+        };
+        def.push_str(&format!(" GENERATED ALWAYS AS ({}) {}", expression, kind));
+    }
+
+    def.push_str(if column.nullable {
+        " NULL"
+    } else {
+        " NOT NULL"
+    });
+    if snapshot.generation_expression.is_none() {
+        if let Some(default) = &column.default {
+            def.push_str(" DEFAULT ");
+            def.push_str(&default_expression(default, &raw_extra));
+        }
+    }
+
+    let extra = column_extra(column);
+    if !extra.is_empty() {
+        def.push(' ');
+        def.push_str(&extra.to_uppercase());
+    }
+    if let Some(comment) = column.comment.as_deref().filter(|c| !c.is_empty()) {
+        def.push_str(&format!(" COMMENT {}", quote_plain_string(comment)));
+    }
+    def
+}
+
+/// The EXTRA column without the markers that are not part of a definition
+fn column_extra(column: &MysqlColumn) -> String {
+    let extra = column.extra.as_deref().unwrap_or_default();
+    let mut cleaned = String::new();
+    let mut words = extra.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        let upper = word.to_uppercase();
+        if upper == "DEFAULT_GENERATED" {
+            continue;
+        }
+        if (upper == "VIRTUAL" || upper == "STORED")
+            && words
+                .peek()
+                .is_some_and(|w| w.eq_ignore_ascii_case("GENERATED"))
+        {
+            words.next();
+            continue;
+        }
+        if !cleaned.is_empty() {
+            cleaned.push(' ');
+        }
+        cleaned.push_str(word);
+    }
+    cleaned
+}
+
+/// information_schema reports literal defaults unquoted and expression
+/// defaults flagged DEFAULT_GENERATED (or bare CURRENT_TIMESTAMP before 8.0)
+fn default_expression(default: &str, extra: &str) -> String {
+    let upper = default.to_uppercase();
+    if upper.starts_with("CURRENT_TIMESTAMP")
+        || upper.starts_with("NOW(")
+        || upper.starts_with("B'")
+        || upper.starts_with("0X")
+    {
+        default.to_string()
+    } else if extra.contains("DEFAULT_GENERATED") {
+        format!("({})", default)
+    } else {
+        quote_string(default)
+    }
+}
+
+/// Key parts of an index as written in its definition: columns with their
+/// prefix length, or parenthesized expressions
+pub(crate) fn key_parts(index: &IndexInfo) -> Vec<String> {
+    index
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            if let Some(expression) = index.expressions.get(i).cloned().flatten() {
+                return format!("({})", expression);
+            }
+            match index.sub_parts.get(i).copied().flatten() {
+                Some(length) => format!("{}({})", quote_name(column), length),
+                None => quote_name(column),
+            }
+        })
+        .collect()
+}
+
+fn index_definition(index: &IndexInfo) -> String {
+    let columns = key_parts(index).join(", ");
+    if index.is_primary {
+        return format!("PRIMARY KEY ({})", columns);
+    }
+
+    let kind = match index.index_type.as_str() {
+        "FULLTEXT" => "FULLTEXT INDEX",
+        "SPATIAL" => "SPATIAL INDEX",
+        _ if index.unique => "UNIQUE INDEX",
+        _ => "INDEX",
+    };
+    let mut def = format!("{} {} ({})", kind, quote_name(&index.name), columns);
+    if index.index_type == "HASH" {
+        def.push_str(" USING HASH");
+    }
+    if let Some(comment) = index.comment.as_deref().filter(|c| !c.is_empty()) {
+        def.push_str(&format!(" COMMENT {}", quote_plain_string(comment)));
+    }
+    def
+}
+
+fn add_foreign_key(table: &str, fk: &ForeignKeyInfo) -> Statement {
+    let columns = |names: &[String]| {
+        names
+            .iter()
+            .map(|c| quote_name(c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    Statement::simple(format!(
+        "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({}) ON DELETE {} ON UPDATE {}",
+        quote_name(table),
+        quote_name(&fk.name),
+        columns(&fk.columns),
+        quote_name(&fk.ref_table),
+        columns(&fk.ref_columns),
+        fk.on_delete,
+        fk.on_update
+    ))
+}
+
+fn drop_foreign_key(table: &str, name: &str) -> Statement {
+    Statement::simple(format!(
+        "ALTER TABLE {} DROP FOREIGN KEY {}",
+        quote_name(table),
+        quote_name(name)
+    ))
+}
+
+/// Tables ordered so that referenced tables come before the tables
+/// referencing them, plus the tables caught in reference cycles
+fn table_order<'a>(
+    tables: &[&'a TableSnapshot],
+) -> (Vec<&'a TableSnapshot>, Vec<&'a TableSnapshot>) {
+    dependency_order(tables, |table, other| {
+        table.name != other.name
+            && table
+                .foreign_keys
+                .iter()
+                .any(|fk| fk.ref_table == other.name)
+    })
+}
+
+/// Views ordered so that views used by other views come first. Views in a
+/// cycle cannot exist on the server, so they are appended as they are.
+fn view_order<'a>(views: &[&'a ObjectSnapshot]) -> Vec<&'a ObjectSnapshot> {
+    let (mut ordered, cycles) = dependency_order(views, |view, other| {
+        view.name != other.name && view.definition.contains(&quote_name(&other.name))
+    });
+    ordered.extend(cycles);
+    ordered
+}
+
+/// Kahn's algorithm over `depends_on(item, other)`; keeps the input order
+/// among independent items. Returns the sorted items and those left over.
+pub(crate) fn dependency_order<'a, T>(
+    items: &[&'a T],
+    depends_on: impl Fn(&T, &T) -> bool,
+) -> (Vec<&'a T>, Vec<&'a T>) {
+    let mut remaining: Vec<&'a T> = items.to_vec();
+    let mut ordered = Vec::with_capacity(items.len());
+    loop {
+        let ready = remaining
+            .iter()
+            .position(|item| !remaining.iter().any(|other| depends_on(item, other)));
+        match ready {
+            Some(i) => ordered.push(remaining.remove(i)),
+            None => break,
+        }
+    }
+    (ordered, remaining)
+}
+
+fn objects_of<'a>(objects: &[&'a ObjectSnapshot], types: &[&str]) -> Vec<&'a ObjectSnapshot> {
+    let mut selected: Vec<&ObjectSnapshot> = objects
+        .iter()
+        .filter(|o| types.contains(&o.object_type.as_str()))
+        .copied()
+        .collect();
+    selected.sort_by(|a, b| a.name.cmp(&b.name));
+    selected
+}
+
+/// Strip `DEFINER=...` and qualifiers naming the database itself, so the
+/// same object compares equal across databases and servers
+pub fn normalize_definition(definition: &str, database: &str) -> String {
+    let mut normalized = definition.to_string();
+    if let Some(start) = normalized.find("DEFINER=") {
+        let mut in_quotes = false;
+        let mut end = normalized.len();
+        for (offset, c) in normalized[start..].char_indices() {
+            match c {
+                '`' => in_quotes = !in_quotes,
+                c if c.is_whitespace() && !in_quotes => {
+                    end = start + offset;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let rest = normalized[end..].trim_start().to_string();
+        normalized.truncate(start);
+        normalized.push_str(&rest);
+    }
+    normalized.replace(&format!("{}.", quote_name(database)), "")
+}
+
+fn replace_create(definition: &str, replacement: &str) -> String {
+    match definition.get(..7) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("CREATE ") => {
+            format!("{}{}", replacement, &definition[7..])
+        }
+        _ => definition.to_string(),
+    }
+}
+
+fn collapse(sql: &str) -> String {
+    sql.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn render_script(statements: &[Statement]) -> String {
+    let mut script = String::new();
+    for statement in statements {
+        if statement.compound {
+            script.push_str(&format!(
+                "DELIMITER $$\n{}$$\nDELIMITER ;\n\n",
+                statement.sql
+            ));
+        } else {
+            script.push_str(&format!("{};\n\n", statement.sql));
+        }
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, column_type: &str, comment: &str) -> ColumnSnapshot {
+        ColumnSnapshot {
+            column: MysqlColumn {
+                name: name.to_string(),
+                column_type: column_type.to_string(),
+                nullable: false,
+                key: None,
+                default: None,
+                extra: None,
+                comment: Some(comment.to_string()),
+            },
+            charset: None,
+            collation: None,
+            generation_expression: None,
+        }
+    }
+
+    fn table(name: &str, columns: Vec<ColumnSnapshot>, references: &[&str]) -> TableSnapshot {
+        TableSnapshot {
+            name: name.to_string(),
+            columns,
+            foreign_keys: references
+                .iter()
+                .map(|r| ForeignKeyInfo {
+                    name: format!("fk_{}_{}", name, r),
+                    columns: vec![format!("{}_id", r)],
+                    ref_table: r.to_string(),
+                    ref_columns: vec!["id".to_string()],
+                    on_delete: "RESTRICT".to_string(),
+                    on_update: "RESTRICT".to_string(),
+                })
+                .collect(),
+            auto_increment: Some(10),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compare_snapshots() {
+        let source = SchemaSnapshot {
+            database: "staging".to_string(),
+            tables: vec![
+                table("orders", vec![column("id", "bigint", "")], &["users"]),
+                table("users", vec![column("id", "bigint", "new")], &[]),
+                table(
+                    "items",
+                    vec![column("id", "bigint", ""), column("sku", "varchar(32)", "")],
+                    &[],
+                ),
+            ],
+            objects: vec![ObjectSnapshot {
+                name: "v_orders".to_string(),
+                object_type: "view".to_string(),
+                definition: "CREATE VIEW `v_orders` AS select 1".to_string(),
+            }],
+        };
+        let mut items = table("items", vec![column("id", "int", "")], &[]);
+        items.auto_increment = Some(99);
+        let target = SchemaSnapshot {
+            database: "prod".to_string(),
+            tables: vec![
+                items,
+                table("users", vec![column("id", "bigint", "old")], &[]),
+            ],
+            objects: vec![],
+        };
+
+        let options = SchemaCompareOptions {
+            ignore_auto_increment: true,
+            ignore_comments: true,
+            ..Default::default()
+        };
+        let report = compare_snapshots(&source, &target, &options);
+        assert_eq!(report.added_tables, vec!["orders"]);
+        assert_eq!(report.modified_tables.len(), 1);
+        assert_eq!(report.modified_tables[0].added_columns, vec!["sku"]);
+        assert_eq!(
+            report.statements,
+            vec![
+                "USE `prod`",
+                "CREATE TABLE `orders` (\n  `id` bigint NOT NULL\n)",
+                "ALTER TABLE `items`\n  MODIFY COLUMN `id` bigint NOT NULL,\n  \
+                 ADD COLUMN `sku` varchar(32) NOT NULL AFTER `id`",
+                "ALTER TABLE `orders` ADD CONSTRAINT `fk_orders_users` FOREIGN KEY (`users_id`) \
+                 REFERENCES `users` (`id`) ON DELETE RESTRICT ON UPDATE RESTRICT",
+                "CREATE OR REPLACE VIEW `v_orders` AS select 1",
+            ]
+        );
+
+        let report = compare_snapshots(&source, &source, &SchemaCompareOptions::default());
+        assert!(report.identical);
+        assert!(report.script.is_empty());
+    }
+
+    #[test]
+    fn test_normalize_definition() {
+        assert_eq!(
+            normalize_definition(
+                "CREATE ALGORITHM=UNDEFINED DEFINER=`app`@`%` SQL SECURITY DEFINER VIEW `v` \
+                 AS select `shop`.`t`.`id` AS `id` from `shop`.`t`",
+                "shop"
+            ),
+            "CREATE ALGORITHM=UNDEFINED SQL SECURITY DEFINER VIEW `v` \
+             AS select `t`.`id` AS `id` from `t`"
+        );
+    }
+
+    #[test]
+    fn test_index_definition_key_parts() {
+        let index = IndexInfo {
+            name: "idx_name".to_string(),
+            columns: vec!["name".to_string(), "".to_string(), "id".to_string()],
+            sub_parts: vec![Some(10), None, None],
+            expressions: vec![None, Some("lower(`email`)".to_string()), None],
+            unique: false,
+            index_type: "BTREE".to_string(),
+            is_primary: false,
+            comment: None,
+        };
+        assert_eq!(
+            index_definition(&index),
+            "INDEX `idx_name` (`name`(10), (lower(`email`)), `id`)"
+        );
+    }
+}
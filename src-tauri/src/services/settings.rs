@@ -3,10 +3,16 @@
 //! Provides a key-value store for user settings with JSON value support.
 
 use crate::db::sqlite::SqlitePool;
-use crate::db::models::{UserSetting, UpsertSettingRequest, BatchSettingsResponse};
-use crate::error::AppResult;
+use crate::db::models::{UserSetting, UpsertSettingRequest, BatchSettingsResponse, FilterPreset};
+use crate::error::{AppError, AppResult};
 use std::collections::HashMap;
 
+/// Default hard cap on rows returned by a single MySQL query
+pub const DEFAULT_MYSQL_MAX_ROWS: u64 = 50_000;
+
+/// Default idle time before a MySQL session is rolled back and closed
+pub const DEFAULT_MYSQL_SESSION_IDLE_TIMEOUT_SECS: u64 = 900;
+
 /// Service for managing user settings
 pub struct SettingsService {
     pool: SqlitePool,
@@ -103,4 +109,132 @@ impl SettingsService {
         self.set(&request).await?;
         Ok(())
     }
+
+    /// Get the hard cap on rows a MySQL query may return across all pages
+    pub async fn get_mysql_max_rows(&self) -> AppResult<u64> {
+        let value = self.get("mysql_max_rows").await?;
+        match value {
+            Some(v) => Ok(v.as_u64().unwrap_or(DEFAULT_MYSQL_MAX_ROWS).max(1)),
+            None => Ok(DEFAULT_MYSQL_MAX_ROWS),
+        }
+    }
+
+    /// Set the hard cap on rows a MySQL query may return
+    pub async fn set_mysql_max_rows(&self, max_rows: u64) -> AppResult<()> {
+        let request = UpsertSettingRequest {
+            key: "mysql_max_rows".to_string(),
+            value: serde_json::Value::Number(max_rows.into()),
+        };
+        self.set(&request).await?;
+        Ok(())
+    }
+
+    /// Get the idle timeout, in seconds, of pinned MySQL sessions
+    pub async fn get_mysql_session_idle_timeout(&self) -> AppResult<u64> {
+        let value = self.get("mysql_session_idle_timeout").await?;
+        match value {
+            Some(v) => Ok(v
+                .as_u64()
+                .unwrap_or(DEFAULT_MYSQL_SESSION_IDLE_TIMEOUT_SECS)
+                .max(1)),
+            None => Ok(DEFAULT_MYSQL_SESSION_IDLE_TIMEOUT_SECS),
+        }
+    }
+
+    /// Set the idle timeout, in seconds, of pinned MySQL sessions
+    pub async fn set_mysql_session_idle_timeout(&self, secs: u64) -> AppResult<()> {
+        let request = UpsertSettingRequest {
+            key: "mysql_session_idle_timeout".to_string(),
+            value: serde_json::Value::Number(secs.into()),
+        };
+        self.set(&request).await?;
+        Ok(())
+    }
+
+    /// Get the filter presets saved for a table
+    pub async fn get_filter_presets(
+        &self,
+        connection_id: i64,
+        database: &str,
+        table: &str,
+    ) -> AppResult<Vec<FilterPreset>> {
+        let value = self
+            .get(&filter_presets_key(connection_id, database, table))
+            .await?;
+        match value {
+            Some(v) => Ok(serde_json::from_value(v)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Save a filter preset for a table, replacing one of the same name
+    pub async fn save_filter_preset(
+        &self,
+        connection_id: i64,
+        database: &str,
+        table: &str,
+        preset: FilterPreset,
+    ) -> AppResult<Vec<FilterPreset>> {
+        if preset.name.trim().is_empty() {
+            return Err(AppError::Validation(
+                "Preset name cannot be empty".to_string(),
+            ));
+        }
+        let mut presets = self
+            .get_filter_presets(connection_id, database, table)
+            .await?;
+        match presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => presets.push(preset),
+        }
+        self.set_filter_presets(connection_id, database, table, &presets)
+            .await?;
+        Ok(presets)
+    }
+
+    /// Delete a filter preset of a table
+    pub async fn delete_filter_preset(
+        &self,
+        connection_id: i64,
+        database: &str,
+        table: &str,
+        name: &str,
+    ) -> AppResult<Vec<FilterPreset>> {
+        let mut presets = self
+            .get_filter_presets(connection_id, database, table)
+            .await?;
+        presets.retain(|p| p.name != name);
+        if presets.is_empty() {
+            self.delete(&filter_presets_key(connection_id, database, table))
+                .await?;
+        } else {
+            self.set_filter_presets(connection_id, database, table, &presets)
+                .await?;
+        }
+        Ok(presets)
+    }
+
+    async fn set_filter_presets(
+        &self,
+        connection_id: i64,
+        database: &str,
+        table: &str,
+        presets: &[FilterPreset],
+    ) -> AppResult<()> {
+        let request = UpsertSettingRequest {
+            key: filter_presets_key(connection_id, database, table),
+            value: serde_json::to_value(presets)?,
+        };
+        self.set(&request).await?;
+        Ok(())
+    }
+}
+
+/// Settings key holding the filter presets of a table. The names are JSON
+/// encoded, since both may contain dots
+fn filter_presets_key(connection_id: i64, database: &str, table: &str) -> String {
+    format!(
+        "mysql_filter_presets:{}",
+        serde_json::json!([connection_id, database, table])
+    )
 }
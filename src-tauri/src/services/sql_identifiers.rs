@@ -0,0 +1,29 @@
+//! MySQL identifier quoting
+//!
+//! Every statement built from user-supplied or server-reported names goes
+//! through these helpers, so a backtick inside a name cannot end its quotes.
+
+/// Quote a single database, table or column name; a dot is part of the name
+pub fn quote_name(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+/// Quote a user-typed identifier, allowing `db.table` qualified names
+pub fn quote_identifier(value: &str) -> String {
+    value
+        .split('.')
+        .map(quote_name)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_names() {
+        assert_eq!(quote_name("app.us`ers"), "`app.us``ers`");
+        assert_eq!(quote_identifier("app.us`ers"), "`app`.`us``ers`");
+    }
+}
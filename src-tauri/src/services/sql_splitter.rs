@@ -0,0 +1,406 @@
+//! SQL script splitting and statement classification
+//!
+//! Splits a MySQL script into statements the way the `mysql` client does:
+//! delimiters inside quotes, identifiers and comments are ignored, and
+//! `DELIMITER` lines change the delimiter for procedure/trigger bodies.
+
+use serde::{Deserialize, Serialize};
+
+/// One statement of a script
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SqlStatement {
+    /// Statement text without its delimiter
+    pub sql: String,
+    /// 1-based line on which the statement starts
+    pub line: usize,
+    /// Statement type, see `classify_statement`
+    pub query_type: String,
+}
+
+/// Split a script into statements, skipping comment-only fragments.
+///
+/// Leading comments are dropped from each statement; `/*! ... */`
+/// executable comments are kept as code.
+pub fn split_statements(script: &str) -> Vec<SqlStatement> {
+    let mut reader = StatementReader::new();
+    let mut statements = Vec::new();
+    for line in script.split_inclusive('\n') {
+        statements.extend(reader.push_line(line));
+    }
+    statements.extend(reader.finish());
+    statements
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScanState {
+    Code,
+    Quoted(char),
+    /// Inside a quoted string right after a backslash
+    Escaped(char),
+    BlockComment,
+}
+
+/// Incremental splitter for scripts read line by line, such as dump files
+/// too large to hold in memory. Produces the same statements as
+/// `split_statements`.
+pub struct StatementReader {
+    delimiter: Vec<char>,
+    current: String,
+    /// Byte offset in `current` of the first code character, once seen
+    code_start: Option<usize>,
+    state: ScanState,
+    /// Line number of the next line pushed
+    line: usize,
+    current_line: usize,
+}
+
+impl Default for StatementReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatementReader {
+    /// Create a reader using `;` as the delimiter
+    pub fn new() -> Self {
+        Self {
+            delimiter: vec![';'],
+            current: String::new(),
+            code_start: None,
+            state: ScanState::Code,
+            line: 1,
+            current_line: 1,
+        }
+    }
+
+    /// Feed one line, including its newline, and return the statements it
+    /// completes
+    pub fn push_line(&mut self, line: &str) -> Vec<SqlStatement> {
+        let mut statements = Vec::new();
+        let line_number = self.line;
+        self.line += 1;
+
+        // DELIMITER is a client command and only valid at the start of a line
+        if self.state == ScanState::Code && self.code_start.is_none() {
+            if let Some(delimiter) = parse_delimiter_line(line) {
+                self.delimiter = delimiter;
+                self.current.clear();
+                return statements;
+            }
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            match self.state {
+                ScanState::Escaped(quote) => {
+                    self.state = ScanState::Quoted(quote);
+                }
+                ScanState::Quoted(quote) => {
+                    if c == '\\' && quote != '`' {
+                        self.state = ScanState::Escaped(quote);
+                    } else if c == quote {
+                        if chars.get(i + 1) == Some(&quote) {
+                            self.current.push(c);
+                            i += 1;
+                        } else {
+                            self.state = ScanState::Code;
+                        }
+                    }
+                }
+                ScanState::BlockComment => {
+                    if c == '*' && chars.get(i + 1) == Some(&'/') {
+                        self.current.push(c);
+                        i += 1;
+                        self.state = ScanState::Code;
+                    }
+                }
+                ScanState::Code => {
+                    if chars[i..].starts_with(&self.delimiter) {
+                        if let Some(start) = self.code_start.take() {
+                            push_statement(
+                                &mut statements,
+                                &self.current[start..],
+                                self.current_line,
+                            );
+                        }
+                        self.current.clear();
+                        i += self.delimiter.len();
+                        continue;
+                    }
+
+                    let is_comment = match c {
+                        '\'' | '"' | '`' => {
+                            self.state = ScanState::Quoted(c);
+                            false
+                        }
+                        '#' => true,
+                        '-' if is_dash_comment(&chars, i) => true,
+                        '/' if chars.get(i + 1) == Some(&'*') => {
+                            self.state = ScanState::BlockComment;
+                            // Executable comments are code
+                            let executable = chars.get(i + 2) == Some(&'!');
+                            if !executable {
+                                self.current.push_str("/*");
+                                i += 2;
+                                continue;
+                            }
+                            false
+                        }
+                        _ => false,
+                    };
+
+                    if is_comment {
+                        // The comment runs to the end of the line
+                        self.current.extend(&chars[i..]);
+                        break;
+                    }
+                    if self.code_start.is_none() && !c.is_whitespace() {
+                        self.code_start = Some(self.current.len());
+                        self.current_line = line_number;
+                    }
+                }
+            }
+            self.current.push(c);
+            i += 1;
+        }
+
+        statements
+    }
+
+    /// Return the unterminated statement left at the end of the input
+    pub fn finish(&mut self) -> Option<SqlStatement> {
+        let start = self.code_start.take()?;
+        let mut statements = Vec::new();
+        push_statement(&mut statements, &self.current[start..], self.current_line);
+        self.current.clear();
+        statements.pop()
+    }
+}
+
+/// Classify a statement by its leading keyword, looking past comments,
+/// parentheses and `WITH` common table expressions.
+///
+/// Returns one of select, insert, update, delete, show, describe, explain,
+/// create, drop, alter or other.
+pub fn classify_statement(sql: &str) -> String {
+    let words = top_level_words(sql);
+    let Some(first) = words.first() else {
+        return "other".to_string();
+    };
+
+    let keyword = if first.word == "with" {
+        // The statement verb is the first top-level keyword after the CTEs
+        words
+            .iter()
+            .skip(1)
+            .find(|w| {
+                w.depth == 0
+                    && matches!(
+                        w.word.as_str(),
+                        "select" | "insert" | "replace" | "update" | "delete" | "table" | "values"
+                    )
+            })
+            .map(|w| w.word.as_str())
+            .unwrap_or("other")
+    } else {
+        first.word.as_str()
+    };
+
+    match keyword {
+        "select" | "table" | "values" => "select",
+        "insert" | "replace" => "insert",
+        "update" => "update",
+        "delete" => "delete",
+        "show" => "show",
+        "describe" | "desc" => "describe",
+        "explain" => "explain",
+        "create" => "create",
+        "drop" => "drop",
+        "alter" => "alter",
+        _ => "other",
+    }
+    .to_string()
+}
+
+/// Whether statements of this type produce a result set
+pub fn returns_rows(query_type: &str) -> bool {
+    matches!(query_type, "select" | "show" | "describe" | "explain")
+}
+
+fn push_statement(statements: &mut Vec<SqlStatement>, text: &str, line: usize) {
+    let sql = text.trim();
+    statements.push(SqlStatement {
+        sql: sql.to_string(),
+        line,
+        query_type: classify_statement(sql),
+    });
+}
+
+struct Word {
+    word: String,
+    depth: usize,
+}
+
+/// Lowercased bare words of a statement with their parenthesis depth,
+/// ignoring comments, string literals and quoted identifiers
+fn top_level_words(sql: &str) -> Vec<Word> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut words = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\'' | '"' | '`' => i = skip_quoted(&chars, i),
+            '#' => i = skip_line_comment(&chars, i),
+            '-' if is_dash_comment(&chars, i) => i = skip_line_comment(&chars, i),
+            '/' if chars.get(i + 1) == Some(&'*') => i = skip_block_comment(&chars, i),
+            '(' => {
+                depth += 1;
+                i += 1;
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                words.push(Word {
+                    word: chars[start..i].iter().collect::<String>().to_lowercase(),
+                    depth,
+                });
+            }
+            _ => i += 1,
+        }
+    }
+
+    // A statement wrapped in parentheses, e.g. (SELECT ...) UNION (...)
+    if let Some(min) = words.iter().map(|w| w.depth).min() {
+        for w in &mut words {
+            w.depth -= min;
+        }
+    }
+
+    words
+}
+
+/// Parse a `DELIMITER <token>` line, returning the new delimiter
+pub(crate) fn parse_delimiter_line(line: &str) -> Option<Vec<char>> {
+    let trimmed = line.trim_start_matches(|c: char| c.is_whitespace() && c != '\n');
+    let keyword = trimmed.get(..9)?;
+    if !keyword.eq_ignore_ascii_case("delimiter") {
+        return None;
+    }
+    let rest = &trimmed[9..];
+    if !rest.starts_with(|c: char| c.is_whitespace() && c != '\n') {
+        return None;
+    }
+    rest.split_whitespace()
+        .next()
+        .map(|token| token.chars().collect())
+}
+
+/// Position after the quoted string starting at i; handles backslash escapes
+/// (not in identifiers) and doubled quotes
+pub(crate) fn skip_quoted(chars: &[char], i: usize) -> usize {
+    let quote = chars[i];
+    let mut pos = i + 1;
+    while pos < chars.len() {
+        let c = chars[pos];
+        if c == '\\' && quote != '`' {
+            pos += 2;
+        } else if c == quote {
+            if chars.get(pos + 1) == Some(&quote) {
+                pos += 2;
+            } else {
+                return pos + 1;
+            }
+        } else {
+            pos += 1;
+        }
+    }
+    chars.len()
+}
+
+/// `--` starts a comment only when followed by whitespace or the end
+pub(crate) fn is_dash_comment(chars: &[char], i: usize) -> bool {
+    chars.get(i + 1) == Some(&'-') && chars.get(i + 2).map_or(true, |c| c.is_whitespace())
+}
+
+/// Position of the newline ending the comment starting at i
+pub(crate) fn skip_line_comment(chars: &[char], i: usize) -> usize {
+    chars[i..]
+        .iter()
+        .position(|c| *c == '\n')
+        .map(|p| i + p)
+        .unwrap_or(chars.len())
+}
+
+/// Position after the block comment starting at i
+pub(crate) fn skip_block_comment(chars: &[char], i: usize) -> usize {
+    let mut pos = i + 2;
+    while pos + 1 < chars.len() {
+        if chars[pos] == '*' && chars[pos + 1] == '/' {
+            return pos + 2;
+        }
+        pos += 1;
+    }
+    chars.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_statement() {
+        assert_eq!(classify_statement("-- header\nSELECT 1"), "select");
+        assert_eq!(
+            classify_statement("/* hint */ (SELECT 1) UNION (SELECT 2)"),
+            "select"
+        );
+        assert_eq!(
+            classify_statement(
+                "WITH RECURSIVE t (n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM t), \
+                 u AS (SELECT 2) SELECT * FROM t, u"
+            ),
+            "select"
+        );
+        assert_eq!(
+            classify_statement(
+                "WITH d AS (SELECT id FROM a) DELETE FROM b WHERE id IN (SELECT id FROM d)"
+            ),
+            "delete"
+        );
+        assert_eq!(classify_statement("desc users"), "describe");
+        assert_eq!(classify_statement("# only a comment"), "other");
+    }
+
+    #[test]
+    fn test_split_statements() {
+        let script = "SELECT 'a;b', `c;d` FROM t; -- trailing; comment\n\
+                      /* block; */ UPDATE t SET v = \"x\\\";\" ;\n\
+                      \n\
+                      DELIMITER $$\n\
+                      CREATE PROCEDURE p() BEGIN SELECT 1; SELECT 2; END$$\n\
+                      DELIMITER ;\n\
+                      WITH x AS (SELECT 1) SELECT * FROM x;";
+
+        let statements = split_statements(script);
+        let types: Vec<&str> = statements.iter().map(|s| s.query_type.as_str()).collect();
+        assert_eq!(types, vec!["select", "update", "create", "select"]);
+        assert_eq!(statements[0].sql, "SELECT 'a;b', `c;d` FROM t");
+        assert_eq!(
+            statements[2].sql,
+            "CREATE PROCEDURE p() BEGIN SELECT 1; SELECT 2; END"
+        );
+        assert_eq!(statements[2].line, 5);
+        assert_eq!(statements[3].line, 7);
+    }
+}